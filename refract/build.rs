@@ -6,7 +6,6 @@ be pulled into GTK.
 */
 
 use argyle::KeyWordsBuilder;
-use dowser::Extension;
 use oxford_join::JoinFmt;
 use std::{
 	collections::HashMap,
@@ -38,7 +37,6 @@ pub fn main() {
 
 	build_cli();
 	build_credits();
-	build_exts();
 	build_resources();
 }
 
@@ -46,23 +44,46 @@ pub fn main() {
 fn build_cli() {
 	let mut builder = KeyWordsBuilder::default();
 	builder.push_keys([
+		"--avif-yuv420",
+		"--background",
+		"--ci",
+		"--dedupe-lossless",
+		"--durable",
+		"--greyscale",
 		"-h", "--help",
+		"--jxl-progressive",
+		"--keep-metadata",
+		"--lossless-last",
+		"--smart",
+		"--new-instance",
 		"--no-avif",
 		"--no-jxl",
 		"--no-webp",
 		"--no-lossless",
 		"--no-lossy",
+		"--no-recurse",
 		"--no-ycbcr",
+		"--photo-lossless",
+		"--picture",
+		"--png",
+		"--jpeg",
+		"--sidecar",
+		"--stdin",
 		"-V", "--version",
+		"--webp-sharp-yuv",
 	]);
-	builder.push_keys_with_values(["-l", "--list"]);
+	builder.push_keys_with_values(["--avif-codec", "--existing", "--fallback", "--format", "--job", "--job-root", "--jobs", "--jxl-decoding-speed", "--jxl-effort", "-l", "--lossless-above", "--list", "--max-depth", "--max-size", "--max-steps", "--name-template", "-o", "--output", "--out-dir", "--preset", "--preview-max", "--progress", "--quality", "--record", "--region", "--report", "--report-every", "--resume", "--stream-above", "--time-limit", "--webp-method"]);
+	builder.push_command("decode");
+	builder.push_command("estimate");
+	builder.push_command("preset");
+	builder.push_command("replay");
 	builder.save(_out_path("argyle.rs").expect("Missing OUT_DIR."));
 }
 
 /// # Build Credits.
 ///
-/// This compiles a list of crates used as direct dependencies (to both GTK and
-/// core, since both are ours).
+/// This compiles a list of crates used as direct dependencies (to GTK, core,
+/// and frontend, since all are ours).
 ///
 /// This data gets used inside the Help > About dialogue.
 fn build_credits() {
@@ -102,11 +123,17 @@ fn build_credits() {
 		});
 
 	// Make sure we have *this* entry.
-	assert!(raw.contains_key("refract") && raw.contains_key("refract_core"), "Unable to parse Cargo.lock.");
+	assert!(
+		raw.contains_key("refract") &&
+		raw.contains_key("refract_core") &&
+		raw.contains_key("refract_frontend"),
+		"Unable to parse Cargo.lock.",
+	);
 
 	// Build a list of direct package dependencies for *this* crate.
 	let mut list: Vec<String> = _credits_deps_formatted("refract", &raw);
 	list.extend(_credits_deps_formatted("refract_core", &raw));
+	list.extend(_credits_deps_formatted("refract_frontend", &raw));
 	list.sort();
 	list.dedup();
 
@@ -122,45 +149,6 @@ fn build_credits() {
 		.expect("Unable to save credits.");
 }
 
-/// # Build Extensions.
-///
-/// While we're here, we may as we pre-compute our various image extension
-/// constants.
-fn build_exts() {
-	let out = format!(
-		r"
-/// # Extension: AVIF.
-const E_AVIF: Extension = {};
-/// # Extension: JPEG.
-const E_JPEG: Extension = {};
-/// # Extension: JPG.
-const E_JPG: Extension = {};
-/// # Extension: JXL.
-const E_JXL: Extension = {};
-/// # Extension: PNG.
-const E_PNG: Extension = {};
-/// # Extension: WEBP.
-const E_WEBP: Extension = {};
-",
-		Extension::codegen(b"avif"),
-		Extension::codegen(b"jpeg"),
-		Extension::codegen(b"jpg"),
-		Extension::codegen(b"jxl"),
-		Extension::codegen(b"png"),
-		Extension::codegen(b"webp"),
-	);
-
-	// Save them as a slice value!
-	let mut file = _out_path("refract-extensions.rs")
-		.and_then(|p| File::create(p).ok())
-		.expect("Missing OUT_DIR.");
-
-	file.write_all(out.as_bytes())
-		.and_then(|_| file.flush())
-		.expect("Unable to save extensions.");
-
-}
-
 /// # Build Resource Bundle.
 fn build_resources() {
 	// The directory with all the files.
@@ -220,7 +208,7 @@ fn _credits_deps_formatted(key: &str, map: &HashMap<String, (String, Vec<String>
 			// Ignore our build dependencies, etc.
 			.filter(|x| ! matches!(
 				x.as_str(),
-				"refract_core" | "toml" | "version-compare"
+				"refract_core" | "refract_frontend" | "toml" | "version-compare"
 			))
 			.filter_map(|name| map.get(name).map(|entry| format!(
 				"\"{} v{} https://crates.io/crates/{}\"",
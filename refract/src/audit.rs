@@ -0,0 +1,154 @@
+/*!
+# `Refract GTK` - Output Audits
+*/
+
+use crate::progress::push_json_str;
+use refract_frontend::sha256;
+use refract_core::{
+	Output,
+	RefractError,
+};
+use std::{
+	fmt::Write,
+	num::NonZeroUsize,
+	path::{
+		Path,
+		PathBuf,
+	},
+};
+
+
+
+/// # Output Audit.
+///
+/// This captures exactly how a saved output was made — core flags, encoder
+/// version and settings, and the preset in effect, if any — so two similar
+/// looking files with very different sizes can be explained after the fact.
+///
+/// It also includes SHA-256 hashes of the source and output so the files can
+/// be verified or deduplicated later.
+///
+/// A one-line summary is added to the activity log (and hence any report),
+/// and with `--sidecar`, the full record is saved as JSON alongside the
+/// output, e.g. `image.jpg.webp.json`.
+pub(super) struct Audit<'a> {
+	/// # Source Path.
+	src: &'a Path,
+
+	/// # Output Path.
+	dst: &'a Path,
+
+	/// # Output.
+	output: &'a Output,
+
+	/// # Source Hash.
+	src_hash: Option<&'a str>,
+
+	/// # Output Hash.
+	hash: String,
+
+	/// # Encoder Version.
+	encoder: String,
+
+	/// # Preset.
+	preset: Option<&'a str>,
+}
+
+impl<'a> Audit<'a> {
+	/// # New.
+	pub(super) fn new(
+		src: &'a Path,
+		dst: &'a Path,
+		output: &'a Output,
+		src_hash: Option<&'a str>,
+		preset: Option<&'a str>,
+	) -> Self {
+		Self {
+			src,
+			dst,
+			output,
+			src_hash,
+			hash: sha256(output),
+			encoder: output.kind().encoder_version().unwrap_or_default(),
+			preset,
+		}
+	}
+
+	/// # Output Hash.
+	///
+	/// Return the SHA-256 of the output.
+	pub(super) fn hash(&self) -> &str { &self.hash }
+
+	/// # Summary.
+	///
+	/// Return a brief, plain-text summary of the encoder details.
+	pub(super) fn summary(&self) -> String {
+		let mut out = format!(
+			"{}; {}; flags {:#010b}",
+			self.encoder,
+			self.output.encoder_settings(),
+			self.output.flags(),
+		);
+		if let Some(preset) = self.preset {
+			let _res = write!(out, "; preset {preset}");
+		}
+		out
+	}
+
+	/// # Save Sidecar.
+	///
+	/// Write the audit as JSON to the output path plus `.json`, returning the
+	/// path written.
+	///
+	/// ## Errors
+	///
+	/// Returns an error if the file cannot be written.
+	pub(super) fn save_sidecar(&self) -> Result<PathBuf, RefractError> {
+		let mut path = self.dst.to_path_buf();
+		path.as_mut_os_string().push(".json");
+
+		let mut json = self.to_json();
+		json.push('\n');
+		write_atomic::write_file(&path, json.as_bytes())
+			.map_err(|_| RefractError::Write)?;
+		Ok(path)
+	}
+
+	/// # As JSON.
+	fn to_json(&self) -> String {
+		let quality = self.output.quality();
+
+		let mut out = String::with_capacity(256);
+		out.push_str(r#"{"source":"#);
+		push_json_str(&mut out, &self.src.to_string_lossy());
+		out.push_str(r#","source_sha256":"#);
+		match self.src_hash {
+			Some(hash) => { push_json_str(&mut out, hash); },
+			None => { out.push_str("null"); },
+		}
+		out.push_str(r#","output":"#);
+		push_json_str(&mut out, &self.dst.to_string_lossy());
+		out.push_str(r#","output_sha256":"#);
+		push_json_str(&mut out, &self.hash);
+		out.push_str(r#","kind":"#);
+		push_json_str(&mut out, quality.kind().extension());
+		out.push_str(r#","quality":"#);
+		push_json_str(&mut out, &quality.quality().to_string());
+		let _res = write!(
+			out,
+			r#","size":{},"flags":{},"encoder":"#,
+			self.output.size().map_or(0, NonZeroUsize::get),
+			self.output.flags(),
+		);
+		push_json_str(&mut out, &self.encoder);
+		out.push_str(r#","settings":"#);
+		push_json_str(&mut out, &self.output.encoder_settings());
+		out.push_str(r#","preset":"#);
+		match self.preset {
+			Some(preset) => { push_json_str(&mut out, preset); },
+			None => { out.push_str("null"); },
+		}
+		out.push('}');
+		out
+	}
+}
@@ -2,9 +2,12 @@
 # `Refract GTK` - Candidate
 */
 
-use gtk::gdk_pixbuf::{
-	Colorspace,
-	Pixbuf,
+use gtk::{
+	gdk_pixbuf::{
+		Colorspace,
+		Pixbuf,
+	},
+	glib::Bytes,
 };
 use refract_core::{
 	ColorKind,
@@ -13,6 +16,10 @@ use refract_core::{
 	Quality,
 	RefractError,
 };
+use std::{
+	num::NonZeroU8,
+	sync::Arc,
+};
 
 
 
@@ -23,9 +30,12 @@ use refract_core::{
 /// `Pixbuf` nor `Input` are willing to make that journey directly.) It holds
 /// a buffer of RGBA pixels, the image dimensions, the encoding quality and
 /// iteration number — if applicable — and the byte size of the raw image.
+///
+/// For sources, the pixel buffer is shared with the [`Input`] itself, so
+/// displaying the image doesn't cost a second copy of it.
 pub(super) struct Candidate {
 	/// # Image Data.
-	buf: Box<[u8]>,
+	buf: Arc<[u8]>,
 
 	/// # Image Width.
 	width: i32,
@@ -44,30 +54,73 @@ pub(super) struct Candidate {
 
 	/// # Size.
 	pub(super) size: usize,
+
+	/// # Previously Saved?
+	///
+	/// This is `true` for existing conversions loaded for review.
+	pub(super) saved: bool,
+
+	/// # Content Hash.
+	///
+	/// The SHA-256 of the raw file, for sources.
+	pub(super) hash: Option<String>,
+
+	/// # Similarity.
+	///
+	/// The DSSIM score relative to the source, for candidates.
+	pub(super) dssim: Option<f64>,
+
+	/// # Quality Bounds.
+	///
+	/// The (raw) qualities still in the running when the candidate was
+	/// produced, for candidates.
+	pub(super) bounds: Option<(NonZeroU8, NonZeroU8)>,
+
+	/// # Search Progress.
+	///
+	/// The lossy candidates produced so far — this one included — and the
+	/// most that could yet follow, for candidates.
+	pub(super) progress: Option<(u8, u8)>,
+
+	/// # Undoable?
+	///
+	/// This is `true` if the decision before this candidate can be taken
+	/// back.
+	pub(super) undo: bool,
 }
 
-impl TryFrom<&Input<'_>> for Candidate {
+impl TryFrom<&mut Input<'_>> for Candidate {
 	type Error = RefractError;
 
 	/// # Source Image.
-	fn try_from(src: &Input) -> Result<Self, Self::Error> {
-		// Upscale.
-		if src.depth() != ColorKind::Rgba {
-			return Self::try_from(&src.as_rgba());
-		}
-
+	fn try_from(src: &mut Input) -> Result<Self, Self::Error> {
 		let width = src.width_i32()?;
 		let height = src.height_i32()?;
-		let row_size = src.row_size_i32()?;
+
+		// Share the source's own pixels rather than copying them; big sources
+		// are big enough as it is. (Anything else needs upscaling first.)
+		let (buf, row_size) =
+			if src.depth() == ColorKind::Rgba { (src.share_pixels(), src.row_size_i32()?) }
+			else {
+				let rgba = src.as_rgba();
+				let row_size = rgba.row_size_i32()?;
+				(Arc::from(rgba.take_pixels()), row_size)
+			};
 
 		Ok(Self {
-			buf: Box::from(src.as_ref()),
+			buf,
 			width,
 			height,
 			row_size,
 			quality: Quality::Lossless(src.kind()),
 			count: 0,
 			size: src.size(),
+			saved: false,
+			hash: None,
+			dssim: None,
+			bounds: None,
+			progress: None,
+			undo: false,
 		})
 	}
 }
@@ -84,21 +137,28 @@ impl TryFrom<&Output> for Candidate {
 		let size = input.size();
 
 		Ok(Self {
-			buf: input.take_pixels().into_boxed_slice(),
+			buf: Arc::from(input.take_pixels()),
 			width,
 			height,
 			row_size,
 			quality: src.quality(),
 			count: 1,
 			size,
+			saved: false,
+			hash: None,
+			dssim: None,
+			bounds: None,
+			progress: None,
+			undo: false,
 		})
 	}
 }
 
 impl From<Candidate> for Pixbuf {
 	fn from(src: Candidate) -> Self {
-		Self::from_mut_slice(
-			src.buf,
+		// The pixels are never modified, so can be wrapped rather than copied.
+		Self::from_bytes(
+			&Bytes::from_owned(src.buf),
 			Colorspace::Rgb,
 			true,
 			8,
@@ -110,6 +170,26 @@ impl From<Candidate> for Pixbuf {
 }
 
 impl Candidate {
+	/// # Verify Dimensions.
+	///
+	/// Make sure the candidate's (decoded) dimensions match the source's, so
+	/// that encoder padding bugs and the like don't sneak through to A/B
+	/// review.
+	///
+	/// ## Errors
+	///
+	/// Returns [`RefractError::Dimensions`] if they don't match.
+	pub(super) fn verify_dimensions(self, width: usize, height: usize)
+	-> Result<Self, RefractError> {
+		if
+			usize::try_from(self.width).is_ok_and(|w| w == width) &&
+			usize::try_from(self.height).is_ok_and(|h| h == height)
+		{
+			Ok(self)
+		}
+		else { Err(RefractError::Dimensions) }
+	}
+
 	/// # With Count.
 	///
 	/// This method is used to add an iteration count to a [`Candidate`]
@@ -118,4 +198,84 @@ impl Candidate {
 		self.count = count;
 		self
 	}
+
+	/// # With Similarity.
+	///
+	/// This method is used to add a DSSIM score — relative to the source — to
+	/// a [`Candidate`].
+	pub(super) const fn with_dssim(mut self, dssim: Option<f64>) -> Self {
+		self.dssim = dssim;
+		self
+	}
+
+	/// # With Bounds.
+	///
+	/// This method is used to add the search's current quality bounds to a
+	/// [`Candidate`].
+	pub(super) const fn with_bounds(mut self, bounds: (NonZeroU8, NonZeroU8)) -> Self {
+		self.bounds = Some(bounds);
+		self
+	}
+
+	/// # With Progress.
+	///
+	/// This method is used to add the search's progress to a [`Candidate`].
+	pub(super) const fn with_progress(mut self, progress: (u8, u8)) -> Self {
+		self.progress = Some(progress);
+		self
+	}
+
+	/// # With Undo.
+	///
+	/// This method is used to note whether the previous decision can be
+	/// undone.
+	pub(super) const fn with_undo(mut self, undo: bool) -> Self {
+		self.undo = undo;
+		self
+	}
+
+	/// # With Saved.
+	///
+	/// Mark a [`Candidate`] — created from an existing conversion — as
+	/// previously saved, for review.
+	pub(super) const fn with_saved(mut self) -> Self {
+		self.saved = true;
+		self
+	}
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use refract_core::ImageKind;
+
+	#[test]
+	fn t_verify_dimensions() {
+		let can = || Candidate {
+			buf: Arc::from(vec![0; 6 * 4 * 4]),
+			width: 6,
+			height: 4,
+			row_size: 6 * 4,
+			quality: Quality::Lossless(ImageKind::Webp),
+			count: 1,
+			size: 100,
+			saved: false,
+			hash: None,
+			dssim: None,
+			bounds: None,
+			progress: None,
+			undo: false,
+		};
+
+		assert!(can().verify_dimensions(6, 4).is_ok(), "Dimensions should match.");
+		for (w, h) in [(4, 6), (6, 5), (8, 4), (0, 0), (usize::MAX, 4)] {
+			assert_eq!(
+				can().verify_dimensions(w, h).err(),
+				Some(RefractError::Dimensions),
+				"Dimensions {w}x{h} should not match.",
+			);
+		}
+	}
 }
@@ -0,0 +1,316 @@
+/*!
+# `Refract GTK` - CI Output
+*/
+
+use crate::progress::Event;
+use dactyl::{
+	NicePercent,
+	NiceU64,
+	traits::IntDivFloat,
+};
+use refract_core::Quality;
+use std::{
+	fmt::Write as _,
+	io::Write,
+	path::{
+		Path,
+		PathBuf,
+	},
+	sync::{
+		Mutex,
+		PoisonError,
+	},
+};
+
+
+
+/// # State.
+///
+/// This is `None` unless `--ci` was passed.
+static CI: Mutex<Option<Ci>> = Mutex::new(None);
+
+
+
+#[derive(Debug, Default)]
+/// # CI Results.
+///
+/// When enabled with `--ci`, the results of each source are collected as
+/// they happen, then written out at the end of the run as GitHub Actions
+/// workflow annotations (to STDOUT) and a Markdown job summary (to
+/// `$GITHUB_STEP_SUMMARY`, or STDOUT if unset), so a refract-based check can
+/// comment sizes and regressions directly on pull requests.
+struct Ci {
+	/// # Current Source.
+	current: Option<Entry>,
+
+	/// # Finished Sources.
+	done: Vec<Entry>,
+
+	/// # Errors Not Tied to a Source.
+	errors: Vec<String>,
+}
+
+impl Ci {
+	/// # Finish Current Source.
+	fn finish(&mut self) {
+		if let Some(entry) = self.current.take() { self.done.push(entry); }
+	}
+
+	/// # Annotations.
+	///
+	/// One workflow command per saved output, source without savings, and
+	/// error.
+	fn annotations(&self) -> String {
+		let mut out = String::new();
+		for entry in &self.done {
+			let file = escape_property(&entry.name());
+			for &(quality, size) in &entry.saved {
+				let _res = writeln!(
+					out,
+					"::notice file={file},title=Refract::{}",
+					escape_data(&format!(
+						"Created {} with {}, saving {} bytes ({}).",
+						quality.kind(),
+						quality,
+						NiceU64::from(entry.size.saturating_sub(size as u64)),
+						NicePercent::from(entry.savings(size)),
+					)),
+				);
+			}
+
+			if entry.saved.is_empty() && entry.errors.is_empty() {
+				let _res = writeln!(
+					out,
+					"::warning file={file},title=Refract::{}",
+					escape_data(entry.skipped.as_deref().map_or_else(
+						|| "No conversion was smaller than the source.".to_owned(),
+						|reason| format!("Skipped ({reason})."),
+					).as_str()),
+				);
+			}
+
+			for err in &entry.errors {
+				let _res = writeln!(out, "::error file={file},title=Refract::{}", escape_data(err));
+			}
+		}
+
+		for err in &self.errors {
+			let _res = writeln!(out, "::error title=Refract::{}", escape_data(err));
+		}
+
+		out
+	}
+
+	/// # Summary.
+	///
+	/// A Markdown table listing each source with its best conversion, if
+	/// any, followed by the totals.
+	fn summary(&self) -> String {
+		let mut out = String::from("## Refract\n\n| Image | Original | Best | Savings |\n| ----- | -------: | ---- | ------: |\n");
+		let mut before = 0_u64;
+		let mut after = 0_u64;
+		for entry in &self.done {
+			let name = entry.name().replace('|', "\\|");
+			before += entry.size;
+			if let Some(&(quality, size)) = entry.saved.iter().min_by_key(|(_, s)| *s) {
+				after += size as u64;
+				let _res = writeln!(
+					out,
+					"| `{name}` | {} | {} ({}, {}) | {} |",
+					NiceU64::from(entry.size),
+					NiceU64::from(size as u64),
+					quality.kind(),
+					quality,
+					NicePercent::from(entry.savings(size)),
+				);
+			}
+			else {
+				after += entry.size;
+				let _res = writeln!(
+					out,
+					"| `{name}` | {} | {} | — |",
+					NiceU64::from(entry.size),
+					if entry.errors.is_empty() { "—" } else { "**Error**" },
+				);
+			}
+		}
+
+		let _res = writeln!(
+			out,
+			"\n**Total:** {} → {} bytes ({} saved).",
+			NiceU64::from(before),
+			NiceU64::from(after),
+			NicePercent::from(before.saturating_sub(after).div_float(before).unwrap_or(0.0)),
+		);
+		out
+	}
+}
+
+
+
+#[derive(Debug)]
+/// # Source Results.
+struct Entry {
+	/// # Source Path.
+	path: PathBuf,
+
+	/// # Source Size.
+	size: u64,
+
+	/// # Saved Outputs.
+	saved: Vec<(Quality, usize)>,
+
+	/// # Skip Reason.
+	skipped: Option<String>,
+
+	/// # Errors.
+	errors: Vec<String>,
+}
+
+impl Entry {
+	/// # New.
+	fn new(path: &Path) -> Self {
+		Self {
+			path: path.to_path_buf(),
+			size: std::fs::metadata(path).map_or(0, |m| m.len()),
+			saved: Vec::new(),
+			skipped: None,
+			errors: Vec::new(),
+		}
+	}
+
+	/// # Display Name.
+	///
+	/// Annotations expect paths relative to the workspace, so strip the
+	/// working directory from the front, if present.
+	fn name(&self) -> String {
+		std::env::current_dir().ok()
+			.and_then(|dir| self.path.strip_prefix(dir).ok().map(Path::to_path_buf))
+			.unwrap_or_else(|| self.path.clone())
+			.to_string_lossy()
+			.into_owned()
+	}
+
+	/// # Savings (Percent).
+	fn savings(&self, size: usize) -> f64 {
+		self.size.saturating_sub(size as u64).div_float(self.size).unwrap_or(0.0)
+	}
+}
+
+
+
+/// # Enable.
+pub(super) fn enable() {
+	CI.lock().unwrap_or_else(PoisonError::into_inner).replace(Ci::default());
+}
+
+/// # Record Event.
+///
+/// Note the event, if enabled. Once everything is done, the results are
+/// written out.
+pub(super) fn record(event: Event) {
+	let mut guard = CI.lock().unwrap_or_else(PoisonError::into_inner);
+	let Some(ci) = guard.as_mut() else { return; };
+
+	match event {
+		Event::Source(path) => {
+			ci.finish();
+			ci.current = Some(Entry::new(path));
+		},
+		Event::Saved(quality, size, _, _) => {
+			if let Some(entry) = ci.current.as_mut() { entry.saved.push((quality, size)); }
+		},
+		Event::Skipped(reason) => {
+			if let Some(entry) = ci.current.as_mut() { entry.skipped = Some(reason.to_owned()); }
+		},
+		Event::Error(err) =>
+			if let Some(entry) = ci.current.as_mut() { entry.errors.push(err.to_string()); }
+			else { ci.errors.push(err.to_string()); },
+		Event::Done => {
+			ci.finish();
+			let ci = std::mem::take(ci);
+			drop(guard);
+			write_results(&ci);
+		},
+		Event::Encoder(_) | Event::Candidate(_, _, _) | Event::Decision(_, _) |
+		Event::Fallback(_, _, _) | Event::Timing(_, _, _) => {},
+	}
+}
+
+
+
+/// # Escape Data.
+///
+/// Workflow command messages need their percents and line breaks escaped.
+fn escape_data(src: &str) -> String {
+	src.replace('%', "%25")
+		.replace('\r', "%0D")
+		.replace('\n', "%0A")
+}
+
+/// # Escape Property.
+///
+/// Property values — like the file — also need their colons and commas
+/// escaped.
+fn escape_property(src: &str) -> String {
+	escape_data(src)
+		.replace(':', "%3A")
+		.replace(',', "%2C")
+}
+
+/// # Write Results.
+///
+/// Print the annotations to STDOUT, and append the summary to the job
+/// summary file (or STDOUT). Write errors are ignored; there's nowhere to
+/// report them!
+fn write_results(ci: &Ci) {
+	let summary = ci.summary();
+	let mut out = ci.annotations();
+
+	let written = std::env::var_os("GITHUB_STEP_SUMMARY")
+		.filter(|p| ! p.is_empty())
+		.and_then(|p| std::fs::OpenOptions::new().create(true).append(true).open(p).ok())
+		.is_some_and(|mut f| f.write_all(summary.as_bytes()).is_ok());
+	if ! written {
+		out.push('\n');
+		out.push_str(&summary);
+	}
+
+	let mut handle = std::io::stdout().lock();
+	let _res = handle.write_all(out.as_bytes()).and_then(|()| handle.flush());
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use refract_core::ImageKind;
+	use std::num::NonZeroU8;
+
+	#[test]
+	fn t_annotations() {
+		let mut ci = Ci::default();
+		ci.done.push(Entry {
+			path: PathBuf::from("/nowhere/a,b:c.jpg"),
+			size: 1000,
+			saved: vec![(Quality::Lossy(ImageKind::Webp, NonZeroU8::new(80).unwrap()), 750)],
+			skipped: None,
+			errors: Vec::new(),
+		});
+		ci.done.push(Entry {
+			path: PathBuf::from("/nowhere/d.png"),
+			size: 1000,
+			saved: Vec::new(),
+			skipped: None,
+			errors: vec!["100% broken\nsorry".to_owned()],
+		});
+
+		assert_eq!(
+			ci.annotations(),
+			"::notice file=/nowhere/a%2Cb%3Ac.jpg,title=Refract::Created WebP with quality 80, saving 250 bytes (25.00%25).\n\
+			::error file=/nowhere/d.png,title=Refract::100%25 broken%0Asorry\n",
+		);
+		assert!(ci.summary().ends_with("**Total:** 2,000 → 1,750 bytes (12.50% saved).\n"));
+	}
+}
@@ -0,0 +1,367 @@
+/*!
+# `Refract GTK` - Config
+*/
+
+use crate::{
+	CLI_AVIF_YUV420,
+	CLI_BACKGROUND,
+	CLI_DEDUPE,
+	CLI_DURABLE,
+	CLI_GREYSCALE,
+	CLI_JPEG,
+	CLI_JXL_PROGRESSIVE,
+	CLI_KEEP_METADATA,
+	CLI_LOSSLESS_LAST,
+	CLI_NO_AVIF,
+	CLI_NO_JXL,
+	CLI_NO_LOSSLESS,
+	CLI_NO_LOSSY,
+	CLI_NO_WEBP,
+	CLI_NO_YCBCR,
+	CLI_PHOTO_LOSSLESS,
+	CLI_PICTURE,
+	CLI_PNG,
+	CLI_SIDECAR,
+	CLI_SMART,
+	CLI_WEBP_SHARP_YUV,
+};
+use refract_core::RefractError;
+use refract_frontend::Naming;
+use std::path::PathBuf;
+use toml::{
+	Table,
+	Value,
+};
+
+
+
+/// # Toggles.
+///
+/// The simple boolean settings: key, default, and the corresponding flag.
+const TOGGLES: [(&str, bool, u32); 16] = [
+	("lossless", true, CLI_NO_LOSSLESS),
+	("lossy", true, CLI_NO_LOSSY),
+	("ycbcr", true, CLI_NO_YCBCR),
+	("avif-yuv420", false, CLI_AVIF_YUV420),
+	("jxl-progressive", false, CLI_JXL_PROGRESSIVE),
+	("webp-sharp-yuv", false, CLI_WEBP_SHARP_YUV),
+	("lossless-last", false, CLI_LOSSLESS_LAST),
+	("photo-lossless", false, CLI_PHOTO_LOSSLESS),
+	("smart", false, CLI_SMART),
+	("greyscale", false, CLI_GREYSCALE),
+	("keep-metadata", false, CLI_KEEP_METADATA),
+	("background", false, CLI_BACKGROUND),
+	("dedupe", false, CLI_DEDUPE),
+	("durable", false, CLI_DURABLE),
+	("picture", false, CLI_PICTURE),
+	("sidecar", false, CLI_SIDECAR),
+];
+
+/// # Tunables.
+///
+/// The preset keys for the non-flag settings, and the equivalent CLI options.
+const TUNABLES: [(&str, &str); 8] = [
+	("avif-codec", "--avif-codec"),
+	("jxl-decoding-speed", "--jxl-decoding-speed"),
+	("jxl-effort", "--jxl-effort"),
+	("lossless-above", "--lossless-above"),
+	("max-size", "--max-size"),
+	("max-steps", "--max-steps"),
+	("time-limit", "--time-limit"),
+	("webp-method", "--webp-method"),
+];
+
+/// # Built-In Presets.
+///
+/// These are always available, though the config file can override them by
+/// name.
+const BUILT_IN: &str = r#"
+[archival]
+formats = ["jxl"]
+lossy = false
+keep-metadata = true
+durable = true
+sidecar = true
+jxl-effort = 9
+
+[thumbnails]
+formats = ["avif", "webp"]
+lossless = false
+max-size = "24K"
+max-steps = 8
+webp-method = 4
+
+[web-hero]
+formats = ["avif", "webp"]
+lossless = false
+avif-yuv420 = true
+webp-sharp-yuv = true
+picture = true
+"#;
+
+
+#[derive(Debug, Clone)]
+/// # Job Preset.
+///
+/// A preset is a named bundle of encoding settings, stored in the config file
+/// under `[presets.NAME]`, and expressed internally as the equivalent `CLI_*`
+/// flags and `--option` values.
+///
+/// ```toml
+/// [presets.web-hero]
+/// formats = ["avif", "webp"] # Default: avif, jxl, webp.
+/// lossless = false           # Default: true.
+/// lossy = true               # Default: true.
+/// ycbcr = true               # Default: true.
+/// avif-yuv420 = false        # Default: false.
+/// jxl-progressive = false    # Default: false.
+/// webp-sharp-yuv = false     # Default: false.
+/// lossless-last = true       # Default: false.
+/// photo-lossless = false     # Default: false.
+/// smart = false              # Default: false.
+/// greyscale = false          # Default: false.
+/// keep-metadata = false      # Default: false.
+/// background = false         # Default: false.
+/// dedupe = false             # Default: false.
+/// durable = true             # Default: false.
+/// picture = true             # Default: false.
+/// sidecar = false            # Default: false.
+///
+/// # Optional; same as the corresponding --option.
+/// avif-codec = "aom"
+/// jxl-decoding-speed = 0
+/// jxl-effort = 7
+/// lossless-above = "500K"
+/// max-size = "200K"
+/// max-steps = 10
+/// time-limit = 60
+/// webp-method = 4
+///
+/// # Optional; same as --quality FORMAT=VALUE.
+/// quality = { avif = "20-35", webp = "70-90" }
+/// ```
+pub(super) struct Preset {
+	/// # Name.
+	pub(super) name: String,
+
+	/// # Flags.
+	pub(super) flags: u32,
+
+	/// # Tunables.
+	///
+	/// Any other settings, as CLI option/value pairs for
+	/// [`set_tunable`](crate::set_tunable).
+	pub(super) tunables: Vec<(&'static str, String)>,
+}
+
+impl TryFrom<(&str, &Value)> for Preset {
+	type Error = RefractError;
+
+	fn try_from((name, src): (&str, &Value)) -> Result<Self, Self::Error> {
+		let src = src.as_table().ok_or(RefractError::Config)?;
+		let mut flags: u32 = 0;
+
+		// Formats are listed by name, if at all.
+		if let Some(formats) = src.get("formats") {
+			let formats = formats.as_array().ok_or(RefractError::Config)?;
+			flags |= CLI_NO_AVIF | CLI_NO_JXL | CLI_NO_WEBP;
+			for f in formats {
+				match f.as_str().map(str::to_ascii_lowercase).as_deref() {
+					Some("avif") => { flags &= ! CLI_NO_AVIF; },
+					Some("jxl" | "jpeg xl" | "jpegxl") => { flags &= ! CLI_NO_JXL; },
+					Some("webp") => { flags &= ! CLI_NO_WEBP; },
+					Some("png") => { flags |= CLI_PNG; },
+					Some("jpeg" | "jpg") => { flags |= CLI_JPEG; },
+					_ => return Err(RefractError::Config),
+				}
+			}
+		}
+
+		// Everything else is a simple toggle.
+		for (key, default, flag) in TOGGLES {
+			let v = match src.get(key) {
+				Some(v) => v.as_bool().ok_or(RefractError::Config)?,
+				None => default,
+			};
+
+			// The "no" flags are set when the value is false; the others when
+			// it is true.
+			if v != default { flags |= flag; }
+		}
+
+		// The tunables are passed through as strings; they'll be validated
+		// when applied.
+		let mut tunables = Vec::new();
+		for (key, opt) in TUNABLES {
+			match src.get(key) {
+				Some(Value::String(v)) => { tunables.push((opt, v.clone())); },
+				Some(Value::Integer(v)) => { tunables.push((opt, v.to_string())); },
+				Some(_) => return Err(RefractError::Config),
+				None => {},
+			}
+		}
+		match src.get("quality") {
+			Some(Value::Table(all)) => for (k, v) in all {
+				let v = match v {
+					Value::String(v) => v.clone(),
+					Value::Integer(v) => v.to_string(),
+					Value::Float(v) => v.to_string(),
+					_ => return Err(RefractError::Config),
+				};
+				tunables.push(("--quality", format!("{k}={v}")));
+			},
+			Some(_) => return Err(RefractError::Config),
+			None => {},
+		}
+
+		Ok(Self { name: name.to_owned(), flags, tunables })
+	}
+}
+
+
+
+/// # Settings Table.
+///
+/// This is the inverse of the [`Preset`] parsing, expressing the `CLI_*`
+/// flags as a table of settings, e.g. for a job file.
+pub(super) fn settings_table(flags: u32) -> Table {
+	let mut out = Table::new();
+	out.insert("formats".to_owned(), Value::Array(
+		[(CLI_NO_AVIF, "avif"), (CLI_NO_JXL, "jxl"), (CLI_NO_WEBP, "webp")].into_iter()
+			.filter(|(flag, _)| 0 == flags & flag)
+			.map(|(_, name)| name)
+			.chain((0 != flags & CLI_PNG).then_some("png"))
+			.chain((0 != flags & CLI_JPEG).then_some("jpeg"))
+			.map(Value::from)
+			.collect()
+	));
+	for (key, default, flag) in TOGGLES {
+		out.insert(key.to_owned(), Value::Boolean(default == (0 == flags & flag)));
+	}
+	out
+}
+
+/// # Config File Path.
+///
+/// This is `$XDG_CONFIG_HOME/refract/refract.toml`, falling back to
+/// `$HOME/.config/refract/refract.toml` (or `%APPDATA%\refract\refract.toml`
+/// on Windows).
+pub(super) fn config_path() -> Option<PathBuf> {
+	let mut out = std::env::var_os("XDG_CONFIG_HOME")
+		.filter(|v| ! v.is_empty())
+		.map(PathBuf::from)
+		.or_else(config_dir)?;
+	out.push("refract");
+	out.push("refract.toml");
+	Some(out)
+}
+
+#[cfg(not(windows))]
+/// # Default Config Directory.
+fn config_dir() -> Option<PathBuf> {
+	std::env::var_os("HOME").map(|v| PathBuf::from(v).join(".config"))
+}
+
+#[cfg(windows)]
+/// # Default Config Directory.
+fn config_dir() -> Option<PathBuf> {
+	std::env::var_os("APPDATA").filter(|v| ! v.is_empty()).map(PathBuf::from)
+}
+
+/// # Load Config.
+///
+/// Parse and return the presets — built-in and custom, sorted by name — and
+/// output naming templates defined in the config file. If there is no config
+/// file, the defaults are returned.
+///
+/// Naming templates are set under `[naming]`, keyed by format:
+///
+/// ```toml
+/// [naming]
+/// avif = "{src_stem}.{ext}"
+/// webp = "{src_stem}-{src_ext}.{ext}"
+/// html = "{src_stem}.picture.html"
+/// ```
+///
+/// ## Errors
+///
+/// An error is returned if the file exists but is malformed.
+pub(super) fn load() -> Result<(Vec<Preset>, Naming), RefractError> {
+	let mut naming = Naming::default();
+	let mut presets = built_in();
+	let Some(raw) = config_path().and_then(|p| std::fs::read_to_string(p).ok())
+	else { return Ok((presets, naming)); };
+
+	let raw = raw.parse::<Table>().map_err(|_| RefractError::Config)?;
+	match raw.get("presets") {
+		Some(Value::Table(all)) => for (k, v) in all {
+			let preset = Preset::try_from((k.as_str(), v))?;
+			presets.retain(|p| p.name != preset.name);
+			presets.push(preset);
+		},
+		Some(_) => return Err(RefractError::Config),
+		None => {},
+	}
+	presets.sort_by(|a, b| a.name.cmp(&b.name));
+
+	match raw.get("naming") {
+		Some(Value::Table(all)) => for (k, v) in all {
+			naming.set(k, v.as_str().ok_or(RefractError::Config)?)?;
+		},
+		Some(_) => return Err(RefractError::Config),
+		None => {},
+	}
+
+	Ok((presets, naming))
+}
+
+/// # Built-In Presets.
+///
+/// Parse and return the [`BUILT_IN`] presets.
+fn built_in() -> Vec<Preset> {
+	BUILT_IN.parse::<Table>().ok()
+		.map(|all| all.iter()
+			.filter_map(|(k, v)| Preset::try_from((k.as_str(), v)).ok())
+			.collect()
+		)
+		.unwrap_or_default()
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn t_built_in() {
+		let all = built_in();
+		assert_eq!(
+			all.iter().map(|p| p.name.as_str()).collect::<Vec<_>>(),
+			["archival", "thumbnails", "web-hero"],
+		);
+
+		let thumbs = &all[1];
+		assert_eq!(thumbs.flags & (CLI_NO_AVIF | CLI_NO_JXL | CLI_NO_WEBP | CLI_NO_LOSSLESS), CLI_NO_JXL | CLI_NO_LOSSLESS);
+		assert!(thumbs.tunables.contains(&("--max-size", "24K".to_owned())));
+		assert!(thumbs.tunables.contains(&("--max-steps", "8".to_owned())));
+	}
+
+	#[test]
+	fn t_tunables() {
+		let raw: Table = "jxl-effort = 7\nmax-size = \"1M\"\nquality = { avif = \"20-35\", jxl = 1.5 }"
+			.parse()
+			.expect("Invalid TOML.");
+		let preset = Preset::try_from(("test", &Value::Table(raw))).expect("Invalid preset.");
+		assert_eq!(preset.flags, 0);
+		assert_eq!(preset.tunables, [
+			("--jxl-effort", "7".to_owned()),
+			("--max-size", "1M".to_owned()),
+			("--quality", "avif=20-35".to_owned()),
+			("--quality", "jxl=1.5".to_owned()),
+		]);
+
+		let raw: Table = "webp-method = true".parse().expect("Invalid TOML.");
+		assert!(Preset::try_from(("test", &Value::Table(raw))).is_err());
+	}
+}
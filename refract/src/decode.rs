@@ -0,0 +1,41 @@
+/*!
+# `Refract GTK` - Decode
+
+`refract decode <FILE> [-o <FILE>]` converts an AVIF, JPEG XL, or WebP image
+back to PNG — no GUI — so it can be opened in tools that don't support the
+newer formats yet.
+*/
+
+use dactyl::NiceU64;
+use refract_core::RefractError;
+use std::path::{
+	Path,
+	PathBuf,
+};
+
+
+
+/// # Decode.
+///
+/// Decode the source and save it as a PNG, either to `dst` or alongside the
+/// source with a `.png` extension appended.
+///
+/// ## Errors
+///
+/// Returns an error if the source cannot be read or decoded, or the PNG
+/// cannot be saved.
+pub(super) fn decode(src: &Path, dst: Option<PathBuf>, durable: bool)
+-> Result<(), RefractError> {
+	let raw = std::fs::read(src).map_err(|_| RefractError::Read)?;
+	let png = refract_core::decode_to_png(&raw)?;
+
+	let dst = dst.unwrap_or_else(|| {
+		let mut dst = src.to_path_buf();
+		dst.as_mut_os_string().push(".png");
+		dst
+	});
+	refract_frontend::save(&dst, &png, durable)?;
+
+	println!("{} ({} bytes)", dst.display(), NiceU64::from(png.len()));
+	Ok(())
+}
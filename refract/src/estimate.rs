@@ -0,0 +1,132 @@
+/*!
+# `Refract GTK` - Quick Estimates
+
+`refract estimate <PATH(S)>` encodes each source once per enabled format at a
+fixed, medium quality and prints the projected savings to STDOUT — no GUI,
+no feedback — to help triage which images are worth the full guided
+treatment.
+*/
+
+use crate::{
+	CLI_GREYSCALE,
+	CLI_JPEG,
+	CLI_NO_AVIF,
+	CLI_NO_JXL,
+	CLI_NO_WEBP,
+	CLI_PNG,
+};
+use dactyl::{
+	NicePercent,
+	NiceU64,
+	traits::{
+		IntDivFloat,
+		NiceInflection,
+	},
+};
+use refract_core::{
+	EncodeIter,
+	ErrorContext,
+	ImageKind,
+	Input,
+	RefractError,
+};
+use std::{
+	num::NonZeroUsize,
+	path::{
+		Path,
+		PathBuf,
+	},
+};
+
+
+
+/// # Estimate.
+///
+/// Print the projected savings for each source and format, followed by a
+/// summary assuming the smallest format is chosen for each.
+///
+/// ## Errors
+///
+/// Returns an error if there are no sources or no encoders.
+pub(super) fn estimate(paths: &[PathBuf], opts: u32) -> Result<(), RefractError> {
+	if paths.is_empty() { return Err(RefractError::NothingDoing); }
+
+	let encoders: Vec<ImageKind> = [
+		(ImageKind::Webp, CLI_NO_WEBP),
+		(ImageKind::Avif, CLI_NO_AVIF),
+		(ImageKind::Jxl, CLI_NO_JXL),
+	]
+		.into_iter()
+		.filter_map(|(k, f)| (0 == opts & f).then_some(k))
+		.chain((0 != opts & CLI_PNG).then_some(ImageKind::Png))
+		.chain((0 != opts & CLI_JPEG).then_some(ImageKind::Jpeg))
+		.collect();
+	if encoders.is_empty() { return Err(RefractError::NoEncoders); }
+
+	let mut count = 0_usize;
+	let mut total_before = 0_usize;
+	let mut total_after = 0_usize;
+	for path in paths {
+		println!("{}", path.display());
+		let src = match read_source(path, 0 != opts & CLI_GREYSCALE) {
+			Ok(src) => src,
+			Err(e) => {
+				println!("    {}", ErrorContext::new(e));
+				continue;
+			},
+		};
+
+		let before = src.size();
+		let mut best = before;
+		for &kind in &encoders {
+			// JPEG would lose the transparency.
+			if kind == ImageKind::Jpeg && src.has_alpha() {
+				println!("    {kind}: skipped (transparency)");
+				continue;
+			}
+
+			match EncodeIter::estimate(&src, kind, 0) {
+				Ok(out) => {
+					let after = out.size().map_or(before, NonZeroUsize::get);
+					best = best.min(after);
+					println!("    {kind}: {}", savings(before, after));
+				},
+				Err(RefractError::TooBig) => { println!("    {kind}: no savings"); },
+				Err(e) => { println!("    {kind}: {}", ErrorContext::new(e)); },
+			}
+		}
+
+		count += 1;
+		total_before += before;
+		total_after += best;
+	}
+
+	println!(
+		"----\n{}, using the smallest format for each: {}",
+		count.nice_inflect("image", "images"),
+		savings(total_before, total_after),
+	);
+
+	Ok(())
+}
+
+
+
+/// # Read Source.
+fn read_source(path: &Path, greyscale: bool) -> Result<Input<'static>, RefractError> {
+	let raw: &[u8] = &std::fs::read(path).map_err(|_| RefractError::Read)?;
+	let mut out = Input::try_from(raw)?;
+	if greyscale { out = out.into_greyscale(); }
+	Ok(out)
+}
+
+/// # Format Savings.
+fn savings(before: usize, after: usize) -> String {
+	let diff = before.saturating_sub(after);
+	format!(
+		"{} → {} bytes (-{})",
+		NiceU64::from(before),
+		NiceU64::from(after),
+		NicePercent::from(diff.div_float(before).unwrap_or(0.0)),
+	)
+}
@@ -0,0 +1,163 @@
+/*!
+# `Refract GTK` - Job Files
+
+A job file is a portable snapshot of a queue and the settings it should be
+run with, letting a large batch be exported from one machine and picked up
+on another (or split across several):
+
+```toml
+root = "/home/user/images"
+paths = ["a/one.jpg", "b/two.png"]
+
+[settings]
+formats = ["avif", "webp"]
+lossless = true
+# …
+```
+
+Paths are stored relative to `root` so they can be rebased onto wherever
+the same tree lives on the importing machine.
+*/
+
+use crate::config::{
+	Preset,
+	settings_table,
+};
+use refract_core::RefractError;
+use std::path::{
+	Path,
+	PathBuf,
+};
+use toml::{
+	Table,
+	Value,
+};
+
+
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+/// # Job.
+pub(super) struct Job {
+	/// # Flags.
+	pub(super) flags: u32,
+
+	/// # Paths.
+	pub(super) paths: Vec<PathBuf>,
+}
+
+impl Job {
+	/// # Load.
+	///
+	/// Read and parse a job file, rebasing its paths onto `root`, if
+	/// provided, or the root they were exported from otherwise.
+	///
+	/// ## Errors
+	///
+	/// Returns an error if the file cannot be read or is malformed.
+	pub(super) fn load(file: &Path, root: Option<&Path>) -> Result<Self, RefractError> {
+		let raw = std::fs::read_to_string(file).map_err(|_| RefractError::Job)?;
+		Self::parse(&raw, root)
+	}
+
+	/// # Parse.
+	fn parse(raw: &str, root: Option<&Path>) -> Result<Self, RefractError> {
+		let raw = raw.parse::<Table>().map_err(|_| RefractError::Job)?;
+
+		let root = match (root, raw.get("root")) {
+			(Some(root), _) => root.to_path_buf(),
+			(None, Some(Value::String(root))) => PathBuf::from(root),
+			_ => return Err(RefractError::Job),
+		};
+
+		let paths = raw.get("paths")
+			.and_then(Value::as_array)
+			.ok_or(RefractError::Job)?
+			.iter()
+			.map(|p| p.as_str().map(|p| root.join(p)).ok_or(RefractError::Job))
+			.collect::<Result<Vec<_>, _>>()?;
+
+		let flags = match raw.get("settings") {
+			Some(v) => Preset::try_from(("job", v)).map_err(|_| RefractError::Job)?.flags,
+			None => 0,
+		};
+
+		Ok(Self { flags, paths })
+	}
+
+	/// # Save.
+	///
+	/// Write the job to a file.
+	///
+	/// ## Errors
+	///
+	/// Returns an error if the file cannot be written.
+	pub(super) fn save(&self, file: &Path) -> Result<(), RefractError> {
+		write_atomic::write_file(file, self.to_toml().as_bytes())
+			.map_err(|_| RefractError::Write)
+	}
+
+	/// # As TOML.
+	fn to_toml(&self) -> String {
+		let root = common_root(&self.paths);
+		let paths: Vec<Value> = self.paths.iter()
+			.map(|p| Value::from(
+				p.strip_prefix(&root).unwrap_or(p).to_string_lossy().into_owned()
+			))
+			.collect();
+
+		let mut out = Table::new();
+		out.insert("root".to_owned(), Value::from(root.to_string_lossy().into_owned()));
+		out.insert("paths".to_owned(), Value::Array(paths));
+		out.insert("settings".to_owned(), Value::Table(settings_table(self.flags)));
+		out.to_string()
+	}
+}
+
+
+
+/// # Common Root.
+///
+/// Return the deepest directory containing all of the paths, or an empty
+/// path if there isn't one.
+fn common_root(paths: &[PathBuf]) -> PathBuf {
+	let Some(first) = paths.first().and_then(|p| p.parent()) else {
+		return PathBuf::new();
+	};
+
+	first.ancestors()
+		.find(|dir| paths.iter().all(|p| p.starts_with(dir)))
+		.map_or_else(PathBuf::new, Path::to_path_buf)
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		CLI_NO_JXL,
+		CLI_PICTURE,
+	};
+
+	#[test]
+	fn t_roundtrip() {
+		let job = Job {
+			flags: CLI_NO_JXL | CLI_PICTURE,
+			paths: vec![
+				PathBuf::from("/home/a/images/one.jpg"),
+				PathBuf::from("/home/a/images/sub/two.png"),
+			],
+		};
+
+		let raw = job.to_toml();
+		assert!(raw.contains(r#"root = "/home/a/images""#), "Root not shared.");
+		assert_eq!(Job::parse(&raw, None), Ok(job));
+
+		// Rebase.
+		let moved = Job::parse(&raw, Some(Path::new("/mnt/b"))).expect("Parse failed.");
+		assert_eq!(
+			moved.paths,
+			vec![PathBuf::from("/mnt/b/one.jpg"), PathBuf::from("/mnt/b/sub/two.png")],
+		);
+	}
+}
@@ -0,0 +1,146 @@
+/*!
+# `Refract GTK` - Keyboard Shortcuts
+*/
+
+use gtk::prelude::*;
+use refract_core::RefractError;
+
+
+
+/// # Keyboard Shortcuts.
+///
+/// This is the one and only list of keyboard shortcuts. Each is bound to its
+/// widget by [`register`], and the same list is used to build the in-app
+/// cheat sheet, so the two can't drift apart.
+const SHORTCUTS: [Shortcut; 27] = [
+	Shortcut::new("mnu_fopen", "activate", "<Control>o", "Open File"),
+	Shortcut::new("mnu_dopen", "activate", "<Shift><Control>o", "Open Directory"),
+	Shortcut::new("mnu_review", "activate", "<Control>r", "Review Saved"),
+	Shortcut::new("mnu_export", "activate", "<Control>e", "Export Queue"),
+	Shortcut::new("mnu_quit", "activate", "<Control>q", "Quit"),
+	Shortcut::new("chk_webp", "activate", "<Control>1", "Toggle WebP"),
+	Shortcut::new("chk_avif", "activate", "<Control>2", "Toggle AVIF"),
+	Shortcut::new("chk_jxl", "activate", "<Control>3", "Toggle JPEG XL"),
+	Shortcut::new("chk_png", "activate", "<Control>4", "Toggle PNG"),
+	Shortcut::new("chk_jpeg", "activate", "<Control>5", "Toggle JPEG"),
+	Shortcut::new("chk_lossless", "activate", "<Control>l", "Toggle Lossless"),
+	Shortcut::new("chk_lossy", "activate", "<Shift><Control>l", "Toggle Lossy"),
+	Shortcut::new("mnu_clear_log", "activate", "<Control>Delete", "Clear Log"),
+	Shortcut::new("chk_dark", "activate", "<Control>n", "Toggle Dark Mode"),
+	Shortcut::new("btn_toggle", "activate", "space", "Toggle A/B View"),
+	Shortcut::new("chk_diff", "activate", "x", "Toggle Differences"),
+	Shortcut::new("chk_full_size", "activate", "1", "Toggle 1:1 View"),
+	Shortcut::new("mnu_zoom_in", "activate", "plus", "Zoom In"),
+	Shortcut::new("mnu_zoom_out", "activate", "minus", "Zoom Out"),
+	Shortcut::new("mnu_zoom_reset", "activate", "0", "Actual Size"),
+	Shortcut::new("btn_discard", "clicked", "d", "Discard Candidate"),
+	Shortcut::new("btn_keep", "clicked", "k", "Keep Candidate"),
+	Shortcut::new("btn_undo", "clicked", "<Control>z", "Undo Keep/Discard"),
+	Shortcut::new("btn_quality", "clicked", "q", "Try a Quality"),
+	Shortcut::new("btn_pause", "clicked", "p", "Pause/Resume Encoding"),
+	Shortcut::new("btn_skip", "clicked", "s", "Skip Format"),
+	Shortcut::new("mnu_shortcuts", "activate", "question", "Show/Hide Shortcuts"),
+];
+
+
+
+/// # Keyboard Shortcut.
+struct Shortcut {
+	/// # Widget ID.
+	///
+	/// This is the widget's ID in the Glade file.
+	id: &'static str,
+
+	/// # Signal.
+	///
+	/// The signal to emit on the widget.
+	signal: &'static str,
+
+	/// # Accelerator.
+	///
+	/// The key combination, in the format understood by
+	/// `gtk_accelerator_parse`.
+	accel: &'static str,
+
+	/// # Description.
+	label: &'static str,
+}
+
+impl Shortcut {
+	/// # New.
+	const fn new(
+		id: &'static str,
+		signal: &'static str,
+		accel: &'static str,
+		label: &'static str,
+	) -> Self {
+		Self { id, signal, accel, label }
+	}
+
+	/// # Parse Accelerator.
+	fn parse(&self) -> Result<(u32, gtk::gdk::ModifierType), RefractError> {
+		let (key, mods) = gtk::accelerator_parse(self.accel);
+		if key == 0 { Err(RefractError::GtkInit) }
+		else { Ok((key, mods)) }
+	}
+}
+
+
+
+/// # Register Shortcuts.
+///
+/// Bind each of the [`SHORTCUTS`] to its widget.
+///
+/// ## Errors
+///
+/// Returns an error if a widget is missing or an accelerator is invalid.
+pub(super) fn register(builder: &gtk::Builder, wnd: &gtk::ApplicationWindow)
+-> Result<(), RefractError> {
+	let group = gtk::AccelGroup::new();
+	wnd.add_accel_group(&group);
+
+	for s in &SHORTCUTS {
+		let widget: gtk::Widget = builder.object(s.id).ok_or(RefractError::GtkInit)?;
+		let (key, mods) = s.parse()?;
+		widget.add_accelerator(s.signal, &group, key, mods, gtk::AccelFlags::VISIBLE);
+	}
+
+	Ok(())
+}
+
+/// # Cheat Sheet.
+///
+/// Build a (non-modal) popover listing each of the [`SHORTCUTS`] alongside
+/// its key combination, attached to the given widget.
+///
+/// ## Errors
+///
+/// Returns an error if an accelerator is invalid.
+pub(super) fn cheat_sheet<W>(relative_to: &W) -> Result<gtk::Popover, RefractError>
+where W: IsA<gtk::Widget> {
+	let grid = gtk::Grid::builder()
+		.row_spacing(6)
+		.column_spacing(24)
+		.margin(12)
+		.build();
+
+	for (row, s) in (0_i32..).zip(SHORTCUTS.iter()) {
+		let (key, mods) = s.parse()?;
+		let keys = gtk::accelerator_get_label(key, mods).ok_or(RefractError::GtkInit)?;
+
+		let lbl_keys = gtk::Label::new(None);
+		lbl_keys.set_markup(&["<b>", &gtk::glib::markup_escape_text(&keys), "</b>"].concat());
+		lbl_keys.set_xalign(1.0);
+		grid.attach(&lbl_keys, 0, row, 1, 1);
+
+		let lbl_desc = gtk::Label::new(Some(s.label));
+		lbl_desc.set_xalign(0.0);
+		grid.attach(&lbl_desc, 1, row, 1, 1);
+	}
+	grid.show_all();
+
+	let pop = gtk::Popover::new(Some(relative_to));
+	pop.set_modal(false);
+	pop.add(&grid);
+	Ok(pop)
+}
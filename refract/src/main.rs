@@ -55,8 +55,25 @@
 
 
 
+mod audit;
 mod candidate;
+mod ci;
+mod config;
+mod decode;
+mod estimate;
+mod job;
+mod keymap;
+mod pause;
+mod pipe;
+mod priority;
+mod progress;
+mod queue;
+mod report;
+mod script;
+mod session;
+mod settings;
 mod share;
+mod theme;
 mod window;
 
 use candidate::Candidate;
@@ -68,45 +85,245 @@ use share::{
 	SisterRx,
 	SisterTx,
 };
-use window::Window;
+use window::{
+	REASON_FORMATS,
+	REASON_MODES,
+	Window,
+};
 
 use argyle::Argument;
-use dowser::Dowser;
 use gtk::{
 	glib::Bytes,
 	prelude::*,
 };
-use refract_core::RefractError;
+use refract_core::{
+	AvifCodec,
+	ImageKind,
+	JxlTuning,
+	RefractError,
+	WebpTuning,
+};
+use refract_frontend::{
+	Existing,
+	Fallback,
+	QualityOverride,
+	Region,
+};
 use std::{
-	path::PathBuf,
+	num::{
+		NonZeroU8,
+		NonZeroUsize,
+	},
+	path::{
+		Path,
+		PathBuf,
+	},
 	rc::Rc,
+	sync::{
+		atomic::{
+			AtomicU8,
+			AtomicU32,
+			AtomicUsize,
+			Ordering::Relaxed,
+		},
+		Mutex,
+	},
+	time::Duration,
 };
 
 
 
 /// # CLI Flag: Format Bits.
-pub(crate) const CLI_FORMATS: u8 =     0b0000_0111;
+///
+/// These are the opt-_out_ next-gen formats; all three being set only means
+/// "no encoders" if the opt-in [`CLI_PNG`] and [`CLI_JPEG`] are unset too.
+pub(crate) const CLI_FORMATS: u32 =     0b0000_0000_0000_0000_0000_0000_0000_0111;
 
 /// # CLI Flag: No Avif.
-pub(crate) const CLI_NO_AVIF: u8 =     0b0000_0001;
+pub(crate) const CLI_NO_AVIF: u32 =     0b0000_0000_0000_0000_0000_0000_0000_0001;
 
 /// # CLI Flag: No JXL.
-pub(crate) const CLI_NO_JXL: u8 =      0b0000_0010;
+pub(crate) const CLI_NO_JXL: u32 =      0b0000_0000_0000_0000_0000_0000_0000_0010;
 
 /// # CLI Flag: No WebP
-pub(crate) const CLI_NO_WEBP: u8 =     0b0000_0100;
+pub(crate) const CLI_NO_WEBP: u32 =     0b0000_0000_0000_0000_0000_0000_0000_0100;
 
 /// # CLI Flag: Mode Bits.
-pub(crate) const CLI_MODES: u8 =       0b0001_1000;
+pub(crate) const CLI_MODES: u32 =       0b0000_0000_0000_0000_0000_0000_0001_1000;
 
 /// # CLI Flag: No Lossless.
-pub(crate) const CLI_NO_LOSSLESS: u8 = 0b0000_1000;
+pub(crate) const CLI_NO_LOSSLESS: u32 = 0b0000_0000_0000_0000_0000_0000_0000_1000;
 
 /// # CLI Flag: No Lossy.
-pub(crate) const CLI_NO_LOSSY: u8 =    0b0001_0000;
+pub(crate) const CLI_NO_LOSSY: u32 =    0b0000_0000_0000_0000_0000_0000_0001_0000;
 
 /// # CLI Flag: No Ycbcr.
-pub(crate) const CLI_NO_YCBCR: u8 =    0b0010_0000;
+pub(crate) const CLI_NO_YCBCR: u32 =    0b0000_0000_0000_0000_0000_0000_0010_0000;
+
+/// # CLI Flag: Durable Saves.
+pub(crate) const CLI_DURABLE: u32 =     0b0000_0000_0000_0000_0000_0000_0100_0000;
+
+/// # CLI Flag: Picture Snippets.
+pub(crate) const CLI_PICTURE: u32 =     0b0000_0000_0000_0000_0000_0000_1000_0000;
+
+/// # CLI Flag: Greyscale.
+pub(crate) const CLI_GREYSCALE: u32 =   0b0000_0000_0000_0000_0000_0001_0000_0000;
+
+/// # CLI Flag: Background Mode.
+pub(crate) const CLI_BACKGROUND: u32 =  0b0000_0000_0000_0000_0000_0010_0000_0000;
+
+/// # CLI Flag: Dedupe Lossless.
+pub(crate) const CLI_DEDUPE: u32 =      0b0000_0000_0000_0000_0000_0100_0000_0000;
+
+/// # CLI Flag: Audit Sidecars.
+pub(crate) const CLI_SIDECAR: u32 =     0b0000_0000_0000_0000_0000_1000_0000_0000;
+
+/// # CLI Flag: Lossless Last.
+pub(crate) const CLI_LOSSLESS_LAST: u32 = 0b0000_0000_0000_0000_0001_0000_0000_0000;
+
+/// # CLI Flag: Progressive JPEG XL.
+pub(crate) const CLI_JXL_PROGRESSIVE: u32 = 0b0000_0000_0000_0000_0010_0000_0000_0000;
+
+/// # CLI Flag: Smart Defaults.
+pub(crate) const CLI_SMART: u32 =       0b0000_0000_0000_0000_0100_0000_0000_0000;
+
+/// # CLI Flag: PNG.
+pub(crate) const CLI_PNG: u32 =         0b0000_0000_0000_0000_1000_0000_0000_0000;
+
+/// # CLI Flag: JPEG.
+pub(crate) const CLI_JPEG: u32 =        0b0000_0000_0000_0001_0000_0000_0000_0000;
+
+/// # CLI Flag: Keep Metadata.
+pub(crate) const CLI_KEEP_METADATA: u32 = 0b0000_0000_0000_0010_0000_0000_0000_0000;
+
+/// # CLI Flag: AVIF 4:2:0.
+pub(crate) const CLI_AVIF_YUV420: u32 = 0b0000_0000_0000_0100_0000_0000_0000_0000;
+
+/// # CLI Flag: WebP Sharp YUV.
+pub(crate) const CLI_WEBP_SHARP_YUV: u32 = 0b0000_0000_0000_1000_0000_0000_0000_0000;
+
+/// # CLI Flag: Lossless Photos.
+///
+/// By default, lossless encoding is skipped for photographic sources.
+pub(crate) const CLI_PHOTO_LOSSLESS: u32 = 0b0000_0000_0001_0000_0000_0000_0000_0000;
+
+/// # CLI Switches.
+///
+/// The command line keys that simply set the corresponding `CLI_*` flag.
+const CLI_SWITCHES: [(&str, u32); 21] = [
+	("--avif-yuv420", CLI_AVIF_YUV420),
+	("--background", CLI_BACKGROUND),
+	("--dedupe-lossless", CLI_DEDUPE),
+	("--durable", CLI_DURABLE),
+	("--greyscale", CLI_GREYSCALE),
+	("--jpeg", CLI_JPEG),
+	("--jxl-progressive", CLI_JXL_PROGRESSIVE),
+	("--keep-metadata", CLI_KEEP_METADATA),
+	("--lossless-last", CLI_LOSSLESS_LAST),
+	("--no-avif", CLI_NO_AVIF),
+	("--no-jxl", CLI_NO_JXL),
+	("--no-lossless", CLI_NO_LOSSLESS),
+	("--no-lossy", CLI_NO_LOSSY),
+	("--no-webp", CLI_NO_WEBP),
+	("--no-ycbcr", CLI_NO_YCBCR),
+	("--photo-lossless", CLI_PHOTO_LOSSLESS),
+	("--picture", CLI_PICTURE),
+	("--png", CLI_PNG),
+	("--sidecar", CLI_SIDECAR),
+	("--smart", CLI_SMART),
+	("--webp-sharp-yuv", CLI_WEBP_SHARP_YUV),
+];
+
+/// # Megapixel.
+const MEGAPIXEL: usize = 1_000_000;
+
+/// # Candidate Cap Limit.
+///
+/// The highest `--max-steps` allowed; nothing takes more steps than this.
+const MAX_STEPS_LIMIT: u8 = 100;
+
+/// # `AVIF` Codec.
+///
+/// This is the index of an [`AvifCodec`], or `u8::MAX` if unset, in which
+/// case the saved setting — or default — applies. See `--avif-codec`.
+static AVIF_CODEC: AtomicU8 = AtomicU8::new(u8::MAX);
+
+/// # Existing Output Policy.
+///
+/// This is the index of an [`Existing`] policy, or `u8::MAX` if unset, in
+/// which case the saved setting — or default — applies. See `--existing`.
+static EXISTING: AtomicU8 = AtomicU8::new(u8::MAX);
+
+/// # Parallel Jobs.
+///
+/// The number of sources to encode at once when no feedback is needed, with
+/// zero meaning one per CPU. See `--jobs`.
+static JOBS: AtomicU8 = AtomicU8::new(1);
+
+/// # `JPEG XL` Effort.
+///
+/// Zero means unset, in which case the saved setting — or default — applies.
+/// See `--jxl-effort`.
+static JXL_EFFORT: AtomicU8 = AtomicU8::new(0);
+
+/// # `JPEG XL` Decoding Speed.
+///
+/// `u8::MAX` means unset, in which case the saved setting — or default —
+/// applies. See `--jxl-decoding-speed`.
+static JXL_DECODING_SPEED: AtomicU8 = AtomicU8::new(u8::MAX);
+
+/// # Lossless Threshold (Bytes).
+///
+/// When non-zero, lossless encoding is run after the lossy search, and only
+/// if the lossy winner is larger than this many bytes.
+static LOSSLESS_ABOVE: AtomicUsize = AtomicUsize::new(0);
+
+/// # Size Budget (Bytes).
+///
+/// When non-zero, the quality search is driven by size rather than feedback,
+/// keeping the best quality that fits. See `--max-size`.
+static MAX_SIZE: AtomicUsize = AtomicUsize::new(0);
+
+/// # Candidate Cap.
+///
+/// The most lossy candidates to try per format, with zero meaning no limit.
+/// `u8::MAX` means unset, in which case the saved setting — or default —
+/// applies. See `--max-steps`.
+static MAX_STEPS: AtomicU8 = AtomicU8::new(u8::MAX);
+
+/// # Preview Size Limit (Pixels).
+///
+/// Sources and candidates with a longer side than this are shown scaled down
+/// to fit, unless viewed 1:1. Zero disables scaling. See `--preview-max`.
+static PREVIEW_MAX: AtomicUsize = AtomicUsize::new(0);
+
+/// # Quality Overrides.
+///
+/// Per-format seeds or bounds for the quality search. See `--quality`.
+static QUALITIES: Mutex<Vec<QualityOverride>> = Mutex::new(Vec::new());
+
+/// # Region of Interest.
+///
+/// The crop to run the quality search on, if any. See `--region`.
+static REGION: Mutex<Option<Region>> = Mutex::new(None);
+
+/// # `WebP` Method.
+///
+/// `u8::MAX` means unset, in which case the saved setting — or default —
+/// applies. See `--webp-method`.
+static WEBP_METHOD: AtomicU8 = AtomicU8::new(u8::MAX);
+
+/// # Streaming Threshold (Pixels).
+///
+/// Lossless `JPEG XL` encodes of sources with more pixels than this are
+/// streamed to bound memory usage. Zero disables streaming altogether. See
+/// `--stream-above`.
+static STREAM_ABOVE: AtomicUsize = AtomicUsize::new(64 * MEGAPIXEL);
+
+/// # Per-Candidate Time Limit (Seconds).
+///
+/// Zero means no limit. See `--time-limit`.
+static TIME_LIMIT: AtomicU32 = AtomicU32::new(0);
 
 
 
@@ -141,49 +358,131 @@ fn main() {
 /// with a pretty CLI error reason.
 fn main__() -> Result<(), RefractError> {
 	init_resources()?;
-	let application = gtk::Application::new(
-		Some("com.refract.gtk"),
-		gtk::gio::ApplicationFlags::default()
-	);
+	let mut paths: Vec<PathBuf> = Vec::new();
+	let mut max_depth: Option<usize> = None;
+	let mut flags = 0_u32;
+	let mut preset: Option<Option<String>> = None;
+	let mut replay: Option<Option<String>> = None;
+	let mut decode: Option<Option<String>> = None;
+	let mut output: Option<PathBuf> = None;
+	let mut out_dir: Option<PathBuf> = None;
+	let mut name_template: Option<String> = None;
+	let mut estimate = false;
+	let mut pipe = pipe::Pipe::default();
+	let mut fallback: Option<Fallback> = None;
+	let mut job: Option<PathBuf> = None;
+	let mut job_root: Option<PathBuf> = None;
+	let mut record: Option<PathBuf> = None;
+	let mut report: Option<PathBuf> = None;
+	let mut report_every = report::DEFAULT_EVERY;
+	let mut tunables: Vec<(&'static str, String)> = Vec::new();
+	let mut unique = true;
 
 	// Load CLI arguments, if any.
-	let args = argyle::args()
-		.with_keywords(include!(concat!(env!("OUT_DIR"), "/argyle.rs")));
-
-	let mut paths = Dowser::default();
-	let mut flags = 0_u8;
-	for arg in args {
+	for arg in argyle::args().with_keywords(include!(concat!(env!("OUT_DIR"), "/argyle.rs"))) {
 		match arg {
+			Argument::Command("decode") => { decode = Some(None); },
+			Argument::Other(s) if matches!(decode, Some(None)) => { decode = Some(Some(s)); },
+			Argument::Command("estimate") => { estimate = true; },
+			Argument::Command("preset") => { preset = Some(None); },
+			Argument::Other(s) if matches!(preset, Some(None)) => { preset = Some(Some(s)); },
+			Argument::Command("replay") => { replay = Some(None); },
+			Argument::Other(s) if matches!(replay, Some(None)) => { replay = Some(Some(s)); },
+
+			Argument::Key("--ci") => { ci::enable(); },
 			Argument::Key("-h" | "--help") => return Err(RefractError::PrintHelp),
-			Argument::Key("--no-avif") => { flags |= CLI_NO_AVIF; },
-			Argument::Key("--no-jxl") => { flags |= CLI_NO_JXL; },
-			Argument::Key("--no-webp") => { flags |= CLI_NO_WEBP; },
-			Argument::Key("--no-lossless") => { flags |= CLI_NO_LOSSLESS; },
-			Argument::Key("--no-lossy") => { flags |= CLI_NO_LOSSY; },
-			Argument::Key("--no-ycbcr") => { flags |= CLI_NO_YCBCR; },
+			Argument::Key("--new-instance") => { unique = false; },
+			Argument::Key("--no-recurse") => { max_depth = Some(0); },
+			Argument::Key("--stdin") => { pipe.enable(); },
 			Argument::Key("-V" | "--version") => return Err(RefractError::PrintVersion),
+			Argument::Key(k) => {
+				if let Some((_, f)) = CLI_SWITCHES.iter().find(|(key, _)| *key == k) { flags |= f; }
+			},
 
-			Argument::KeyWithValue("-l" | "--list", s) => {
-				let _res = paths.read_paths_from_file(s);
+			Argument::KeyWithValue("--quality", s) if s.contains('=') => { tunables.push(("--quality", s)); },
+			Argument::KeyWithValue(k @ ("--format" | "--quality"), s) => { pipe.set(k, &s)?; },
+			Argument::KeyWithValue("--fallback", s) => { fallback = Some(Fallback::try_from(s.as_str())?); },
+			Argument::KeyWithValue("--job", s) => { job = Some(PathBuf::from(s)); },
+			Argument::KeyWithValue("--job-root", s) => { job_root = Some(PathBuf::from(s)); },
+			Argument::KeyWithValue("-l" | "--list", s) => { paths.extend(refract_frontend::read_list(&s)); },
+			Argument::KeyWithValue("--max-depth", s) => { max_depth = Some(s.trim().parse().map_err(|_| RefractError::MaxDepth)?); },
+			Argument::KeyWithValue("--name-template", s) => { name_template = Some(s); },
+			Argument::KeyWithValue("-o" | "--output", s) => { output = Some(PathBuf::from(s)); },
+			Argument::KeyWithValue("--out-dir", s) => { out_dir = Some(PathBuf::from(s)); },
+			Argument::KeyWithValue("--preset", s) => { preset = Some(Some(s)); },
+			Argument::KeyWithValue("--progress", s) => { progress::enable(&s)?; },
+			Argument::KeyWithValue("--record", s) => { record = Some(PathBuf::from(s)); },
+			Argument::KeyWithValue("--report", s) => { report = Some(PathBuf::from(s)); },
+			Argument::KeyWithValue("--report-every", s) => { report_every = s.trim().parse().map_err(|_| RefractError::ReportEvery)?; },
+			Argument::KeyWithValue("--resume", s) => { session::init(Path::new(&s)); },
+			Argument::KeyWithValue(k @ ("--avif-codec" | "--existing" | "--jobs" | "--jxl-decoding-speed" | "--jxl-effort" | "--lossless-above" | "--max-size" | "--max-steps" | "--preview-max" | "--region" | "--stream-above" | "--time-limit" | "--webp-method"), s) => {
+				tunables.push((k, s));
 			},
 
 			// Assume paths.
-			Argument::Other(s) => { paths = paths.with_path(s); },
-			Argument::InvalidUtf8(s) => { paths = paths.with_path(s); },
+			Argument::Other(s) => { paths.push(PathBuf::from(s)); },
+			Argument::InvalidUtf8(s) => { paths.push(PathBuf::from(s)); },
 
 			// Nothing else is relevant.
 			_ => {},
 		}
 	}
 
+	// Load the config, and apply the chosen preset, if any, before anything
+	// set explicitly, so the latter wins.
+	let (presets, mut naming) = config::load()?;
+	let preset = preset.map(|name| name.ok_or(RefractError::NoPreset)).transpose()?;
+	if let Some(name) = preset.as_deref() { flags |= apply_preset(&presets, name)?; }
+	for (k, v) in tunables { set_tunable(k, &v)?; }
+
+	// Pull in the job, if any.
+	if let Some(file) = job {
+		let job = job::Job::load(&file, job_root.as_deref())?;
+		flags |= job.flags;
+		paths.extend(job.paths);
+	}
+
+	// Replaying happens entirely without the GUI.
+	if let Some(file) = replay { return script::replay(Path::new(&file.ok_or(RefractError::Script)?)); }
+
+	// As does decoding.
+	if let Some(file) = decode {
+		return decode::decode(Path::new(&file.ok_or(RefractError::NothingDoing)?), output, 0 != flags & CLI_DURABLE);
+	}
+
+	// And piping.
+	if pipe.is_enabled() { return pipe.run(flags); }
+
+	// And estimates.
+	if estimate {
+		return estimate::estimate(&refract_frontend::crawl(&paths, max_depth), flags);
+	}
+
+	// Start the report, if any.
+	if let Some(report) = report { report::init(report, report_every); }
+
+	// Finish up the naming.
+	if let Some(tpl) = name_template.as_deref() { naming.set_images(tpl)?; }
+	if let Some(dir) = out_dir { naming.set_out_dir(&dir, &paths)?; }
+	let settings = settings::Settings::load();
+
+	// If Refract is already running, hand the paths off to it instead.
+	let application = gtk::Application::new(
+		Some("com.refract.gtk"),
+		if unique { gtk::gio::ApplicationFlags::HANDLES_OPEN }
+		else { gtk::gio::ApplicationFlags::NON_UNIQUE },
+	);
+	if hand_off(&application, &paths) { return Ok(()); }
+
 	application.connect_activate(move |app| {
-		let window = Rc::new(Window::new(app, flags)
+		let window = Rc::new(Window::new(app, flags | settings.flags, naming.clone(), fallback, record.clone())
 				.expect("Unable to build GTK window."));
 
 		// We have to clone this because GTK doesn't do Rust properly. Haha.
-		let paths = paths.clone().into_vec_filtered(window::is_jpeg_png);
+		let paths = refract_frontend::crawl(&paths, max_depth);
 
-		setup_ui(&window, paths);
+		setup_ui_presets(&window, &presets, preset.as_deref());
+		setup_ui(&window, paths, &settings);
 		window.paint();
 	});
 
@@ -192,6 +491,343 @@ fn main__() -> Result<(), RefractError> {
 	Ok(())
 }
 
+/// # Apply Preset.
+///
+/// Find the named preset, apply its tunables, and return its flags.
+fn apply_preset(presets: &[config::Preset], name: &str) -> Result<u32, RefractError> {
+	let preset = presets.iter().find(|p| p.name == name).ok_or(RefractError::NoPreset)?;
+	for (k, v) in &preset.tunables { set_tunable(k, v)?; }
+	Ok(preset.flags)
+}
+
+/// # Hand Off.
+///
+/// If another instance of Refract is already running, pass the paths — if
+/// any — along to it to encode, or else just bring it to the front, and
+/// return `true`. The command line settings of this instance are ignored.
+fn hand_off(application: &gtk::Application, paths: &[PathBuf]) -> bool {
+	if
+		application.register(None::<&gtk::gio::Cancellable>).is_err() ||
+		! application.is_remote()
+	{
+		return false;
+	}
+
+	let files: Vec<gtk::gio::File> = paths.iter().map(gtk::gio::File::for_path).collect();
+	if files.is_empty() { application.activate(); }
+	else { application.open(&files, ""); }
+	true
+}
+
+/// # Set Tunable.
+///
+/// Parse and store the value of one of the process-wide encoder settings,
+/// from the command line or a preset.
+fn set_tunable(key: &str, raw: &str) -> Result<(), RefractError> {
+	match key {
+		"--avif-codec" => {
+			let codec = AvifCodec::from_name(raw)
+				.filter(|c| c.is_available())
+				.ok_or(RefractError::AvifCodec)?;
+			set_avif_codec(codec, false);
+			Ok(())
+		},
+		"--existing" => {
+			set_existing(Existing::try_from(raw)?, false);
+			Ok(())
+		},
+		"--jobs" => {
+			let v = raw.trim().parse::<u8>().map_err(|_| RefractError::Jobs)?;
+			JOBS.store(v, Relaxed);
+			Ok(())
+		},
+		"--jxl-decoding-speed" => {
+			let v = raw.trim().parse::<u8>().ok()
+				.filter(|v| *v <= 4)
+				.ok_or(RefractError::JxlTuning)?;
+			JXL_DECODING_SPEED.store(v, Relaxed);
+			Ok(())
+		},
+		"--jxl-effort" => {
+			let v = raw.trim().parse::<u8>().ok()
+				.filter(|v| (1..=9).contains(v))
+				.ok_or(RefractError::JxlTuning)?;
+			JXL_EFFORT.store(v, Relaxed);
+			Ok(())
+		},
+		"--lossless-above" => {
+			let v = parse_size(raw).ok_or(RefractError::LosslessAbove)?;
+			LOSSLESS_ABOVE.store(v, Relaxed);
+			Ok(())
+		},
+		"--max-size" => set_max_size(raw),
+		"--max-steps" => {
+			let v = raw.trim().parse::<u8>().ok()
+				.filter(|v| *v <= MAX_STEPS_LIMIT)
+				.ok_or(RefractError::MaxSteps)?;
+			MAX_STEPS.store(v, Relaxed);
+			Ok(())
+		},
+		"--quality" => add_quality(raw),
+		"--preview-max" => {
+			let v = raw.trim().parse::<usize>().map_err(|_| RefractError::PreviewMax)?;
+			PREVIEW_MAX.store(v, Relaxed);
+			Ok(())
+		},
+		"--region" => {
+			set_region(Some(Region::try_from(raw)?));
+			Ok(())
+		},
+		"--time-limit" => {
+			let v = raw.trim().parse::<u32>().map_err(|_| RefractError::TimeLimit)?;
+			TIME_LIMIT.store(v, Relaxed);
+			Ok(())
+		},
+		"--webp-method" => {
+			let v = raw.trim().parse::<u8>().ok()
+				.filter(|v| *v <= 6)
+				.ok_or(RefractError::WebpMethod)?;
+			WEBP_METHOD.store(v, Relaxed);
+			Ok(())
+		},
+		"--stream-above" => set_stream_above(raw),
+		_ => Err(RefractError::Config),
+	}
+}
+
+/// # `AVIF` Codec.
+///
+/// Return the current `AVIF` codec.
+pub(crate) fn avif_codec() -> AvifCodec {
+	AvifCodec::ALL.get(usize::from(AVIF_CODEC.load(Relaxed))).copied().unwrap_or_default()
+}
+
+/// # Set `AVIF` Codec.
+///
+/// If `saved` is true, this only applies if unset, so the command line wins
+/// over the saved settings. Codecs that weren't built are ignored.
+pub(crate) fn set_avif_codec(codec: AvifCodec, saved: bool) {
+	if ! codec.is_available() { return; }
+	let idx = AvifCodec::ALL.iter().position(|c| *c == codec)
+		.and_then(|idx| u8::try_from(idx).ok())
+		.unwrap_or_default();
+	if saved {
+		let _res = AVIF_CODEC.compare_exchange(u8::MAX, idx, Relaxed, Relaxed);
+	}
+	else { AVIF_CODEC.store(idx, Relaxed); }
+}
+
+/// # Existing Output Policy.
+///
+/// Return the current policy for outputs that already exist.
+pub(crate) fn existing() -> Existing {
+	Existing::ALL.get(usize::from(EXISTING.load(Relaxed))).copied().unwrap_or_default()
+}
+
+/// # Set Existing Output Policy.
+///
+/// If `saved` is true, this only applies if unset, so the command line wins
+/// over the saved settings.
+pub(crate) fn set_existing(policy: Existing, saved: bool) {
+	let idx = Existing::ALL.iter().position(|p| *p == policy)
+		.and_then(|idx| u8::try_from(idx).ok())
+		.unwrap_or_default();
+	if saved {
+		let _res = EXISTING.compare_exchange(u8::MAX, idx, Relaxed, Relaxed);
+	}
+	else { EXISTING.store(idx, Relaxed); }
+}
+
+/// # Parallel Jobs.
+///
+/// Return the number of sources to encode at once when no feedback is
+/// needed.
+pub(crate) fn jobs() -> NonZeroUsize {
+	match JOBS.load(Relaxed) {
+		0 => std::thread::available_parallelism().unwrap_or(NonZeroUsize::MIN),
+		n => NonZeroUsize::from(NonZeroU8::new(n).unwrap_or(NonZeroU8::MIN)),
+	}
+}
+
+/// # `JPEG XL` Tuning.
+///
+/// Return the current `JPEG XL` effort and decoding speed.
+pub(crate) fn jxl_tuning() -> JxlTuning {
+	let effort = JXL_EFFORT.load(Relaxed);
+	let speed = JXL_DECODING_SPEED.load(Relaxed);
+	JxlTuning::new(
+		if effort == 0 { JxlTuning::DEFAULT.effort() } else { effort },
+		if speed == u8::MAX { JxlTuning::DEFAULT.decoding_speed() } else { speed },
+	)
+}
+
+/// # Set `JPEG XL` Tuning.
+///
+/// Replace the `JPEG XL` effort and decoding speed. If `saved` is true, only
+/// unset values are replaced, so the command line wins over the saved
+/// settings.
+pub(crate) fn set_jxl_tuning(tuning: JxlTuning, saved: bool) {
+	if saved {
+		let _res = JXL_EFFORT.compare_exchange(0, tuning.effort(), Relaxed, Relaxed);
+		let _res = JXL_DECODING_SPEED.compare_exchange(u8::MAX, tuning.decoding_speed(), Relaxed, Relaxed);
+	}
+	else {
+		JXL_EFFORT.store(tuning.effort(), Relaxed);
+		JXL_DECODING_SPEED.store(tuning.decoding_speed(), Relaxed);
+	}
+}
+
+/// # `WebP` Tuning.
+///
+/// Return the current `WebP` method, along with the sharp YUV setting from
+/// the `CLI_*` flags.
+pub(crate) fn webp_tuning(opts: u32) -> WebpTuning {
+	let method = WEBP_METHOD.load(Relaxed);
+	WebpTuning::new(
+		if method == u8::MAX { WebpTuning::DEFAULT.method() } else { method },
+		0 != opts & CLI_WEBP_SHARP_YUV,
+	)
+}
+
+/// # Set `WebP` Method.
+///
+/// Replace the `WebP` method. If `saved` is true, this only applies if unset,
+/// so the command line wins over the saved settings.
+pub(crate) fn set_webp_method(method: u8, saved: bool) {
+	let method = method.min(6);
+	if saved {
+		let _res = WEBP_METHOD.compare_exchange(u8::MAX, method, Relaxed, Relaxed);
+	}
+	else { WEBP_METHOD.store(method, Relaxed); }
+}
+
+/// # Set Size Budget.
+///
+/// Parse a `--max-size` value — in bytes, or with a `K` or `M` suffix for
+/// KiB or MiB — and store it. Zero removes the budget.
+pub(crate) fn set_max_size(raw: &str) -> Result<(), RefractError> {
+	let size = parse_size(raw).ok_or(RefractError::MaxSize)?;
+	MAX_SIZE.store(size, Relaxed);
+	Ok(())
+}
+
+/// # Size Budget.
+///
+/// Return the size budget in bytes, if any.
+pub(crate) fn max_size() -> Option<NonZeroUsize> {
+	NonZeroUsize::new(MAX_SIZE.load(Relaxed))
+}
+
+/// # Lossless Threshold.
+///
+/// Return the lossy size in bytes above which lossless is still worth
+/// trying, if set.
+pub(crate) fn lossless_above() -> Option<NonZeroUsize> {
+	NonZeroUsize::new(LOSSLESS_ABOVE.load(Relaxed))
+}
+
+/// # Parse Size.
+///
+/// Parse a byte count, optionally suffixed with `K` or `M` for KiB or MiB.
+fn parse_size(raw: &str) -> Option<usize> {
+	let raw = raw.trim();
+	let (num, unit) = raw.strip_suffix(['k', 'K']).map(|n| (n, 1024))
+		.or_else(|| raw.strip_suffix(['m', 'M']).map(|n| (n, 1024 * 1024)))
+		.unwrap_or((raw, 1));
+	num.trim().parse::<usize>().ok().and_then(|n| n.checked_mul(unit))
+}
+
+/// # Add Quality Override.
+///
+/// Parse a `--quality FORMAT=VALUE` or `FORMAT=LOW-HIGH` value and store it,
+/// replacing any earlier one for the same format.
+fn add_quality(raw: &str) -> Result<(), RefractError> {
+	let quality = QualityOverride::try_from(raw)?;
+	let mut all = QUALITIES.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+	all.retain(|q| q.kind() != quality.kind());
+	all.push(quality);
+	drop(all);
+	Ok(())
+}
+
+/// # Quality Override.
+///
+/// Return the `--quality` override for the format, if any.
+pub(crate) fn quality_override(kind: ImageKind) -> Option<QualityOverride> {
+	QUALITIES.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+		.iter()
+		.find(|q| q.kind() == kind)
+		.copied()
+}
+
+/// # Region of Interest.
+///
+/// Return the crop to run the quality search on, if any.
+pub(crate) fn region() -> Option<Region> {
+	*REGION.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// # Set Region of Interest.
+pub(crate) fn set_region(region: Option<Region>) {
+	*REGION.lock().unwrap_or_else(std::sync::PoisonError::into_inner) = region;
+}
+
+/// # Candidate Cap.
+///
+/// Return the most lossy candidates to try per format, if limited.
+pub(crate) fn max_steps() -> Option<NonZeroU8> {
+	let steps = MAX_STEPS.load(Relaxed);
+	if steps == u8::MAX { None }
+	else { NonZeroU8::new(steps) }
+}
+
+/// # Set Candidate Cap.
+///
+/// Replace the candidate cap, zero for none. If `saved` is true, this only
+/// applies if unset, so the command line wins over the saved settings.
+pub(crate) fn set_max_steps(steps: u8, saved: bool) {
+	let steps = steps.min(MAX_STEPS_LIMIT);
+	if saved {
+		let _res = MAX_STEPS.compare_exchange(u8::MAX, steps, Relaxed, Relaxed);
+	}
+	else { MAX_STEPS.store(steps, Relaxed); }
+}
+
+/// # Preview Size Limit.
+///
+/// Return the longest side to show without scaling, if limited.
+pub(crate) fn preview_max() -> Option<NonZeroUsize> {
+	NonZeroUsize::new(PREVIEW_MAX.load(Relaxed))
+}
+
+/// # Set Streaming Threshold.
+///
+/// Parse the `--stream-above` value — in megapixels — and store it.
+fn set_stream_above(raw: &str) -> Result<(), RefractError> {
+	let px = raw.trim().parse::<usize>().ok()
+		.and_then(|mp| mp.checked_mul(MEGAPIXEL))
+		.ok_or(RefractError::StreamAbove)?;
+	STREAM_ABOVE.store(px, Relaxed);
+	Ok(())
+}
+
+/// # Streaming Threshold.
+///
+/// Return the `JPEG XL` streaming threshold in pixels, if enabled.
+pub(crate) fn stream_above() -> Option<NonZeroUsize> {
+	NonZeroUsize::new(STREAM_ABOVE.load(Relaxed))
+}
+
+/// # Time Limit.
+///
+/// Return the per-candidate encoding time limit, if any.
+pub(crate) fn time_limit() -> Option<Duration> {
+	match TIME_LIMIT.load(Relaxed) {
+		0 => None,
+		n => Some(Duration::from_secs(u64::from(n))),
+	}
+}
+
 /// # Initialize Resources.
 ///
 /// Load and register the resource bundle.
@@ -207,19 +843,45 @@ fn init_resources() -> Result<(), RefractError> {
 #[expect(clippy::similar_names, reason = "Consistency wins here.")]
 /// # Setup UI.
 ///
-/// This finishes the UI setup, hooking up communication channels, event
-/// bindings, etc.
-fn setup_ui(window: &Rc<Window>, paths: Vec<PathBuf>) {
+/// This finishes the UI setup, restoring the saved settings, hooking up
+/// communication channels, event bindings, etc.
+fn setup_ui(window: &Rc<Window>, paths: Vec<PathBuf>, settings: &settings::Settings) {
+	window.restore(settings);
 	let (stx, mtx, srx) = Share::init(Rc::clone(window));
 
 	// Bind things that just need the window.
 	setup_ui_window(window);
+	setup_ui_advanced(window);
+	setup_ui_zoom(window);
+	setup_ui_region(window);
 
 	// Discard button.
 	let mtx2 = mtx.clone();
 	let wnd2 = Rc::clone(window);
 	window.btn_discard.connect_clicked(move |_| { wnd2.feedback(&mtx2, ShareFeedback::Discard); });
 
+	// Queue button.
+	let wnd2 = Rc::clone(window);
+	window.btn_queue.connect_clicked(move |_| { wnd2.maybe_queue(); });
+
+	// Pause button.
+	let wnd2 = Rc::clone(window);
+	window.btn_pause.connect_toggled(move |btn| { wnd2.toggle_pause(btn.is_active()); });
+
+	// Skip button.
+	let wnd2 = Rc::clone(window);
+	window.btn_skip.connect_clicked(move |_| { wnd2.skip_format(); });
+
+	// Quality button.
+	let mtx2 = mtx.clone();
+	let wnd2 = Rc::clone(window);
+	window.btn_quality.connect_clicked(move |_| { wnd2.maybe_seek(&mtx2); });
+
+	// Undo button.
+	let mtx2 = mtx.clone();
+	let wnd2 = Rc::clone(window);
+	window.btn_undo.connect_clicked(move |_| { wnd2.feedback(&mtx2, ShareFeedback::Undo); });
+
 	// Keep button. (Note: mtx goes out of scope here.)
 	let wnd2 = Rc::clone(window);
 	window.btn_keep.connect_clicked(move |_| { wnd2.feedback(&mtx, ShareFeedback::Keep); });
@@ -248,6 +910,23 @@ fn setup_ui(window: &Rc<Window>, paths: Vec<PathBuf>) {
 		wnd2.encode(&stx2, &srx2);
 	});
 
+	// Open With… (paths handed over by another instance).
+	if let Some(app) = window.wnd_main.application() {
+		let wnd2 = Rc::clone(window);
+		let srx2 = srx.clone();
+		let stx2 = stx.clone();
+		app.connect_open(move |_, files, _| {
+			let paths: Vec<PathBuf> = files.iter().filter_map(gtk::prelude::FileExt::path).collect();
+			wnd2.open_paths(&paths, &stx2, &srx2);
+		});
+	}
+
+	// Review saved conversions.
+	let wnd2 = Rc::clone(window);
+	let srx2 = srx.clone();
+	let stx2 = stx.clone();
+	window.mnu_review.connect_activate(move |_| { wnd2.maybe_review(&stx2, &srx2); });
+
 	// Add a directory! (Note: stx and srx go out of scope here.)
 	let wnd2 = Rc::clone(window);
 	let srx2 = srx.clone();
@@ -271,6 +950,65 @@ fn setup_ui(window: &Rc<Window>, paths: Vec<PathBuf>) {
 	}
 }
 
+/// # Setup UI (Presets).
+///
+/// Populate the Settings > Presets menu with the built-in presets and any
+/// from the config file, and note the one chosen from the command line, if
+/// any.
+fn setup_ui_presets(window: &Rc<Window>, presets: &[config::Preset], active: Option<&str>) {
+	if let Some(name) = active { window.set_preset(name); }
+	if let Some(p) = config::config_path() {
+		window.mnu_presets.set_tooltip_text(Some(&format!(
+			"More presets can be defined in {}.",
+			p.display(),
+		)));
+	}
+
+	for preset in presets {
+		let item = gtk::MenuItem::with_label(&preset.name);
+		let wnd2 = Rc::clone(window);
+		let (name, flags, tunables) = (preset.name.clone(), preset.flags, preset.tunables.clone());
+		item.connect_activate(move |_| {
+			wnd2.set_flags(flags);
+			wnd2.set_tunables(&tunables);
+			wnd2.set_preset(&name);
+			wnd2.paint();
+		});
+		window.mnu_presets_list.append(&item);
+	}
+	window.mnu_presets_list.show_all();
+}
+
+/// # Setup UI (Advanced Settings).
+///
+/// Bind the Settings > Advanced dialogues, which also just need the window.
+fn setup_ui_advanced(window: &Rc<Window>) {
+	// The AVIF codec.
+	let wnd2 = Rc::clone(window);
+	window.mnu_avif_codec.connect_activate(move |_| { wnd2.maybe_avif_codec(); });
+
+	// The existing output policy.
+	let wnd2 = Rc::clone(window);
+	window.mnu_existing.connect_activate(move |_| { wnd2.maybe_existing(); });
+
+	// The JPEG XL tuning.
+	let wnd2 = Rc::clone(window);
+	window.mnu_jxl_tuning.connect_activate(move |_| { wnd2.maybe_jxl_tuning(); });
+
+	// The size budget.
+	let wnd2 = Rc::clone(window);
+	window.mnu_max_size.connect_activate(move |_| { wnd2.maybe_max_size(); });
+	window.sync_max_size();
+
+	// The candidate cap.
+	let wnd2 = Rc::clone(window);
+	window.mnu_max_steps.connect_activate(move |_| { wnd2.maybe_max_steps(); });
+
+	// The WebP method.
+	let wnd2 = Rc::clone(window);
+	window.mnu_webp_method.connect_activate(move |_| { wnd2.maybe_webp_method(); });
+}
+
 /// # Setup UI (Callbacks Needing Window).
 ///
 /// These event bindings require access to an `Arc<Window>`, but nothing else.
@@ -283,6 +1021,13 @@ fn setup_ui_window(window: &Rc<Window>) {
 	let wnd2 = Rc::clone(window);
 	window.mnu_quit.connect_activate(move |_| { wnd2.wnd_main.close(); });
 
+	// Save the settings on the way out.
+	let wnd2 = Rc::clone(window);
+	window.wnd_main.connect_delete_event(move |_, _| {
+		wnd2.settings().save();
+		gtk::glib::Propagation::Proceed
+	});
+
 	// The about menu.
 	let wnd2 = Rc::clone(window);
 	window.mnu_about.connect_activate(move |_| {
@@ -290,6 +1035,14 @@ fn setup_ui_window(window: &Rc<Window>) {
 		if gtk::ResponseType::None != about.run() { about.emit_close(); }
 	});
 
+	// Export the queue.
+	let wnd2 = Rc::clone(window);
+	window.mnu_export.connect_activate(move |_| { wnd2.maybe_export(); });
+
+	// The keyboard shortcut cheat sheet.
+	let wnd2 = Rc::clone(window);
+	window.mnu_shortcuts.connect_activate(move |_| { wnd2.toggle_shortcuts(); });
+
 	// The A/B toggle.
 	let wnd2 = Rc::clone(window);
 	window.btn_toggle.connect_state_notify(move |btn| {
@@ -308,16 +1061,31 @@ fn setup_ui_window(window: &Rc<Window>) {
 	let wnd2 = Rc::clone(window);
 	window.chk_dark.connect_toggled(move |_| { wnd2.toggle_dark(); });
 
+	// Difference view toggle.
+	let wnd2 = Rc::clone(window);
+	window.chk_diff.connect_toggled(move |_| { wnd2.toggle_diff(); });
+
+	// Full size toggle.
+	let wnd2 = Rc::clone(window);
+	window.chk_full_size.connect_toggled(move |_| { wnd2.toggle_full_size(); });
+	window.watch_theme();
+
 	// Make sure people don't disable every encoder or encoding mode. This will
 	// flip the last (just clicked) value back on if none of its sisters are
-	// active.
+	// active, and say as much in the log so it isn't a mystery.
 	{
 		/// # Helper Check Handling.
 		macro_rules! chk_cb {
-			($cb:ident, $($btn:ident),+) => ($(
+			($group:ident, $inline:ident, $reason:ident, $($btn:ident),+) => ($(
 				let wnd2 = Rc::clone(window);
 				window.$btn.connect_toggled(move |btn| {
-					if ! btn.is_active() && ! wnd2.$cb() { btn.set_active(true); }
+					if btn.is_active() { wnd2.$inline.hide(); }
+					else if ! wnd2.$group().iter().any(|c| c.is_active()) {
+						btn.set_active(true);
+						let label = btn.label().unwrap_or_default().replace('_', "");
+						wnd2.confirm_corrected($reason, &label, &wnd2.$inline, &wnd2.$group());
+						wnd2.paint();
+					}
 				});
 
 				// Stop the menu from closing on button press.
@@ -328,14 +1096,33 @@ fn setup_ui_window(window: &Rc<Window>) {
 			)+);
 		}
 
-		chk_cb!(has_encoders, chk_avif, chk_jxl, chk_webp);
-		chk_cb!(has_modes, chk_lossless, chk_lossy);
+		chk_cb!(encoders, mnu_formats_invalid, REASON_FORMATS, chk_avif, chk_jxl, chk_webp, chk_png, chk_jpeg);
+		chk_cb!(modes, mnu_modes_invalid, REASON_MODES, chk_lossless, chk_lossy);
 
-		// Stop the menu from closing on button press.
-		window.chk_ycbcr.connect_button_release_event(|btn, _| {
-			btn.set_active(! btn.is_active());
-			gtk::glib::Propagation::Stop
-		});
+		// Stop the menu from closing on button press for the rest too.
+		for btn in [
+			&window.chk_ycbcr,
+			&window.chk_avif_yuv420,
+			&window.chk_jxl_progressive,
+			&window.chk_webp_sharp_yuv,
+			&window.chk_lossless_last,
+			&window.chk_photo_lossless,
+			&window.chk_smart,
+			&window.chk_greyscale,
+			&window.chk_keep_metadata,
+			&window.chk_background,
+			&window.chk_interleave,
+			&window.chk_dedupe,
+			&window.chk_durable,
+			&window.chk_quick_save,
+			&window.chk_picture,
+			&window.chk_sidecar,
+		] {
+			btn.connect_button_release_event(|btn, _| {
+				btn.set_active(! btn.is_active());
+				gtk::glib::Propagation::Stop
+			});
+		}
 	}
 
 	// Sync preview field display to `lbl_quality` (so we only have to directly
@@ -358,3 +1145,66 @@ fn setup_ui_window(window: &Rc<Window>) {
 		preview_cb!(connect_hide, hide, 0.0);
 	}
 }
+
+/// # Setup UI (Region).
+///
+/// Hook up `SHIFT` + drag over the source image to select a region of
+/// interest, and the menu item to clear it.
+fn setup_ui_region(window: &Rc<Window>) {
+	use gtk::gdk::{
+		EventMask,
+		ModifierType,
+	};
+
+	window.vp_scrolled.add_events(EventMask::BUTTON_PRESS_MASK | EventMask::BUTTON_RELEASE_MASK);
+	let wnd2 = Rc::clone(window);
+	window.vp_scrolled.connect_button_press_event(move |_, e| {
+		if e.button() != 1 || ! e.state().contains(ModifierType::SHIFT_MASK) {
+			return gtk::glib::Propagation::Proceed;
+		}
+		wnd2.drag_region(e.position(), false);
+		gtk::glib::Propagation::Stop
+	});
+	let wnd2 = Rc::clone(window);
+	window.vp_scrolled.connect_button_release_event(move |_, e| {
+		if e.button() != 1 { return gtk::glib::Propagation::Proceed; }
+		wnd2.drag_region(e.position(), true);
+		gtk::glib::Propagation::Stop
+	});
+
+	let wnd2 = Rc::clone(window);
+	window.mnu_clear_region.connect_activate(move |_| { wnd2.set_region_of_interest(None); });
+	window.mnu_clear_region.set_sensitive(region().is_some());
+
+	let wnd2 = Rc::clone(window);
+	window.mnu_clear_log.connect_activate(move |_| { wnd2.clear_log(); });
+}
+
+/// # Setup UI (Zoom).
+///
+/// Hook up the zoom menu items, and `CTRL` + mouse wheel over the image.
+fn setup_ui_zoom(window: &Rc<Window>) {
+	let wnd2 = Rc::clone(window);
+	window.mnu_zoom_in.connect_activate(move |_| { wnd2.zoom(Some(true)); });
+	let wnd2 = Rc::clone(window);
+	window.mnu_zoom_out.connect_activate(move |_| { wnd2.zoom(Some(false)); });
+	let wnd2 = Rc::clone(window);
+	window.mnu_zoom_reset.connect_activate(move |_| { wnd2.zoom(None); });
+	let wnd2 = Rc::clone(window);
+	window.wnd_image.connect_scroll_event(move |_, e| {
+		if ! e.state().contains(gtk::gdk::ModifierType::CONTROL_MASK) {
+			return gtk::glib::Propagation::Proceed;
+		}
+		match e.direction() {
+			gtk::gdk::ScrollDirection::Up => wnd2.zoom(Some(true)),
+			gtk::gdk::ScrollDirection::Down => wnd2.zoom(Some(false)),
+			gtk::gdk::ScrollDirection::Smooth => match e.delta() {
+				(_, y) if y < 0.0 => wnd2.zoom(Some(true)),
+				(_, y) if 0.0 < y => wnd2.zoom(Some(false)),
+				_ => {},
+			},
+			_ => {},
+		}
+		gtk::glib::Propagation::Stop
+	});
+}
@@ -0,0 +1,66 @@
+/*!
+# `Refract GTK` - Pausing
+*/
+
+use refract_core::CancelToken;
+use std::{
+	sync::{
+		atomic::{
+			AtomicBool,
+			Ordering::Relaxed,
+		},
+		LazyLock,
+	},
+	time::Duration,
+};
+
+
+
+/// # Paused?
+static PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// # Poll Interval.
+///
+/// How often a waiting thread checks whether it may resume.
+const POLL: Duration = Duration::from_millis(100);
+
+/// # Skip Format.
+///
+/// This is handed to each format's search so the user can abandon it midway.
+static SKIP: LazyLock<CancelToken> = LazyLock::new(CancelToken::default);
+
+
+
+/// # Set Paused.
+///
+/// Pause or resume the encoding thread. Pausing takes effect once the current
+/// candidate has finished; nothing is lost in the meantime.
+pub(super) fn set(paused: bool) { PAUSED.store(paused, Relaxed); }
+
+/// # Is Paused?
+pub(super) fn is_paused() -> bool { PAUSED.load(Relaxed) }
+
+/// # Wait.
+///
+/// Block the current (encoding) thread for as long as things are paused.
+/// This is called between candidates, so encoder state is simply held in
+/// place until resumed.
+pub(super) fn wait() {
+	while is_paused() { std::thread::sleep(POLL); }
+}
+
+
+
+/// # Skip.
+///
+/// Abandon the current format, cutting the candidate in progress short if
+/// the encoder allows it.
+pub(super) fn skip() { SKIP.cancel(); }
+
+/// # Skip Token.
+///
+/// Clear and return the skip token for a new format search.
+pub(super) fn skip_token() -> CancelToken {
+	SKIP.reset();
+	CancelToken::clone(&SKIP)
+}
@@ -0,0 +1,126 @@
+/*!
+# `Refract GTK` - Pipes
+
+`refract --stdin --format <EXT> [--quality <NUM>]` reads a JPEG or PNG source
+from STDIN, encodes it exactly once — no GUI, no feedback — and writes the
+result to STDOUT, so Refract can slot into shell pipelines and build systems
+without touching the filesystem.
+
+The quality is written in the format's own scale, as with `--quality
+FORMAT=VALUE` — e.g. an `AVIF` quantizer, where lower is better. If omitted,
+the image is encoded losslessly, or for `JPEG`, which has no lossless mode,
+at [`JPEG_QUALITY`].
+*/
+
+use crate::{
+	CLI_GREYSCALE,
+	window::cli_encoder_flags,
+};
+use refract_core::{
+	EncodeIter,
+	ImageKind,
+	Input,
+	Quality,
+	RefractError,
+};
+use std::{
+	io::{
+		Read,
+		Write,
+	},
+	num::NonZeroU8,
+};
+
+
+
+/// # Default `JPEG` Quality.
+///
+/// This is used when no `--quality` is given for `JPEG`, which can't fall
+/// back to lossless like the others.
+const JPEG_QUALITY: NonZeroU8 = NonZeroU8::new(80).unwrap();
+
+
+
+#[derive(Debug, Clone, Default)]
+/// # Pipe Settings.
+pub(super) struct Pipe {
+	/// # Enabled?
+	enabled: bool,
+
+	/// # Output Format.
+	kind: Option<ImageKind>,
+
+	/// # Quality (Native).
+	///
+	/// This is only parsed once the format is known.
+	quality: Option<String>,
+}
+
+impl Pipe {
+	/// # Enable.
+	pub(super) const fn enable(&mut self) { self.enabled = true; }
+
+	/// # Is Enabled?
+	pub(super) const fn is_enabled(&self) -> bool { self.enabled }
+
+	/// # Set Option.
+	///
+	/// Parse and store the `--format` or `--quality` value.
+	///
+	/// ## Errors
+	///
+	/// Returns an error if the value is invalid.
+	pub(super) fn set(&mut self, key: &str, raw: &str) -> Result<(), RefractError> {
+		let raw = raw.trim();
+		if key == "--format" {
+			let kind = [ImageKind::Avif, ImageKind::Jpeg, ImageKind::Jxl, ImageKind::Png, ImageKind::Webp].into_iter()
+				.find(|k| k.extension().eq_ignore_ascii_case(raw) || (*k == ImageKind::Jpeg && raw.eq_ignore_ascii_case("jpeg")))
+				.ok_or(RefractError::PipeFormat)?;
+			self.kind = Some(kind);
+		}
+		else { self.quality = Some(raw.to_owned()); }
+		Ok(())
+	}
+
+	/// # Run.
+	///
+	/// Read the source from STDIN, encode it, and write the result to
+	/// STDOUT.
+	///
+	/// ## Errors
+	///
+	/// Returns an error if no format was chosen, the quality is invalid for
+	/// it, the source cannot be read or decoded, the encoding fails (or saves
+	/// nothing), or the result cannot be written.
+	pub(super) fn run(self, opts: u32) -> Result<(), RefractError> {
+		let kind = self.kind.ok_or(RefractError::PipeFormat)?;
+		let quality = match self.quality.as_deref() {
+			Some(raw) => Some(
+				Quality::from_native(kind, raw).ok_or(RefractError::PipeQuality)?.raw()
+			),
+			None if kind == ImageKind::Jpeg => Some(JPEG_QUALITY),
+			None => None,
+		};
+
+		let mut raw = Vec::new();
+		std::io::stdin().lock().read_to_end(&mut raw).map_err(|_| RefractError::Read)?;
+		let mut src = Input::try_from(raw.as_slice())?;
+		if 0 != opts & CLI_GREYSCALE { src = src.into_greyscale(); }
+
+		// There's nobody to ask, but 16-bit PNGs deserve a heads up.
+		if src.is_high_bit_depth() && ! kind.supports_high_bit_depth() {
+			eprintln!("Warning: The source has more than 8 bits per channel; only AVIF and JPEG XL will keep them.");
+		}
+
+		let mut guide = EncodeIter::new(&src, kind, cli_encoder_flags(opts))?;
+		guide.set_avif_codec(crate::avif_codec());
+		guide.set_jxl_tuning(crate::jxl_tuning());
+		guide.set_webp_tuning(crate::webp_tuning(opts));
+		if let Some(px) = crate::stream_above() { guide.set_streaming_above(px); }
+		let out = guide.once(quality)?;
+
+		let mut stdout = std::io::stdout().lock();
+		stdout.write_all(&out).and_then(|()| stdout.flush())
+			.map_err(|_| RefractError::Write)
+	}
+}
@@ -0,0 +1,43 @@
+/*!
+# `Refract GTK` - Thread Priority
+*/
+
+use thread_priority::{
+	set_current_thread_priority,
+	ThreadPriority,
+};
+
+
+
+/// # Background Mode.
+///
+/// Lower the scheduling priority of the current thread — and by extension any
+/// threads it spawns, like the encoders' own workers — so long batches don't
+/// bog down the rest of the desktop.
+///
+/// On Linux this uses the `SCHED_IDLE` class, falling back to the lowest
+/// niceness if that isn't allowed. Elsewhere it uses whatever the platform
+/// considers the minimum.
+///
+/// This is a best-effort operation; `false` is returned if it failed.
+pub(super) fn background() -> bool {
+	#[cfg(target_os = "linux")]
+	{
+		use thread_priority::{
+			NormalThreadSchedulePolicy,
+			set_thread_priority_and_policy,
+			thread_native_id,
+			ThreadSchedulePolicy,
+		};
+
+		if set_thread_priority_and_policy(
+			thread_native_id(),
+			ThreadPriority::Min,
+			ThreadSchedulePolicy::Normal(NormalThreadSchedulePolicy::Idle),
+		).is_ok() {
+			return true;
+		}
+	}
+
+	set_current_thread_priority(ThreadPriority::Min).is_ok()
+}
@@ -0,0 +1,301 @@
+/*!
+# `Refract GTK` - Progress Stream
+*/
+
+use refract_core::{
+	ErrorContext,
+	ImageKind,
+	Quality,
+	RefractError,
+};
+use refract_frontend::Distortion;
+use std::{
+	io::Write,
+	path::Path,
+	sync::atomic::{
+		AtomicBool,
+		Ordering::Relaxed,
+	},
+	time::Duration,
+};
+
+
+
+/// # Enabled?
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+
+
+#[derive(Debug, Clone, Copy)]
+/// # Progress Event.
+///
+/// When enabled with `--progress json`, each of these is written to STDOUT as
+/// a single line of JSON, giving wrapper programs a stable, machine-readable
+/// view of what's going on. Everything meant for humans stays in the window
+/// (or STDERR).
+///
+/// | Event | Fields |
+/// | ----- | ------ |
+/// | `source` | `path` |
+/// | `encoder` | `kind` |
+/// | `candidate` | `kind`, `quality`, `size`, `progress` (percent, if known) |
+/// | `decision` | `kind`, `quality`, `decision` (`keep` or `discard`) |
+/// | `saved` | `kind`, `quality`, `size`, `path`, `distortion` (`max`, `mean`, `changed`; if known) |
+/// | `fallback` | `kind`, `mode`, `path` |
+/// | `timing` | `kind`, `wall`, `cpu` (seconds; if known) |
+/// | `skipped` | `reason` |
+/// | `error` | `message` |
+/// | `done` | |
+pub(super) enum Event<'a> {
+	/// # Image Started.
+	Source(&'a Path),
+
+	/// # Encoder Started.
+	Encoder(ImageKind),
+
+	/// # Candidate Produced (With Progress).
+	Candidate(Quality, usize, Option<u8>),
+
+	/// # Decision Made.
+	Decision(Quality, bool),
+
+	/// # Output Saved (With Distortion Stats).
+	Saved(Quality, usize, &'a Path, Option<Distortion>),
+
+	/// # Fallback Created.
+	Fallback(ImageKind, &'a str, &'a Path),
+
+	/// # Encoder Finished (Wall and CPU Time).
+	Timing(ImageKind, Duration, Option<Duration>),
+
+	/// # Skipped.
+	Skipped(&'a str),
+
+	/// # Error.
+	Error(&'a ErrorContext),
+
+	/// # All Done.
+	Done,
+}
+
+impl Event<'_> {
+	/// # As JSON.
+	fn to_json(self) -> String {
+		let mut out = String::with_capacity(64);
+		match self {
+			Self::Source(path) => {
+				out.push_str(r#"{"event":"source","path":"#);
+				push_json_str(&mut out, &path.to_string_lossy());
+			},
+			Self::Encoder(kind) => {
+				out.push_str(r#"{"event":"encoder","kind":"#);
+				push_json_str(&mut out, kind.extension());
+			},
+			Self::Candidate(quality, size, percent) => {
+				out.push_str(r#"{"event":"candidate""#);
+				push_quality(&mut out, quality);
+				push_size(&mut out, size);
+				if let Some(percent) = percent {
+					out.push_str(r#","progress":"#);
+					out.push_str(&percent.to_string());
+				}
+			},
+			Self::Decision(quality, keep) => {
+				out.push_str(r#"{"event":"decision""#);
+				push_quality(&mut out, quality);
+				out.push_str(
+					if keep { r#","decision":"keep""# }
+					else { r#","decision":"discard""# }
+				);
+			},
+			Self::Saved(quality, size, path, dist) => {
+				out.push_str(r#"{"event":"saved""#);
+				push_quality(&mut out, quality);
+				push_size(&mut out, size);
+				out.push_str(r#","path":"#);
+				push_json_str(&mut out, &path.to_string_lossy());
+				if let Some(dist) = dist { push_distortion(&mut out, dist); }
+			},
+			Self::Fallback(kind, mode, path) => {
+				out.push_str(r#"{"event":"fallback","kind":"#);
+				push_json_str(&mut out, kind.extension());
+				out.push_str(r#","mode":"#);
+				push_json_str(&mut out, mode);
+				out.push_str(r#","path":"#);
+				push_json_str(&mut out, &path.to_string_lossy());
+			},
+			Self::Timing(kind, wall, cpu) => {
+				use std::fmt::Write;
+				out.push_str(r#"{"event":"timing","kind":"#);
+				push_json_str(&mut out, kind.extension());
+				let _res = write!(out, r#","wall":{:.3}"#, wall.as_secs_f64());
+				if let Some(cpu) = cpu {
+					let _res = write!(out, r#","cpu":{:.3}"#, cpu.as_secs_f64());
+				}
+			},
+			Self::Skipped(reason) => {
+				out.push_str(r#"{"event":"skipped","reason":"#);
+				push_json_str(&mut out, reason);
+			},
+			Self::Error(err) => {
+				out.push_str(r#"{"event":"error","message":"#);
+				push_json_str(&mut out, err.error().as_str());
+				if let Some(path) = err.path() {
+					out.push_str(r#","path":"#);
+					push_json_str(&mut out, &path.to_string_lossy());
+				}
+				if let Some(kind) = err.kind() {
+					out.push_str(r#","kind":"#);
+					push_json_str(&mut out, kind.extension());
+				}
+				if let Some(cause) = err.cause() {
+					out.push_str(r#","cause":"#);
+					push_json_str(&mut out, cause.as_str());
+				}
+				if let Some(upstream) = err.upstream() {
+					out.push_str(r#","upstream":"#);
+					push_json_str(&mut out, upstream);
+				}
+			},
+			Self::Done => { out.push_str(r#"{"event":"done""#); },
+		}
+		out.push('}');
+		out
+	}
+}
+
+
+
+/// # Enable.
+///
+/// Turn on the stream for the given `--progress` format. The only supported
+/// format is "json".
+pub(super) fn enable(fmt: &str) -> Result<(), RefractError> {
+	if fmt.trim() == "json" {
+		ENABLED.store(true, Relaxed);
+		Ok(())
+	}
+	else { Err(RefractError::Progress) }
+}
+
+/// # Emit Event.
+///
+/// Write the event to STDOUT, if enabled. Write errors are ignored; if
+/// nobody is listening, that's their business.
+///
+/// The event is also passed along to the `--ci` and `--report` collectors.
+pub(super) fn emit(event: Event) {
+	crate::ci::record(event);
+	crate::report::record(event);
+	if ENABLED.load(Relaxed) {
+		let mut line = event.to_json();
+		line.push('\n');
+
+		let mut handle = std::io::stdout().lock();
+		let _res = handle.write_all(line.as_bytes()).and_then(|()| handle.flush());
+	}
+}
+
+
+
+/// # Push JSON String.
+///
+/// Append a quoted, escaped JSON string.
+pub(super) fn push_json_str(out: &mut String, src: &str) {
+	use std::fmt::Write;
+
+	out.push('"');
+	for c in src.chars() {
+		match c {
+			'"' => { out.push_str("\\\""); },
+			'\\' => { out.push_str("\\\\"); },
+			'\n' => { out.push_str("\\n"); },
+			'\r' => { out.push_str("\\r"); },
+			'\t' => { out.push_str("\\t"); },
+			c if c.is_control() => { let _res = write!(out, "\\u{:04x}", u32::from(c)); },
+			c => { out.push(c); },
+		}
+	}
+	out.push('"');
+}
+
+/// # Push Distortion.
+///
+/// Append the distortion stats as an object. The `changed` share is a
+/// fraction between `0.0` and `1.0`, as with [`Distortion::changed`].
+pub(super) fn push_distortion(out: &mut String, dist: Distortion) {
+	use std::fmt::Write;
+
+	let _res = write!(
+		out,
+		r#","distortion":{{"max":{},"mean":{:.3},"changed":{:.4}}}"#,
+		dist.max,
+		dist.mean,
+		dist.changed,
+	);
+}
+
+/// # Push Kind and Quality.
+fn push_quality(out: &mut String, quality: Quality) {
+	out.push_str(r#","kind":"#);
+	push_json_str(out, quality.kind().extension());
+	out.push_str(r#","quality":"#);
+	push_json_str(out, &quality.quality().to_string());
+}
+
+/// # Push Size.
+fn push_size(out: &mut String, size: usize) {
+	out.push_str(r#","size":"#);
+	out.push_str(&size.to_string());
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn t_json() {
+		assert_eq!(
+			Event::Source(Path::new("/tmp/a \"b\".jpg")).to_json(),
+			r#"{"event":"source","path":"/tmp/a \"b\".jpg"}"#,
+		);
+		assert_eq!(
+			Event::Skipped("tab\there\u{1}").to_json(),
+			r#"{"event":"skipped","reason":"tab\there\u0001"}"#,
+		);
+		assert_eq!(
+			Event::Timing(ImageKind::Webp, Duration::from_millis(1500), Some(Duration::from_millis(4250))).to_json(),
+			r#"{"event":"timing","kind":"webp","wall":1.500,"cpu":4.250}"#,
+		);
+		assert_eq!(
+			Event::Timing(ImageKind::Webp, Duration::from_millis(1500), None).to_json(),
+			r#"{"event":"timing","kind":"webp","wall":1.500}"#,
+		);
+		assert_eq!(
+			Event::Candidate(Quality::Lossless(ImageKind::Webp), 123, Some(40)).to_json(),
+			r#"{"event":"candidate","kind":"webp","quality":"lossless","size":123,"progress":40}"#,
+		);
+		assert_eq!(
+			Event::Error(
+				&ErrorContext::new(RefractError::NoBest(ImageKind::Avif))
+					.with_path("/tmp/a.jpg")
+					.with_kind(ImageKind::Avif)
+					.with_cause(RefractError::Encode)
+			).to_json(),
+			r#"{"event":"error","message":"No acceptable AVIF candidate was found.","path":"/tmp/a.jpg","kind":"avif","cause":"The image could not be encoded."}"#,
+		);
+		let q = Quality::Lossless(ImageKind::Png);
+		assert_eq!(
+			Event::Saved(q, 42, Path::new("/tmp/a.png"), None).to_json(),
+			r#"{"event":"saved","kind":"png","quality":"lossless","size":42,"path":"/tmp/a.png"}"#,
+		);
+		let dist = Distortion { max: 7, mean: 0.5, changed: 0.125 };
+		assert_eq!(
+			Event::Saved(q, 42, Path::new("/tmp/a.png"), Some(dist)).to_json(),
+			r#"{"event":"saved","kind":"png","quality":"lossless","size":42,"path":"/tmp/a.png","distortion":{"max":7,"mean":0.500,"changed":0.1250}}"#,
+		);
+		assert_eq!(Event::Done.to_json(), r#"{"event":"done"}"#);
+	}
+}
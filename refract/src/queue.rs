@@ -0,0 +1,161 @@
+/*!
+# `Refract GTK` - Encoding Queue
+
+The sources waiting to be encoded are shared between the main thread and the
+encoding thread, so the former can reorder them, drop them, change their
+formats — via the queue dialog — or add more — via "Open With" — while the
+latter works its way through.
+*/
+
+use refract_core::ImageKind;
+use std::{
+	collections::VecDeque,
+	path::PathBuf,
+	sync::{
+		Arc,
+		Mutex,
+		MutexGuard,
+	},
+};
+
+
+
+#[derive(Debug, Clone)]
+/// # Queued Source.
+pub(super) struct QueueItem {
+	/// # Path.
+	pub(super) path: PathBuf,
+
+	/// # Encoders.
+	///
+	/// This overrides the global format selection for this source, if set.
+	pub(super) encoders: Option<Box<[ImageKind]>>,
+}
+
+impl From<PathBuf> for QueueItem {
+	fn from(path: PathBuf) -> Self { Self { path, encoders: None } }
+}
+
+
+
+#[derive(Debug, Default)]
+/// # Queue Contents.
+struct QueueInner {
+	/// # Sources.
+	items: VecDeque<QueueItem>,
+
+	/// # Closed?
+	///
+	/// This is set once the encoding thread finds the queue empty, after
+	/// which it won't be checking again.
+	closed: bool,
+}
+
+
+
+#[derive(Debug, Clone, Default)]
+/// # Encoding Queue.
+pub(super) struct Queue(Arc<Mutex<QueueInner>>);
+
+impl Queue {
+	/// # Lock.
+	///
+	/// A poisoned lock just means the encoding thread fell over; the data is
+	/// still fine.
+	fn lock(&self) -> MutexGuard<'_, QueueInner> {
+		self.0.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+	}
+
+	/// # Set.
+	///
+	/// Replace the queue with a fresh set of sources, using the global
+	/// formats.
+	pub(super) fn set(&self, paths: Vec<PathBuf>) {
+		let mut queue = self.lock();
+		queue.items = paths.into_iter().map(QueueItem::from).collect();
+		queue.closed = false;
+	}
+
+	/// # Extend.
+	///
+	/// Add more sources to the end of a queue that is still being worked
+	/// through, returning `false` — and leaving it be — if the encoding
+	/// thread has already run out.
+	pub(super) fn extend(&self, paths: Vec<PathBuf>) -> bool {
+		let mut queue = self.lock();
+		if queue.closed { false }
+		else {
+			queue.items.extend(paths.into_iter().map(QueueItem::from));
+			true
+		}
+	}
+
+	/// # Next.
+	///
+	/// Remove and return the next source, if any. If there isn't one, the
+	/// queue is closed to further additions.
+	pub(super) fn pop(&self) -> Option<QueueItem> {
+		let mut queue = self.lock();
+		let next = queue.items.pop_front();
+		if next.is_none() { queue.closed = true; }
+		next
+	}
+
+	/// # Snapshot.
+	///
+	/// Return a copy of everything still waiting.
+	pub(super) fn snapshot(&self) -> Vec<QueueItem> { self.lock().items.iter().cloned().collect() }
+
+	/// # Replace.
+	///
+	/// Swap in an edited copy of the queue. Anything the encoder has picked
+	/// up since the copy was taken is left out, so nothing runs twice.
+	pub(super) fn replace(&self, items: Vec<QueueItem>) {
+		let mut queue = self.lock();
+		let items: VecDeque<QueueItem> = items.into_iter()
+			.filter(|i| queue.items.iter().any(|q| q.path == i.path))
+			.collect();
+		queue.items = items;
+	}
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::path::Path;
+
+	#[test]
+	fn t_replace() {
+		let queue = Queue::default();
+		queue.set(vec![
+			PathBuf::from("/a.png"),
+			PathBuf::from("/b.png"),
+			PathBuf::from("/c.png"),
+		]);
+		let mut edit = queue.snapshot();
+
+		// The encoder moves on while the copy is being edited.
+		assert_eq!(queue.pop().map(|i| i.path), Some(PathBuf::from("/a.png")));
+
+		// Reverse, drop one, and narrow the formats of another.
+		edit.reverse();
+		edit.retain(|i| i.path != Path::new("/b.png"));
+		edit[0].encoders = Some(Box::new([ImageKind::Webp]));
+		queue.replace(edit);
+
+		let left = queue.snapshot();
+		assert_eq!(left.len(), 1);
+		assert_eq!(left[0].path, PathBuf::from("/c.png"));
+		assert_eq!(left[0].encoders.as_deref(), Some(&[ImageKind::Webp][..]));
+		assert!(queue.pop().is_some());
+
+		// Once the encoder runs dry, there's no adding to it.
+		assert!(queue.extend(vec![PathBuf::from("/d.png")]));
+		assert!(queue.pop().is_some());
+		assert!(queue.pop().is_none());
+		assert!(! queue.extend(vec![PathBuf::from("/e.png")]));
+		assert!(queue.snapshot().is_empty());
+	}
+}
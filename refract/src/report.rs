@@ -0,0 +1,410 @@
+/*!
+# `Refract GTK` - Activity Report
+*/
+
+use crate::progress::{
+	Event,
+	push_distortion,
+	push_json_str,
+};
+use refract_core::{
+	ImageKind,
+	Quality,
+};
+use refract_frontend::Distortion;
+use std::{
+	fmt::Write,
+	num::NonZeroUsize,
+	path::{
+		Path,
+		PathBuf,
+	},
+	sync::{
+		Mutex,
+		PoisonError,
+	},
+	time::Duration,
+};
+
+
+
+/// # Default Flush Interval.
+pub(super) const DEFAULT_EVERY: NonZeroUsize = NonZeroUsize::new(10).unwrap();
+
+/// # Report.
+///
+/// This is global so the panic hook can get at it.
+static REPORT: Mutex<Option<Report>> = Mutex::new(None);
+
+
+
+/// # Activity Report.
+///
+/// When enabled, the activity log is saved — as plain text — to a file every
+/// N images, at the end of each run, and if the program panics, so that a
+/// crash late in a long run doesn't lose the record of everything done before
+/// it.
+///
+/// If the file has a `.json` extension, the per-source results are saved
+/// instead, as a JSON array, for dashboards and the like.
+struct Report {
+	/// # File Path.
+	path: PathBuf,
+
+	/// # Flush Interval.
+	every: NonZeroUsize,
+
+	/// # Images Since Last Flush.
+	pending: usize,
+
+	/// # Latest Log (Markup).
+	log: String,
+
+	/// # Cleared Log (Markup).
+	///
+	/// Entries cleared from the window's log, so the report still covers the
+	/// whole session.
+	cleared: String,
+
+	/// # JSON Results.
+	json: Option<JsonReport>,
+}
+
+impl Report {
+	/// # Flush.
+	///
+	/// Write the latest log to disk. Errors are ignored; there's nowhere to
+	/// report them!
+	fn flush(&mut self) {
+		self.pending = 0;
+		let out = self.json.as_ref().map_or_else(
+			|| plain_text(&[self.cleared.as_str(), self.log.as_str()].concat()),
+			JsonReport::to_json,
+		);
+		let _res = write_atomic::write_file(&self.path, out.as_bytes());
+	}
+}
+
+
+
+#[derive(Debug, Default)]
+/// # JSON Results.
+struct JsonReport {
+	/// # Finished Sources (Serialized).
+	done: Vec<String>,
+
+	/// # Current Source.
+	current: Option<JsonSource>,
+}
+
+impl JsonReport {
+	/// # Record Event.
+	fn record(&mut self, event: Event) {
+		match event {
+			Event::Source(path) => {
+				self.finish();
+				self.current = Some(JsonSource::new(path));
+			},
+			Event::Done => { self.finish(); },
+			event => if let Some(src) = self.current.as_mut() { src.record(event); },
+		}
+	}
+
+	/// # Finish Current Source.
+	fn finish(&mut self) {
+		if let Some(src) = self.current.take() { self.done.push(src.to_json()); }
+	}
+
+	/// # As JSON.
+	///
+	/// The finished sources — and the current one, if any — as a JSON array.
+	fn to_json(&self) -> String {
+		let current = self.current.as_ref().map(JsonSource::to_json);
+		let mut out = String::from("[");
+		for (k, v) in self.done.iter().chain(current.as_ref()).enumerate() {
+			if k != 0 { out.push(','); }
+			out.push_str("\n\t");
+			out.push_str(v);
+		}
+		out.push_str("\n]\n");
+		out
+	}
+}
+
+
+
+#[derive(Debug)]
+/// # Source Results.
+struct JsonSource {
+	/// # Path.
+	path: PathBuf,
+
+	/// # Size.
+	size: u64,
+
+	/// # Encoders.
+	encoders: Vec<JsonEncoder>,
+
+	/// # Skip Reason.
+	skipped: Option<String>,
+
+	/// # Errors.
+	errors: Vec<String>,
+}
+
+impl JsonSource {
+	/// # New.
+	fn new(path: &Path) -> Self {
+		Self {
+			path: path.to_path_buf(),
+			size: std::fs::metadata(path).map_or(0, |m| m.len()),
+			encoders: Vec::new(),
+			skipped: None,
+			errors: Vec::new(),
+		}
+	}
+
+	/// # Encoder.
+	///
+	/// Return the entry for the encoder, adding it if needed.
+	fn encoder(&mut self, kind: ImageKind) -> &mut JsonEncoder {
+		let idx = self.encoders.iter().position(|e| e.kind == kind).unwrap_or_else(|| {
+			self.encoders.push(JsonEncoder::new(kind));
+			self.encoders.len() - 1
+		});
+		&mut self.encoders[idx]
+	}
+
+	/// # Record Event.
+	fn record(&mut self, event: Event) {
+		match event {
+			Event::Encoder(kind) => { self.encoder(kind); },
+			Event::Candidate(quality, _, _) => { self.encoder(quality.kind()).candidates += 1; },
+			Event::Saved(quality, size, path, dist) => {
+				let enc = self.encoder(quality.kind());
+				enc.saved = Some((quality, size, path.to_path_buf()));
+				enc.distortion = dist;
+			},
+			Event::Fallback(kind, mode, _) => { self.encoder(kind).fallback = Some(mode.to_owned()); },
+			Event::Timing(kind, wall, cpu) => { self.encoder(kind).time = Some((wall, cpu)); },
+			Event::Skipped(reason) => { self.skipped = Some(reason.to_owned()); },
+			Event::Error(err) => { self.errors.push(err.to_string()); },
+			Event::Source(_) | Event::Decision(_, _) | Event::Done => {},
+		}
+	}
+
+	/// # As JSON.
+	fn to_json(&self) -> String {
+		let mut out = String::from(r#"{"path":"#);
+		push_json_str(&mut out, &self.path.to_string_lossy());
+		let _res = write!(out, r#","size":{},"encoders":["#, self.size);
+		for (k, enc) in self.encoders.iter().enumerate() {
+			if k != 0 { out.push(','); }
+			enc.push_json(&mut out);
+		}
+		out.push_str(r#"],"skipped":"#);
+		match self.skipped.as_deref() {
+			Some(reason) => { push_json_str(&mut out, reason); },
+			None => { out.push_str("null"); },
+		}
+		out.push_str(r#","errors":["#);
+		for (k, msg) in self.errors.iter().enumerate() {
+			if k != 0 { out.push(','); }
+			push_json_str(&mut out, msg);
+		}
+		out.push_str("]}");
+		out
+	}
+}
+
+
+
+#[derive(Debug)]
+/// # Encoder Results.
+struct JsonEncoder {
+	/// # Kind.
+	kind: ImageKind,
+
+	/// # Candidates Reviewed.
+	candidates: usize,
+
+	/// # Saved Output (Quality, Size, Path).
+	saved: Option<(Quality, usize, PathBuf)>,
+
+	/// # Saved Output Distortion.
+	distortion: Option<Distortion>,
+
+	/// # Fallback Mode.
+	fallback: Option<String>,
+
+	/// # Wall and CPU Time.
+	time: Option<(Duration, Option<Duration>)>,
+}
+
+impl JsonEncoder {
+	/// # New.
+	const fn new(kind: ImageKind) -> Self {
+		Self { kind, candidates: 0, saved: None, distortion: None, fallback: None, time: None }
+	}
+
+	/// # Push JSON.
+	fn push_json(&self, out: &mut String) {
+		out.push_str(r#"{"kind":"#);
+		push_json_str(out, self.kind.extension());
+		let _res = write!(out, r#","candidates":{}"#, self.candidates);
+		if let Some((quality, size, path)) = &self.saved {
+			out.push_str(r#","status":"saved","quality":"#);
+			push_json_str(out, &quality.quality().to_string());
+			let _res = write!(out, r#","size":{size},"path":"#);
+			push_json_str(out, &path.to_string_lossy());
+			if let Some(dist) = self.distortion { push_distortion(out, dist); }
+		}
+		else { out.push_str(r#","status":"rejected""#); }
+		if let Some(mode) = self.fallback.as_deref() {
+			out.push_str(r#","fallback":"#);
+			push_json_str(out, mode);
+		}
+		if let Some((wall, cpu)) = self.time {
+			let _res = write!(out, r#","wall":{:.3}"#, wall.as_secs_f64());
+			if let Some(cpu) = cpu {
+				let _res = write!(out, r#","cpu":{:.3}"#, cpu.as_secs_f64());
+			}
+		}
+		out.push('}');
+	}
+}
+
+
+
+/// # Enable Reporting.
+///
+/// Set the report path and interval, and install a panic hook to flush it
+/// one last time if things go terribly wrong.
+pub(super) fn init(path: PathBuf, every: NonZeroUsize) {
+	let json = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("json"))
+		.then(JsonReport::default);
+	REPORT.lock().unwrap_or_else(PoisonError::into_inner).replace(Report {
+		path,
+		every,
+		pending: 0,
+		log: String::new(),
+		cleared: String::new(),
+		json,
+	});
+
+	let hook = std::panic::take_hook();
+	std::panic::set_hook(Box::new(move |info| {
+		// The lock might already be held by the panicking thread, so don't
+		// wait around for it.
+		if let Ok(mut report) = REPORT.try_lock() {
+			if let Some(report) = report.as_mut() { report.flush(); }
+		}
+		hook(info);
+	}));
+}
+
+/// # Update Log.
+///
+/// Store a copy of the latest activity log (markup).
+pub(super) fn update(log: &str) {
+	let mut report = REPORT.lock().unwrap_or_else(PoisonError::into_inner);
+	if let Some(report) = report.as_mut() { log.clone_into(&mut report.log); }
+}
+
+/// # Clear Log.
+///
+/// Set the latest copy of the activity log aside, because the window's log
+/// is about to be emptied.
+pub(super) fn clear() {
+	let mut report = REPORT.lock().unwrap_or_else(PoisonError::into_inner);
+	if let Some(report) = report.as_mut() {
+		let log = std::mem::take(&mut report.log);
+		report.cleared.push_str(&log);
+	}
+}
+
+/// # Record Event.
+///
+/// Note the event, if the report is JSON.
+pub(super) fn record(event: Event) {
+	let mut report = REPORT.lock().unwrap_or_else(PoisonError::into_inner);
+	if let Some(json) = report.as_mut().and_then(|r| r.json.as_mut()) { json.record(event); }
+}
+
+/// # Tick.
+///
+/// Note that an image has finished, flushing the report if enough of them
+/// have piled up.
+pub(super) fn tick() {
+	let mut report = REPORT.lock().unwrap_or_else(PoisonError::into_inner);
+	if let Some(report) = report.as_mut() {
+		report.pending += 1;
+		if report.every.get() <= report.pending { report.flush(); }
+	}
+}
+
+/// # Flush Now.
+pub(super) fn flush() {
+	let mut report = REPORT.lock().unwrap_or_else(PoisonError::into_inner);
+	if let Some(report) = report.as_mut() { report.flush(); }
+}
+
+
+
+/// # Plain Text.
+///
+/// Strip the Pango markup from the log.
+fn plain_text(src: &str) -> String {
+	let mut out = String::with_capacity(src.len());
+	let mut in_tag = false;
+	for c in src.chars() {
+		match c {
+			'<' => { in_tag = true; },
+			'>' if in_tag => { in_tag = false; },
+			_ if ! in_tag => { out.push(c); },
+			_ => {},
+		}
+	}
+
+	out.replace("&lt;", "<")
+		.replace("&gt;", ">")
+		.replace("&quot;", "\"")
+		.replace("&apos;", "'")
+		.replace("&amp;", "&")
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::num::NonZeroU8;
+
+	#[test]
+	fn t_json() {
+		let mut json = JsonReport::default();
+		assert_eq!(json.to_json(), "[\n]\n");
+
+		let q = Quality::Lossy(ImageKind::Webp, NonZeroU8::new(80).unwrap());
+		json.record(Event::Source(Path::new("/nowhere/a.jpg")));
+		json.record(Event::Encoder(ImageKind::Webp));
+		json.record(Event::Candidate(q, 750, None));
+		json.record(Event::Saved(q, 750, Path::new("/nowhere/a.webp"), Some(Distortion { max: 12, mean: 0.75, changed: 0.5 })));
+		json.record(Event::Timing(ImageKind::Webp, Duration::from_millis(1500), Some(Duration::from_secs(3))));
+		json.record(Event::Encoder(ImageKind::Avif));
+		json.record(Event::Source(Path::new("/nowhere/b.png")));
+		json.record(Event::Skipped("color"));
+		json.record(Event::Done);
+
+		assert_eq!(
+			json.to_json(),
+			concat!(
+				"[\n\t",
+				r#"{"path":"/nowhere/a.jpg","size":0,"encoders":[{"kind":"webp","candidates":1,"status":"saved","quality":"80","size":750,"path":"/nowhere/a.webp","distortion":{"max":12,"mean":0.750,"changed":0.5000},"wall":1.500,"cpu":3.000},{"kind":"avif","candidates":0,"status":"rejected"}],"skipped":null,"errors":[]}"#,
+				",\n\t",
+				r#"{"path":"/nowhere/b.png","size":0,"encoders":[],"skipped":"color","errors":[]}"#,
+				"\n]\n",
+			),
+		);
+	}
+}
@@ -0,0 +1,483 @@
+/*!
+# `Refract GTK` - Feedback Scripts
+
+A feedback script is a plain-text record of the decisions made during an
+encoding run. They can be recorded with `--record <FILE>` and replayed —
+without the GUI — with `refract replay <FILE>`, which re-runs the same
+sources and encoders, answers each candidate the same way, and verifies the
+final qualities and sizes match what was originally saved.
+
+The format is one instruction per line:
+
+```text
+flags 0
+greyscale
+smart
+skip-photo-lossless
+stream-above 64000000
+max-size 204800
+avif-codec rav1e
+jxl-tuning 7 0
+webp-tuning 4 1
+source /path/to/image.jpg
+encoder webp
+first 70
+discard
+keep
+best 12345 quality 80
+encoder avif
+abort
+retry 1
+keep
+best 23456 lossless quality
+```
+*/
+
+use crate::{
+	Candidate,
+	CLI_GREYSCALE,
+	CLI_PHOTO_LOSSLESS,
+	CLI_SMART,
+	ShareFeedback,
+};
+use refract_core::{
+	AvifCodec,
+	EncodeIter,
+	ImageKind,
+	Input,
+	JxlTuning,
+	Output,
+	Profile,
+	RefractError,
+	WebpTuning,
+};
+use std::{
+	fs::File,
+	io::Write,
+	iter::Peekable,
+	num::{
+		NonZeroU8,
+		NonZeroUsize,
+	},
+	path::Path,
+};
+
+
+
+#[derive(Debug, Default)]
+/// # Feedback Recorder.
+///
+/// This writes the sister thread's decisions to the script file, if any.
+/// Write errors are ignored; recording is strictly best-effort.
+pub(super) struct Recorder(Option<File>);
+
+impl Recorder {
+	/// # New.
+	///
+	/// Open (or create) the script file for appending, and write the run
+	/// header.
+	pub(super) fn new(path: Option<&Path>, flags: u8, opts: u32) -> Self {
+		let file = path.and_then(|p|
+			File::options().create(true).append(true).open(p).ok()
+		);
+		let mut out = Self(file);
+		out.line(&format!("flags {flags}"));
+		if 0 != opts & CLI_GREYSCALE { out.line("greyscale"); }
+		if 0 != opts & CLI_SMART { out.line("smart"); }
+		if 0 == opts & CLI_PHOTO_LOSSLESS { out.line("skip-photo-lossless"); }
+		if let Some(px) = crate::stream_above() { out.line(&format!("stream-above {px}")); }
+		if let Some(max) = crate::max_size() { out.line(&format!("max-size {max}")); }
+		if let Some(min) = crate::lossless_above() { out.line(&format!("lossless-above {min}")); }
+		if let Some(max) = crate::max_steps() { out.line(&format!("max-steps {max}")); }
+		let avif = crate::avif_codec();
+		if avif != AvifCodec::default() { out.line(&format!("avif-codec {avif}")); }
+		let jxl = crate::jxl_tuning();
+		if jxl != JxlTuning::DEFAULT {
+			out.line(&format!("jxl-tuning {} {}", jxl.effort(), jxl.decoding_speed()));
+		}
+		let webp = crate::webp_tuning(opts);
+		if webp != WebpTuning::DEFAULT {
+			out.line(&format!("webp-tuning {} {}", webp.method(), u8::from(webp.sharp_yuv())));
+		}
+		out
+	}
+
+	/// # Is Recording?
+	pub(super) const fn is_active(&self) -> bool { self.0.is_some() }
+
+	/// # Source.
+	pub(super) fn source(&mut self, path: &Path) {
+		if self.0.is_some() {
+			self.line(&format!("source {}", path.to_string_lossy()));
+		}
+	}
+
+	/// # Skip.
+	///
+	/// This is used when the user skips a source after a color warning.
+	pub(super) fn skip(&mut self) { self.line("skip"); }
+
+	/// # Encoder.
+	pub(super) fn encoder(&mut self, kind: ImageKind) {
+		if self.0.is_some() {
+			self.line(&format!("encoder {}", kind.extension()));
+		}
+	}
+
+	/// # First Probe.
+	///
+	/// This is used when the search starts from a predicted quality rather
+	/// than the midpoint.
+	pub(super) fn first(&mut self, quality: NonZeroU8) {
+		if self.0.is_some() { self.line(&format!("first {quality}")); }
+	}
+
+	/// # Region of Interest.
+	///
+	/// This is used when the search is run on a crop per `--region`.
+	pub(super) fn region(&mut self, x: usize, y: usize, width: usize, height: usize) {
+		if self.0.is_some() { self.line(&format!("region {x} {y} {width} {height}")); }
+	}
+
+	/// # Quality Bounds.
+	///
+	/// This is used when the search is narrowed per `--quality`.
+	pub(super) fn bounds(&mut self, bottom: NonZeroU8, top: NonZeroU8) {
+		if self.0.is_some() { self.line(&format!("bounds {bottom} {top}")); }
+	}
+
+	/// # Candidate Feedback.
+	pub(super) fn feedback(&mut self, res: ShareFeedback) {
+		match res {
+			ShareFeedback::Keep => { self.line("keep"); },
+			ShareFeedback::Discard => { self.line("discard"); },
+			ShareFeedback::Undo => { self.line("undo"); },
+			ShareFeedback::Abort => { self.line("abort"); },
+			ShareFeedback::Seek(q) if self.0.is_some() => { self.line(&format!("seek {q}")); },
+			_ => {},
+		}
+	}
+
+	/// # Retry.
+	///
+	/// This is used when the user retries a failed encoder with different
+	/// flags.
+	pub(super) fn retry(&mut self, flags: u8) {
+		if self.0.is_some() { self.line(&format!("retry {flags}")); }
+	}
+
+	/// # Best.
+	pub(super) fn best(&mut self, best: Option<&Output>) {
+		if self.0.is_some() { self.line(&best_line(best)); }
+	}
+
+	/// # Write Line.
+	fn line(&mut self, line: &str) {
+		if let Some(f) = &mut self.0 {
+			let _res = f.write_all(line.as_bytes()).and_then(|()| f.write_all(b"\n"));
+		}
+	}
+}
+
+
+
+#[derive(Debug, Clone, Copy, Default)]
+/// # Replay Options.
+///
+/// These are the iterator settings from the run header, applied to every
+/// encoder.
+struct ReplayOpts {
+	/// # Streaming Threshold.
+	stream_above: Option<NonZeroUsize>,
+
+	/// # Size Budget.
+	max_size: Option<NonZeroUsize>,
+
+	/// # Lossless Threshold.
+	lossless_above: Option<NonZeroUsize>,
+
+	/// # Candidate Cap.
+	max_steps: Option<NonZeroU8>,
+
+	/// # Skip Lossless for Photos.
+	///
+	/// This is off for scripts recorded before it became the default.
+	skip_photo_lossless: bool,
+
+	/// # `AVIF` Codec.
+	avif: AvifCodec,
+
+	/// # `JPEG XL` Tuning.
+	jxl: JxlTuning,
+
+	/// # `WebP` Tuning.
+	webp: WebpTuning,
+}
+
+
+
+/// # Replay Script.
+///
+/// Replay a recorded feedback script, printing the outcome of each encoder
+/// to STDOUT.
+///
+/// ## Errors
+///
+/// Returns an error if the script cannot be read or parsed, or if any of the
+/// final results differ from the recording.
+pub(super) fn replay(path: &Path) -> Result<(), RefractError> {
+	let raw = std::fs::read_to_string(path).map_err(|_| RefractError::Read)?;
+	let mut lines = raw.lines()
+		.map(str::trim)
+		.filter(|l| ! l.is_empty() && ! l.starts_with('#'))
+		.peekable();
+
+	let mut flags: u8 = 0;
+	let mut greyscale = false;
+	let mut smart = false;
+	let mut opts = ReplayOpts::default();
+	let mut src: Option<(String, Input)> = None;
+	let mut mismatches = 0_usize;
+	while let Some(line) = lines.next() {
+		let (cmd, rest) = line.split_once(' ').unwrap_or((line, ""));
+		match cmd {
+			"flags" => {
+				flags = rest.parse().map_err(|_| RefractError::Script)?;
+				greyscale = false;
+				smart = false;
+				opts = ReplayOpts::default();
+			},
+			"greyscale" => { greyscale = true; },
+			"smart" => { smart = true; },
+			"skip-photo-lossless" => { opts.skip_photo_lossless = true; },
+			"stream-above" => { opts.stream_above = Some(rest.parse().map_err(|_| RefractError::Script)?); },
+			"max-size" => { opts.max_size = Some(rest.parse().map_err(|_| RefractError::Script)?); },
+			"lossless-above" => { opts.lossless_above = Some(rest.parse().map_err(|_| RefractError::Script)?); },
+			"max-steps" => { opts.max_steps = Some(rest.parse().map_err(|_| RefractError::Script)?); },
+			"avif-codec" => {
+				opts.avif = AvifCodec::from_name(rest).ok_or(RefractError::Script)?;
+			},
+			"jxl-tuning" => {
+				let (effort, speed) = rest.split_once(' ').ok_or(RefractError::Script)?;
+				opts.jxl = JxlTuning::new(
+					effort.parse().map_err(|_| RefractError::Script)?,
+					speed.parse().map_err(|_| RefractError::Script)?,
+				);
+			},
+			"webp-tuning" => {
+				let (method, sharp) = rest.split_once(' ').ok_or(RefractError::Script)?;
+				opts.webp = WebpTuning::new(
+					method.parse().map_err(|_| RefractError::Script)?,
+					sharp.parse::<u8>().map_err(|_| RefractError::Script)? != 0,
+				);
+			},
+			"source" => {
+				src = match replay_source(Path::new(rest), greyscale) {
+					Ok(input) => Some((rest.to_owned(), input)),
+					Err(e) => {
+						println!("{rest}: {e}");
+						None
+					},
+				};
+			},
+			"skip" => { src = None; },
+			"encoder" => {
+				let (name, input) = src.as_ref().ok_or(RefractError::Script)?;
+				let kind = [ImageKind::Avif, ImageKind::Jpeg, ImageKind::Jxl, ImageKind::Png, ImageKind::Webp].into_iter()
+					.find(|k| k.extension() == rest)
+					.ok_or(RefractError::Script)?;
+
+				let flags =
+					if smart { Profile::from(input).flags(flags) }
+					else { flags };
+				let Some((expected, actual)) = replay_retries(input, kind, flags, opts, &mut lines)?
+				else { continue; };
+
+				if expected == actual { println!("{name}: {kind} OK ({actual})"); }
+				else {
+					println!("{name}: {kind} MISMATCH (expected {expected}; got {actual})");
+					mismatches += 1;
+				}
+			},
+			_ => return Err(RefractError::Script),
+		}
+	}
+
+	if mismatches == 0 { Ok(()) }
+	else { Err(RefractError::Replay) }
+}
+
+
+
+/// # Best Line.
+///
+/// Format the final result for an encoder.
+fn best_line(best: Option<&Output>) -> String {
+	best.map_or_else(
+		|| String::from("none"),
+		|b| format!(
+			"best {} {}",
+			b.size().map_or(0, NonZeroUsize::get),
+			b.quality(),
+		),
+	)
+}
+
+/// # Replay Source.
+fn replay_source(path: &Path, greyscale: bool) -> Result<Input<'static>, RefractError> {
+	let raw: &[u8] = &std::fs::read(path).map_err(|_| RefractError::Read)?;
+	let mut out = Input::try_from(raw)?;
+	if greyscale { out = out.into_greyscale(); }
+	Ok(out)
+}
+
+/// # Replay Encoder (With Retries).
+///
+/// Run the encoder, re-running it with different flags if the script says
+/// to, and return the expected and actual results of the last go.
+fn replay_retries<'a, I>(
+	src: &Input,
+	kind: ImageKind,
+	mut flags: u8,
+	opts: ReplayOpts,
+	lines: &mut Peekable<I>,
+) -> Result<Option<(&'a str, String)>, RefractError>
+where I: Iterator<Item=&'a str> {
+	loop {
+		let res = replay_encoder(src, kind, flags, opts, lines)?;
+		match res.as_ref().and_then(|(expected, _)| expected.strip_prefix("retry ")) {
+			Some(f) => { flags = f.parse().map_err(|_| RefractError::Script)?; },
+			None => return Ok(res),
+		}
+	}
+}
+
+/// # Replay Guide.
+///
+/// Set up the iterator with the run header's settings.
+fn replay_guide<'a>(src: &'a Input, kind: ImageKind, flags: u8, opts: ReplayOpts)
+-> Result<EncodeIter<'a>, RefractError> {
+	let mut guide = EncodeIter::new(src, kind, flags)?;
+	if let Some(px) = opts.stream_above { guide.set_streaming_above(px); }
+	if let Some(n) = opts.max_steps { guide.set_max_steps(n); }
+	if let Some(min) = opts.lossless_above { guide.set_lossless_above(min); }
+	guide.set_avif_codec(opts.avif);
+	guide.set_jxl_tuning(opts.jxl);
+	guide.set_webp_tuning(opts.webp);
+	if opts.skip_photo_lossless { guide.skip_lossless_if_photo(); }
+	Ok(guide)
+}
+
+/// # Replay Encoder.
+///
+/// Run the encoder, answering each candidate per the script, and return the
+/// expected and actual results, unless the encoder couldn't be started.
+fn replay_encoder<'a, I>(
+	src: &Input,
+	kind: ImageKind,
+	flags: u8,
+	opts: ReplayOpts,
+	lines: &mut Peekable<I>,
+) -> Result<Option<(&'a str, String)>, RefractError>
+where I: Iterator<Item=&'a str> {
+	// Search the same crop, if any.
+	let crop = match lines.next_if(|l| l.starts_with("region ")) {
+		Some(region) => {
+			let mut parts = region.trim_start_matches("region ")
+				.split(' ')
+				.map(|v| v.parse::<usize>().map_err(|_| RefractError::Script));
+			let mut next = || parts.next().unwrap_or(Err(RefractError::Script));
+			let (x, y, w, h) = (next()?, next()?, next()?, next()?);
+			Some(src.crop(x, y, w, h).ok_or(RefractError::Script)?)
+		},
+		None => None,
+	};
+	let target = crop.as_ref().unwrap_or(src);
+
+	let Ok(mut guide) = replay_guide(target, kind, flags, opts) else { return Ok(None); };
+
+	// Search the same range, and start from the same place, if it wasn't the
+	// middle.
+	if let Some(bounds) = lines.next_if(|l| l.starts_with("bounds ")) {
+		let (bottom, top) = bounds.trim_start_matches("bounds ").split_once(' ')
+			.and_then(|(b, t)| Some((b.parse::<NonZeroU8>().ok()?, t.parse::<NonZeroU8>().ok()?)))
+			.ok_or(RefractError::Script)?;
+		guide.set_bounds(bottom, top);
+	}
+	if let Some(first) = lines.next_if(|l| l.starts_with("first ")) {
+		let first: NonZeroU8 = first.trim_start_matches("first ").parse().map_err(|_| RefractError::Script)?;
+		guide.set_first(first);
+	}
+
+	// This needs to mirror the main encoding loop.
+	if let Some(max) = opts.max_size {
+		guide.set_max_size(max);
+		while guide.advance().is_some() {}
+	}
+	else {
+		let mut again = false;
+		loop {
+			let out =
+				if again {
+					again = false;
+					guide.candidate()
+				}
+				else { guide.advance() };
+			let Some(out) = out else { break; };
+			match Candidate::try_from(out).and_then(|c| c.verify_dimensions(target.width(), target.height())) {
+				Ok(_) => {},
+				Err(RefractError::Dimensions) => {
+					guide.discard();
+					continue;
+				},
+				Err(_) => break,
+			}
+
+			match lines.next() {
+				Some("keep") => { guide.keep(); },
+				Some("discard") => { guide.discard(); },
+				Some("undo") => {
+					guide.undo();
+					again = true;
+				},
+				Some("abort") => break,
+				Some(l) if l.starts_with("seek ") => {
+					let q: NonZeroU8 = l.trim_start_matches("seek ").parse().map_err(|_| RefractError::Script)?;
+					guide.set_first(q);
+				},
+				_ => return Err(RefractError::Script),
+			}
+		}
+	}
+
+	let best = guide.take();
+	let best =
+		if opts.max_size.is_some() {
+			best.and_then(|x| {
+				Candidate::try_from(&x)?.verify_dimensions(src.width(), src.height())?;
+				Ok(x)
+			})
+		}
+		else { best };
+
+	#[cfg(feature = "avif_dav1d")]
+	let best = best.and_then(|x| x.verify_dav1d().map(|()| x));
+
+	// Apply the crop's verdict to the whole thing.
+	let best =
+		if crop.is_some() {
+			best.and_then(|b| {
+				let q = b.quality();
+				let best = replay_guide(src, kind, flags, opts)?
+					.once((! q.is_lossless()).then(|| q.raw()))?;
+				Candidate::try_from(&best)?.verify_dimensions(src.width(), src.height())?;
+				#[cfg(feature = "avif_dav1d")]
+				best.verify_dav1d()?;
+				Ok(best)
+			})
+		}
+		else { best };
+
+	let expected = lines.next().ok_or(RefractError::Script)?;
+	Ok(Some((expected, best_line(best.ok().as_ref()))))
+}
@@ -0,0 +1,127 @@
+/*!
+# `Refract GTK` - Session Journal
+
+When enabled with `--resume <FILE>`, each finished source — and the outputs
+saved for it — is appended to the journal as it happens, so that a crash or
+accidental quit partway through a big batch doesn't lose everything. Run the
+same command again and any sources already marked done are skipped.
+
+The format is one entry per line:
+
+```text
+saved /path/to/image.jpg.webp
+saved /path/to/image.jpg.avif
+done /path/to/image.jpg
+```
+*/
+
+use std::{
+	collections::HashSet,
+	fs::File,
+	io::Write,
+	path::{
+		Path,
+		PathBuf,
+	},
+	sync::{
+		Mutex,
+		PoisonError,
+	},
+};
+
+
+
+/// # Session.
+static SESSION: Mutex<Option<Session>> = Mutex::new(None);
+
+
+
+/// # Session Journal.
+struct Session {
+	/// # Journal File.
+	file: Option<File>,
+
+	/// # Finished Sources.
+	done: HashSet<PathBuf>,
+}
+
+impl Session {
+	/// # Write Line.
+	///
+	/// Write errors are ignored; journaling is strictly best-effort.
+	fn line(&mut self, kind: &str, path: &Path) {
+		if let Some(file) = self.file.as_mut() {
+			let line = format!("{kind} {}\n", path.to_string_lossy());
+			let _res = file.write_all(line.as_bytes()).and_then(|()| file.flush());
+		}
+	}
+}
+
+
+
+/// # Enable Journaling.
+///
+/// Load the sources already finished according to the journal, if it
+/// exists, and open it for appending.
+pub(super) fn init(path: &Path) {
+	let done = std::fs::read_to_string(path)
+		.map(|raw| parse(&raw))
+		.unwrap_or_default();
+	let file = File::options().create(true).append(true).open(path).ok();
+
+	SESSION.lock().unwrap_or_else(PoisonError::into_inner)
+		.replace(Session { file, done });
+}
+
+/// # Is Done?
+///
+/// Returns `true` if the source was finished in a previous run.
+pub(super) fn is_done(path: &Path) -> bool {
+	SESSION.lock().unwrap_or_else(PoisonError::into_inner)
+		.as_ref()
+		.is_some_and(|s| s.done.contains(path))
+}
+
+/// # Output Saved.
+pub(super) fn saved(path: &Path) {
+	let mut session = SESSION.lock().unwrap_or_else(PoisonError::into_inner);
+	if let Some(session) = session.as_mut() { session.line("saved", path); }
+}
+
+/// # Source Done.
+pub(super) fn done(path: &Path) {
+	let mut session = SESSION.lock().unwrap_or_else(PoisonError::into_inner);
+	if let Some(session) = session.as_mut() {
+		session.line("done", path);
+		session.done.insert(path.to_path_buf());
+	}
+}
+
+
+
+/// # Parse Journal.
+///
+/// Return the finished sources. Anything else — including a final line left
+/// half-written by a crash — is ignored.
+fn parse(raw: &str) -> HashSet<PathBuf> {
+	raw.split_inclusive('\n')
+		.filter_map(|line| line.strip_suffix('\n')?.strip_prefix("done "))
+		.map(PathBuf::from)
+		.collect()
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn t_parse() {
+		let done = parse("saved /a.jpg.webp\ndone /a.jpg\ndone /b with space.png\ndone /c.jp");
+		assert_eq!(done.len(), 2);
+		assert!(done.contains(Path::new("/a.jpg")));
+		assert!(done.contains(Path::new("/b with space.png")));
+		assert!(! done.contains(Path::new("/a.jpg.webp")));
+	}
+}
@@ -0,0 +1,225 @@
+/*!
+# `Refract GTK` - Saved Settings
+
+The state of the settings menus — along with a few other odds and ends, like
+the last-used directory — is saved to `settings.toml`, alongside the main
+config file, when the window closes, and restored at the next launch, so
+Refract opens the way it was left:
+
+```toml
+avif-codec = "aom"
+dark = true
+dir = "/home/user/images"
+existing = "ask"
+interleave = true
+jxl-decoding-speed = 0
+jxl-effort = 9
+max-steps = 0
+quick-save = false
+webp-method = 6
+
+[settings]
+formats = ["avif", "webp"]
+lossless = true
+# …
+```
+
+This file is machine-written; hand-tuned settings belong in a preset.
+*/
+
+use crate::config::{
+	config_path,
+	Preset,
+	settings_table,
+};
+use refract_core::{
+	AvifCodec,
+	JxlTuning,
+	WebpTuning,
+};
+use refract_frontend::Existing;
+use std::path::PathBuf;
+use toml::{
+	Table,
+	Value,
+};
+
+
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+/// # Saved Settings.
+pub(super) struct Settings {
+	/// # Flags.
+	pub(super) flags: u32,
+
+	/// # `AVIF` Codec.
+	pub(super) avif_codec: AvifCodec,
+
+	/// # Dark Mode.
+	///
+	/// This is only set if the user toggled it themselves; otherwise the
+	/// desktop preference is followed.
+	pub(super) dark: Option<bool>,
+
+	/// # Existing Output Policy.
+	pub(super) existing: Existing,
+
+	/// # Interleave Formats.
+	pub(super) interleave: bool,
+
+	/// # `JPEG XL` Tuning.
+	pub(super) jxl: JxlTuning,
+
+	/// # Candidate Cap.
+	///
+	/// Zero means no limit.
+	pub(super) max_steps: u8,
+
+	/// # Quick Save.
+	pub(super) quick_save: bool,
+
+	/// # `WebP` Method.
+	pub(super) webp_method: u8,
+
+	/// # Last-Used Directory.
+	pub(super) dir: Option<PathBuf>,
+}
+
+impl Default for Settings {
+	fn default() -> Self {
+		Self {
+			flags: 0,
+			avif_codec: AvifCodec::Aom,
+			dark: None,
+			existing: Existing::Ask,
+			interleave: true,
+			jxl: JxlTuning::DEFAULT,
+			max_steps: 0,
+			quick_save: false,
+			webp_method: WebpTuning::DEFAULT.method(),
+			dir: None,
+		}
+	}
+}
+
+impl Settings {
+	/// # Load.
+	///
+	/// Read the saved settings, if any. Missing or malformed values fall back
+	/// to the defaults; there's no sense refusing to start over a file the
+	/// program wrote itself.
+	pub(super) fn load() -> Self {
+		settings_path()
+			.and_then(|p| std::fs::read_to_string(p).ok())
+			.map_or_else(Self::default, |raw| Self::parse(&raw))
+	}
+
+	/// # Parse.
+	fn parse(raw: &str) -> Self {
+		let mut out = Self::default();
+		let Ok(raw) = raw.parse::<Table>() else { return out; };
+
+		if let Some(v) = raw.get("settings") {
+			if let Ok(p) = Preset::try_from(("settings", v)) { out.flags = p.flags; }
+		}
+		if let Some(v) = raw.get("avif-codec").and_then(Value::as_str).and_then(AvifCodec::from_name) {
+			out.avif_codec = v;
+		}
+		out.dark = raw.get("dark").and_then(Value::as_bool);
+		if let Some(v) = raw.get("existing").and_then(Value::as_str).and_then(|v| Existing::try_from(v).ok()) {
+			out.existing = v;
+		}
+		if let Some(v) = raw.get("interleave").and_then(Value::as_bool) { out.interleave = v; }
+		if let Some(v) = raw.get("quick-save").and_then(Value::as_bool) { out.quick_save = v; }
+		let effort = raw.get("jxl-effort").and_then(Value::as_integer).and_then(|v| u8::try_from(v).ok());
+		let speed = raw.get("jxl-decoding-speed").and_then(Value::as_integer).and_then(|v| u8::try_from(v).ok());
+		if effort.is_some() || speed.is_some() {
+			let (e, s) = (out.jxl.effort(), out.jxl.decoding_speed());
+			out.jxl = JxlTuning::new(effort.unwrap_or(e), speed.unwrap_or(s));
+		}
+		if let Some(v) = raw.get("max-steps").and_then(Value::as_integer).and_then(|v| u8::try_from(v).ok()) {
+			out.max_steps = v.min(100);
+		}
+		if let Some(v) = raw.get("webp-method").and_then(Value::as_integer).and_then(|v| u8::try_from(v).ok()) {
+			out.webp_method = v.min(6);
+		}
+		out.dir = raw.get("dir")
+			.and_then(Value::as_str)
+			.map(PathBuf::from)
+			.filter(|p| p.is_absolute());
+
+		out
+	}
+
+	/// # Save.
+	///
+	/// Write the settings to disk, creating the config directory if needed.
+	/// Errors are ignored; the worst case is starting fresh next time.
+	pub(super) fn save(&self) {
+		let Some(path) = settings_path() else { return; };
+		if let Some(parent) = path.parent() {
+			if std::fs::create_dir_all(parent).is_err() { return; }
+		}
+		let _res = write_atomic::write_file(&path, self.to_toml().as_bytes());
+	}
+
+	/// # As TOML.
+	fn to_toml(&self) -> String {
+		let mut out = Table::new();
+		out.insert("avif-codec".to_owned(), Value::from(self.avif_codec.as_str()));
+		if let Some(dark) = self.dark { out.insert("dark".to_owned(), Value::Boolean(dark)); }
+		if let Some(dir) = self.dir.as_ref() {
+			out.insert("dir".to_owned(), Value::from(dir.to_string_lossy().into_owned()));
+		}
+		out.insert("existing".to_owned(), Value::from(self.existing.as_str()));
+		out.insert("interleave".to_owned(), Value::Boolean(self.interleave));
+		out.insert("jxl-decoding-speed".to_owned(), Value::Integer(self.jxl.decoding_speed().into()));
+		out.insert("jxl-effort".to_owned(), Value::Integer(self.jxl.effort().into()));
+		out.insert("max-steps".to_owned(), Value::Integer(self.max_steps.into()));
+		out.insert("quick-save".to_owned(), Value::Boolean(self.quick_save));
+		out.insert("settings".to_owned(), Value::Table(settings_table(self.flags)));
+		out.insert("webp-method".to_owned(), Value::Integer(self.webp_method.into()));
+		out.to_string()
+	}
+}
+
+
+
+/// # Settings File Path.
+///
+/// This is `settings.toml`, in the same directory as the config file.
+fn settings_path() -> Option<PathBuf> {
+	config_path().map(|p| p.with_file_name("settings.toml"))
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		CLI_NO_WEBP,
+		CLI_PNG,
+	};
+
+	#[test]
+	fn t_roundtrip() {
+		let settings = Settings {
+			flags: CLI_NO_WEBP | CLI_PNG,
+			avif_codec: AvifCodec::Rav1e,
+			dark: Some(true),
+			existing: Existing::Smaller,
+			interleave: false,
+			jxl: JxlTuning::new(7, 2),
+			max_steps: 5,
+			quick_save: true,
+			webp_method: 4,
+			dir: Some(PathBuf::from("/home/a/images")),
+		};
+		assert_eq!(Settings::parse(&settings.to_toml()), settings);
+
+		// Garbage falls back to the defaults.
+		assert_eq!(Settings::parse("dark = 5\ndir = \"relative\""), Settings::default());
+		assert_eq!(Settings::parse("}{"), Settings::default());
+	}
+}
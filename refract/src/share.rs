@@ -11,20 +11,29 @@ use crossbeam_channel::{
 	Sender,
 };
 use refract_core::{
+	ErrorContext,
 	ImageKind,
 	Output,
+	Profile,
+	Quality,
 	RefractError,
 };
+use refract_frontend::Distortion;
 use std::{
 	cell::RefCell,
+	num::NonZeroU8,
 	path::PathBuf,
 	rc::Rc,
+	time::Duration,
 };
 
 
 
 /// # Payload Type.
-pub(super) type SharePayload = Result<Share, RefractError>;
+///
+/// Errors carry whatever context — source, format, cause — the sister thread
+/// has on hand, so the log can say what actually went wrong.
+pub(super) type SharePayload = Result<Share, ErrorContext>;
 
 /// # Main Thread Receiver.
 type MainRx = Receiver<SharePayload>;
@@ -61,14 +70,83 @@ pub(super) enum Share {
 	/// # New Source.
 	Source(Candidate),
 
+	/// # Source Color Warning.
+	///
+	/// This holds the source bit depth and whether or not it has a non-sRGB
+	/// color profile, one or both of which will be flattened during encoding,
+	/// and whether or not the user should be asked about it. (Unattended runs
+	/// just log the warning and carry on.)
+	Warning(u8, bool, bool),
+
+	/// # Smart Profile.
+	///
+	/// This is sent when smart defaults narrowed the formats and modes for a
+	/// source.
+	Profile(Profile),
+
 	/// # Encoder.
 	Encoder(ImageKind),
 
+	/// # Existing Output?
+	///
+	/// This is sent before each encoder with the source path, giving the main
+	/// thread a chance to skip formats that have already been saved. A reply
+	/// of [`ShareFeedback::Discard`] means skip.
+	Existing(PathBuf, ImageKind),
+
+	/// # Region of Interest.
+	///
+	/// This is sent before a format's quality search is run on a crop of the
+	/// source, holding the crop to show in its place, then again with `None`
+	/// once the search is over to put the whole source back.
+	Region(Option<Candidate>),
+
 	/// # New Candidate.
 	Candidate(Candidate),
 
 	/// # Final "Best" Output.
-	Best(PathBuf, Output),
+	///
+	/// This also holds the distortion stats between it and the source, if
+	/// they could be computed.
+	Best(PathBuf, Output, Option<Distortion>),
+
+	/// # Fallback.
+	///
+	/// This is sent when a format yields nothing for a source — no savings,
+	/// failure, or a skip — with the source path and the format.
+	Fallback(PathBuf, ImageKind),
+
+	/// # Prediction.
+	///
+	/// This holds the predicted first quality and the one actually settled
+	/// on, sent after the latter is saved.
+	Prediction(Quality, Quality),
+
+	/// # Timing.
+	///
+	/// This holds the wall and CPU time an encoder spent on a source, sent
+	/// after each attempt. The latter is `None` if it couldn't be measured
+	/// fairly, e.g. because other `--jobs` workers were running alongside.
+	Timing(ImageKind, Duration, Option<Duration>),
+
+	/// # Retry?
+	///
+	/// This is sent when an encoder fails outright, holding the kind, the
+	/// error, and the encoder flags that were used, giving the user a chance
+	/// to try again with different settings.
+	Retry(ImageKind, RefractError, u8),
+
+	/// # Reviewed.
+	///
+	/// This holds the path to a previously saved conversion and whether or
+	/// not it was approved.
+	Reviewed(PathBuf, bool),
+
+	/// # Done With Source.
+	///
+	/// This is sent after all encoders have finished with a source, along
+	/// with its path and dimensions.
+	DoneSource(PathBuf, usize, usize),
 
 	/// # Totally Done.
 	DoneEncoding,
@@ -138,7 +216,17 @@ pub(super) enum ShareFeedback {
 	Abort,
 	Discard,
 	Keep,
+
+	/// # Undo the Last Keep/Discard.
+	Undo,
+
 	Wait,
+
+	/// # Retry With These (Encoder) Flags.
+	Retry(u8),
+
+	/// # Try This (Raw) Quality Next.
+	Seek(NonZeroU8),
 }
 
 
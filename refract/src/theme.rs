@@ -0,0 +1,141 @@
+/*!
+# `Refract GTK` - Desktop Theme
+
+This keeps the Dark Mode setting in sync with the desktop's light/dark
+preference, as reported by the XDG settings portal — or failing that, the
+GTK theme — both at startup and whenever it changes.
+
+Toggling Dark Mode manually overrides the desktop for the rest of the
+session.
+*/
+
+use crate::Window;
+use gtk::{
+	gio::{
+		BusType,
+		Cancellable,
+		DBusCallFlags,
+		DBusProxy,
+		DBusProxyFlags,
+	},
+	glib::{
+		Value,
+		Variant,
+	},
+	prelude::*,
+};
+use std::rc::Rc;
+
+
+
+/// # Portal Settings Namespace.
+const NAMESPACE: &str = "org.freedesktop.appearance";
+
+/// # Portal Settings Key.
+const KEY: &str = "color-scheme";
+
+/// # Portal Call Timeout (Milliseconds).
+const TIMEOUT: i32 = 500;
+
+
+
+/// # Watch Desktop Theme.
+///
+/// Apply the desktop's current preference, if any, and keep watching for
+/// changes.
+///
+/// The portal connection, if any, is returned; it needs to be kept alive
+/// for as long as the window is.
+pub(super) fn watch(window: &Rc<Window>) -> Option<DBusProxy> {
+	let proxy = DBusProxy::for_bus_sync(
+		BusType::Session,
+		DBusProxyFlags::NONE,
+		None,
+		"org.freedesktop.portal.Desktop",
+		"/org/freedesktop/portal/desktop",
+		"org.freedesktop.portal.Settings",
+		None::<&Cancellable>,
+	).ok();
+
+	// Prefer the portal.
+	if let Some(dark) = proxy.as_ref().and_then(portal_dark) {
+		window.sync_dark(dark);
+
+		let wnd2 = Rc::clone(window);
+		proxy.as_ref()?.connect_local("g-signal", false, move |args| {
+			if let Some(dark) = changed_dark(args) { wnd2.sync_dark(dark); }
+			None
+		});
+		return proxy;
+	}
+
+	// Otherwise fall back to the GTK theme.
+	let settings = gtk::Settings::default()?;
+	window.sync_dark(gtk_dark(&settings));
+	let wnd2 = Rc::clone(window);
+	settings.connect_gtk_theme_name_notify(move |s| { wnd2.sync_dark(gtk_dark(s)); });
+	None
+}
+
+
+
+/// # Changed Dark?
+///
+/// Parse the arguments of a portal signal, returning the new preference if
+/// it is a change to the color scheme.
+fn changed_dark(args: &[Value]) -> Option<bool> {
+	if args.get(2)?.get::<String>().ok()? != "SettingChanged" { return None; }
+	let (ns, key, value) = args.get(3)?
+		.get::<Variant>().ok()?
+		.get::<(String, String, Variant)>()?;
+	if ns == NAMESPACE && key == KEY { scheme_dark(&value) }
+	else { None }
+}
+
+/// # GTK Dark?
+///
+/// Infer the preference from the GTK settings: either an explicit
+/// preference for dark variants, or a theme with "dark" in its name.
+fn gtk_dark(settings: &gtk::Settings) -> bool {
+	settings.is_gtk_application_prefer_dark_theme() ||
+	settings.gtk_theme_name().is_some_and(|n| n.to_ascii_lowercase().contains("dark"))
+}
+
+/// # Portal Dark?
+///
+/// Query the portal for the current color scheme, returning `None` if it
+/// is unavailable or has no opinion.
+fn portal_dark(proxy: &DBusProxy) -> Option<bool> {
+	let args = (NAMESPACE, KEY).to_variant();
+	["ReadOne", "Read"].into_iter()
+		.find_map(|method| proxy.call_sync(
+			method,
+			Some(&args),
+			DBusCallFlags::NONE,
+			TIMEOUT,
+			None::<&Cancellable>,
+		).ok())
+		.and_then(|v| scheme_dark(&v))
+}
+
+/// # Scheme Dark?
+///
+/// Parse a color-scheme value — `1` for dark, `2` for light, `0` for no
+/// preference — digging through however many layers of variant and tuple
+/// wrappers the portal version has seen fit to add.
+fn scheme_dark(value: &Variant) -> Option<bool> {
+	if let Some(v) = value.get::<u32>() {
+		return match v {
+			1 => Some(true),
+			2 => Some(false),
+			_ => None,
+		};
+	}
+
+	if let Some(inner) = value.as_variant() { return scheme_dark(&inner); }
+	if value.is_container() && value.n_children() == 1 {
+		return scheme_dark(&value.child_value(0));
+	}
+
+	None
+}
@@ -3,8 +3,19 @@
 */
 
 use crate::{
+	audit::Audit,
 	Candidate,
+	CLI_AVIF_YUV420,
+	CLI_BACKGROUND,
+	CLI_DEDUPE,
+	CLI_DURABLE,
 	CLI_FORMATS,
+	CLI_GREYSCALE,
+	CLI_JPEG,
+	CLI_SMART,
+	CLI_JXL_PROGRESSIVE,
+	CLI_KEEP_METADATA,
+	CLI_LOSSLESS_LAST,
 	CLI_MODES,
 	CLI_NO_AVIF,
 	CLI_NO_JXL,
@@ -12,12 +23,30 @@ use crate::{
 	CLI_NO_LOSSY,
 	CLI_NO_WEBP,
 	CLI_NO_YCBCR,
+	CLI_PHOTO_LOSSLESS,
+	CLI_PICTURE,
+	CLI_PNG,
+	CLI_SIDECAR,
+	CLI_WEBP_SHARP_YUV,
+	job::Job,
+	keymap,
 	MainTx,
+	progress::{
+		self,
+		Event,
+	},
+	queue::{
+		Queue,
+		QueueItem,
+	},
 	Share,
 	ShareFeedback,
 	SharePayload,
+	script::Recorder,
+	settings::Settings,
 	SisterRx,
 	SisterTx,
+	theme,
 };
 use dactyl::{
 	NicePercent,
@@ -28,28 +57,60 @@ use dactyl::{
 		NiceInflection,
 	},
 };
-use dowser::{
-	Dowser,
-	Extension,
-};
 use gtk::{
 	FileChooserAction,
 	FileFilter,
-	gdk_pixbuf::Pixbuf,
+	gdk_pixbuf::{
+		Colorspace,
+		Pixbuf,
+	},
+	gio::DBusProxy,
 	prelude::*,
 	ResponseType,
 };
 use oxford_join::OxfordJoinFmt;
 use refract_core::{
+	AvifCodec,
 	EncodeIter,
+	ErrorContext,
+	FLAG_AVIF_YUV420,
+	FLAG_JXL_PROGRESSIVE,
+	FLAG_KEEP_METADATA,
+	FLAG_LOSSLESS_LAST,
 	FLAG_NO_AVIF_YCBCR,
 	FLAG_NO_LOSSLESS,
 	FLAG_NO_LOSSY,
+	HistoryPredictor,
 	ImageKind,
 	Input,
+	JxlTuning,
 	Output,
+	Predictor,
+	Profile,
 	Quality,
 	RefractError,
+	similarity,
+};
+use refract_frontend::{
+	crawl,
+	Distortion,
+	DISTORTION_THRESHOLD,
+	Existing,
+	Fallback,
+	interleave,
+	is_jpeg_png,
+	is_next_gen,
+	Naming,
+	picture,
+	PictureSource,
+	QualityOverride,
+	Region,
+	same_file,
+	save,
+	sha256,
+	sparkline,
+	sync_service,
+	with_extension,
 };
 use std::{
 	borrow::Cow,
@@ -57,20 +118,25 @@ use std::{
 		Cell,
 		RefCell,
 	},
-	ffi::OsStr,
-	num::NonZeroUsize,
+	num::{
+		NonZeroU8,
+		NonZeroU32,
+		NonZeroUsize,
+	},
 	path::{
 		Path,
 		PathBuf,
 	},
+	rc::Rc,
+	sync::{
+		Mutex,
+		PoisonError,
+	},
+	time::Duration,
 };
 
 
 
-// The E_AVIF, E_JPEG, E_JPG, E_JXL, E_PNG, and E_WEBP constants are generated
-// by build.rs.
-include!(concat!(env!("OUT_DIR"), "/refract-extensions.rs"));
-
 /// # State Flag: We're in the middle of encoding.
 const FLAG_LOCK_ENCODING: u8 = 0b0000_0001;
 
@@ -89,6 +155,46 @@ const FLAG_TICK_STATUS: u8 =   0b0001_0000;
 /// # State Flag: e need to repaint format labels.
 const FLAG_TICK_AB: u8 =       0b0010_0000;
 
+/// # State Flag: We've warned about cloud-synced output.
+const FLAG_WARNED_SYNC: u8 =   0b0100_0000;
+
+/// # Flag: Dark Mode Set Manually.
+const FLAG_DARK_MANUAL: u8 =   0b1000_0000;
+
+/// # Lossy Kinds (for Quality Equivalents).
+const EQUIVALENT_KINDS: [ImageKind; 4] = [
+	ImageKind::Avif,
+	ImageKind::Jpeg,
+	ImageKind::Jxl,
+	ImageKind::Webp,
+];
+
+/// # Queue Dialog Formats.
+///
+/// These are in the same order as [`cli_encoders`], and double as the first
+/// columns of the queue dialog's model.
+const QUEUE_KINDS: [ImageKind; 5] = [
+	ImageKind::Webp,
+	ImageKind::Avif,
+	ImageKind::Jxl,
+	ImageKind::Png,
+	ImageKind::Jpeg,
+];
+
+/// # Queue Dialog Path Column.
+const QUEUE_COL_PATH: u32 = 5;
+
+/// # Queue Dialog Index Column.
+const QUEUE_COL_IDX: u32 = 6;
+
+/// # Zoom Levels (Percent).
+const ZOOM_LEVELS: [u16; 7] = [25, 50, 100, 150, 200, 300, 400];
+
+/// # Maximum Zoomed Dimension.
+///
+/// Cairo can't paint images any wider or taller than this.
+const ZOOM_MAX_DIMENSION: u64 = 32_767;
+
 
 
 /// # Helper: Pango-Formatted Span.
@@ -114,6 +220,16 @@ macro_rules! log_prefix {
 	);
 }
 
+/// # Initial Status.
+const STATUS_INTRO: &str = concat!(
+	log_prefix!("#9b59b6", "Refract GTK"),
+	log_colored!("#ff3596", concat!("v", env!("CARGO_PKG_VERSION")), true),
+	"\n",
+	log_colored!("#999", "Tweak the settings (if you want to), then select an image or directory to encode!"),
+	"\n",
+	log_colored!("#999", "----"),
+);
+
 /// # Helper: GTK Objects From Builder.
 macro_rules! gtk_obj {
 	($builder:ident, $key:literal) => (
@@ -157,31 +273,74 @@ struct WindowSource {
 
 	/// # Size.
 	size: usize,
+
+	/// # Previously Saved?
+	saved: bool,
+
+	/// # Content Hash.
+	hash: Option<String>,
+
+	/// # Similarity.
+	dssim: Option<f64>,
+
+	/// # Quality Bounds.
+	bounds: Option<(NonZeroU8, NonZeroU8)>,
+
+	/// # Search Progress.
+	progress: Option<(u8, u8)>,
+
+	/// # Scaled Preview.
+	///
+	/// A smaller copy of `buf` for display, if it exceeds `--preview-max`.
+	preview: Option<Pixbuf>,
 }
 
 impl From<Candidate> for WindowSource {
 	#[inline]
-	fn from(src: Candidate) -> Self {
+	fn from(mut src: Candidate) -> Self {
 		let quality = src.quality;
 		let count = src.count;
 		let size = src.size;
+		let saved = src.saved;
+		let hash = src.hash.take();
+		let dssim = src.dssim;
+		let bounds = src.bounds;
+		let progress = src.progress;
+		let buf = Pixbuf::from(src);
+		let preview = crate::preview_max().and_then(|max| previewed(&buf, max));
 
 		Self {
-			buf: Pixbuf::from(src),
+			buf,
 			quality,
 			count,
 			size,
+			saved,
+			hash,
+			dssim,
+			bounds,
+			progress,
+			preview,
 		}
 	}
 }
 
 impl WindowSource {
+	/// # Display Image.
+	///
+	/// Return the image to show: the scaled preview, if any, unless `full`
+	/// is true.
+	fn image(&self, full: bool) -> &Pixbuf {
+		if full { &self.buf }
+		else { self.preview.as_ref().unwrap_or(&self.buf) }
+	}
+
 	/// # Format Value.
 	///
 	/// This returns a value suitable for the `lbl_format_val` widget. It is
-	/// the image kind, optionally with an iteration number (for candidates).
+	/// the image kind, optionally with an iteration number (for candidates
+	/// whose progress is otherwise unknown).
 	fn format_val(&self) -> Cow<str> {
-		if self.count == 0 { Cow::Borrowed(self.quality.kind().as_str()) }
+		if self.count == 0 || self.progress.is_some() { Cow::Borrowed(self.quality.kind().as_str()) }
 		else {
 			Cow::Owned([
 				self.quality.kind().as_str(),
@@ -202,13 +361,34 @@ impl WindowSource {
 	///
 	/// This returns a value suitable for the `lbl_quality_val` widget. This
 	/// will be a normalized quality value like "1.0" unless encoding was
-	/// lossless, in which case it will be a word.
+	/// lossless — or the image was loaded from disk for review, leaving the
+	/// quality unknown — in which case it will be a word.
+	///
+	/// Candidates also get their DSSIM score tacked onto the end, if known,
+	/// as an objective anchor for the subjective judgement.
 	fn quality_val(&self) -> Cow<str> {
-		if self.quality.is_lossless() {
-			if self.count == 0 { Cow::Borrowed("Original") }
-			else { Cow::Borrowed("Lossless") }
+		let out =
+			if self.saved { Cow::Borrowed("Saved") }
+			else if self.quality.is_lossless() {
+				if self.count == 0 { Cow::Borrowed("Original") }
+				else { Cow::Borrowed("Lossless") }
+			}
+			else { Cow::Owned(self.quality.quality().to_string()) };
+
+		match self.dssim {
+			Some(d) => Cow::Owned(format!("{out} (DSSIM {d:.5})")),
+			None => out,
 		}
-		else { Cow::Owned(self.quality.quality().to_string()) }
+	}
+
+	/// # Quality Tooltip.
+	///
+	/// For lossy candidates, this returns the roughly-equivalent qualities
+	/// for the other encoders, giving a starting point for manual qualities
+	/// and bounds.
+	fn quality_tip(&self) -> Option<String> {
+		if self.saved || self.quality.is_lossless() { None }
+		else { quality_equivalents(self.quality) }
 	}
 }
 
@@ -224,38 +404,89 @@ impl WindowSource {
 /// It's pretty monstrous, but what can you do?
 pub(super) struct Window {
 	flags: Cell<u8>,
+	unsupported: Cell<usize>,
 	paths: RefCell<Vec<PathBuf>>,
+	pending: RefCell<Vec<PathBuf>>,
+	queue: Queue,
 	dir: RefCell<Option<PathBuf>>,
 	status: RefCell<String>,
 	source: RefCell<Option<WindowSource>>,
+	whole: RefCell<Option<WindowSource>>,
 	candidate: RefCell<Option<WindowSource>>,
+	saved: RefCell<Vec<PictureSource>>,
+	ladder: RefCell<Vec<(Quality, usize)>>,
+	history: RefCell<Vec<(Quality, bool)>>,
+	predictions: Cell<(usize, usize)>,
+	timings: RefCell<Vec<(ImageKind, Duration, Option<Duration>)>>,
+	preset: RefCell<Option<String>>,
+	dark_auto: Cell<Option<bool>>,
+	zoom: Cell<u16>,
+	shown: RefCell<[Option<Pixbuf>; 3]>,
+	drag: Cell<Option<(f64, f64)>>,
+	diff: RefCell<Option<Pixbuf>>,
+	portal: RefCell<Option<DBusProxy>>,
+	naming: Naming,
+	fallback: Option<Fallback>,
+	record: Option<PathBuf>,
 
 	flt_image: FileFilter,
 	flt_avif: FileFilter,
 	flt_jxl: FileFilter,
+	flt_jpeg: FileFilter,
+	flt_png: FileFilter,
 	flt_webp: FileFilter,
 
 	pub(super) wnd_main: gtk::ApplicationWindow,
-	wnd_image: gtk::ScrolledWindow,
+	pub(super) wnd_image: gtk::ScrolledWindow,
+	pub(super) vp_scrolled: gtk::Viewport,
 	pub(super) wnd_status: gtk::ScrolledWindow,
 
 	pub(super) img_main: gtk::Image,
+	img_candidate: gtk::Image,
+	img_diff: gtk::Image,
+	stk_main: gtk::Stack,
 	pub(super) box_ab: gtk::Box,
+	pub(super) bar_search: gtk::ProgressBar,
 
 	pub(super) btn_discard: gtk::Button,
 	pub(super) btn_keep: gtk::Button,
+	pub(super) btn_undo: gtk::Button,
+	pub(super) btn_pause: gtk::ToggleButton,
+	pub(super) btn_skip: gtk::Button,
+	pub(super) btn_quality: gtk::Button,
+	pub(super) btn_queue: gtk::Button,
 	pub(super) btn_toggle: gtk::Switch,
 
 	pub(super) chk_avif: gtk::CheckMenuItem,
 	pub(super) chk_jxl: gtk::CheckMenuItem,
 	pub(super) chk_webp: gtk::CheckMenuItem,
+	pub(super) chk_png: gtk::CheckMenuItem,
+	pub(super) chk_jpeg: gtk::CheckMenuItem,
 	pub(super) chk_lossless: gtk::CheckMenuItem,
 	pub(super) chk_lossy: gtk::CheckMenuItem,
 	pub(super) chk_ycbcr: gtk::CheckMenuItem,
+	pub(super) chk_avif_yuv420: gtk::CheckMenuItem,
+	pub(super) chk_jxl_progressive: gtk::CheckMenuItem,
+	pub(super) chk_webp_sharp_yuv: gtk::CheckMenuItem,
+	pub(super) chk_lossless_last: gtk::CheckMenuItem,
+	pub(super) chk_photo_lossless: gtk::CheckMenuItem,
+	pub(super) chk_smart: gtk::CheckMenuItem,
+	pub(super) chk_greyscale: gtk::CheckMenuItem,
+	pub(super) chk_keep_metadata: gtk::CheckMenuItem,
+	pub(super) chk_background: gtk::CheckMenuItem,
+	pub(super) chk_interleave: gtk::CheckMenuItem,
+	pub(super) chk_dedupe: gtk::CheckMenuItem,
+	pub(super) chk_durable: gtk::CheckMenuItem,
+	pub(super) chk_quick_save: gtk::CheckMenuItem,
+	pub(super) chk_picture: gtk::CheckMenuItem,
+	pub(super) chk_sidecar: gtk::CheckMenuItem,
 	pub(super) chk_dark: gtk::CheckMenuItem,
+	pub(super) chk_diff: gtk::CheckMenuItem,
+	pub(super) chk_full_size: gtk::CheckMenuItem,
 
 	pub(super) lbl_format: gtk::Label,
 	pub(super) lbl_format_val: gtk::Label,
+	pub(super) lbl_history: gtk::Label,
 	pub(super) lbl_quality: gtk::Label,
 	pub(super) lbl_quality_val: gtk::Label,
 
@@ -268,63 +499,134 @@ pub(super) struct Window {
 	pub(super) mnu_about: gtk::MenuItem,
 	pub(super) mnu_fopen: gtk::MenuItem,
 	pub(super) mnu_dopen: gtk::MenuItem,
+	pub(super) mnu_review: gtk::MenuItem,
+	pub(super) mnu_export: gtk::MenuItem,
+	pub(super) mnu_avif_codec: gtk::MenuItem,
+	pub(super) mnu_existing: gtk::MenuItem,
+	pub(super) mnu_formats_invalid: gtk::MenuItem,
+	pub(super) mnu_jxl_tuning: gtk::MenuItem,
+	pub(super) mnu_max_size: gtk::MenuItem,
+	pub(super) mnu_max_steps: gtk::MenuItem,
+	pub(super) mnu_modes_invalid: gtk::MenuItem,
+	pub(super) mnu_webp_method: gtk::MenuItem,
 	pub(super) mnu_quit: gtk::MenuItem,
-
+	pub(super) mnu_presets: gtk::MenuItem,
+	pub(super) mnu_presets_list: gtk::Menu,
+	pub(super) mnu_shortcuts: gtk::MenuItem,
+	pub(super) mnu_zoom_in: gtk::MenuItem,
+	pub(super) mnu_zoom_out: gtk::MenuItem,
+	pub(super) mnu_zoom_reset: gtk::MenuItem,
+	pub(super) mnu_clear_region: gtk::MenuItem,
+	pub(super) mnu_clear_log: gtk::MenuItem,
+
+	pop_shortcuts: gtk::Popover,
 	spn_loading: gtk::Spinner,
 }
 
 /// ## Instantiation.
 impl Window {
+	#[expect(clippy::too_many_lines, reason = "It is mostly a list of widgets.")]
 	/// # New Instance.
-	pub(super) fn new(app: &gtk::Application, flags: u8) -> Result<Self, RefractError> {
+	pub(super) fn new(
+		app: &gtk::Application,
+		flags: u32,
+		naming: Naming,
+		fallback: Option<Fallback>,
+		record: Option<PathBuf>,
+	) -> Result<Self, RefractError> {
 		// Start the builder.
-		let builder = gtk::Builder::new();
-		builder.add_from_resource(gtk_src!("refract.glade"))
-			.map_err(|_| RefractError::GtkInit)?;
+		let builder = Self::builder()?;
 
 		// Create the main UI shell.
 		let out = Self {
 			flags: Cell::new(FLAG_TICK_STATUS),
+			unsupported: Cell::new(0),
 			paths: RefCell::new(Vec::new()),
+			pending: RefCell::new(Vec::new()),
+			queue: Queue::default(),
 			dir: RefCell::new(None),
-			status: RefCell::new(String::from(concat!(
-				log_prefix!("#9b59b6", "Refract GTK"),
-				log_colored!("#ff3596", concat!("v", env!("CARGO_PKG_VERSION")), true),
-				"\n",
-				log_colored!("#999", "Tweak the settings (if you want to), then select an image or directory to encode!"),
-				"\n",
-				log_colored!("#999", "----"),
-			))),
+			status: RefCell::new(String::from(STATUS_INTRO)),
 			source: RefCell::new(None),
+			whole: RefCell::new(None),
 			candidate: RefCell::new(None),
-
+			saved: RefCell::new(Vec::new()),
+			ladder: RefCell::new(Vec::new()),
+			history: RefCell::new(Vec::new()),
+			predictions: Cell::new((0, 0)),
+			timings: RefCell::new(Vec::new()),
+			preset: RefCell::new(None),
+			dark_auto: Cell::new(None),
+			zoom: Cell::new(100),
+			shown: RefCell::new([None, None, None]),
+			drag: Cell::new(None),
+			diff: RefCell::new(None),
+			portal: RefCell::new(None),
+			naming,
+			fallback,
+			record,
+
+			#[cfg(not(windows))]
 			flt_image: gtk_obj!(builder, "flt_image"),
+			#[cfg(windows)]
+			flt_image: image_filter(),
 			flt_avif: gtk_obj!(builder, "flt_avif"),
 			flt_jxl: gtk_obj!(builder, "flt_jxl"),
+			flt_jpeg: gtk_obj!(builder, "flt_jpeg"),
+			flt_png: gtk_obj!(builder, "flt_png"),
 			flt_webp: gtk_obj!(builder, "flt_webp"),
 
 			wnd_main: gtk_obj!(builder, "wnd_main"),
 			wnd_image: gtk_obj!(builder, "wnd_image"),
+			vp_scrolled: gtk_obj!(builder, "vp_scrolled"),
 			wnd_status: gtk_obj!(builder, "wnd_status"),
 
 			img_main: gtk_obj!(builder, "img_main"),
+			img_candidate: gtk_obj!(builder, "img_candidate"),
+			img_diff: gtk_obj!(builder, "img_diff"),
+			stk_main: gtk_obj!(builder, "stk_main"),
 			box_ab: gtk_obj!(builder, "box_ab"),
+			bar_search: gtk_obj!(builder, "bar_search"),
 
 			btn_discard: gtk_obj!(builder, "btn_discard"),
 			btn_keep: gtk_obj!(builder, "btn_keep"),
+			btn_undo: gtk_obj!(builder, "btn_undo"),
+			btn_pause: gtk_obj!(builder, "btn_pause"),
+			btn_skip: gtk_obj!(builder, "btn_skip"),
+			btn_quality: gtk_obj!(builder, "btn_quality"),
+			btn_queue: gtk_obj!(builder, "btn_queue"),
 			btn_toggle: gtk_obj!(builder, "btn_toggle"),
 
 			chk_avif: gtk_obj!(builder, "chk_avif"),
 			chk_jxl: gtk_obj!(builder, "chk_jxl"),
 			chk_webp: gtk_obj!(builder, "chk_webp"),
+			chk_png: gtk_obj!(builder, "chk_png"),
+			chk_jpeg: gtk_obj!(builder, "chk_jpeg"),
 			chk_lossless: gtk_obj!(builder, "chk_lossless"),
 			chk_lossy: gtk_obj!(builder, "chk_lossy"),
 			chk_ycbcr: gtk_obj!(builder, "chk_ycbcr"),
+			chk_avif_yuv420: gtk_obj!(builder, "chk_avif_yuv420"),
+			chk_jxl_progressive: gtk_obj!(builder, "chk_jxl_progressive"),
+			chk_webp_sharp_yuv: gtk_obj!(builder, "chk_webp_sharp_yuv"),
+			chk_lossless_last: gtk_obj!(builder, "chk_lossless_last"),
+			chk_photo_lossless: gtk_obj!(builder, "chk_photo_lossless"),
+			chk_smart: gtk_obj!(builder, "chk_smart"),
+			chk_greyscale: gtk_obj!(builder, "chk_greyscale"),
+			chk_keep_metadata: gtk_obj!(builder, "chk_keep_metadata"),
+			chk_background: gtk_obj!(builder, "chk_background"),
+			chk_interleave: gtk_obj!(builder, "chk_interleave"),
+			chk_dedupe: gtk_obj!(builder, "chk_dedupe"),
+			chk_durable: gtk_obj!(builder, "chk_durable"),
+			chk_quick_save: gtk_obj!(builder, "chk_quick_save"),
+			chk_picture: gtk_obj!(builder, "chk_picture"),
+			chk_sidecar: gtk_obj!(builder, "chk_sidecar"),
 
 			chk_dark: gtk_obj!(builder, "chk_dark"),
+			chk_diff: gtk_obj!(builder, "chk_diff"),
+			chk_full_size: gtk_obj!(builder, "chk_full_size"),
 
 			lbl_format: gtk_obj!(builder, "lbl_format"),
 			lbl_format_val: gtk_obj!(builder, "lbl_format_val"),
+			lbl_history: gtk_obj!(builder, "lbl_history"),
 			lbl_quality: gtk_obj!(builder, "lbl_quality"),
 			lbl_quality_val: gtk_obj!(builder, "lbl_quality_val"),
 
@@ -337,69 +639,96 @@ impl Window {
 			mnu_about: gtk_obj!(builder, "mnu_about"),
 			mnu_fopen: gtk_obj!(builder, "mnu_fopen"),
 			mnu_dopen: gtk_obj!(builder, "mnu_dopen"),
+			mnu_review: gtk_obj!(builder, "mnu_review"),
+			mnu_export: gtk_obj!(builder, "mnu_export"),
+			mnu_avif_codec: gtk_obj!(builder, "mnu_avif_codec"),
+			mnu_existing: gtk_obj!(builder, "mnu_existing"),
+			mnu_formats_invalid: gtk_obj!(builder, "mnu_formats_invalid"),
+			mnu_jxl_tuning: gtk_obj!(builder, "mnu_jxl_tuning"),
+			mnu_max_size: gtk_obj!(builder, "mnu_max_size"),
+			mnu_max_steps: gtk_obj!(builder, "mnu_max_steps"),
+			mnu_modes_invalid: gtk_obj!(builder, "mnu_modes_invalid"),
+			mnu_webp_method: gtk_obj!(builder, "mnu_webp_method"),
 			mnu_quit: gtk_obj!(builder, "mnu_quit"),
-
+			mnu_presets: gtk_obj!(builder, "mnu_presets"),
+			mnu_presets_list: gtk_obj!(builder, "mnu_presets_list"),
+			mnu_shortcuts: gtk_obj!(builder, "mnu_shortcuts"),
+			mnu_zoom_in: gtk_obj!(builder, "mnu_zoom_in"),
+			mnu_zoom_out: gtk_obj!(builder, "mnu_zoom_out"),
+			mnu_zoom_reset: gtk_obj!(builder, "mnu_zoom_reset"),
+			mnu_clear_region: gtk_obj!(builder, "mnu_clear_region"),
+			mnu_clear_log: gtk_obj!(builder, "mnu_clear_log"),
+
+			// The shortcut cheat sheet hangs off the image area.
+			pop_shortcuts: keymap::cheat_sheet::<gtk::ScrolledWindow>(&gtk_obj!(builder, "wnd_image"))?,
 			spn_loading: gtk_obj!(builder, "spn_loading"),
 		};
 
-		// Close down with the window.
-		out.wnd_main.connect_delete_event(|_, _| {
-			gtk::main_quit();
-			gtk::glib::Propagation::Proceed
-		});
+		// Start it up!
+		out.start(app, &builder, flags).map(|()| out)
+	}
 
-		// Start with a fun image.
-		out.img_main.set_from_resource(Some(gtk_src!("start.png")));
+	/// # Builder.
+	///
+	/// Load the UI definition.
+	///
+	/// ## Errors
+	///
+	/// Returns an error if the resource cannot be parsed.
+	fn builder() -> Result<gtk::Builder, RefractError> {
+		let builder = gtk::Builder::new();
+		builder.add_from_resource(gtk_src!("refract.glade"))
+			.map_err(|_| RefractError::GtkInit)?;
+		Ok(builder)
+	}
 
-		// Hook up some styles.
-		set_widget_style(&out.btn_discard, gtk_src!("btn-discard.css"));
-		set_widget_style(&out.btn_keep, gtk_src!("btn-keep.css"));
-		set_widget_style(&out.spn_loading, gtk_src!("spn-loading.css"));
-		set_widget_style(&out.wnd_image, gtk_src!("wnd-image.css"));
-
-		// Change encoder defaults?
-		match flags & CLI_FORMATS {
-			0 | CLI_FORMATS => {},
-			_ => {
-				if 0 != flags & CLI_NO_AVIF {
-					out.chk_avif.set_active(false);
-				}
-				if 0 != flags & CLI_NO_JXL {
-					out.chk_jxl.set_active(false);
-				}
-				if 0 != flags & CLI_NO_WEBP {
-					out.chk_webp.set_active(false);
-				}
-			},
-		}
+	/// # Start.
+	///
+	/// Bind the keyboard shortcuts, apply the starting flags, hook up the
+	/// styles, load the starting image, enable drag-and-drop, attach the
+	/// window to the application — closing down with it — and show it.
+	///
+	/// ## Errors
+	///
+	/// Returns an error if the keyboard shortcuts cannot be bound.
+	fn start(&self, app: &gtk::Application, builder: &gtk::Builder, flags: u32)
+	-> Result<(), RefractError> {
+		// Bind the keyboard shortcuts.
+		keymap::register(builder, &self.wnd_main)?;
 
-		// Change encoding mode defaults?
-		match flags & CLI_MODES {
-			0 | CLI_MODES => {},
-			_ => {
-				if 0 != flags & CLI_NO_LOSSLESS {
-					out.chk_lossless.set_active(false);
-				}
-				else if 0 != flags & CLI_NO_LOSSY {
-					out.chk_lossy.set_active(false);
-				}
-			},
-		}
+		// Change the defaults?
+		self.set_flags(flags);
 
-		// One other mode, only this isn't affected by the other two.
-		if 0 != flags & CLI_NO_YCBCR {
-			out.chk_ycbcr.set_active(false);
-		}
+		// Hook up some styles.
+		self.set_styles();
 
-		// Enable drag-and-drop.
-		out.toggle_drag_and_drop(true);
+		// Start with a fun image.
+		self.img_main.set_from_resource(Some(gtk_src!("start.png")));
+		self.toggle_drag_and_drop(true);
 
-		// Start it up!
-		out.wnd_main.set_application(Some(app));
-		out.wnd_main.show_all();
-		out.wnd_main.maximize();
+		// Close down with the window.
+		self.wnd_main.connect_delete_event(|_, _| {
+			gtk::main_quit();
+			gtk::glib::Propagation::Proceed
+		});
+
+		self.wnd_main.set_application(Some(app));
+		self.wnd_main.show_all();
+		self.wnd_main.maximize();
+		Ok(())
+	}
+}
 
-		Ok(out)
+/// ## Styles.
+impl Window {
+	/// # Set Styles.
+	///
+	/// Attach the custom CSS to the widgets that need it.
+	fn set_styles(&self) {
+		set_widget_style(&self.btn_discard, gtk_src!("btn-discard.css"));
+		set_widget_style(&self.btn_keep, gtk_src!("btn-keep.css"));
+		set_widget_style(&self.spn_loading, gtk_src!("spn-loading.css"));
+		set_widget_style(&self.wnd_image, gtk_src!("wnd-image.css"));
 	}
 }
 
@@ -445,6 +774,7 @@ impl Window {
 			self.remove_flag(FLAG_LOCK_ENCODING);
 			self.toggle_drag_and_drop(true);
 			self.spn_loading.stop();
+			self.btn_pause.set_active(false);
 		}
 	}
 }
@@ -464,6 +794,9 @@ impl Window {
 		tx: &SisterTx,
 		rx: &SisterRx,
 	) -> bool {
+		// Drop anything finished in a previous session.
+		self.skip_finished();
+
 		// We can abort early if we have no paths or are already encoding.
 		if ! self.has_paths() || ! self.add_flag(FLAG_LOCK_ENCODING) { return false; }
 
@@ -472,66 +805,381 @@ impl Window {
 		self.toggle_drag_and_drop(false);
 
 		// Pull out the data we need.
-		let paths: Vec<PathBuf> = self.paths.borrow_mut().split_off(0);
-		let encoders: Box<[ImageKind]> = self.encoders();
-		let flags: u8 = self.encoder_flags();
+		let mut paths: Vec<PathBuf> = self.paths.borrow_mut().split_off(0);
+		if self.chk_interleave.is_active() { paths = interleave(paths); }
+		self.pending.borrow_mut().clone_from(&paths);
+		let cli = self.cli_flags();
+		let encoders: Box<[ImageKind]> = cli_encoders(cli);
+		let flags: u8 = cli_encoder_flags(cli);
+		let opts: u32 = cli_sister_flags(cli);
+		let record = self.record.clone();
 
 		// Mention that we're starting.
 		self.log_start(paths.len(), &encoders);
 		self.spn_loading.start();
+		self.queue.set(paths);
 
 		// Shove the actual work into a separate thread.
+		let queue = self.queue.clone();
+		let tx2 = tx.clone();
+		let rx2 = rx.clone();
+		std::thread::spawn(move || {
+			if 0 != opts & CLI_BACKGROUND { crate::priority::background(); }
+			let mut rec = Recorder::new(record.as_deref(), flags, opts);
+			encode_outer__(&queue, &encoders, flags, opts, &mut rec, &tx2, &rx2);
+		});
+
+		true
+	}
+
+	/// # Skip Finished.
+	///
+	/// Remove any queued paths already finished according to the `--resume`
+	/// journal, noting how many in the log.
+	fn skip_finished(&self) {
+		let mut paths = self.paths.borrow_mut();
+		let before = paths.len();
+		paths.retain(|p| ! crate::session::is_done(p));
+		let skipped = before - paths.len();
+		drop(paths);
+
+		if 0 != skipped { self.log_resumed(skipped); }
+	}
+
+	/// # Review Saved Conversions.
+	///
+	/// Prompt for a source image, then load it and any previously saved
+	/// conversions — matched by the output naming templates — into the A/B
+	/// viewer. Nothing is re-encoded; keeping or discarding a conversion
+	/// simply records the verdict in the log.
+	///
+	/// Like [`Window::encode`], the work is done in a separate thread.
+	pub(super) fn maybe_review(&self, tx: &SisterTx, rx: &SisterRx) -> bool {
+		if self.is_encoding() { return false; }
+
+		let window = self.file_chooser(
+			"Choose an Image to Review",
+			FileChooserAction::Open,
+			"_Open",
+			self.dir.borrow().as_ref(),
+			Some(&self.flt_image),
+		);
+
+		// Run and close the dialogue.
+		let res = window.run();
+		window.destroy();
+
+		let Some(path) = window.filename().filter(|_| ResponseType::Accept == res)
+		else { return false; };
+
+		// Store the "last used" directory for next time.
+		if let Some(parent) = path.parent() {
+			self.dir.borrow_mut().replace(parent.to_path_buf());
+		}
+
+		// Find the saved conversions, if any.
+		let saved: Vec<(ImageKind, PathBuf)> = [ImageKind::Avif, ImageKind::Jxl, ImageKind::Webp, ImageKind::Png, ImageKind::Jpeg]
+			.into_iter()
+			.map(|k| (k, self.naming.output_path(&path, k)))
+			.filter(|(_, p)| p.is_file() && *p != path)
+			.collect();
+		if saved.is_empty() {
+			self.log_error(RefractError::NoReview);
+			return false;
+		}
+
+		if ! self.add_flag(FLAG_LOCK_ENCODING) { return false; }
+		self.toggle_drag_and_drop(false);
+		self.spn_loading.start();
+
 		let tx2 = tx.clone();
 		let rx2 = rx.clone();
 		std::thread::spawn(move || {
-			encode_outer__(paths, &encoders, flags, &tx2, &rx2);
+			if let Err(e) = review__(&path, &saved, &tx2, &rx2) {
+				Share::sync(&tx2, &rx2, Err(ErrorContext::new(e).with_path(&path)));
+			}
+			Share::sync(&tx2, &rx2, Ok(Share::DoneEncoding));
 		});
 
 		true
 	}
 
-	/// # Encoder Flags.
+	/// # Export Queue.
+	///
+	/// Prompt for a destination, then save everything still waiting to be
+	/// encoded — including the current image, if any — along with the
+	/// current settings to a job file, for use with `--job` elsewhere.
+	pub(super) fn maybe_export(&self) -> bool {
+		let paths: Vec<PathBuf> = self.pending.borrow().iter()
+			.chain(self.paths.borrow().iter())
+			.cloned()
+			.collect();
+		if paths.is_empty() {
+			self.log_error(RefractError::NothingDoing);
+			return false;
+		}
+
+		let window = self.file_chooser(
+			"Export the Queue",
+			FileChooserAction::Save,
+			"_Save",
+			self.dir.borrow().as_ref(),
+			None,
+		);
+		window.set_do_overwrite_confirmation(true);
+		window.set_current_name("refract-job.toml");
+
+		// Run and close the dialogue.
+		let res = window.run();
+		window.destroy();
+
+		let Some(file) = window.filename().filter(|_| ResponseType::Accept == res)
+		else { return false; };
+
+		let job = Job { flags: self.cli_flags(), paths };
+		match job.save(&file) {
+			Ok(()) => {
+				self.log_exported(&file, job.paths.len());
+				true
+			},
+			Err(e) => {
+				self.log_error(e);
+				false
+			},
+		}
+	}
+
+	/// # Set Started.
+	///
+	/// Drop any pending paths ahead of the one the encoder just started on;
+	/// they're finished.
+	fn set_started(&self, path: &Path) {
+		let mut pending = self.pending.borrow_mut();
+		if let Some(idx) = pending.iter().position(|p| p == path) {
+			pending.drain(..idx);
+		}
+	}
+
+	/// # CLI Flags.
+	///
+	/// This is the inverse of [`Window::set_flags`], expressing the current
+	/// settings as the equivalent `CLI_*` flags.
+	fn cli_flags(&self) -> u32 {
+		[
+			(&self.chk_avif, false, CLI_NO_AVIF),
+			(&self.chk_jxl, false, CLI_NO_JXL),
+			(&self.chk_webp, false, CLI_NO_WEBP),
+			(&self.chk_png, true, CLI_PNG),
+			(&self.chk_jpeg, true, CLI_JPEG),
+			(&self.chk_lossless, false, CLI_NO_LOSSLESS),
+			(&self.chk_lossy, false, CLI_NO_LOSSY),
+			(&self.chk_ycbcr, false, CLI_NO_YCBCR),
+			(&self.chk_avif_yuv420, true, CLI_AVIF_YUV420),
+			(&self.chk_jxl_progressive, true, CLI_JXL_PROGRESSIVE),
+			(&self.chk_webp_sharp_yuv, true, CLI_WEBP_SHARP_YUV),
+			(&self.chk_lossless_last, true, CLI_LOSSLESS_LAST),
+			(&self.chk_photo_lossless, true, CLI_PHOTO_LOSSLESS),
+			(&self.chk_smart, true, CLI_SMART),
+			(&self.chk_greyscale, true, CLI_GREYSCALE),
+			(&self.chk_keep_metadata, true, CLI_KEEP_METADATA),
+			(&self.chk_background, true, CLI_BACKGROUND),
+			(&self.chk_dedupe, true, CLI_DEDUPE),
+			(&self.chk_durable, true, CLI_DURABLE),
+			(&self.chk_picture, true, CLI_PICTURE),
+			(&self.chk_sidecar, true, CLI_SIDECAR),
+		]
+			.into_iter()
+			.filter_map(|(chk, on, flag)| (on == chk.is_active()).then_some(flag))
+			.fold(0, |acc, flag| acc | flag)
+	}
+
+	/// # Set Preset.
 	///
-	/// This maps the UI settings to the equivalent [`EncodeIter`] flags.
-	fn encoder_flags(&self) -> u8 {
-		let mut flags: u8 = 0;
+	/// Note the preset being applied so it can be credited in output audits.
+	pub(super) fn set_preset(&self, name: &str) {
+		self.preset.replace(Some(name.to_owned()));
+		self.log_preset(name);
+	}
 
-		if ! self.chk_lossy.is_active() { flags |= FLAG_NO_LOSSY; }
-		else if ! self.chk_lossless.is_active() { flags |= FLAG_NO_LOSSLESS; }
+	/// # Set Tunables.
+	///
+	/// Apply a preset's non-flag settings — effort, size budget, etc. — and
+	/// sync the menus to match. Invalid values are logged and skipped.
+	pub(super) fn set_tunables(&self, tunables: &[(&str, String)]) {
+		for (k, v) in tunables {
+			if let Err(e) = crate::set_tunable(k, v) { self.log_error(e); }
+		}
+		self.sync_avif_codec();
+		self.sync_jxl_tuning();
+		self.sync_max_size();
+		self.sync_max_steps();
+		self.sync_webp_method();
+	}
 
-		if ! self.chk_ycbcr.is_active() { flags |= FLAG_NO_AVIF_YCBCR; }
+	/// # Set (CLI) Flags.
+	///
+	/// This syncs the settings checkboxes to the equivalent `CLI_*` flags,
+	/// either from the command line or a preset.
+	///
+	/// As with the menus themselves, attempts to disable every format or mode
+	/// are overridden, and explained via [`Window::confirm_corrected`].
+	pub(super) fn set_flags(&self, flags: u32) {
+		// PNG and JPEG are opt-in, so count as "none" only if missing.
+		let no_formats = CLI_FORMATS == flags & CLI_FORMATS && 0 == flags & (CLI_PNG | CLI_JPEG);
+		let no_modes = CLI_MODES == flags & CLI_MODES;
+		let formats = if no_formats { 0 } else { flags & CLI_FORMATS };
+		let modes = if no_modes { 0 } else { flags & CLI_MODES };
+		let all = [
+			(&self.chk_avif, 0 == formats & CLI_NO_AVIF),
+			(&self.chk_jxl, 0 == formats & CLI_NO_JXL),
+			(&self.chk_webp, 0 == formats & CLI_NO_WEBP),
+			(&self.chk_png, 0 != flags & CLI_PNG),
+			(&self.chk_jpeg, 0 != flags & CLI_JPEG),
+			(&self.chk_lossless, 0 == modes & CLI_NO_LOSSLESS),
+			(&self.chk_lossy, 0 == modes & CLI_NO_LOSSY),
+			(&self.chk_ycbcr, 0 == flags & CLI_NO_YCBCR),
+			(&self.chk_avif_yuv420, 0 != flags & CLI_AVIF_YUV420),
+			(&self.chk_jxl_progressive, 0 != flags & CLI_JXL_PROGRESSIVE),
+			(&self.chk_webp_sharp_yuv, 0 != flags & CLI_WEBP_SHARP_YUV),
+			(&self.chk_lossless_last, 0 != flags & CLI_LOSSLESS_LAST),
+			(&self.chk_photo_lossless, 0 != flags & CLI_PHOTO_LOSSLESS),
+			(&self.chk_smart, 0 != flags & CLI_SMART),
+			(&self.chk_greyscale, 0 != flags & CLI_GREYSCALE),
+			(&self.chk_keep_metadata, 0 != flags & CLI_KEEP_METADATA),
+			(&self.chk_background, 0 != flags & CLI_BACKGROUND),
+			(&self.chk_dedupe, 0 != flags & CLI_DEDUPE),
+			(&self.chk_durable, 0 != flags & CLI_DURABLE),
+			(&self.chk_picture, 0 != flags & CLI_PICTURE),
+			(&self.chk_sidecar, 0 != flags & CLI_SIDECAR),
+		];
+
+		// Enable before disabling so the "at least one" guards don't trip.
+		for (chk, v) in all { if v { chk.set_active(true); } }
+		for (chk, v) in all { if ! v { chk.set_active(false); } }
+
+		if no_formats {
+			self.confirm_corrected(REASON_FORMATS, "All formats", &self.mnu_formats_invalid, &[]);
+		}
+		if no_modes {
+			self.confirm_corrected(REASON_MODES, "Both modes", &self.mnu_modes_invalid, &[]);
+		}
+	}
 
-		flags
+	/// # Restore Settings.
+	///
+	/// Apply the settings saved at the end of the last session, other than
+	/// the flags, which are handled by [`Window::set_flags`].
+	pub(super) fn restore(&self, settings: &Settings) {
+		self.chk_interleave.set_active(settings.interleave);
+		self.chk_quick_save.set_active(settings.quick_save);
+		if let Some(dir) = settings.dir.as_ref().filter(|d| d.is_dir()) {
+			self.dir.borrow_mut().replace(dir.clone());
+		}
+		if let Some(dark) = settings.dark {
+			self.add_flag(FLAG_DARK_MANUAL);
+			self.chk_dark.set_active(dark);
+			self.toggle_dark();
+		}
+		crate::set_avif_codec(settings.avif_codec, true);
+		self.sync_avif_codec();
+		crate::set_existing(settings.existing, true);
+		self.sync_existing();
+		crate::set_jxl_tuning(settings.jxl, true);
+		self.sync_jxl_tuning();
+		crate::set_max_steps(settings.max_steps, true);
+		self.sync_max_steps();
+		crate::set_webp_method(settings.webp_method, true);
+		self.sync_webp_method();
 	}
 
-	/// # Enabled Encoders.
+	/// # Settings.
 	///
-	/// Return an array of the enabled encoders.
-	fn encoders(&self) -> Box<[ImageKind]> {
-		let mut out: Vec<ImageKind> = Vec::with_capacity(3);
-		if self.chk_webp.is_active() { out.push(ImageKind::Webp); }
-		if self.chk_avif.is_active() { out.push(ImageKind::Avif); }
-		if self.chk_jxl.is_active() { out.push(ImageKind::Jxl); }
-		out.into_boxed_slice()
+	/// Return the current settings, for saving.
+	pub(super) fn settings(&self) -> Settings {
+		Settings {
+			flags: self.cli_flags(),
+			avif_codec: crate::avif_codec(),
+			dark: self.has_flag(FLAG_DARK_MANUAL).then(|| self.chk_dark.is_active()),
+			existing: crate::existing(),
+			interleave: self.chk_interleave.is_active(),
+			jxl: crate::jxl_tuning(),
+			max_steps: crate::max_steps().map_or(0, NonZeroU8::get),
+			quick_save: self.chk_quick_save.is_active(),
+			webp_method: crate::webp_tuning(0).method(),
+			dir: self.dir.borrow().clone(),
+		}
 	}
 
 	/// # Process Feedback.
 	pub(super) fn feedback(&self, tx: &MainTx, status: ShareFeedback) {
+		if matches!(status, ShareFeedback::Keep | ShareFeedback::Discard) {
+			if let Some(can) = self.candidate.borrow().as_ref() {
+				progress::emit(Event::Decision(can.quality, ShareFeedback::Keep == status));
+				if ! can.saved {
+					self.history.borrow_mut().push((can.quality, ShareFeedback::Keep == status));
+				}
+			}
+		}
+		else if ShareFeedback::Undo == status { self.history.borrow_mut().pop(); }
 		self.remove_candidate();
 		self.paint();
 		tx.send(status).unwrap();
 	}
 
-	/// # Has Encoders.
-	pub(super) fn has_encoders(&self) -> bool {
-		self.chk_avif.is_active() ||
-		self.chk_jxl.is_active() ||
-		self.chk_webp.is_active()
+	/// # Encoder Checkboxes.
+	pub(super) const fn encoders(&self) -> [&gtk::CheckMenuItem; 5] {
+		[&self.chk_avif, &self.chk_jxl, &self.chk_webp, &self.chk_png, &self.chk_jpeg]
 	}
 
-	/// # Has (Lossy/Lossless) Modes.
-	pub(super) fn has_modes(&self) -> bool {
-		self.chk_lossless.is_active() || self.chk_lossy.is_active()
+	/// # (Lossy/Lossless) Mode Checkboxes.
+	pub(super) const fn modes(&self) -> [&gtk::CheckMenuItem; 2] {
+		[&self.chk_lossless, &self.chk_lossy]
+	}
+
+	/// # Confirm Corrected.
+	///
+	/// When a setting has been switched back on to keep the configuration
+	/// valid, explain as much inline — beneath the affected group in the
+	/// menu — and in the log, then, if the window is up, ask the user to
+	/// confirm, offering to switch the whole `group` back on instead.
+	pub(super) fn confirm_corrected(
+		&self,
+		reason: &str,
+		what: &str,
+		inline: &gtk::MenuItem,
+		group: &[&gtk::CheckMenuItem],
+	) {
+		self.log_corrected(reason, what);
+
+		if self.wnd_main.is_visible() {
+			let window = gtk::MessageDialog::new(
+				Some(&self.wnd_main),
+				gtk::DialogFlags::MODAL,
+				gtk::MessageType::Warning,
+				gtk::ButtonsType::None,
+				&format!("{reason}."),
+			);
+			window.set_secondary_text(Some(&format!(
+				"{what} will stay on; otherwise there would be nothing to do.",
+			)));
+			if ! group.is_empty() {
+				window.add_button("Enable _All", ResponseType::Apply);
+			}
+			window.add_button("_OK", ResponseType::Accept);
+			window.set_default_response(ResponseType::Accept);
+
+			// Run and close the dialogue.
+			let res = window.run();
+			window.emit_close();
+
+			if ResponseType::Apply == res {
+				for chk in group { chk.set_active(true); }
+			}
+		}
+
+		// Do this last; ticking a box clears it.
+		inline.set_label(&format!("{reason}."));
+		inline.show();
 	}
 
 	#[inline]
@@ -552,7 +1200,14 @@ impl Window {
 		if self.has_candidate() {
 			self.remove_flag(FLAG_LOCK_FEEDBACK);
 			self.candidate.borrow_mut().take();
-			gtk_sensitive!(false, self.btn_discard, self.btn_keep, self.btn_toggle);
+			self.diff.borrow_mut().take();
+			let mut shown = self.shown.borrow_mut();
+			shown[1] = None;
+			shown[2] = None;
+			drop(shown);
+			self.img_candidate.clear();
+			self.img_diff.clear();
+			gtk_sensitive!(false, self.btn_discard, self.btn_keep, self.btn_quality, self.btn_undo, self.btn_toggle);
 			self.toggle_preview(false, false);
 			self.add_flag(FLAG_TICK_AB);
 		}
@@ -568,7 +1223,7 @@ impl Window {
 	}
 
 	/// # Set Best.
-	fn set_best(&self, mut path: PathBuf, src: Output)
+	fn set_best(&self, path: &Path, src: Output, dist: Option<Distortion>)
 	-> Result<ShareFeedback, RefractError> {
 		// We still need a source.
 		if ! self.has_source() {
@@ -579,20 +1234,67 @@ impl Window {
 		self.remove_candidate();
 		self.toggle_spinner(false);
 
+		// If we're only keeping one lossless copy, see if we already have a
+		// smaller one, or a bigger one to replace.
+		let lossless = src.quality().is_lossless();
+		let new_size = src.size().map_or(0, NonZeroUsize::get);
+		let mut redundant = None;
+		if lossless && src.kind() != ImageKind::Png && self.chk_dedupe.is_active() {
+			if let Some(old) = self.saved.borrow().iter().find(|s| s.lossless && s.kind != ImageKind::Png) {
+				if old.size <= new_size {
+					self.log_redundant(src.kind().as_str(), old.kind);
+					progress::emit(Event::Skipped("redundant"));
+					return Ok(ShareFeedback::Continue);
+				}
+				redundant.replace(old.path.clone());
+			}
+		}
+
+		// If there's a smaller copy from an earlier run, keep it.
+		if crate::existing() == Existing::Smaller {
+			let old = self.naming.output_path(path, src.kind());
+			if let Ok(meta) = std::fs::metadata(&old) {
+				if ! Existing::Smaller.replaces(meta.len(), new_size as u64) {
+					self.log_existing(&old);
+					progress::emit(Event::Skipped("existing"));
+					return Ok(ShareFeedback::Continue);
+				}
+			}
+		}
+
 		// Save it.
-		path = self.maybe_save(&path, &src)?;
+		let new_path = self.maybe_save(path, &src)?;
+		self.saved.borrow_mut().push(PictureSource::new(
+			new_path.clone(),
+			src.kind(),
+			new_size,
+			lossless,
+		));
+
+		// Remove the redundant copy, if any.
+		if let Some(old) = redundant {
+			if std::fs::remove_file(&old).is_ok() {
+				self.saved.borrow_mut().retain(|s| s.path != old);
+				self.log_redundant(&old.to_string_lossy(), src.kind());
+			}
+		}
 
 		// Record the happiness.
+		progress::emit(Event::Saved(src.quality(), new_size, &new_path, dist));
+		crate::session::saved(&new_path);
 		let old_size: usize = self.source.borrow()
 			.as_ref()
 			.map(|x| x.size)
 			.ok_or(RefractError::MissingSource)?;
 		self.log_saved(
-			path,
+			&new_path,
 			src.quality(),
 			old_size,
 			src.size().map_or(old_size, NonZeroUsize::get),
 		);
+		if let Some(dist) = dist { self.log_distortion(dist); }
+		self.log_ladder(src.quality(), new_size);
+		self.maybe_audit(path, &new_path, &src);
 
 		drop(src);
 		Ok(ShareFeedback::Continue)
@@ -601,9 +1303,22 @@ impl Window {
 	/// # Set Candidate.
 	fn set_candidate(&self, src: Candidate) -> Result<ShareFeedback, RefractError> {
 		if self.has_source() {
+			progress::emit(Event::Candidate(src.quality, src.size, src.progress.and_then(percent)));
+			if ! src.saved {
+				// Undone candidates come around twice.
+				let mut ladder = self.ladder.borrow_mut();
+				let rung = ladder_rung(src.quality);
+				if ! ladder.iter().any(|&(q, s)| ladder_rung(q) == rung && s == src.size) {
+					ladder.push((src.quality, src.size));
+				}
+			}
+			let undo = src.undo && ! src.saved;
+			let seek = src.bounds.is_some_and(|(b, t)| b < t) && ! src.quality.is_lossless();
 			self.candidate.borrow_mut().replace(WindowSource::from(src));
 			self.toggle_preview(true, false);
 			gtk_sensitive!(true, self.btn_discard, self.btn_keep, self.btn_toggle);
+			gtk_sensitive!(undo, self.btn_undo);
+			gtk_sensitive!(seek, self.btn_quality);
 			self.add_flag(FLAG_LOCK_FEEDBACK | FLAG_TICK_AB);
 			Ok(ShareFeedback::Wait)
 		}
@@ -612,29 +1327,49 @@ impl Window {
 
 	/// # Set Image.
 	///
-	/// This method updates the `Pixbuf` associated with the `img_main` widget.
+	/// This method updates the `Pixbuf` associated with the `img_main` widget
+	/// — or `img_candidate` when viewing a candidate, or `img_diff` when
+	/// viewing its differences — and brings it to the front of the `stk_main`
+	/// stack.
 	///
 	/// As this is a relatively heavy operation, a flag is used to track when
 	/// the image actually needs updating, and this method will no-op if no
-	/// update is required.
+	/// update is required. Each widget also holds onto its image between
+	/// flips, so toggling between the source and candidate only has to swap
+	/// the visible child, keeping comparisons instantaneous even for very
+	/// large images.
 	///
 	/// If `None` is passed, the image is cleared.
 	///
 	/// For source/candidate switching, this will also update the background
 	/// class associated with the `wnd_image` widget.
+	///
+	/// Images are scaled to the current zoom level. Because both widgets share
+	/// the same scrolled window, and are scaled alike, the pan position
+	/// carries over from one to the other.
 	fn set_image(&self, img: Option<&Pixbuf>) {
 		if self.remove_flag(FLAG_TICK_IMAGE) {
+			let preview_b = img.is_some() && self.btn_toggle.is_active();
+			let idx =
+				if ! preview_b { 0 }
+				else if self.chk_diff.is_active() { 2 }
+				else { 1 };
+			let widget = [&self.img_main, &self.img_candidate, &self.img_diff][idx];
+
 			// Set the done image.
 			if img.is_none() && ! self.is_encoding() {
+				self.shown.borrow_mut()[0] = None;
 				self.img_main.set_from_resource(Some(gtk_src!("stop.png")));
 			}
-			// Set/unset the image as instructed.
-			else {
-				self.img_main.set_from_pixbuf(img);
+			// Set/unset the image as instructed, unless it is already there.
+			else if self.shown.borrow()[idx].as_ref() != img {
+				self.shown.borrow_mut()[idx] = img.cloned();
+				widget.set_from_pixbuf(img.map(|i| zoomed(i, self.zoom.get())).as_ref());
 			}
+			self.stk_main.set_visible_child(widget);
 
 			// Toggle the background class.
-			if img.is_some() && self.btn_toggle.is_active() {
+			if preview_b {
 				add_widget_class(&self.wnd_image, "preview_b");
 			}
 			else {
@@ -647,21 +1382,222 @@ impl Window {
 	/// # Set Source.
 	fn set_source(&self, src: Candidate) -> Result<ShareFeedback, RefractError> {
 		self.remove_candidate();
-		self.source.borrow_mut().replace(WindowSource::from(src));
+		self.saved.borrow_mut().clear();
+		self.whole.borrow_mut().take();
+		let src = WindowSource::from(src);
+		if let Some(hash) = src.hash.as_deref() { self.log_hash(hash); }
+		self.source.borrow_mut().replace(src);
 		self.toggle_preview(false, true);
 		self.add_flag(FLAG_LOCK_ENCODING | FLAG_TICK_AB);
 		Ok(ShareFeedback::Continue)
 	}
 
+	#[expect(clippy::unnecessary_wraps, reason = "Needed for branch consistency.")]
+	/// # Set Region of Interest.
+	///
+	/// Show the crop in place of the source while a format's search is run
+	/// on it, or put the whole source back if `None`.
+	fn set_region(&self, crop: Option<Candidate>) -> Result<ShareFeedback, RefractError> {
+		self.remove_candidate();
+		if let Some(crop) = crop {
+			let old = self.source.borrow_mut().replace(WindowSource::from(crop));
+			if let Some(old) = old { self.whole.borrow_mut().get_or_insert(old); }
+		}
+		else if let Some(whole) = self.whole.borrow_mut().take() {
+			self.source.borrow_mut().replace(whole);
+		}
+		self.toggle_preview(false, true);
+		Ok(ShareFeedback::Continue)
+	}
+
+	/// # Drag Region of Interest.
+	///
+	/// Note where a (shift-)drag across the source image starts, or, if
+	/// `done`, select everything between there and here.
+	pub(super) fn drag_region(&self, pos: (f64, f64), done: bool) {
+		if ! done { self.drag.set(Some(pos)); }
+		else if let Some(start) = self.drag.take() { self.select_region(start, pos); }
+	}
+
+	/// # Select Region of Interest.
+	///
+	/// Turn a drag across the source image — in `vp_scrolled` coordinates —
+	/// into the region of interest for subsequent quality searches. Tiny
+	/// selections are ignored as likely slips.
+	fn select_region(&self, start: (f64, f64), end: (f64, f64)) {
+		// Only whole sources, please.
+		if self.btn_toggle.is_active() || self.whole.borrow().is_some() { return; }
+		let source = self.source.borrow();
+		let shown = self.shown.borrow();
+		let (Some(src), Some(img)) = (source.as_ref(), shown[0].as_ref()) else { return; };
+
+		// Work out where the (centered, scaled) image sits in the widget.
+		let alloc = self.img_main.allocation();
+		let zoom = f64::from(self.zoom.get()) / 100.0;
+		let (dw, dh) = (f64::from(img.width()) * zoom, f64::from(img.height()) * zoom);
+		let left = f64::from(alloc.x()) + (f64::from(alloc.width()) - dw).max(0.0) / 2.0;
+		let top = f64::from(alloc.y()) + (f64::from(alloc.height()) - dh).max(0.0) / 2.0;
+		let scale = f64::from(src.buf.width()) / dw;
+
+		// Map both corners to source pixels, clamped to the image.
+		let px = |v: f64, edge: f64, max: i32| {
+			let v = ((v - edge) * scale).round().clamp(0.0, f64::from(max));
+			#[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss, reason = "It is clamped.")]
+			{ v as u32 }
+		};
+		let (x1, x2) = (px(start.0, left, src.buf.width()), px(end.0, left, src.buf.width()));
+		let (y1, y2) = (px(start.1, top, src.buf.height()), px(end.1, top, src.buf.height()));
+		drop(shown);
+		drop(source);
+
+		let width = NonZeroU32::new(x1.abs_diff(x2)).filter(|w| 8 <= w.get());
+		let height = NonZeroU32::new(y1.abs_diff(y2)).filter(|h| 8 <= h.get());
+		if let Some((width, height)) = width.zip(height) {
+			self.set_region_of_interest(Some(Region::new(x1.min(x2), y1.min(y2), width, height)));
+		}
+	}
+
+	/// # Set Region of Interest.
+	///
+	/// Change (or clear) the region of interest, effective from the next
+	/// format's quality search.
+	pub(super) fn set_region_of_interest(&self, region: Option<Region>) {
+		crate::set_region(region);
+		self.mnu_clear_region.set_sensitive(region.is_some());
+		self.log_region(region);
+		self.paint_status();
+	}
+
+	/// # Sync Dark Mode.
+	///
+	/// Match the desktop's light/dark preference, unless the user has toggled
+	/// dark mode themselves.
+	pub(super) fn sync_dark(&self, dark: bool) {
+		if ! self.has_flag(FLAG_DARK_MANUAL) {
+			self.dark_auto.set(Some(dark));
+			self.chk_dark.set_active(dark);
+		}
+	}
+
 	/// # Toggle Dark Mode.
 	pub(super) fn toggle_dark(&self) {
 		// Toggle the background class.
-		if self.chk_dark.is_active() {
+		let dark = self.chk_dark.is_active();
+		if dark {
 			add_widget_class(&self.wnd_image, "dark");
 		}
 		else {
 			remove_widget_class(&self.wnd_image, "dark");
 		}
+
+		// If this doesn't match the desktop, the user must have flipped it,
+		// so stop following along.
+		if self.dark_auto.get() != Some(dark) { self.add_flag(FLAG_DARK_MANUAL); }
+	}
+
+	/// # Toggle Differences.
+	///
+	/// Differences are shown in place of the candidate, so jump to it, if
+	/// there is one, when they're enabled.
+	pub(super) fn toggle_diff(&self) {
+		let preview_b =
+			self.btn_toggle.is_active() ||
+			(self.chk_diff.is_active() && self.has_candidate());
+		self.toggle_preview(preview_b, true);
+		self.paint();
+	}
+
+	/// # Toggle Full Size.
+	///
+	/// Switch between the scaled previews and the full-resolution images, if
+	/// `--preview-max` called for the former.
+	pub(super) fn toggle_full_size(&self) {
+		self.diff.borrow_mut().take();
+		self.add_flag(FLAG_TICK_IMAGE | FLAG_TICK_AB);
+		self.paint();
+	}
+
+	/// # Difference Heatmap.
+	///
+	/// Return the heatmap of differences between the source and candidate,
+	/// if that is what should be shown. It is computed the first time it is
+	/// needed, then held onto until the candidate is removed.
+	fn difference(&self) -> Option<Pixbuf> {
+		if ! self.chk_diff.is_active() || ! self.btn_toggle.is_active() { return None; }
+		if let Some(diff) = self.diff.borrow().as_ref() { return Some(diff.clone()); }
+
+		let src = self.source.borrow();
+		let can = self.candidate.borrow();
+		let (a, b) = (&src.as_ref()?.buf, &can.as_ref()?.buf);
+		let (width, height) = (a.width(), a.height());
+		if width != b.width() || height != b.height() { return None; }
+
+		let diff = refract_core::difference(
+			&a.read_pixel_bytes(),
+			&b.read_pixel_bytes(),
+			usize::try_from(width).ok()?,
+			usize::try_from(height).ok()?,
+		)?;
+		let diff = Pixbuf::from_mut_slice(diff, Colorspace::Rgb, true, 8, width, height, width * 4);
+
+		// Match the scale of the images it stands in for.
+		let diff =
+			if self.chk_full_size.is_active() { diff }
+			else {
+				crate::preview_max().and_then(|max| previewed(&diff, max)).unwrap_or(diff)
+			};
+		self.diff.borrow_mut().replace(diff.clone());
+		Some(diff)
+	}
+
+	/// # Watch Desktop Theme.
+	///
+	/// Follow the desktop's light/dark preference, holding onto the portal
+	/// connection, if any, so it stays alive.
+	pub(super) fn watch_theme(self: &Rc<Self>) {
+		let portal = theme::watch(self);
+		self.portal.replace(portal);
+	}
+
+	/// # Toggle Shortcuts.
+	///
+	/// Show or hide the keyboard shortcut cheat sheet.
+	pub(super) fn toggle_shortcuts(&self) {
+		if self.pop_shortcuts.is_visible() { self.pop_shortcuts.popdown(); }
+		else { self.pop_shortcuts.popup(); }
+	}
+
+	/// # Zoom.
+	///
+	/// Step the preview zoom in (`Some(true)`), out (`Some(false)`), or back
+	/// to 100% (`None`), rescaling both the source and candidate images.
+	///
+	/// The view stays centered on the same spot, so zooming in for a closer
+	/// look doesn't lose one's place.
+	pub(super) fn zoom(&self, dir: Option<bool>) {
+		let old = self.zoom.get();
+		let shown = self.shown.borrow();
+		let Some(new) = zoom_level(old, dir, shown.iter().flatten()) else { return; };
+		self.zoom.set(new);
+
+		// Note the (relative) center before rescaling.
+		let adjs = [self.wnd_image.hadjustment(), self.wnd_image.vadjustment()];
+		let centers = adjs.each_ref().map(|adj|
+			if adj.upper() > 0.0 { (adj.value() + adj.page_size() / 2.0) / adj.upper() }
+			else { 0.5 }
+		);
+
+		for (img, widget) in shown.iter().zip([&self.img_main, &self.img_candidate, &self.img_diff]) {
+			if let Some(img) = img { widget.set_from_pixbuf(Some(&zoomed(img, new))); }
+		}
+
+		// The new bounds won't be known until GTK gets around to resizing
+		// everything, so restore the center once it has.
+		gtk::glib::idle_add_local_once(move || {
+			for (adj, center) in adjs.into_iter().zip(centers) {
+				adj.set_value(center.mul_add(adj.upper(), adj.page_size() / -2.0));
+			}
+		});
 	}
 
 	/// # Toggle Preview.
@@ -684,6 +1620,30 @@ impl Window {
 	}
 
 	#[inline]
+	/// # Toggle Pause.
+	///
+	/// This is a special handler for the `btn_pause` widget, holding or
+	/// releasing the encoding thread.
+	pub(super) fn toggle_pause(&self, val: bool) {
+		crate::pause::set(val);
+		if self.is_encoding() {
+			self.log_paused(val);
+			self.paint_status();
+		}
+	}
+
+	#[inline]
+	/// # Skip Format.
+	///
+	/// This is a special handler for the `btn_skip` widget, abandoning the
+	/// current format's search.
+	pub(super) fn skip_format(&self) {
+		if self.is_encoding() {
+			crate::pause::skip();
+			self.log_skip_format();
+		}
+	}
+
 	/// # Toggle Spinner.
 	fn toggle_spinner(&self, val: bool) {
 		if val != self.spn_loading.is_active() {
@@ -722,7 +1682,7 @@ impl Window {
 
 		if
 			path.is_file() &&
-			is_jpeg_png(&path)
+			(is_jpeg_png(&path) || is_next_gen(&path))
 		{
 			self.paths.borrow_mut().push(path);
 			true
@@ -730,12 +1690,35 @@ impl Window {
 		else { false }
 	}
 
+	/// # Open With.
+	///
+	/// Queue up paths handed over by another instance — e.g. via a file
+	/// manager's "Open With" — and bring the window to the front. If an
+	/// encoding run is already underway, they're tacked onto the end of it;
+	/// otherwise a new one is started.
+	pub(super) fn open_paths(&self, paths: &[PathBuf], tx: &SisterTx, rx: &SisterRx) {
+		let paths: Vec<PathBuf> = crawl(paths, None);
+		self.wnd_main.present();
+		if paths.is_empty() { return; }
+
+		if self.is_encoding() && self.queue.extend(paths.clone()) {
+			self.pending.borrow_mut().extend(paths);
+			self.log_queue(self.queue.snapshot().len());
+			self.paint_status();
+			return;
+		}
+
+		// Otherwise start a new run. (If the last one is just wrapping up,
+		// they'll wait for the next.)
+		for p in paths { self.add_file(p); }
+		self.encode(tx, rx);
+	}
+
 	/// # Add Directory.
 	fn add_directory<P>(&self, path: P) -> bool
 	where P: AsRef<Path> {
 		// And find the paths.
-		let mut paths: Vec<PathBuf> = Dowser::from(path.as_ref())
-			.into_vec_filtered(is_jpeg_png);
+		let mut paths: Vec<PathBuf> = crawl(&[path.as_ref().to_path_buf()], None);
 
 		if paths.is_empty() { false }
 		else {
@@ -749,6 +1732,9 @@ impl Window {
 	///
 	/// This makes a new file chooser dialogue of the specified kind, and
 	/// optionally sets the working directory and/or filter.
+	///
+	/// These are "native" choosers, so use the platform's own dialogues where
+	/// there are any, e.g. on Windows or in a Flatpak.
 	fn file_chooser<P>(
 		&self,
 		title: &str,
@@ -756,13 +1742,14 @@ impl Window {
 		btn: &str,
 		dir: Option<P>,
 		filter: Option<&FileFilter>,
-	) -> gtk::FileChooserDialog
+	) -> gtk::FileChooserNative
 	where P: AsRef<Path> {
-		let out = gtk::FileChooserDialog::with_buttons(
+		let out = gtk::FileChooserNative::new(
 			Some(title),
 			Some(&self.wnd_main),
 			action,
-			&[("_Cancel", ResponseType::Cancel), (btn, ResponseType::Accept)]
+			Some(btn),
+			Some("_Cancel"),
 		);
 
 		if let Some(filter) = filter {
@@ -776,8 +1763,508 @@ impl Window {
 		out
 	}
 
-	/// # Has Paths?
-	fn has_paths(&self) -> bool { ! self.paths.borrow().is_empty() }
+	/// # Save Chooser.
+	///
+	/// This creates, spawns, and kills a file chooser for saving the output,
+	/// returning the path selected.
+	fn save_chooser(&self, dir: &Path, kind: ImageKind, filter: &FileFilter, name: &str)
+	-> Result<PathBuf, RefractError> {
+		let window = self.file_chooser(
+			&["Save the ", kind.as_str(), "!"].concat(),
+			FileChooserAction::Save,
+			"_Save",
+			Some(dir),
+			Some(filter),
+		);
+
+		// Warn about collisions.
+		window.set_do_overwrite_confirmation(true);
+		window.set_current_name(name);
+
+		// Read the result!
+		let res = window.run();
+		window.destroy();
+
+		if ResponseType::Accept == res { window.filename() }
+		else { None }
+		.ok_or(RefractError::NoSave)
+	}
+
+	/// # Quick Save Path.
+	///
+	/// This creates, spawns, and kills a lightweight dialogue with a single
+	/// text field holding the suggested file name, returning the (tweaked)
+	/// path if the user accepts it with `Enter`.
+	///
+	/// If the user presses `Escape` instead, or leaves the name empty, `None`
+	/// is returned so the regular file chooser can take over. (The same goes
+	/// for names that would clobber an existing file, but that is checked by
+	/// the caller.)
+	fn quick_save_path(&self, dir: &Path, name: &str) -> Option<PathBuf> {
+		let window = gtk::Dialog::with_buttons(
+			Some("Save As"),
+			Some(&self.wnd_main),
+			gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+			&[("_Browse…", ResponseType::Cancel), ("_Save", ResponseType::Accept)],
+		);
+		window.set_default_response(ResponseType::Accept);
+
+		let entry = gtk::Entry::new();
+		entry.set_text(name);
+		entry.set_activates_default(true);
+		entry.set_width_chars(48);
+		entry.set_margin(12);
+		window.content_area().add(&entry);
+		window.show_all();
+
+		// Select the stem so typing replaces it but keeps the extension.
+		let stem = name.rfind('.').filter(|&idx| idx != 0).unwrap_or(name.len());
+		entry.select_region(0, i32::try_from(name[..stem].chars().count()).unwrap_or(-1));
+
+		let res = window.run();
+		let text = entry.text();
+		if ResponseType::None != res { window.emit_close(); }
+		if ResponseType::Accept != res { return None; }
+
+		let text = text.trim();
+		if text.is_empty() { None }
+		else { Some(dir.join(text)) }
+	}
+
+	/// # Set `JPEG XL` Tuning.
+	///
+	/// This creates, spawns, and kills a lightweight dialogue with fields for
+	/// the `JPEG XL` effort and decoding speed (see `--jxl-effort` and
+	/// `--jxl-decoding-speed`), applying the new values if the user accepts
+	/// them.
+	pub(super) fn maybe_jxl_tuning(&self) {
+		let window = gtk::Dialog::with_buttons(
+			Some("JPEG XL Effort"),
+			Some(&self.wnd_main),
+			gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+			&[("_Cancel", ResponseType::Cancel), ("_Apply", ResponseType::Accept)],
+		);
+		window.set_default_response(ResponseType::Accept);
+
+		let tuning = crate::jxl_tuning();
+		let effort = gtk::SpinButton::with_range(1.0, 9.0, 1.0);
+		effort.set_value(f64::from(tuning.effort()));
+		let speed = gtk::SpinButton::with_range(0.0, 4.0, 1.0);
+		speed.set_value(f64::from(tuning.decoding_speed()));
+
+		let grid = gtk::Grid::new();
+		grid.set_row_spacing(6);
+		grid.set_column_spacing(12);
+		grid.set_margin(12);
+		for (row, label, spin) in [(0, "Effort (1–9)", &effort), (1, "Decoding Speed (0–4)", &speed)] {
+			let label = gtk::Label::new(Some(label));
+			label.set_halign(gtk::Align::Start);
+			spin.set_activates_default(true);
+			grid.attach(&label, 0, row, 1, 1);
+			grid.attach(spin, 1, row, 1, 1);
+		}
+		window.content_area().add(&grid);
+		window.show_all();
+
+		let res = window.run();
+		effort.update();
+		speed.update();
+		let (e, s) = (tuning.effort(), tuning.decoding_speed());
+		let tuning = JxlTuning::new(
+			u8::try_from(effort.value_as_int()).unwrap_or(e),
+			u8::try_from(speed.value_as_int()).unwrap_or(s),
+		);
+		if ResponseType::None != res { window.emit_close(); }
+		if ResponseType::Accept == res {
+			crate::set_jxl_tuning(tuning, false);
+			self.sync_jxl_tuning();
+		}
+	}
+
+	/// # Sync `JPEG XL` Tuning Label.
+	///
+	/// Show the current `JPEG XL` effort and decoding speed in the menu.
+	pub(super) fn sync_jxl_tuning(&self) {
+		let tuning = crate::jxl_tuning();
+		self.mnu_jxl_tuning.set_label(&format!(
+			"Effort {}, Decoding Speed {} (JPEG XL only)…",
+			tuning.effort(),
+			tuning.decoding_speed(),
+		));
+	}
+
+	/// # Set Size Budget.
+	///
+	/// This creates, spawns, and kills a lightweight dialogue with a single
+	/// text field holding the size budget (see `--max-size`), applying the
+	/// new value if the user accepts it. An empty field removes the budget.
+	pub(super) fn maybe_max_size(&self) {
+		let window = gtk::Dialog::with_buttons(
+			Some("Max Size"),
+			Some(&self.wnd_main),
+			gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+			&[("_Cancel", ResponseType::Cancel), ("_Apply", ResponseType::Accept)],
+		);
+		window.set_default_response(ResponseType::Accept);
+
+		let entry = gtk::Entry::new();
+		entry.set_text(&crate::max_size().map_or_else(String::new, |n| n.to_string()));
+		entry.set_placeholder_text(Some("Bytes, e.g. 200K or 1M"));
+		entry.set_activates_default(true);
+		entry.set_width_chars(24);
+		entry.set_margin(12);
+		window.content_area().add(&entry);
+		window.show_all();
+
+		let res = window.run();
+		let text = entry.text();
+		if ResponseType::None != res { window.emit_close(); }
+		if ResponseType::Accept != res { return; }
+
+		let text = text.trim();
+		match crate::set_max_size(if text.is_empty() { "0" } else { text }) {
+			Ok(()) => { self.sync_max_size(); },
+			Err(e) => {
+				self.log_error(e);
+				self.paint();
+			},
+		}
+	}
+
+	/// # Try a Quality.
+	///
+	/// This creates, spawns, and kills a lightweight dialogue with a single
+	/// field for the quality — in the format's own scale, limited to the
+	/// range still in the running — to try next in place of the current
+	/// candidate, which is passed over without a verdict.
+	pub(super) fn maybe_seek(&self, tx: &MainTx) {
+		let Some((kind, now, (bottom, top))) = self.candidate.borrow().as_ref()
+			.and_then(|c| Some((c.quality.kind(), c.quality, c.bounds?)))
+		else { return; };
+		let native = |q: NonZeroU8| Quality::new(kind, Some(q)).quality().to_string()
+			.parse::<f64>()
+			.unwrap_or_default();
+		let (a, b) = (native(bottom), native(top));
+
+		let window = gtk::Dialog::with_buttons(
+			Some(now.label_title()),
+			Some(&self.wnd_main),
+			gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+			&[("_Cancel", ResponseType::Cancel), ("_Try", ResponseType::Accept)],
+		);
+		window.set_default_response(ResponseType::Accept);
+
+		let step = if kind == ImageKind::Jxl { 0.1 } else { 1.0 };
+		let quality = gtk::SpinButton::with_range(a.min(b), a.max(b), step);
+		quality.set_value(native(now.raw()));
+		quality.set_activates_default(true);
+		quality.set_margin(12);
+		window.content_area().add(&quality);
+		window.show_all();
+
+		let res = window.run();
+		let value = quality.value();
+		if ResponseType::None != res { window.emit_close(); }
+		if ResponseType::Accept != res { return; }
+
+		let value = if kind == ImageKind::Jxl { format!("{value:.1}") } else { format!("{value:.0}") };
+		if let Some(q) = Quality::from_native(kind, &value) {
+			self.feedback(tx, ShareFeedback::Seek(q.raw()));
+		}
+	}
+
+	/// # Sync Size Budget Label.
+	///
+	/// Show the current size budget, if any, in the menu.
+	pub(super) fn sync_max_size(&self) {
+		self.mnu_max_size.set_label(&crate::max_size().map_or_else(
+			|| String::from("Max Size…"),
+			|n| format!("Max Size ({} bytes)…", NiceU64::from(n)),
+		));
+	}
+
+	/// # Set Candidate Cap.
+	///
+	/// This creates, spawns, and kills a lightweight dialogue with a single
+	/// field for the most lossy candidates to try per format (see
+	/// `--max-steps`), applying the new value if the user accepts it.
+	pub(super) fn maybe_max_steps(&self) {
+		let window = gtk::Dialog::with_buttons(
+			Some("Max Candidates"),
+			Some(&self.wnd_main),
+			gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+			&[("_Cancel", ResponseType::Cancel), ("_Apply", ResponseType::Accept)],
+		);
+		window.set_default_response(ResponseType::Accept);
+
+		let old = crate::max_steps().map_or(0, NonZeroU8::get);
+		let steps = gtk::SpinButton::with_range(0.0, 100.0, 1.0);
+		steps.set_value(f64::from(old));
+		steps.set_tooltip_text(Some("Zero for no limit."));
+		steps.set_activates_default(true);
+		steps.set_margin(12);
+		window.content_area().add(&steps);
+		window.show_all();
+
+		let res = window.run();
+		steps.update();
+		let steps = u8::try_from(steps.value_as_int()).unwrap_or(old);
+		if ResponseType::None != res { window.emit_close(); }
+		if ResponseType::Accept == res {
+			crate::set_max_steps(steps, false);
+			self.sync_max_steps();
+		}
+	}
+
+	/// # Sync Candidate Cap Label.
+	///
+	/// Show the current candidate cap, if any, in the menu.
+	pub(super) fn sync_max_steps(&self) {
+		self.mnu_max_steps.set_label(&crate::max_steps().map_or_else(
+			|| String::from("Max Candidates…"),
+			|n| format!("Max Candidates ({n})…"),
+		));
+	}
+
+	/// # Set `AVIF` Codec.
+	///
+	/// This creates, spawns, and kills a lightweight dialogue with a single
+	/// drop-down for the AV1 codec (see `--avif-codec`), applying the new
+	/// value if the user accepts it. Codecs that weren't built are listed,
+	/// but can't be chosen.
+	pub(super) fn maybe_avif_codec(&self) {
+		let window = gtk::Dialog::with_buttons(
+			Some("AVIF Codec"),
+			Some(&self.wnd_main),
+			gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+			&[("_Cancel", ResponseType::Cancel), ("_Apply", ResponseType::Accept)],
+		);
+		window.set_default_response(ResponseType::Accept);
+
+		let combo = gtk::ComboBoxText::new();
+		for codec in AvifCodec::ALL {
+			if codec.is_available() { combo.append(Some(codec.as_str()), codec.as_str()); }
+			else {
+				combo.append(None, &format!("{codec} (not built)"));
+			}
+		}
+		combo.set_active_id(Some(crate::avif_codec().as_str()));
+		combo.set_margin(12);
+		window.content_area().add(&combo);
+		window.show_all();
+
+		let res = window.run();
+		let codec = combo.active_id().and_then(|id| AvifCodec::from_name(id.as_str()));
+		if ResponseType::None != res { window.emit_close(); }
+		if ResponseType::Accept == res {
+			if let Some(codec) = codec {
+				crate::set_avif_codec(codec, false);
+				self.sync_avif_codec();
+			}
+		}
+	}
+
+	/// # Sync `AVIF` Codec Label.
+	///
+	/// Show the current AV1 codec in the menu.
+	pub(super) fn sync_avif_codec(&self) {
+		self.mnu_avif_codec.set_label(&format!("Codec: {} (AVIF only)…", crate::avif_codec()));
+	}
+
+	/// # Set Existing Output Policy.
+	///
+	/// This creates, spawns, and kills a lightweight dialogue with a single
+	/// drop-down for the existing output policy (see `--existing`), applying
+	/// the new value if the user accepts it.
+	pub(super) fn maybe_existing(&self) {
+		let window = gtk::Dialog::with_buttons(
+			Some("If Output Exists"),
+			Some(&self.wnd_main),
+			gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+			&[("_Cancel", ResponseType::Cancel), ("_Apply", ResponseType::Accept)],
+		);
+		window.set_default_response(ResponseType::Accept);
+
+		let combo = gtk::ComboBoxText::new();
+		for policy in Existing::ALL {
+			combo.append(Some(policy.as_str()), existing_label(policy));
+		}
+		combo.set_active_id(Some(crate::existing().as_str()));
+		combo.set_margin(12);
+		window.content_area().add(&combo);
+		window.show_all();
+
+		let res = window.run();
+		let policy = combo.active_id().and_then(|id| Existing::try_from(id.as_str()).ok());
+		if ResponseType::None != res { window.emit_close(); }
+		if ResponseType::Accept == res {
+			if let Some(policy) = policy {
+				crate::set_existing(policy, false);
+				self.sync_existing();
+			}
+		}
+	}
+
+	/// # Sync Existing Output Policy Label.
+	///
+	/// Show the current existing output policy in the menu.
+	pub(super) fn sync_existing(&self) {
+		self.mnu_existing.set_label(&format!(
+			"If Output Exists: {}…",
+			existing_label(crate::existing()),
+		));
+	}
+
+	/// # Set `WebP` Method.
+	///
+	/// This creates, spawns, and kills a lightweight dialogue with a single
+	/// field for the `WebP` method (see `--webp-method`), applying the new
+	/// value if the user accepts it.
+	pub(super) fn maybe_webp_method(&self) {
+		let window = gtk::Dialog::with_buttons(
+			Some("WebP Method"),
+			Some(&self.wnd_main),
+			gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+			&[("_Cancel", ResponseType::Cancel), ("_Apply", ResponseType::Accept)],
+		);
+		window.set_default_response(ResponseType::Accept);
+
+		let old = crate::webp_tuning(0).method();
+		let method = gtk::SpinButton::with_range(0.0, 6.0, 1.0);
+		method.set_value(f64::from(old));
+		method.set_activates_default(true);
+		method.set_margin(12);
+		window.content_area().add(&method);
+		window.show_all();
+
+		let res = window.run();
+		method.update();
+		let method = u8::try_from(method.value_as_int()).unwrap_or(old);
+		if ResponseType::None != res { window.emit_close(); }
+		if ResponseType::Accept == res {
+			crate::set_webp_method(method, false);
+			self.sync_webp_method();
+		}
+	}
+
+	/// # Sync `WebP` Method Label.
+	///
+	/// Show the current `WebP` method in the menu.
+	pub(super) fn sync_webp_method(&self) {
+		self.mnu_webp_method.set_label(&format!(
+			"Method {} (WebP only)…",
+			crate::webp_tuning(0).method(),
+		));
+	}
+
+	/// # Manage Queue.
+	///
+	/// This creates, spawns, and kills a dialogue listing the sources still
+	/// waiting to be encoded, where they can be dragged into a different
+	/// order, removed, or have their formats changed individually. Edits are
+	/// applied — to whatever hasn't been started in the meantime — if the
+	/// user accepts them.
+	pub(super) fn maybe_queue(&self) {
+		let items = self.queue.snapshot();
+		if items.is_empty() {
+			self.log_error(RefractError::NothingDoing);
+			self.paint_status();
+			return;
+		}
+
+		// Columns: the formats, the path, and the item index.
+		let global = cli_encoders(self.cli_flags());
+		let mut types = vec![bool::static_type(); QUEUE_KINDS.len()];
+		types.extend([String::static_type(), u32::static_type()]);
+		let store = gtk::ListStore::new(&types);
+		for (idx, item) in (0_u32..).zip(&items) {
+			let iter = store.append();
+			let encoders = item.encoders.as_deref().unwrap_or(&global);
+			for (col, kind) in (0_u32..).zip(QUEUE_KINDS) {
+				store.set_value(&iter, col, &encoders.contains(&kind).to_value());
+			}
+			store.set_value(&iter, QUEUE_COL_PATH, &item.path.to_string_lossy().to_value());
+			store.set_value(&iter, QUEUE_COL_IDX, &idx.to_value());
+		}
+
+		let view = gtk::TreeView::with_model(&store);
+		view.set_reorderable(true);
+		for (col, kind) in (0_i32..).zip(QUEUE_KINDS) {
+			let cell = gtk::CellRendererToggle::new();
+			let store2 = store.clone();
+			cell.connect_toggled(move |_, path| {
+				if let Some(iter) = store2.iter(&path) {
+					let old = store2.value(&iter, col).get::<bool>().unwrap_or(false);
+					store2.set_value(&iter, col.unsigned_abs(), &(! old).to_value());
+				}
+			});
+			let column = gtk::TreeViewColumn::new();
+			column.set_title(kind.as_str());
+			TreeViewColumnExt::pack_start(&column, &cell, false);
+			TreeViewColumnExt::add_attribute(&column, &cell, "active", col);
+			view.append_column(&column);
+		}
+		let cell = gtk::CellRendererText::new();
+		let column = gtk::TreeViewColumn::new();
+		column.set_title("Source");
+		column.set_expand(true);
+		TreeViewColumnExt::pack_start(&column, &cell, true);
+		TreeViewColumnExt::add_attribute(&column, &cell, "text", QUEUE_COL_PATH.cast_signed());
+		view.append_column(&column);
+
+		let window = gtk::Dialog::with_buttons(
+			Some("Queue"),
+			Some(&self.wnd_main),
+			gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+			&[("_Remove", ResponseType::Other(0)), ("_Cancel", ResponseType::Cancel), ("_Apply", ResponseType::Accept)],
+		);
+		window.set_default_response(ResponseType::Accept);
+		window.set_default_size(640, 400);
+		let scroll = gtk::ScrolledWindow::builder().vexpand(true).build();
+		scroll.add(&view);
+		window.content_area().add(&scroll);
+		window.show_all();
+
+		// Run the dialogue until it's done with, removing rows as asked.
+		let res = loop {
+			let res = window.run();
+			if ResponseType::Other(0) != res { break res; }
+			if let Some((_, iter)) = view.selection().selected() { store.remove(&iter); }
+		};
+		if ResponseType::None != res { window.emit_close(); }
+		if ResponseType::Accept != res { return; }
+
+		// Rebuild the queue in its new order.
+		let mut edited = Vec::with_capacity(items.len());
+		if let Some(iter) = store.iter_first() {
+			loop {
+				let idx = store.value(&iter, QUEUE_COL_IDX.cast_signed()).get::<u32>().ok()
+					.and_then(|idx| items.get(usize::try_from(idx).ok()?));
+				if let Some(item) = idx {
+					let encoders: Vec<ImageKind> = (0_i32..).zip(QUEUE_KINDS)
+						.filter_map(|(col, kind)| store.value(&iter, col).get::<bool>().ok()?.then_some(kind))
+						.collect();
+					edited.push(QueueItem {
+						path: item.path.clone(),
+						encoders: (encoders.as_slice() != &*global).then(|| encoders.into_boxed_slice()),
+					});
+				}
+				if ! store.iter_next(&iter) { break; }
+			}
+		}
+
+		// Forget any removed sources when it comes time to export.
+		self.pending.borrow_mut().retain(|p|
+			! items.iter().any(|i| &i.path == p) || edited.iter().any(|i| &i.path == p)
+		);
+
+		self.log_queue(edited.len());
+		self.queue.replace(edited);
+		self.paint_status();
+	}
+
+	/// # Has Paths?
+	fn has_paths(&self) -> bool { ! self.paths.borrow().is_empty() }
 
 	/// # Add File Handler.
 	///
@@ -796,8 +2283,7 @@ impl Window {
 
 		// Run and close the dialogue.
 		let res = window.run();
-		if ResponseType::None == res { return false; }
-		window.emit_close();
+		window.destroy();
 
 		if ResponseType::Accept == res {
 			if let Some(file) = window.filename() {
@@ -835,8 +2321,7 @@ impl Window {
 
 		// Run and close the dialogue.
 		let res = window.run();
-		if ResponseType::None == res { return false; }
-		window.emit_close();
+		window.destroy();
 
 		if ResponseType::Accept == res {
 			if let Some(dir) = window.filename() {
@@ -852,6 +2337,195 @@ impl Window {
 		self.has_paths()
 	}
 
+	/// # Maybe Audit.
+	///
+	/// Log how a saved output was made and, if enabled, write the same details
+	/// to a JSON sidecar alongside it.
+	fn maybe_audit(&self, src: &Path, dst: &Path, output: &Output) {
+		let preset = self.preset.borrow();
+		let src_hash = self.source.borrow().as_ref().and_then(|s| s.hash.clone());
+		let audit = Audit::new(src, dst, output, src_hash.as_deref(), preset.as_deref());
+		self.log_audit(&audit.summary());
+		self.log_hash(audit.hash());
+		if self.chk_sidecar.is_active() {
+			match audit.save_sidecar() {
+				Ok(path) => { self.log_sidecar(&path); },
+				Err(e) => { self.log_error(e); },
+			}
+		}
+	}
+
+	/// # Maybe Save Picture.
+	///
+	/// If enabled, and at least one conversion was saved for the source, this
+	/// writes an HTML `<picture>` snippet alongside it.
+	fn maybe_save_picture(&self, path: &Path, width: usize, height: usize)
+	-> Result<ShareFeedback, RefractError> {
+		let mut saved = self.saved.borrow_mut();
+		if saved.is_empty() || ! self.chk_picture.is_active() {
+			saved.clear();
+			return Ok(ShareFeedback::Continue);
+		}
+
+		let out = picture(path, width, height, &mut saved);
+		saved.clear();
+		drop(saved);
+
+		let dst = self.naming.html_path(path);
+		save(&dst, out.as_bytes(), self.chk_durable.is_active())?;
+		self.log_picture(dst);
+		Ok(ShareFeedback::Continue)
+	}
+
+	/// # Maybe Skip Handler.
+	///
+	/// Refract treats everything as sRGB, and only AVIF and JPEG XL keep more
+	/// than 8 bits per channel, which can visibly shift images with other
+	/// color profiles or higher bit depths. This creates,
+	/// spawns, and kills a message dialogue explaining as much, returning
+	/// [`ShareFeedback::Abort`] if the user would rather skip the source.
+	///
+	/// If nobody is there to `ask` — i.e. the run is unattended — the
+	/// warning is logged and the source converted anyway.
+	fn maybe_skip(&self, bits: u8, icc: bool, ask: bool) -> ShareFeedback {
+		let mut issues = Vec::with_capacity(2);
+		if icc {
+			issues.push("It has an embedded color profile Refract cannot convert to sRGB, so colors may shift.");
+		}
+		if 8 < bits {
+			issues.push("It uses more than 8 bits per channel, so fine gradients may be clipped or banded in formats other than AVIF and JPEG XL.");
+		}
+		if issues.is_empty() { return ShareFeedback::Continue; }
+
+		self.log_warning(bits, icc, ask);
+		if ! ask { return ShareFeedback::Continue; }
+
+		let window = gtk::MessageDialog::new(
+			Some(&self.wnd_main),
+			gtk::DialogFlags::MODAL,
+			gtk::MessageType::Warning,
+			gtk::ButtonsType::None,
+			"This image may not convert cleanly.",
+		);
+		window.set_secondary_text(Some(&issues.join("\n\n")));
+		window.add_buttons(&[
+			("_Skip", ResponseType::Reject),
+			("_Continue", ResponseType::Accept),
+		]);
+		window.set_default_response(ResponseType::Accept);
+
+		// Run and close the dialogue.
+		let res = window.run();
+		window.emit_close();
+
+		if ResponseType::Accept == res { ShareFeedback::Continue }
+		else {
+			self.log_skipped();
+			progress::emit(Event::Skipped("color"));
+			ShareFeedback::Abort
+		}
+	}
+
+	/// # Maybe Skip Existing.
+	///
+	/// If the policy is to skip existing outputs and this format has already
+	/// been saved, return [`ShareFeedback::Discard`] so the encoder is passed
+	/// over.
+	fn maybe_skip_existing(&self, path: &Path, kind: ImageKind) -> ShareFeedback {
+		if crate::existing() == Existing::Skip {
+			let dst = self.naming.output_path(path, kind);
+			if dst.is_file() {
+				self.log_existing(&dst);
+				progress::emit(Event::Skipped("existing"));
+				return ShareFeedback::Discard;
+			}
+		}
+
+		ShareFeedback::Continue
+	}
+
+	/// # Maybe Retry.
+	///
+	/// When an encoder fails outright for an image, this creates, spawns, and
+	/// kills a message dialogue offering to retry just that image and format
+	/// with adjusted settings, returning [`ShareFeedback::Retry`] with the new
+	/// encoder flags if the user takes it up on the offer.
+	fn maybe_retry(&self, kind: ImageKind, err: RefractError, flags: u8) -> ShareFeedback {
+		/// # Lossless Only.
+		const LOSSLESS: u8 = FLAG_NO_LOSSY;
+
+		/// # Lossy Only.
+		const LOSSY: u8 = FLAG_NO_LOSSLESS;
+
+		// Figure out which adjustments are actually adjustments.
+		let modes = flags & (FLAG_NO_LOSSLESS | FLAG_NO_LOSSY);
+		let mut options: Vec<(&str, u8, &str)> = Vec::with_capacity(3);
+		if modes != LOSSLESS {
+			options.push(("_Lossless Only", (flags & ! FLAG_NO_LOSSLESS) | FLAG_NO_LOSSY, "lossless only"));
+		}
+		if modes != LOSSY {
+			options.push(("Lossy _Only", (flags & ! FLAG_NO_LOSSY) | FLAG_NO_LOSSLESS, "lossy only"));
+		}
+		if kind == ImageKind::Avif && 0 == flags & FLAG_NO_AVIF_YCBCR {
+			options.push(("_RGB Only", flags | FLAG_NO_AVIF_YCBCR, "without YCbCr"));
+		}
+		if options.is_empty() { return ShareFeedback::Continue; }
+
+		let window = gtk::MessageDialog::new(
+			Some(&self.wnd_main),
+			gtk::DialogFlags::MODAL,
+			gtk::MessageType::Warning,
+			gtk::ButtonsType::None,
+			&["The ", kind.as_str(), " encoder failed."].concat(),
+		);
+		window.set_secondary_text(Some(&[
+			err.as_str(),
+			"\n\nYou can retry this image with adjusted settings, or skip this format and move on. (Your other settings will not be changed.)",
+		].concat()));
+		window.add_button("_Skip", ResponseType::Reject);
+		for (idx, (label, _, _)) in (0_u16..).zip(options.iter()) {
+			window.add_button(label, ResponseType::Other(idx));
+		}
+		window.set_default_response(ResponseType::Reject);
+
+		// Run and close the dialogue.
+		let res = window.run();
+		window.emit_close();
+
+		if let ResponseType::Other(idx) = res {
+			if let Some((_, flags, what)) = options.get(usize::from(idx)) {
+				self.log_retry(kind, what);
+				return ShareFeedback::Retry(*flags);
+			}
+		}
+
+		ShareFeedback::Continue
+	}
+
+	/// # Maybe Fallback.
+	///
+	/// If a fallback mode is set, stand the source in at the suggested output
+	/// path for a format that yielded nothing.
+	fn maybe_fallback(&self, path: &Path, kind: ImageKind) {
+		let Some(mode) = self.fallback else { return; };
+
+		// Only a PNG can stand in for a PNG, and a JPEG for a JPEG.
+		if kind == ImageKind::Png && ! path.extension().is_some_and(|e| e.eq_ignore_ascii_case("png")) {
+			return;
+		}
+		if kind == ImageKind::Jpeg && ! path.extension().is_some_and(|e| e.eq_ignore_ascii_case("jpg") || e.eq_ignore_ascii_case("jpeg")) {
+			return;
+		}
+		let dst = self.naming.create_out_dir(path).join(self.naming.file_name(path, kind));
+		match mode.apply(path, &dst) {
+			Ok(()) => {
+				progress::emit(Event::Fallback(kind, mode.as_str(), &dst));
+				self.log_fallback(&dst, mode);
+			},
+			Err(e) => { self.log_error(e); },
+		}
+	}
+
 	/// # Maybe Save Handler.
 	///
 	/// This creates, spawns, and kills a file save dialogue, and writes the
@@ -863,52 +2537,53 @@ impl Window {
 	/// If successful, the path the file was saved to is returned.
 	fn maybe_save(&self, path: &Path, src: &Output) -> Result<PathBuf, RefractError> {
 		let kind = src.kind();
-		let (filter, ext) = match kind {
-			ImageKind::Avif => (&self.flt_avif, E_AVIF),
-			ImageKind::Jxl => (&self.flt_jxl, E_JXL),
-			ImageKind::Webp => (&self.flt_webp, E_WEBP),
-			// It should not be possible to trigger this.
-			_ => { return Err(RefractError::NoSave); },
+		let filter = match kind {
+			ImageKind::Avif => &self.flt_avif,
+			ImageKind::Jxl => &self.flt_jxl,
+			ImageKind::Jpeg => &self.flt_jpeg,
+			ImageKind::Png => &self.flt_png,
+			ImageKind::Webp => &self.flt_webp,
 		};
 
-		let window = self.file_chooser(
-			&["Save the ", kind.as_str(), "!"].concat(),
-			FileChooserAction::Save,
-			"_Save",
-			path.parent(),
-			Some(filter),
-		);
-
-		// Warn about collisions.
-		window.set_do_overwrite_confirmation(true);
-
-		// Suggest a file name.
-		window.set_current_name(&[
-			path.file_name().map_or_else(|| Cow::Borrowed("image"), OsStr::to_string_lossy).as_ref(),
-			".",
-			src.kind().extension(),
-		].concat());
+		// Suggest a file name, and if quick saves are enabled, give the user
+		// a chance to just go with it (or tweak it).
+		let name = self.naming.file_name(path, kind);
+		let dir = self.naming.create_out_dir(path);
+		let existing = dir.join(&name);
+		let quick =
+			// If the policy allows, quietly replace an earlier run's output.
+			// (A "smaller" policy has already weighed the sizes by now.) The
+			// source itself is never fair game, though.
+			if
+				existing.is_file() &&
+				matches!(crate::existing(), Existing::Overwrite | Existing::Smaller) &&
+				! same_file(&existing, path)
+			{
+				Some(existing)
+			}
+			else if self.chk_quick_save.is_active() {
+				self.quick_save_path(&dir, &name)
+					.map(|p| with_extension(p, kind))
+					.filter(|p| ! p.exists())
+			}
+			else { None };
 
-		// Read the result!
-		let res = window.run();
-		if ResponseType::None == res { return Err(RefractError::NoSave); }
-		window.emit_close();
+		let dst = quick.map_or_else(|| self.save_chooser(&dir, kind, filter, &name), Ok)?;
 
 		// Make sure the chosen path has an appropriate extension. If not, toss
 		// it onto the end.
-		let mut path: PathBuf =
-			if ResponseType::Accept == res { window.filename() }
-			else { None }
-			.ok_or(RefractError::NoSave)?;
-		if ext != path {
-			path.as_mut_os_string().push(".");
-			path.as_mut_os_string().push(kind.extension());
-		}
+		let dst = with_extension(dst, kind);
 
-		// Save it.
-		write_atomic::write_file(&path, src).map_err(|_| RefractError::Write)?;
+		// Save it, unless that would mean writing over the source.
+		if same_file(&dst, path) { return Err(RefractError::SaveSource); }
+		save(&dst, src, self.chk_durable.is_active())?;
+
+		// Give a heads up if this is a cloud-synced folder, once per session.
+		if let Some(service) = sync_service(&dst) {
+			if self.add_flag(FLAG_WARNED_SYNC) { self.log_synced(service); }
+		}
 
-		Ok(path)
+		Ok(dst)
 	}
 }
 
@@ -932,6 +2607,7 @@ impl Window {
 		gtk_sensitive!(sensitive, self.mnu_file);
 		gtk_sensitive!(sensitive, self.mnu_settings);
 		gtk_sensitive!(sensitive, self.mnu_help);
+		gtk_sensitive!(! sensitive, self.btn_pause, self.btn_queue, self.btn_skip);
 	}
 
 	/// # Paint Preview.
@@ -962,25 +2638,114 @@ impl Window {
 				self.lbl_format_val.set_text(&src.format_val());
 				self.lbl_quality.set_text(&src.quality());
 				self.lbl_quality_val.set_text(&src.quality_val());
-				self.set_image(Some(&src.buf));
+				self.lbl_quality_val.set_tooltip_text(src.quality_tip().as_deref());
+
+				// Screen readers can't see the labels beside the image, so
+				// give the image itself the same summary.
+				let summary = format!("{}, {} {}", src.format_val(), src.quality(), src.quality_val());
+				let imgs: &[&gtk::Image] =
+					if self.btn_toggle.is_active() { &[&self.img_candidate, &self.img_diff] }
+					else { &[&self.img_main] };
+				for img in imgs {
+					if let Some(a11y) = img.accessible() { a11y.set_description(&summary); }
+				}
+
+				let diff = self.difference();
+				self.set_image(Some(diff.as_ref().unwrap_or_else(|| src.image(self.chk_full_size.is_active()))));
+				drop(ptr);
+				self.paint_history();
+				self.paint_progress();
 			}
 		}
 		else if self.lbl_quality.is_visible() {
 			self.lbl_quality.hide();
-			gtk_sensitive!(false, self.btn_discard, self.btn_keep, self.btn_toggle);
+			self.lbl_history.set_text("");
+			self.reset_progress();
+			gtk_sensitive!(false, self.btn_discard, self.btn_keep, self.btn_quality, self.btn_undo, self.btn_toggle);
 			self.set_image(None);
 		}
 	}
 
+	/// # Paint History.
+	///
+	/// This charts the current search in the footer: each quality tried so
+	/// far, colored by verdict — green for kept, red for discarded — followed
+	/// by the one under review (underlined) and the range still in the
+	/// running.
+	fn paint_history(&self) {
+		use std::fmt::Write;
+
+		let mut out = String::new();
+		for &(quality, keep) in self.history.borrow().iter() {
+			let color = if keep { "#2ecc71" } else { "#e74c3c" };
+			let _res = write!(&mut out, "<span foreground=\"{color}\">{}</span> ", history_val(quality));
+		}
+
+		if let Some(can) = self.candidate.borrow().as_ref().filter(|c| ! c.saved) {
+			let _res = write!(&mut out, "<u><b>{}</b></u>", history_val(can.quality));
+			if let Some((bottom, top)) = can.bounds.filter(|_| ! can.quality.is_lossless()) {
+				let kind = can.quality.kind();
+				let _res = write!(
+					&mut out,
+					"  <span foreground=\"#999\">[{}–{}]</span>",
+					Quality::new(kind, Some(bottom)).quality(),
+					Quality::new(kind, Some(top)).quality(),
+				);
+			}
+		}
+
+		self.lbl_history.set_markup(out.trim_end());
+	}
+
+	/// # Paint Progress.
+	///
+	/// This fills the search progress bar for the current candidate, counting
+	/// the candidates so far against the most that could yet follow.
+	fn paint_progress(&self) {
+		let Some((done, left)) = self.candidate.borrow().as_ref().and_then(|c| c.progress)
+		else { return; };
+
+		let percent = percent((done, left)).unwrap_or(100);
+		self.bar_search.set_fraction(f64::from(percent) / 100.0);
+		self.bar_search.set_text(Some(&
+			if left == 0 { format!("Candidate {done} of {done}") }
+			else { format!("Candidate {done} of up to {}", u16::from(done) + u16::from(left)) }
+		));
+	}
+
+	/// # Reset Progress.
+	///
+	/// Empty the search progress bar, e.g. when a new format gets going.
+	fn reset_progress(&self) {
+		self.bar_search.set_fraction(0.0);
+		self.bar_search.set_text(None);
+	}
+
 	#[inline]
 	/// # Paint Status.
 	///
 	/// This writes the status log. Easy enough.
 	fn paint_status(&self) {
 		if self.remove_flag(FLAG_TICK_STATUS) {
-			self.lbl_status.set_markup(self.status.borrow().as_str());
+			let status = self.status.borrow();
+			self.lbl_status.set_markup(status.as_str());
+			crate::report::update(status.as_str());
 		}
 	}
+
+	/// # Clear Log.
+	///
+	/// Empty the activity log. The `--report`, if any, holds onto the
+	/// cleared entries, so still covers the whole session.
+	pub(super) fn clear_log(&self) {
+		let mut status = self.status.borrow_mut();
+		crate::report::update(status.as_str());
+		crate::report::clear();
+		status.clear();
+		drop(status);
+		self.add_flag(FLAG_TICK_STATUS);
+		self.paint_status();
+	}
 }
 
 /// ## Sending/Receiving.
@@ -998,40 +2763,160 @@ impl Window {
 	/// sister thread needs a specific answer (and will get one).
 	pub(super) fn process_share(&self, res: SharePayload)
 	-> Result<ShareFeedback, RefractError> {
-		let res = match res {
+		let res: Result<ShareFeedback, ErrorContext> = match res {
 			Ok(Share::Path(x)) => {
+				progress::emit(Event::Source(&x));
+				self.set_started(&x);
 				self.log_source(x);
 				Ok(ShareFeedback::Continue)
 			},
-			Ok(Share::Source(x)) => self.set_source(x),
-			Ok(Share::Encoder(x)) => {
+			Ok(Share::Source(x)) => self.set_source(x).map_err(ErrorContext::from),
+			Ok(Share::Warning(bits, icc, ask)) => Ok(self.maybe_skip(bits, icc, ask)),
+			Ok(Share::Fallback(path, kind)) => {
+				self.maybe_fallback(&path, kind);
+				Ok(ShareFeedback::Continue)
+			},
+			Ok(Share::Prediction(first, best)) => {
+				self.log_prediction(first, best);
+				Ok(ShareFeedback::Continue)
+			},
+			Ok(Share::Timing(kind, wall, cpu)) => {
+				progress::emit(Event::Timing(kind, wall, cpu));
+				self.add_timing(kind, wall, cpu);
+				Ok(ShareFeedback::Continue)
+			},
+			Ok(Share::Retry(kind, err, flags)) => Ok(self.maybe_retry(kind, err, flags)),
+			Ok(Share::Profile(x)) => {
+				self.log_profile(x);
+				Ok(ShareFeedback::Continue)
+			},
+			Ok(Share::Encoder(x)) => {
+				progress::emit(Event::Encoder(x));
+				self.ladder.borrow_mut().clear();
+				self.history.borrow_mut().clear();
+				self.reset_progress();
 				self.log_encoder(x);
+				self.lbl_quality.set_tooltip_text(encoder_equivalents(x).as_deref());
+				Ok(ShareFeedback::Continue)
+			},
+			Ok(Share::Existing(path, kind)) => Ok(self.maybe_skip_existing(&path, kind)),
+			Ok(Share::Region(x)) => self.set_region(x).map_err(ErrorContext::from),
+			Ok(Share::Candidate(x)) => self.set_candidate(x).map_err(ErrorContext::from),
+			Ok(Share::Best(path, x, d)) => self.set_best(&path, x, d).map_err(ErrorContext::from),
+			Ok(Share::Reviewed(path, keep)) => {
+				self.log_reviewed(path, keep);
 				Ok(ShareFeedback::Continue)
 			},
-			Ok(Share::Candidate(x)) => self.set_candidate(x),
-			Ok(Share::Best(path, x)) => self.set_best(path, x),
+			Ok(Share::DoneSource(path, width, height)) => {
+				let res = self.maybe_save_picture(&path, width, height)
+					.map_err(ErrorContext::from);
+				self.paint_status();
+				crate::report::tick();
+				crate::session::done(&path);
+				res
+			},
 			Ok(Share::DoneEncoding) => {
+				self.pending.borrow_mut().clear();
 				self.finish(true);
 				self.log_done();
+				self.paint_status();
+				crate::report::flush();
+				progress::emit(Event::Done);
 				Ok(ShareFeedback::Continue)
 			},
 			Err(e) => { Err(e) },
 		};
 
 		// Log an error?
-		if let Err(e) = res { self.log_error(e); }
-
-		res
+		match res {
+			Ok(res) => Ok(res),
+			Err(e) => {
+				// Keep track of unsupported sources for the summary.
+				if matches!(e.error(), RefractError::ColorJpeg16) {
+					self.unsupported.set(self.unsupported.get() + 1);
+				}
+				progress::emit(Event::Error(&e));
+				self.log_error_context(&e);
+				Err(e.error())
+			},
+		}
 	}
 }
 
+/// # Reason: No Formats.
+pub(super) const REASON_FORMATS: &str = "At least one encoder must be enabled";
+
+/// # Reason: No Modes.
+pub(super) const REASON_MODES: &str = "At least one encoding mode must be enabled";
+
 /// ## Statuses.
 impl Window {
+	/// # Log Corrected.
+	///
+	/// This is used when a setting has been automatically switched back on
+	/// to keep the configuration valid.
+	fn log_corrected(&self, reason: &str, what: &str) {
+		let mut buf = self.status.borrow_mut();
+		buf.push_str(log_prefix!("\n", "#f39c12", "Warning:"));
+		buf.push_str(reason);
+		buf.push_str(", so <b>");
+		buf.push_str(&gtk::glib::markup_escape_text(what));
+		buf.push_str(concat!("</b> will stay on. ", log_colored!("#999", "(Settings auto-corrected.)")));
+		self.add_flag(FLAG_TICK_STATUS);
+	}
+
 	/// # Log Done.
 	///
 	/// This happens when an encoding session finishes.
 	fn log_done(&self) {
+		use std::fmt::Write;
+
 		let mut buf = self.status.borrow_mut();
+
+		// Summarize any sources skipped for unsupported colors.
+		let unsupported = self.unsupported.replace(0);
+		if 0 != unsupported {
+			let _res = write!(
+				buf,
+				concat!(
+					log_prefix!("\n", "#f39c12", "Warning:"),
+					"{} skipped due to unsupported color formats. ",
+					log_colored!("#999", "(See above for workarounds.)"),
+				),
+				unsupported.nice_inflect("source was", "sources were"),
+			);
+		}
+
+		// Summarize the quality predictions, if any.
+		let (count, off) = self.predictions.replace((0, 0));
+		if 0 != count {
+			let _res = write!(
+				buf,
+				concat!(
+					log_prefix!("\n", "#9b59b6", "Notice:"),
+					"First guesses landed an average of {:.1} steps from the final quality. ",
+					log_colored!("#999", "({} predicted.)"),
+				),
+				off.div_float(count).unwrap_or(0.0),
+				count.nice_inflect("encoding was", "encodings were"),
+			);
+		}
+
+		// Summarize the time spent by each encoder, if any.
+		let timings = self.timings.take();
+		if ! timings.is_empty() {
+			buf.push_str(concat!(log_prefix!("\n", "#9b59b6", "Notice:"), "Encoding time:"));
+			for (kind, wall, cpu) in timings {
+				let _res = write!(buf, " {} {:.1}s", kind, wall.as_secs_f64());
+				if let Some(cpu) = cpu {
+					let _res = write!(buf, concat!(" ", log_colored!("#999", "({:.1}s CPU)")), cpu.as_secs_f64());
+				}
+				buf.push(';');
+			}
+			buf.pop();
+			buf.push('.');
+		}
+
 		buf.push_str(concat!(
 			log_prefix!("\n", "#9b59b6", "Notice:"),
 			"Encoding has finished! ",
@@ -1042,6 +2927,36 @@ impl Window {
 		self.add_flag(FLAG_TICK_STATUS);
 	}
 
+	/// # Log Distortion.
+	///
+	/// This records how much the saved image differs from its source, with
+	/// the figures colored by severity.
+	fn log_distortion(&self, dist: Distortion) {
+		use std::fmt::Write;
+
+		// Green is imperceptible, orange is subtle, red is… well, red.
+		let color =
+			if dist.max <= DISTORTION_THRESHOLD { "#2ecc71" }
+			else if dist.max <= 32 { "#f39c12" }
+			else { "#e74c3c" };
+
+		let mut buf = self.status.borrow_mut();
+		let _res = write!(
+			buf,
+			concat!(
+				"\n    ",
+				log_colored!("#999", "Distortion:"),
+				" <span foreground=\"{}\">max Δ {}, mean Δ {:.2}, {} of pixels beyond ±{}.</span>",
+			),
+			color,
+			dist.max,
+			dist.mean,
+			NicePercent::from(dist.changed),
+			DISTORTION_THRESHOLD,
+		);
+		self.add_flag(FLAG_TICK_STATUS);
+	}
+
 	/// # Log Encoder.
 	///
 	/// This triggers when starting a new encoder for a given source.
@@ -1067,6 +2982,291 @@ impl Window {
 		self.add_flag(FLAG_TICK_STATUS);
 	}
 
+	/// # Log Error (With Context).
+	///
+	/// Same as [`Window::log_error`], but with the underlying cause and the
+	/// upstream library's explanation, if known, tacked on. (The source path
+	/// has already been logged.)
+	fn log_error_context(&self, err: &ErrorContext) {
+		if err.error().as_str().is_empty() { return; }
+
+		let mut buf = self.status.borrow_mut();
+		buf.push_str(log_prefix!("\n    ", "#e74c3c", "Error:"));
+		buf.push_str(err.error().as_str());
+		if let Some(cause) = err.cause() {
+			buf.push(' ');
+			buf.push_str(cause.as_str());
+		}
+		if let Some(upstream) = err.upstream() {
+			buf.push_str(" <span foreground=\"#999\">(");
+			buf.push_str(&gtk::glib::markup_escape_text(upstream));
+			buf.push_str(")</span>");
+		}
+		self.add_flag(FLAG_TICK_STATUS);
+	}
+
+	/// # Log Ladder.
+	///
+	/// This charts the candidate sizes across the quality ladder — lowest
+	/// quality first — highlighting the one that was saved, giving a sense of
+	/// whether or not it was a good place to stop.
+	fn log_ladder(&self, best: Quality, size: usize) {
+		let best = (ladder_rung(best), size);
+		let mut ladder: Vec<(u16, usize)> = self.ladder.take()
+			.into_iter()
+			.map(|(q, s)| (ladder_rung(q), s))
+			.collect();
+		if ! ladder.contains(&best) { ladder.push(best); }
+		if ladder.len() < 2 { return; }
+		ladder.sort_unstable();
+		let sizes: Vec<usize> = ladder.iter().map(|&(_, s)| s).collect();
+
+		let mut buf = self.status.borrow_mut();
+		buf.push_str(concat!(log_prefix!("\n    ", "#9b59b6", "Ladder:"), "<tt>"));
+		for (c, rung) in sparkline(&sizes).chars().zip(&ladder) {
+			buf.push_str(
+				if *rung == best { "<span foreground=\"#2ecc71\" weight=\"bold\">" }
+				else { "<span foreground=\"#999\">" }
+			);
+			buf.push(c);
+			buf.push_str("</span>");
+		}
+		buf.push_str(concat!("</tt> ", log_colored!("#999", "(Size by quality, low to high; the saved one is green.)")));
+		self.add_flag(FLAG_TICK_STATUS);
+	}
+
+	/// # Log Fallback.
+	///
+	/// This is used when the source has been stood in for a format that
+	/// yielded nothing.
+	fn log_fallback(&self, path: &Path, mode: Fallback) {
+		let mut buf = self.status.borrow_mut();
+		buf.push_str(concat!(log_prefix!("\n    ", "#f39c12", "Fallback:"), "Created <b>"));
+		buf.push_str(&gtk::glib::markup_escape_text(&path.to_string_lossy()));
+		buf.push_str("</b> from the source ");
+		buf.push_str(match mode {
+			Fallback::Copy => "(copy)",
+			Fallback::HardLink => "(hard link)",
+			Fallback::SymLink => "(symlink)",
+		});
+		buf.push_str(concat!(". ", log_colored!("#999", "(This is not a real conversion!)")));
+		self.add_flag(FLAG_TICK_STATUS);
+	}
+
+	/// # Log Picture.
+	///
+	/// This is used to indicate a `<picture>` snippet has been saved.
+	fn log_picture<P>(&self, path: P)
+	where P: AsRef<Path> {
+		let mut buf = self.status.borrow_mut();
+		buf.push_str(concat!(log_prefix!("\n    ", "#2ecc71", "Success:"), "Created <b>"));
+		buf.push_str(path.as_ref().to_string_lossy().as_ref());
+		buf.push_str(concat!("</b>. ", log_colored!("#999", "(HTML snippet.)")));
+		self.add_flag(FLAG_TICK_STATUS);
+	}
+
+	/// # Log Sidecar.
+	///
+	/// This is used to indicate an audit sidecar has been saved.
+	fn log_sidecar(&self, path: &Path) {
+		let mut buf = self.status.borrow_mut();
+		buf.push_str(concat!(log_prefix!("\n    ", "#2ecc71", "Success:"), "Created <b>"));
+		buf.push_str(&gtk::glib::markup_escape_text(&path.to_string_lossy()));
+		buf.push_str(concat!("</b>. ", log_colored!("#999", "(Audit sidecar.)")));
+		self.add_flag(FLAG_TICK_STATUS);
+	}
+
+	/// # Log Audit.
+	///
+	/// This notes exactly how a saved output was made, so the report can
+	/// account for differences between otherwise similar files.
+	fn log_audit(&self, summary: &str) {
+		let mut buf = self.status.borrow_mut();
+		buf.push_str(log_prefix!("\n    ", "#9b59b6", "Encoder:"));
+		buf.push_str(&gtk::glib::markup_escape_text(summary));
+		self.add_flag(FLAG_TICK_STATUS);
+	}
+
+	/// # Log Hash.
+	///
+	/// This records the SHA-256 of a source or saved output so the report
+	/// can be used to verify or deduplicate files later on.
+	fn log_hash(&self, hash: &str) {
+		let mut buf = self.status.borrow_mut();
+		buf.push_str(concat!(log_prefix!("\n    ", "#9b59b6", "SHA-256:"), "<tt>"));
+		buf.push_str(hash);
+		buf.push_str("</tt>");
+		self.add_flag(FLAG_TICK_STATUS);
+	}
+
+	/// # Log Preset.
+	///
+	/// This is used when a preset is applied.
+	fn log_preset(&self, name: &str) {
+		let mut buf = self.status.borrow_mut();
+		buf.push_str(concat!(log_prefix!("\n", "#9b59b6", "Notice:"), "Applied the <b>"));
+		buf.push_str(&gtk::glib::markup_escape_text(name));
+		buf.push_str("</b> preset.");
+		self.add_flag(FLAG_TICK_STATUS);
+	}
+
+	/// # Log Profile.
+	///
+	/// This is used when smart defaults narrow the formats and modes for a
+	/// source.
+	fn log_profile(&self, profile: Profile) {
+		let what = match profile {
+			Profile::Graphic => "Graphic; sticking to lossless WebP and JPEG XL.",
+			Profile::Photo => "Photo; sticking to lossy AVIF and JPEG XL.",
+			Profile::Other => return,
+		};
+
+		let mut buf = self.status.borrow_mut();
+		buf.push_str(log_prefix!("\n    ", "#9b59b6", "Profile:"));
+		buf.push_str(what);
+		self.add_flag(FLAG_TICK_STATUS);
+	}
+
+	/// # Log Exported.
+	///
+	/// This is used when the queue has been exported to a job file.
+	fn log_exported(&self, path: &Path, count: usize) {
+		let mut buf = self.status.borrow_mut();
+		buf.push_str(concat!(log_prefix!("\n", "#9b59b6", "Notice:"), "Exported "));
+		buf.push_str(&count.nice_inflect("image", "images").to_string());
+		buf.push_str(" to <b>");
+		buf.push_str(&gtk::glib::markup_escape_text(&path.to_string_lossy()));
+		buf.push_str("</b>.");
+		self.add_flag(FLAG_TICK_STATUS);
+	}
+
+	/// # Add Timing.
+	///
+	/// Tally the wall and CPU time spent by an encoder for the summary.
+	fn add_timing(&self, kind: ImageKind, wall: Duration, cpu: Option<Duration>) {
+		let mut timings = self.timings.borrow_mut();
+		if let Some(t) = timings.iter_mut().find(|t| t.0 == kind) {
+			t.1 += wall;
+			t.2 = t.2.zip(cpu).map(|(a, b)| a + b);
+		}
+		else { timings.push((kind, wall, cpu)); }
+	}
+
+	/// # Log Prediction.
+	///
+	/// This notes how far the predicted first quality was from the one
+	/// actually settled on, and tallies it for the summary.
+	fn log_prediction(&self, first: Quality, best: Quality) {
+		use std::fmt::Write;
+
+		let off = usize::from(first.raw().get().abs_diff(best.raw().get()));
+		let (count, total) = self.predictions.get();
+		self.predictions.set((count + 1, total + off));
+
+		let mut buf = self.status.borrow_mut();
+		let _res = write!(
+			buf,
+			concat!(
+				log_prefix!("\n    ", "#9b59b6", "Guess:"),
+				"Started at {}. ",
+				log_colored!("#999", "({} off.)"),
+			),
+			first,
+			off.nice_inflect("step", "steps"),
+		);
+		self.add_flag(FLAG_TICK_STATUS);
+	}
+
+	/// # Log Redundant.
+	///
+	/// This is used when a lossless conversion — either named by format or
+	/// path — is discarded in favor of a smaller lossless copy of a different
+	/// format.
+	fn log_redundant(&self, dropped: &str, kept: ImageKind) {
+		let mut buf = self.status.borrow_mut();
+		buf.push_str(concat!(log_prefix!("\n    ", "#9b59b6", "Notice:"), "Discarded <b>"));
+		buf.push_str(&gtk::glib::markup_escape_text(dropped));
+		buf.push_str("</b>; the lossless ");
+		buf.push_str(kept.as_str());
+		buf.push_str(concat!(" is smaller. ", log_colored!("#999", "(Lossless deduplication.)")));
+		self.add_flag(FLAG_TICK_STATUS);
+	}
+
+	/// # Log Region of Interest.
+	///
+	/// This is used when the region of interest is changed or cleared.
+	fn log_region(&self, region: Option<Region>) {
+		use std::fmt::Write;
+
+		let mut buf = self.status.borrow_mut();
+		buf.push_str(concat!(log_prefix!("\n", "#9b59b6", "Notice:")));
+		let _res =
+			if let Some(region) = region {
+				write!(
+					buf,
+					concat!("Quality searches will be run on the <b>{}</b> region from the next format on. ", log_colored!("#999", "(View > Clear Region to undo.)")),
+					region,
+				)
+			}
+			else {
+				buf.write_str("Quality searches will be run on whole images from the next format on.")
+			};
+		self.add_flag(FLAG_TICK_STATUS);
+	}
+
+	/// # Log Resumed.
+	///
+	/// This is used when queued sources are skipped because a previous
+	/// session already finished them.
+	fn log_resumed(&self, count: usize) {
+		use std::fmt::Write;
+
+		let mut buf = self.status.borrow_mut();
+		let _res = write!(
+			buf,
+			concat!(
+				log_prefix!("\n", "#9b59b6", "Notice:"),
+				"Skipping {} already finished in a previous session. ",
+				log_colored!("#999", "(Resumed.)"),
+			),
+			count.nice_inflect("image", "images"),
+		);
+		self.add_flag(FLAG_TICK_STATUS);
+	}
+
+	/// # Log Reviewed.
+	///
+	/// This records the verdict for a previously saved conversion.
+	fn log_reviewed<P>(&self, path: P, keep: bool)
+	where P: AsRef<Path> {
+		let path = gtk::glib::markup_escape_text(&path.as_ref().to_string_lossy());
+		let mut buf = self.status.borrow_mut();
+		if keep {
+			buf.push_str(concat!(log_prefix!("\n    ", "#2ecc71", "Success:"), "Approved <b>"));
+			buf.push_str(&path);
+			buf.push_str("</b>.");
+		}
+		else {
+			buf.push_str(concat!(log_prefix!("\n    ", "#f39c12", "Warning:"), "Flagged <b>"));
+			buf.push_str(&path);
+			buf.push_str(concat!("</b>. ", log_colored!("#999", "(It may be worth re-encoding.)")));
+		}
+		self.add_flag(FLAG_TICK_STATUS);
+	}
+
+	/// # Log Retry.
+	///
+	/// This is used when a failed encoder is retried with different settings.
+	fn log_retry(&self, kind: ImageKind, what: &str) {
+		let mut buf = self.status.borrow_mut();
+		buf.push_str(concat!(log_prefix!("\n    ", "#9b59b6", "Notice:"), "Retrying "));
+		buf.push_str(kind.as_str());
+		buf.push(' ');
+		buf.push_str(what);
+		buf.push_str(concat!(". ", log_colored!("#999", "(Just for this image.)")));
+		self.add_flag(FLAG_TICK_STATUS);
+	}
+
 	/// # Log Saved.
 	///
 	/// This is used to indicate a new image has been saved.
@@ -1093,6 +3293,73 @@ impl Window {
 		self.add_flag(FLAG_TICK_STATUS);
 	}
 
+	/// # Log Existing.
+	///
+	/// This is used when a format is skipped because its output already
+	/// exists.
+	fn log_existing(&self, dst: &Path) {
+		let mut buf = self.status.borrow_mut();
+		buf.push_str(concat!(log_prefix!("\n    ", "#9b59b6", "Notice:"), "Keeping the existing <b>"));
+		buf.push_str(&dst.to_string_lossy());
+		buf.push_str(concat!("</b>. ", log_colored!("#999", "(No changes were made.)")));
+		self.add_flag(FLAG_TICK_STATUS);
+	}
+
+	/// # Log Paused.
+	///
+	/// This is used when encoding is paused or resumed.
+	fn log_paused(&self, paused: bool) {
+		let mut buf = self.status.borrow_mut();
+		buf.push_str(concat!(log_prefix!("\n", "#9b59b6", "Notice:")));
+		buf.push_str(
+			if paused { concat!("Pausing after the current candidate. ", log_colored!("#999", "(Press Pause again to resume.)")) }
+			else { "Resuming!" }
+		);
+		self.add_flag(FLAG_TICK_STATUS);
+	}
+
+	/// # Log Queue.
+	///
+	/// This is used when the queue has been edited.
+	fn log_queue(&self, len: usize) {
+		use std::fmt::Write;
+
+		let mut buf = self.status.borrow_mut();
+		let _res = write!(
+			buf,
+			concat!(log_prefix!("\n", "#9b59b6", "Notice:"), "The queue has been updated; {} to go."),
+			len.nice_inflect("image", "images"),
+		);
+		self.add_flag(FLAG_TICK_STATUS);
+	}
+
+	/// # Log Skip Format.
+	///
+	/// This is used when the user skips the current format.
+	fn log_skip_format(&self) {
+		let mut buf = self.status.borrow_mut();
+		buf.push_str(concat!(
+			log_prefix!("\n    ", "#9b59b6", "Notice:"),
+			"Skipping the rest of this format. ",
+			log_colored!("#999", "(The best candidate so far, if any, will still be offered.)"),
+		));
+		self.add_flag(FLAG_TICK_STATUS);
+	}
+
+	/// # Log Skipped.
+	///
+	/// This is used when the user opts to skip a source after being warned
+	/// about its colors.
+	fn log_skipped(&self) {
+		let mut buf = self.status.borrow_mut();
+		buf.push_str(concat!(
+			log_prefix!("\n    ", "#9b59b6", "Notice:"),
+			"Skipping this one. ",
+			log_colored!("#999", "(No changes were made.)"),
+		));
+		self.add_flag(FLAG_TICK_STATUS);
+	}
+
 	/// # Log Source.
 	///
 	/// This is used when a new source image is being processed.
@@ -1106,6 +3373,45 @@ impl Window {
 		self.add_flag(FLAG_TICK_STATUS);
 	}
 
+	/// # Log Synced.
+	///
+	/// This is used to point out that output is being saved to a folder
+	/// managed by a cloud-sync client.
+	fn log_synced(&self, service: &str) {
+		let mut buf = self.status.borrow_mut();
+		buf.push_str(concat!(log_prefix!("\n    ", "#f39c12", "Warning:"), "This folder appears to be synced by "));
+		buf.push_str(service);
+		buf.push_str(concat!(
+			"; saves are fully flushed before they land, but give the client time to finish uploading before deploying anything. ",
+			log_colored!("#999", "(Cloud sync.)"),
+		));
+		self.add_flag(FLAG_TICK_STATUS);
+	}
+
+	/// # Log Warning.
+	///
+	/// This is used to point out source color details that will be lost in
+	/// translation.
+	fn log_warning(&self, bits: u8, icc: bool, ask: bool) {
+		let mut buf = self.status.borrow_mut();
+		if icc {
+			buf.push_str(concat!(
+				log_prefix!("\n    ", "#f39c12", "Warning:"),
+				"The source has a color profile that could not be converted to sRGB.",
+			));
+		}
+		if 8 < bits {
+			buf.push_str(concat!(
+				log_prefix!("\n    ", "#f39c12", "Warning:"),
+				"The source has more than 8 bits per channel; only AVIF and JPEG XL will keep them.",
+			));
+		}
+		if ! ask {
+			buf.push_str(log_colored!("#999", " (Converting anyway; no feedback is needed.)"));
+		}
+		self.add_flag(FLAG_TICK_STATUS);
+	}
+
 	/// # Log Start.
 	///
 	/// This triggers when an encoding session starts.
@@ -1113,6 +3419,8 @@ impl Window {
 		use std::fmt::Write;
 
 		if encoders.is_empty() || count == 0 { return; }
+		self.unsupported.set(0);
+		self.timings.borrow_mut().clear();
 
 		let mut buf = self.status.borrow_mut();
 		let _res = write!(
@@ -1168,36 +3476,237 @@ impl Window {
 
 
 
+/// # Crunched Source.
+///
+/// A source decoded and searched ahead of time by one of the [`encode_pool__`]
+/// workers, waiting to be passed along to the main thread.
+struct Crunched {
+	/// # Source.
+	src: Input<'static>,
+
+	/// # Source Candidate.
+	can: Candidate,
+
+	/// # Results (By Format).
+	runs: Vec<EncodeRun>,
+}
+
+/// # Encoder Run.
+///
+/// The outcome of a single format's quality search.
+struct EncodeRun {
+	/// # Format.
+	kind: ImageKind,
+
+	/// # Flags.
+	flags: u8,
+
+	/// # First Quality.
+	first: Option<NonZeroU8>,
+
+	/// # Wall Time.
+	time: Duration,
+
+	/// # CPU Time.
+	///
+	/// This is process-wide, so is dropped for [`encode_pool__`] runs, which
+	/// would all count each other's work.
+	cpu_time: Option<Duration>,
+
+	/// # Encoder Error.
+	failed: Option<RefractError>,
+
+	/// # Best Result.
+	best: Result<Output, RefractError>,
+}
+
+impl EncodeRun {
+	/// # New.
+	///
+	/// Collect the results from a finished search. Unreviewed results are
+	/// checked to make sure they decode to the right size.
+	fn new(src: &Input, kind: ImageKind, flags: u8, first: Option<NonZeroU8>, guide: EncodeIter)
+	-> Self {
+		let time = guide.time();
+		let cpu_time = cfg!(target_os = "linux").then(|| guide.cpu_time());
+		// Skipping isn't failing.
+		let failed = guide.error().filter(|&e| e != RefractError::Cancelled);
+		let best = guide.take();
+
+		// Nobody looked at it, so make sure it decodes to the right size.
+		let best =
+			if crate::max_size().is_some() { best.and_then(|x| encode_verify__(src, x)) }
+			else { best };
+
+		// Make sure browsers will be able to read it first.
+		#[cfg(feature = "avif_dav1d")]
+		let best = best.and_then(|x| x.verify_dav1d().map(|()| x));
+		#[cfg(feature = "avif_dav1d")]
+		let failed = failed.or(
+			if matches!(best, Err(RefractError::CrossDecode)) { Some(RefractError::CrossDecode) }
+			else { None }
+		);
+
+		Self { kind, flags, first, time, cpu_time, failed, best }
+	}
+}
+
+/// # Shared Predictor.
+///
+/// This lets the [`encode_pool__`] workers consult the same history the
+/// encoding thread learns from.
+struct SharedPredictor<'a>(&'a Mutex<HistoryPredictor>);
+
+impl Predictor for SharedPredictor<'_> {
+	fn predict(&self, src: &Input, kind: ImageKind) -> Option<NonZeroU8> {
+		self.0.lock().unwrap_or_else(PoisonError::into_inner).predict(src, kind)
+	}
+
+	fn learn(&mut self, best: Quality) {
+		self.0.lock().unwrap_or_else(PoisonError::into_inner).learn(best);
+	}
+}
+
+
+
+/// # Unattended?
+///
+/// Returns `true` if no feedback is needed — i.e. there is a `--max-size`
+/// budget, or lossy encoding is disabled — so nobody can be assumed to be
+/// watching.
+fn unattended(flags: u8) -> bool {
+	crate::max_size().is_some() || 0 != flags & FLAG_NO_LOSSY
+}
+
 /// ## Encode Wrapper.
 ///
-/// This is an outer wrapper over the individual file path(s). After all paths
-/// have finished, it asks for the encoding lock to be removed.
+/// This is an outer wrapper over the queued file path(s), which may be edited
+/// from the main thread along the way. After the queue runs dry, it asks for
+/// the encoding lock to be removed.
+///
+/// When no feedback is needed — and nothing is being recorded — the work is
+/// spread across `--jobs` threads instead; see [`encode_pool__`].
 fn encode_outer__(
-	paths: Vec<PathBuf>,
+	queue: &Queue,
 	encoders: &[ImageKind],
 	flags: u8,
+	opts: u32,
+	rec: &mut Recorder,
 	tx: &SisterTx,
 	rx: &SisterRx,
 ) {
-	for path in paths {
-		if let Err(e) = encode__(&path, encoders, flags, tx, rx) {
-			Share::sync(tx, rx, Err(e));
+	let jobs = crate::jobs();
+	if 1 < jobs.get() && unattended(flags) && ! rec.is_active() {
+		encode_pool__(queue, encoders, flags, opts, jobs, rec, tx, rx);
+	}
+	else {
+		let mut predictor = HistoryPredictor::default();
+		loop {
+			crate::pause::wait();
+			let Some(item) = queue.pop() else { break; };
+			let encoders = item.encoders.as_deref().unwrap_or(encoders);
+			if let Err(e) = encode__(&item.path, encoders, flags, opts, None, rec, &mut predictor, tx, rx) {
+				Share::sync(tx, rx, Err(ErrorContext::new(e).with_path(&item.path)));
+			}
 		}
 	}
 
 	Share::sync(tx, rx, Ok(Share::DoneEncoding));
 }
 
+/// # Encode Pool.
+///
+/// Crunch up to `jobs` sources at once, each worker popping the next path
+/// from the queue, decoding it, and running every format's (unattended)
+/// search on its own.
+///
+/// The main thread only handles one source at a time, so the finished work
+/// is passed through [`encode__`] one source at a time, in the order it
+/// finishes. Prompts still work as usual; if the answer calls for a do-over,
+/// the format is simply re-run there.
+#[expect(clippy::too_many_arguments, reason = "It is what it is.")]
+fn encode_pool__(
+	queue: &Queue,
+	encoders: &[ImageKind],
+	flags: u8,
+	opts: u32,
+	jobs: NonZeroUsize,
+	rec: &mut Recorder,
+	tx: &SisterTx,
+	rx: &SisterRx,
+) {
+	let history = Mutex::new(HistoryPredictor::default());
+	let (done_tx, done_rx) = crossbeam_channel::bounded(jobs.get());
+	std::thread::scope(|s| {
+		for _ in 0..jobs.get() {
+			let done_tx = done_tx.clone();
+			let predictor = SharedPredictor(&history);
+			s.spawn(move || loop {
+				crate::pause::wait();
+				let Some(item) = queue.pop() else { break; };
+				let encoders = item.encoders.as_deref().unwrap_or(encoders);
+				let pre = encode_crunch__(&item.path, encoders, flags, opts, &predictor);
+				if done_tx.send((item, pre)).is_err() { break; }
+			});
+		}
+		drop(done_tx);
+
+		let mut predictor = SharedPredictor(&history);
+		for (item, pre) in done_rx {
+			let encoders = item.encoders.as_deref().unwrap_or(encoders);
+			if let Err(e) = encode__(&item.path, encoders, flags, opts, Some(pre), rec, &mut predictor, tx, rx) {
+				Share::sync(tx, rx, Err(ErrorContext::new(e).with_path(&item.path)));
+			}
+		}
+	});
+}
+
+/// # Crunch Source.
+///
+/// Decode a source and run each format's unattended search, leaving the
+/// results for [`encode__`] to pass along. This is the part of the job the
+/// [`encode_pool__`] workers can do in parallel.
+fn encode_crunch__(
+	path: &Path,
+	encoders: &[ImageKind],
+	flags: u8,
+	opts: u32,
+	predictor: &dyn Predictor,
+) -> Result<Crunched, RefractError> {
+	let (src, can) = encode_source__(path, 0 != opts & CLI_GREYSCALE)?;
+	let (encoders, flags, _) = encode_plan__(&src, encoders, flags, opts);
+	let runs = encoders.into_iter()
+		.filter_map(|e| {
+			let (mut guide, first) = encode_guide__(&src, e, flags, opts, predictor).ok()?;
+			while guide.advance().is_some() { crate::pause::wait(); }
+			let mut run = EncodeRun::new(&src, e, flags, first, guide);
+			run.cpu_time = None;
+			Some(run)
+		})
+		.collect();
+
+	Ok(Crunched { src, can, runs })
+}
+
 /// # Encode!
 ///
 /// This encoding wrapper runs every requested encoder against a single source
 /// image. It will abort early if there are problems with the path, otherwise
 /// it will guide the user through various qualities and save any "best"
 /// candidates found.
+///
+/// If the source was already crunched by an [`encode_pool__`] worker, its
+/// results are used in place of the encoding, but the conversation with the
+/// main thread is otherwise the same.
+#[expect(clippy::too_many_arguments, reason = "It is what it is.")]
 fn encode__(
 	path: &Path,
 	encoders: &[ImageKind],
 	flags: u8,
+	opts: u32,
+	pre: Option<Result<Crunched, RefractError>>,
+	rec: &mut Recorder,
+	predictor: &mut dyn Predictor,
 	tx: &SisterTx,
 	rx: &SisterRx,
 ) -> Result<(), RefractError> {
@@ -1208,30 +3717,388 @@ fn encode__(
 
 	// First, let's read the main input.
 	Share::sync(tx, rx, Ok(Share::Path(path.to_path_buf())));
-	let (src, can) = encode_source__(path)?;
+	rec.source(path);
+	let (src, can, mut runs) =
+		if let Some(pre) = pre {
+			let Crunched { src, can, runs } = pre?;
+			(src, can, runs)
+		}
+		else {
+			let (src, can) = encode_source__(path, 0 != opts & CLI_GREYSCALE)?;
+			(src, can, Vec::new())
+		};
 	if ShareFeedback::Abort == Share::sync(tx, rx, Ok(Share::Source(can))) {
 		// The status isn't actually OK, but errors are already known, so this
 		// prevents resubmitting the same error later.
 		return Ok(());
 	}
 
-	for &e in encoders {
+	// Give the user a chance to skip sources with colors we can't preserve.
+	// (Extra precision is only lost on the formats that can't hold it.)
+	let icc = src.has_color_profile();
+	let bits =
+		if src.is_high_bit_depth() && encoders.iter().any(|e| ! e.supports_high_bit_depth()) {
+			src.bit_depth()
+		}
+		else { 8 };
+	if
+		(icc || 8 < bits) &&
+		ShareFeedback::Abort == Share::sync(tx, rx, Ok(Share::Warning(bits, icc, ! unattended(flags))))
+	{
+		rec.skip();
+		for &e in encoders {
+			Share::sync(tx, rx, Ok(Share::Fallback(path.to_path_buf(), e)));
+		}
+		return Ok(());
+	}
+
+	// Narrow the formats and modes to suit the source.
+	let (encoders, flags, profile) = encode_plan__(&src, encoders, flags, opts);
+	if let Some(p) = profile { Share::sync(tx, rx, Ok(Share::Profile(p))); }
+
+	for &e in &encoders {
 		Share::sync(tx, rx, Ok(Share::Encoder(e)));
-		if let Ok(mut guide) = EncodeIter::new(&src, e, flags) {
-			let mut count: u8 = 0;
-			while let Some(can) = guide.advance().and_then(|out| Candidate::try_from(out).ok()) {
-				count += 1;
-				let res = Share::sync(tx, rx, Ok(Share::Candidate(can.with_count(count))));
-				match res {
-					ShareFeedback::Keep => { guide.keep(); },
-					ShareFeedback::Discard => { guide.discard(); },
-					ShareFeedback::Abort => { break; },
-					_ => {},
+		if ShareFeedback::Discard == Share::sync(tx, rx, Ok(Share::Existing(path.to_path_buf(), e))) {
+			continue;
+		}
+		rec.encoder(e);
+		let mut flags = flags;
+		loop {
+			// Use the pool's results if we have them, otherwise search now.
+			let run =
+				if let Some(idx) = runs.iter().position(|r| r.kind == e && r.flags == flags) {
+					runs.swap_remove(idx)
 				}
-			}
+				else {
+					let Some(run) = encode_search__(&src, e, flags, opts, rec, predictor, tx, rx)
+					else { break; };
+					run
+				};
 
 			// Save the best, if any!
-			Share::sync(tx, rx, guide.take().map(|x| Share::Best(path.to_path_buf(), x)));
+			Share::sync(tx, rx, Ok(Share::Timing(e, run.time, run.cpu_time)));
+			let EncodeRun { first, failed, best, .. } = run;
+
+			// If the encoder broke, offer to retry this image with different
+			// settings.
+			if let (Err(_), Some(err)) = (&best, failed) {
+				if let ShareFeedback::Retry(f) = Share::sync(tx, rx, Ok(Share::Retry(e, err, flags))) {
+					rec.retry(f);
+					flags = f;
+					continue;
+				}
+			}
+			rec.best(best.as_ref().ok());
+
+			// Learn from the result, and see how close the guess was.
+			let prediction = best.as_ref().ok()
+				.map(Output::quality)
+				.filter(|q| ! q.is_lossless())
+				.and_then(|q| {
+					predictor.learn(q);
+					first.map(|f| Share::Prediction(Quality::new(e, Some(f)), q))
+				});
+
+			// Note how much it actually changed.
+			let best = best
+				.map(|x| {
+					let d = Distortion::new(&src, &x);
+					Share::Best(path.to_path_buf(), x, d)
+				})
+				.map_err(|err| {
+					let ctx = ErrorContext::new(err).with_path(path).with_kind(e);
+					if let Some(cause) = failed { ctx.with_cause(cause) }
+					else { ctx }
+				});
+
+			let fallback = best.is_err();
+			Share::sync(tx, rx, best);
+			if fallback { Share::sync(tx, rx, Ok(Share::Fallback(path.to_path_buf(), e))); }
+			if let Some(prediction) = prediction { Share::sync(tx, rx, Ok(prediction)); }
+			break;
+		}
+	}
+
+	Share::sync(tx, rx, Ok(Share::DoneSource(path.to_path_buf(), src.width(), src.height())));
+
+	Ok(())
+}
+
+/// # Encoding Guide.
+///
+/// Set up the quality search for a given source and format, returning it
+/// along with the first quality to try, if any.
+fn encode_guide__<'a>(
+	src: &'a Input,
+	kind: ImageKind,
+	flags: u8,
+	opts: u32,
+	predictor: &dyn Predictor,
+) -> Result<(EncodeIter<'a>, Option<NonZeroU8>), RefractError> {
+	let mut guide = EncodeIter::new(src, kind, flags)?;
+
+	// Start where the user asked, or where things usually end up, if we have
+	// a guess.
+	let quality = crate::quality_override(kind);
+	if let Some((bottom, top)) = quality.and_then(QualityOverride::bounds) {
+		guide.set_bounds(bottom, top);
+	}
+	let first = quality.and_then(QualityOverride::first)
+		.or_else(|| predictor.predict(src, kind));
+	if let Some(q) = first { guide.set_first(q); }
+	if let Some(px) = crate::stream_above() { guide.set_streaming_above(px); }
+	if let Some(n) = crate::max_steps() { guide.set_max_steps(n); }
+	if let Some(limit) = crate::time_limit() { guide.set_time_limit(limit); }
+	guide.set_avif_codec(crate::avif_codec());
+	guide.set_jxl_tuning(crate::jxl_tuning());
+	guide.set_webp_tuning(crate::webp_tuning(opts));
+	if let Some(max) = crate::max_size() { guide.set_max_size(max); }
+	if let Some(min) = crate::lossless_above() { guide.set_lossless_above(min); }
+	if 0 == opts & CLI_PHOTO_LOSSLESS { guide.skip_lossless_if_photo(); }
+
+	Ok((guide, first))
+}
+
+/// # Search.
+///
+/// Run a format's quality search — on the region of interest, if any, or
+/// the whole source — returning the outcome, or `None` if the encoder
+/// couldn't be started.
+#[expect(clippy::too_many_arguments, reason = "It is what it is.")]
+fn encode_search__(
+	src: &Input,
+	kind: ImageKind,
+	flags: u8,
+	opts: u32,
+	rec: &mut Recorder,
+	predictor: &dyn Predictor,
+	tx: &SisterTx,
+	rx: &SisterRx,
+) -> Option<EncodeRun> {
+	// Search a crop, if asked, swapping it in for display.
+	let crop = encode_region__(src, kind, flags).and_then(|(x, y, w, h)| {
+		let mut crop = src.crop(x, y, w, h)?;
+		let can = Candidate::try_from(&mut crop).ok()?;
+		rec.region(x, y, w, h);
+		Share::sync(tx, rx, Ok(Share::Region(Some(can))));
+		Some(crop)
+	});
+	let target = crop.as_ref().unwrap_or(src);
+
+	let Ok((mut guide, first)) = encode_guide__(target, kind, flags, opts, predictor) else {
+		if crop.is_some() { Share::sync(tx, rx, Ok(Share::Region(None))); }
+		return None;
+	};
+	guide.set_cancel_token(crate::pause::skip_token());
+	if let Some((bottom, top)) = crate::quality_override(kind).and_then(QualityOverride::bounds) {
+		rec.bounds(bottom, top);
+	}
+	if let Some(q) = first { rec.first(q); }
+
+	// With a size budget, the iterator doesn't need any help.
+	if crate::max_size().is_some() {
+		while guide.advance().is_some() { crate::pause::wait(); }
+	}
+	else { encode_review__(target, &mut guide, rec, tx, rx); }
+	let mut run = EncodeRun::new(target, kind, flags, first, guide);
+
+	// Apply the crop's verdict to the whole thing.
+	if crop.is_some() {
+		Share::sync(tx, rx, Ok(Share::Region(None)));
+		run.best = run.best.and_then(|b|
+			encode_whole__(src, kind, flags, opts, &b, predictor)
+		);
+	}
+
+	Some(run)
+}
+
+/// # Region of Interest.
+///
+/// Return the crop — `(x, y, width, height)` — to run the quality search on
+/// for the source and format, if any. Crops only make sense when there are
+/// lossy candidates to review, so size budgets and lossless-only runs always
+/// search the whole image.
+fn encode_region__(src: &Input, kind: ImageKind, flags: u8)
+-> Option<(usize, usize, usize, usize)> {
+	if
+		kind == ImageKind::Png ||
+		0 != flags & FLAG_NO_LOSSY ||
+		crate::max_size().is_some()
+	{
+		return None;
+	}
+	crate::region()?.fit(src.width(), src.height())
+}
+
+/// # Encode Whole.
+///
+/// Encode the whole source once at the quality — and for AVIF, the color
+/// mode — settled on for a crop of it, making sure the result is sound, as
+/// nobody will have looked at it.
+fn encode_whole__(
+	src: &Input,
+	kind: ImageKind,
+	flags: u8,
+	opts: u32,
+	verdict: &Output,
+	predictor: &dyn Predictor,
+) -> Result<Output, RefractError> {
+	let (mut guide, _) = encode_guide__(src, kind, flags, opts, predictor)?;
+	guide.match_mode(verdict);
+	let quality = verdict.quality();
+	let best = guide.once((! quality.is_lossless()).then(|| quality.raw()))?;
+	let best = encode_verify__(src, best)?;
+
+	#[cfg(feature = "avif_dav1d")]
+	best.verify_dav1d()?;
+
+	Ok(best)
+}
+
+/// # Review Candidates.
+///
+/// Send each candidate to the user for feedback, passing their verdicts back
+/// to the iterator, until it runs dry or they abort.
+fn encode_review__(
+	src: &Input,
+	guide: &mut EncodeIter,
+	rec: &mut Recorder,
+	tx: &SisterTx,
+	rx: &SisterRx,
+) {
+	let mut count: u8 = 0;
+	let mut again = false;
+	loop {
+		// After an undo, the restored candidate is up for review again.
+		let out =
+			if again {
+				again = false;
+				guide.candidate()
+			}
+			else {
+				crate::pause::wait();
+				guide.advance()
+			};
+		let Some(out) = out else { break; };
+
+		// Make sure the candidate decodes to the right size before bothering
+		// the user with it.
+		let can = Candidate::try_from(out)
+			.and_then(|c| c.verify_dimensions(src.width(), src.height()));
+		let can = match can {
+			Ok(can) => can,
+			Err(e @ RefractError::Dimensions) => {
+				Share::sync(tx, rx, Err(ErrorContext::new(e).with_kind(guide.output_kind())));
+				guide.discard();
+				continue;
+			},
+			Err(_) => break,
+		};
+
+		count += 1;
+		let can = can.with_count(count)
+			.with_dssim(similarity(src, out))
+			.with_bounds(guide.bounds())
+			.with_progress(guide.progress())
+			.with_undo(guide.can_undo());
+		let res = Share::sync(tx, rx, Ok(Share::Candidate(can)));
+		rec.feedback(res);
+		match res {
+			ShareFeedback::Keep => { guide.keep(); },
+			ShareFeedback::Discard => { guide.discard(); },
+			ShareFeedback::Undo => {
+				count = count.saturating_sub(if guide.undo() { 2 } else { 1 });
+				again = true;
+			},
+			ShareFeedback::Seek(q) => { guide.set_first(q); },
+			ShareFeedback::Abort => { break; },
+			_ => {},
+		}
+	}
+}
+
+/// # Verify Best.
+///
+/// Make sure an unreviewed result decodes to the source's dimensions.
+fn encode_verify__(src: &Input, best: Output) -> Result<Output, RefractError> {
+	Candidate::try_from(&best)?.verify_dimensions(src.width(), src.height())?;
+	Ok(best)
+}
+
+/// # Encoding Plan.
+///
+/// Return the encoders and flags to use for a given source.
+///
+/// Re-encoding a next-gen source in its own format is pointless, so that is
+/// always dropped. (JPEG and PNG are the exception; recompressing those is
+/// the point.) JPEG is also dropped for sources with transparency, since it
+/// would be lost.
+///
+/// With smart defaults enabled, the list and modes are further narrowed to
+/// suit the source's [`Profile`], which is returned if it made a difference.
+fn encode_plan__(src: &Input, encoders: &[ImageKind], flags: u8, opts: u32)
+-> (Vec<ImageKind>, u8, Option<Profile>) {
+	let encoders: Vec<ImageKind> = encoders.iter()
+		.copied()
+		.filter(|&e| e != src.kind() || matches!(e, ImageKind::Jpeg | ImageKind::Png))
+		.filter(|&e| e != ImageKind::Jpeg || ! src.has_alpha())
+		.collect();
+
+	if 0 == opts & CLI_SMART { return (encoders, flags, None); }
+	let profile = Profile::from(src);
+	if profile == Profile::Other { return (encoders, flags, None); }
+
+	let smart: Vec<ImageKind> = encoders.iter()
+		.copied()
+		.filter(|e| matches!(e, ImageKind::Jpeg | ImageKind::Png) || profile.formats().contains(e))
+		.collect();
+	(
+		if smart.is_empty() { encoders } else { smart },
+		profile.flags(flags),
+		Some(profile),
+	)
+}
+
+/// # Review!
+///
+/// This loads a source image and its previously saved conversions, sending
+/// each along for A/B review.
+fn review__(
+	path: &Path,
+	saved: &[(ImageKind, PathBuf)],
+	tx: &SisterTx,
+	rx: &SisterRx,
+) -> Result<(), RefractError> {
+	Share::sync(tx, rx, Ok(Share::Path(path.to_path_buf())));
+	let (src, can) = encode_source__(path, false)?;
+	if ShareFeedback::Abort == Share::sync(tx, rx, Ok(Share::Source(can))) {
+		return Ok(());
+	}
+
+	for (kind, dst) in saved {
+		Share::sync(tx, rx, Ok(Share::Encoder(*kind)));
+		let can = std::fs::read(dst)
+			.map_err(|_| RefractError::Read)
+			.and_then(|raw| Candidate::try_from(&mut Input::try_from(raw.as_slice())?))
+			.and_then(|c| c.verify_dimensions(src.width(), src.height()));
+		let can = match can {
+			Ok(can) => can.with_saved(),
+			Err(e) => {
+				Share::sync(tx, rx, Err(ErrorContext::new(e).with_path(dst).with_kind(*kind)));
+				continue;
+			},
+		};
+
+		match Share::sync(tx, rx, Ok(Share::Candidate(can))) {
+			ShareFeedback::Keep => {
+				Share::sync(tx, rx, Ok(Share::Reviewed(dst.clone(), true)));
+			},
+			ShareFeedback::Discard => {
+				Share::sync(tx, rx, Ok(Share::Reviewed(dst.clone(), false)));
+			},
+			ShareFeedback::Abort => break,
+			_ => {},
 		}
 	}
 
@@ -1242,13 +4109,67 @@ fn encode__(
 ///
 /// This generates an [`Input`] and [`Candidate`] object from a given file
 /// path, or dies trying.
-fn encode_source__(path: &Path) -> Result<(Input, Candidate), RefractError> {
-	let raw: &[u8] = &std::fs::read(path).map_err(|_| RefractError::Read)?;
-	let out = Input::try_from(raw)?;
-	let can = Candidate::try_from(&out)?;
+///
+/// If `greyscale` is set, the source will be flattened before anything else
+/// sees it.
+fn encode_source__(path: &Path, greyscale: bool) -> Result<(Input<'static>, Candidate), RefractError> {
+	let raw = std::fs::read(path).map_err(|_| RefractError::Read)?;
+	let mut out = Input::try_from(raw.as_slice())?;
+	let hash = sha256(&raw);
+
+	// The file itself is no longer needed, so let it go before the pixels get
+	// shared for display; huge sources need all the room they can get.
+	drop(raw);
+
+	if greyscale { out = out.into_greyscale(); }
+	let mut can = Candidate::try_from(&mut out)?;
+	can.hash.replace(hash);
 	Ok((out, can))
 }
 
+/// # Encoder Flags.
+///
+/// This maps the `CLI_*` flags — i.e. [`Window::cli_flags`] — to the
+/// equivalent [`EncodeIter`] flags.
+pub(crate) const fn cli_encoder_flags(cli: u32) -> u8 {
+	let mut flags: u8 = 0;
+
+	if 0 != cli & CLI_NO_LOSSY { flags |= FLAG_NO_LOSSY; }
+	else if 0 != cli & CLI_NO_LOSSLESS { flags |= FLAG_NO_LOSSLESS; }
+
+	if 0 != cli & CLI_NO_YCBCR { flags |= FLAG_NO_AVIF_YCBCR; }
+	if 0 != cli & CLI_AVIF_YUV420 { flags |= FLAG_AVIF_YUV420; }
+	if 0 != cli & CLI_LOSSLESS_LAST { flags |= FLAG_LOSSLESS_LAST; }
+	if 0 != cli & CLI_JXL_PROGRESSIVE { flags |= FLAG_JXL_PROGRESSIVE; }
+	if 0 != cli & CLI_KEEP_METADATA { flags |= FLAG_KEEP_METADATA; }
+
+	flags
+}
+
+/// # Enabled Encoders.
+///
+/// Return an array of the encoders enabled by the `CLI_*` flags.
+fn cli_encoders(cli: u32) -> Box<[ImageKind]> {
+	[
+		(ImageKind::Webp, 0 == cli & CLI_NO_WEBP),
+		(ImageKind::Avif, 0 == cli & CLI_NO_AVIF),
+		(ImageKind::Jxl, 0 == cli & CLI_NO_JXL),
+		(ImageKind::Png, 0 != cli & CLI_PNG),
+		(ImageKind::Jpeg, 0 != cli & CLI_JPEG),
+	]
+		.into_iter()
+		.filter_map(|(kind, on)| on.then_some(kind))
+		.collect()
+}
+
+/// # Sister Flags.
+///
+/// Return the subset of `CLI_*` flags the encoding thread needs to know
+/// about beyond the encoders and their flags.
+const fn cli_sister_flags(cli: u32) -> u32 {
+	cli & (CLI_GREYSCALE | CLI_SMART | CLI_BACKGROUND | CLI_PHOTO_LOSSLESS)
+}
+
 /// # Add Widget Class.
 ///
 /// This adds a class to a widget.
@@ -1258,12 +4179,155 @@ where W: WidgetExt {
 	style_context.add_class(class);
 }
 
-/// # Is JPEG/PNG File.
-pub(super) fn is_jpeg_png(path: &Path) -> bool {
-	Extension::try_from3(path).map_or_else(
-		|| Extension::try_from4(path) == Some(E_JPEG),
-		|e| e == E_JPG || e == E_PNG
-	)
+/// # Zoom Level.
+///
+/// Return the zoom level one step in the given direction — or 100% for
+/// `None` — so long as it differs from the current one and the images can be
+/// painted that large.
+fn zoom_level<'a, I>(current: u16, dir: Option<bool>, imgs: I) -> Option<u16>
+where I: Iterator<Item=&'a Pixbuf> {
+	let next = match dir {
+		Some(true) => ZOOM_LEVELS.iter().copied().find(|&z| current < z)?,
+		Some(false) => ZOOM_LEVELS.iter().copied().rev().find(|&z| z < current)?,
+		None => 100,
+	};
+
+	let biggest = imgs.map(|i| i.width().max(i.height()))
+		.max()
+		.and_then(|d| u64::try_from(d).ok())
+		.unwrap_or(0);
+	(next != current && biggest * u64::from(next) / 100 <= ZOOM_MAX_DIMENSION)
+		.then_some(next)
+}
+
+/// # Preview Image.
+///
+/// Return a copy of the image scaled down so its longest side fits within
+/// `max` pixels, or `None` if it already does.
+fn previewed(img: &Pixbuf, max: NonZeroUsize) -> Option<Pixbuf> {
+	let max = i32::try_from(max.get()).unwrap_or(i32::MAX);
+	let (width, height) = (img.width(), img.height());
+	let longest = width.max(height);
+	if longest <= max { return None; }
+
+	let scale = |d: i32| i32::try_from(i64::from(d) * i64::from(max) / i64::from(longest))
+		.unwrap_or(1)
+		.max(1);
+	img.scale_simple(scale(width), scale(height), gtk::gdk_pixbuf::InterpType::Bilinear)
+}
+
+/// # Zoomed Image.
+///
+/// Return a copy of the image scaled to the zoom level. Upscaling uses
+/// nearest-neighbor so individual pixels — and their artifacts — stay crisp.
+fn zoomed(img: &Pixbuf, zoom: u16) -> Pixbuf {
+	if zoom == 100 { return img.clone(); }
+	let scale = |d: i32| (d * i32::from(zoom) / 100).max(1);
+	let interp =
+		if 100 < zoom { gtk::gdk_pixbuf::InterpType::Nearest }
+		else { gtk::gdk_pixbuf::InterpType::Bilinear };
+	img.scale_simple(scale(img.width()), scale(img.height()), interp)
+		.unwrap_or_else(|| img.clone())
+}
+
+/// # Encoder Equivalents.
+///
+/// Return a short table of roughly-equivalent qualities for typical photos,
+/// led by the given encoder, or `None` if it has no lossy mode to speak of.
+fn encoder_equivalents(kind: ImageKind) -> Option<String> {
+	let mut out = String::from("Roughly equivalent for typical photos:");
+	for q in [60, 80, 90] {
+		let q = Quality::Lossy(ImageKind::Webp, NonZeroU8::new(q)?).equivalent(kind)?;
+		out.push('\n');
+		out.push_str(&equivalent_label(q));
+		for other in EQUIVALENT_KINDS {
+			if other == kind { continue; }
+			if let Some(o) = q.equivalent(other) {
+				out.push_str(" ≈ ");
+				out.push_str(&equivalent_label(o));
+			}
+		}
+	}
+	Some(out)
+}
+
+/// # Equivalent Label.
+///
+/// Format a quality with its format name, e.g. "AVIF quantizer 30".
+fn equivalent_label(quality: Quality) -> String {
+	format!("{} {quality}", quality.kind())
+}
+
+/// # Existing Output Policy Label.
+const fn existing_label(policy: Existing) -> &'static str {
+	match policy {
+		Existing::Ask => "Ask",
+		Existing::Skip => "Skip",
+		Existing::Overwrite => "Overwrite",
+		Existing::Smaller => "Replace If Smaller",
+	}
+}
+
+#[cfg(windows)]
+/// # Image File Filter.
+///
+/// The native Windows file chooser only understands patterns — given a filter
+/// with MIME types, GTK falls back to its own dialogue — so this is a
+/// patterns-only copy of the Glade filter.
+fn image_filter() -> FileFilter {
+	let out = FileFilter::new();
+	for ext in ["*.jpg", "*.jpeg", "*.png", "*.avif", "*.jxl", "*.webp"] {
+		out.add_pattern(ext);
+	}
+	out
+}
+
+/// # History Value.
+///
+/// Return the display value for a quality in the search history, using a
+/// word for lossless.
+fn history_val(quality: Quality) -> String {
+	if quality.is_lossless() { String::from("Lossless") }
+	else { quality.quality().to_string() }
+}
+
+/// # Progress Percentage.
+///
+/// Return how far along a search is — given the candidates so far and the
+/// most that could yet follow — assuming the worst about what's left.
+fn percent((done, left): (u8, u8)) -> Option<u8> {
+	let total = u16::from(done) + u16::from(left);
+	(u16::from(done) * 100).checked_div(total).and_then(|p| u8::try_from(p).ok())
+}
+
+/// # Ladder Rung.
+///
+/// Return a sortable value for the quality, lowest first. Lossless always
+/// comes last.
+fn ladder_rung(quality: Quality) -> u16 {
+	match quality {
+		Quality::Lossless(_) => u16::MAX,
+		Quality::Lossy(_, q) => u16::from(q.get()),
+	}
+}
+
+/// # Quality Equivalents.
+///
+/// Return the roughly-equivalent qualities for the other lossy encoders,
+/// one per line, or `None` if there aren't any.
+fn quality_equivalents(quality: Quality) -> Option<String> {
+	let mut out = String::from("Roughly equivalent for typical photos:");
+	let mut any = false;
+	for other in EQUIVALENT_KINDS {
+		if other == quality.kind() { continue; }
+		if let Some(o) = quality.equivalent(other) {
+			out.push('\n');
+			out.push_str(&equivalent_label(o));
+			any = true;
+		}
+	}
+	if any { Some(out) }
+	else { None }
 }
 
 /// # Remove Widget Class.
@@ -1285,3 +4349,153 @@ where W: WidgetExt {
 	provider.load_from_resource(src);
 	style_context.add_provider(&provider, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
 }
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// # Test Image Path.
+	fn asset(name: &str) -> PathBuf {
+		PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../skel/assets").join(name)
+	}
+
+	/// # Drive Encoding.
+	///
+	/// Run the encoding thread headlessly — no GTK required — standing in for
+	/// the main thread: every candidate is answered by the callback, every
+	/// other payload with [`ShareFeedback::Continue`]. Everything sent is
+	/// returned, in order.
+	fn drive<F>(paths: Vec<PathBuf>, encoders: &[ImageKind], flags: u8, mut answer: F)
+	-> Vec<SharePayload>
+	where F: FnMut(&Candidate) -> ShareFeedback {
+		let (tx, rx) = crossbeam_channel::bounded(8);
+		let (tx2, rx2) = crossbeam_channel::bounded(8);
+		let encoders = encoders.to_vec();
+		let handle = std::thread::spawn(move || {
+			let mut rec = Recorder::new(None, flags, 0);
+			let queue = Queue::default();
+			queue.set(paths);
+			encode_outer__(&queue, &encoders, flags, 0, &mut rec, &tx, &rx2);
+		});
+
+		let mut out = Vec::new();
+		while let Ok(res) = rx.recv() {
+			let feedback = match &res {
+				Ok(Share::Candidate(can)) => answer(can),
+				_ => ShareFeedback::Continue,
+			};
+			out.push(res);
+			if tx2.send(feedback).is_err() { break; }
+		}
+
+		handle.join().expect("The encoding thread panicked.");
+		out
+	}
+
+	#[test]
+	fn t_cli_flags() {
+		assert_eq!(
+			&*cli_encoders(0),
+			&[ImageKind::Webp, ImageKind::Avif, ImageKind::Jxl],
+		);
+		assert_eq!(
+			&*cli_encoders(CLI_NO_AVIF | CLI_NO_WEBP | CLI_PNG | CLI_JPEG),
+			&[ImageKind::Jxl, ImageKind::Png, ImageKind::Jpeg],
+		);
+
+		assert_eq!(cli_encoder_flags(0), 0);
+		assert_eq!(
+			cli_encoder_flags(CLI_NO_LOSSLESS | CLI_NO_YCBCR | CLI_AVIF_YUV420 | CLI_JXL_PROGRESSIVE | CLI_KEEP_METADATA),
+			FLAG_NO_LOSSLESS | FLAG_NO_AVIF_YCBCR | FLAG_AVIF_YUV420 | FLAG_JXL_PROGRESSIVE | FLAG_KEEP_METADATA,
+		);
+		// Lossless wins if both modes are (somehow) disabled.
+		assert_eq!(
+			cli_encoder_flags(CLI_NO_LOSSY | CLI_NO_LOSSLESS | CLI_LOSSLESS_LAST),
+			FLAG_NO_LOSSY | FLAG_LOSSLESS_LAST,
+		);
+
+		assert_eq!(
+			cli_sister_flags(CLI_SMART | CLI_GREYSCALE | CLI_DURABLE | CLI_PNG | CLI_PHOTO_LOSSLESS),
+			CLI_SMART | CLI_GREYSCALE | CLI_PHOTO_LOSSLESS,
+		);
+	}
+
+	#[test]
+	fn t_drive_queue() {
+		// The broken source errors out, but the queue carries on. Lossless
+		// results need no feedback.
+		let good = asset("r.png");
+		let res = drive(
+			vec![asset("fake.png"), good.clone()],
+			&[ImageKind::Webp, ImageKind::Png],
+			FLAG_NO_LOSSY,
+			|_| panic!("Lossless encoding should not need feedback."),
+		);
+
+		assert!(matches!(res.first(), Some(Ok(Share::Path(p))) if p.ends_with("fake.png")));
+		assert!(matches!(res.get(1), Some(Err(_))), "The fake source should fail.");
+		assert!(matches!(res.get(2), Some(Ok(Share::Path(p))) if p == &good));
+		assert!(matches!(res.last(), Some(Ok(Share::DoneEncoding))));
+		assert_eq!(
+			res.iter().filter(|r| matches!(r, Ok(Share::DoneSource(p, _, _)) if p == &good)).count(),
+			1,
+		);
+
+		// The encoders run in order, each timed once.
+		let encoders: Vec<ImageKind> = res.iter()
+			.filter_map(|r| match r {
+				Ok(Share::Encoder(k)) => Some(*k),
+				_ => None,
+			})
+			.collect();
+		assert_eq!(encoders, [ImageKind::Webp, ImageKind::Png]);
+		assert_eq!(res.iter().filter(|r| matches!(r, Ok(Share::Timing(..)))).count(), 2);
+	}
+
+	#[test]
+	fn t_drive_feedback() {
+		let src = asset("poe.png");
+
+		// Keeping candidates yields a best to save.
+		let mut count = 0;
+		let res = drive(vec![src.clone()], &[ImageKind::Webp], FLAG_NO_LOSSLESS, |_| {
+			count += 1;
+			ShareFeedback::Keep
+		});
+		assert!(0 < count, "There should be candidates to review.");
+		assert!(res.iter().any(|r| matches!(
+			r,
+			Ok(Share::Best(p, out, _)) if p == &src && out.kind() == ImageKind::Webp
+		)));
+		assert!(! res.iter().any(|r| matches!(r, Ok(Share::Fallback(..)))));
+
+		// Discarding everything leaves nothing to save.
+		let res = drive(vec![src.clone()], &[ImageKind::Webp], FLAG_NO_LOSSLESS, |_| ShareFeedback::Discard);
+		assert!(! res.iter().any(|r| matches!(r, Ok(Share::Best(..)))));
+		assert!(res.iter().any(|r| matches!(
+			r,
+			Ok(Share::Fallback(p, ImageKind::Webp)) if p == &src
+		)));
+
+		// Undoing brings back the last decided candidate for another look.
+		let mut seen = Vec::new();
+		drive(vec![src.clone()], &[ImageKind::Webp], FLAG_NO_LOSSLESS, |can| {
+			seen.push((can.count, ladder_rung(can.quality)));
+			if seen.len() == 2 { ShareFeedback::Undo }
+			else { ShareFeedback::Discard }
+		});
+		assert!(3 <= seen.len(), "There should be candidates to review.");
+		assert_eq!(seen[2], seen[0]);
+
+		// Aborting stops after the first candidate.
+		let mut count = 0;
+		let res = drive(vec![src], &[ImageKind::Webp], FLAG_NO_LOSSLESS, |_| {
+			count += 1;
+			ShareFeedback::Abort
+		});
+		assert_eq!(count, 1);
+		assert!(matches!(res.last(), Some(Ok(Share::DoneEncoding))));
+	}
+}
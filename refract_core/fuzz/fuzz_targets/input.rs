@@ -0,0 +1,15 @@
+/*!
+# Refract Core: Fuzz Image Decoding.
+*/
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use refract_core::Input;
+
+fuzz_target!(|data: &[u8]| {
+	if let Ok(img) = Input::try_from(data) {
+		// The pixel buffer should always line up with the dimensions.
+		assert_eq!(img.len(), img.width() * img.height() * 4);
+	}
+});
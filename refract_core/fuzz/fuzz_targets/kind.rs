@@ -0,0 +1,16 @@
+/*!
+# Refract Core: Fuzz Image Kind Detection.
+*/
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use refract_core::{
+	ImageKind,
+	SniffResult,
+};
+
+fuzz_target!(|data: &[u8]| {
+	let _res = ImageKind::try_from(data);
+	let _res = SniffResult::try_from(data);
+});
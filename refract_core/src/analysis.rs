@@ -0,0 +1,199 @@
+/*!
+# `Refract` - Source Analysis
+*/
+
+use crate::{
+	FLAG_NO_LOSSLESS,
+	FLAG_NO_LOSSY,
+	ImageKind,
+	Input,
+};
+use std::collections::HashSet;
+
+
+
+/// # Few Colors.
+///
+/// Sources with this many distinct colors or fewer could be saved as an
+/// indexed palette, and usually compress best losslessly.
+const FEW_COLORS: usize = 256;
+
+/// # Photographic Entropy.
+///
+/// Sources whose neighboring pixels differ this unpredictably — in bits per
+/// pixel — are almost certainly photos, or photo-like enough that lossless
+/// compression won't get far with them.
+const PHOTO_ENTROPY: f64 = 4.5;
+
+
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+/// # Source Profile.
+///
+/// This is a rough guess at what sort of image a source is, used to pick
+/// sensible per-image formats and modes rather than throwing every encoder
+/// at everything.
+pub enum Profile {
+	/// # Graphic.
+	///
+	/// A PNG with transparency and only a handful of colors, e.g. a logo or
+	/// icon. These do best with lossless WebP or JPEG XL; AVIF rarely wins.
+	Graphic,
+
+	/// # Photo.
+	///
+	/// A JPEG. These do best with lossy AVIF or JPEG XL; lossless is a waste
+	/// of time.
+	Photo,
+
+	/// # Other.
+	///
+	/// Anything else, which gets the full treatment.
+	Other,
+}
+
+impl From<&Input<'_>> for Profile {
+	fn from(src: &Input<'_>) -> Self {
+		match src.kind() {
+			ImageKind::Jpeg => Self::Photo,
+			ImageKind::Png if src.has_alpha() && has_few_colors(src) => Self::Graphic,
+			_ => Self::Other,
+		}
+	}
+}
+
+impl Profile {
+	#[must_use]
+	/// # As Str.
+	pub const fn as_str(self) -> &'static str {
+		match self {
+			Self::Graphic => "graphic",
+			Self::Photo => "photo",
+			Self::Other => "other",
+		}
+	}
+
+	#[must_use]
+	/// # Preferred Formats.
+	///
+	/// Return the output formats best suited to the profile.
+	pub const fn formats(self) -> &'static [ImageKind] {
+		match self {
+			Self::Graphic => &[ImageKind::Webp, ImageKind::Jxl],
+			Self::Photo => &[ImageKind::Avif, ImageKind::Jxl],
+			Self::Other => &[ImageKind::Webp, ImageKind::Avif, ImageKind::Jxl],
+		}
+	}
+
+	#[must_use]
+	/// # Apply to Flags.
+	///
+	/// Add the profile's preferred mode — lossless for graphics, lossy for
+	/// photos — to the encoder flags, unless the flags already rule that mode
+	/// out, in which case they're returned as-is.
+	pub const fn flags(self, flags: u8) -> u8 {
+		let extra = match self {
+			Self::Graphic => FLAG_NO_LOSSY,
+			Self::Photo => FLAG_NO_LOSSLESS,
+			Self::Other => 0,
+		};
+
+		if (flags | extra) & (FLAG_NO_LOSSY | FLAG_NO_LOSSLESS) == FLAG_NO_LOSSY | FLAG_NO_LOSSLESS {
+			flags
+		}
+		else { flags | extra }
+	}
+}
+
+
+
+#[must_use]
+/// # Is Photographic?
+///
+/// Return `true` if the source is a JPEG, or so noisy — per a quick entropy
+/// estimate of the differences between neighboring pixels — that a lossless
+/// encode would almost certainly lose to a lossy one.
+///
+/// See [`EncodeIter::skip_lossless_if_photo`](crate::EncodeIter::skip_lossless_if_photo).
+pub fn is_photographic(src: &Input) -> bool {
+	src.kind() == ImageKind::Jpeg || PHOTO_ENTROPY <= residual_entropy(src)
+}
+
+/// # Residual Entropy.
+///
+/// Return the Shannon entropy, in bits, of the differences between
+/// horizontally adjacent pixels in the green (or grey) channel, which is
+/// roughly what a lossless encoder is up against.
+fn residual_entropy(src: &Input) -> f64 {
+	let channels = src.depth().channels() as usize;
+	let idx = usize::from(3 <= channels);
+	let mut hist = [0_u64; 256];
+	for line in src.chunks_exact(src.width() * channels) {
+		let mut px = line.chunks_exact(channels).map(|px| px[idx]);
+		let Some(mut last) = px.next() else { continue; };
+		for next in px {
+			hist[usize::from(next.wrapping_sub(last))] += 1;
+			last = next;
+		}
+	}
+
+	let total: u64 = hist.iter().sum();
+	if total == 0 { return 0.0; }
+
+	#[expect(clippy::cast_precision_loss, reason = "Close enough.")]
+	let total = total as f64;
+	hist.iter()
+		.filter(|&&n| n != 0)
+		.map(|&n| {
+			#[expect(clippy::cast_precision_loss, reason = "Close enough.")]
+			let p = n as f64 / total;
+			-p * p.log2()
+		})
+		.sum()
+}
+
+/// # Has Few Colors?
+///
+/// Count the distinct RGBA colors, stopping as soon as there are too many.
+fn has_few_colors(src: &Input) -> bool {
+	let rgba = src.as_rgba();
+	let mut seen: HashSet<[u8; 4]> = HashSet::with_capacity(FEW_COLORS + 1);
+	for px in rgba.chunks_exact(4) {
+		seen.insert([px[0], px[1], px[2], px[3]]);
+		if FEW_COLORS < seen.len() { return false; }
+	}
+	true
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn t_flags() {
+		assert_eq!(Profile::Graphic.flags(0), FLAG_NO_LOSSY);
+		assert_eq!(Profile::Photo.flags(0), FLAG_NO_LOSSLESS);
+		assert_eq!(Profile::Other.flags(0), 0);
+
+		// Don't disable everything!
+		assert_eq!(Profile::Graphic.flags(FLAG_NO_LOSSLESS), FLAG_NO_LOSSLESS);
+		assert_eq!(Profile::Photo.flags(FLAG_NO_LOSSY), FLAG_NO_LOSSY);
+	}
+
+	#[test]
+	fn t_photographic() {
+		for (raw, expected) in [
+			(&include_bytes!("../../skel/assets/cats.jpg")[..], true),
+			(&include_bytes!("../../skel/assets/myrna.png")[..], true),
+			(&include_bytes!("../../skel/assets/mountains-on-mars.png")[..], false),
+			(&include_bytes!("../../skel/assets/poe.png")[..], false),
+			(&include_bytes!("../../skel/assets/r.png")[..], false),
+		] {
+			let src = Input::try_from(raw).expect("Unable to read test image.");
+			assert_eq!(is_photographic(&src), expected);
+			assert_eq!(is_photographic(&src.as_native()), expected);
+		}
+	}
+}
@@ -0,0 +1,188 @@
+/*!
+# `Refract` - Cancellation
+*/
+
+use std::{
+	sync::{
+		Arc,
+		atomic::{
+			AtomicBool,
+			Ordering::Relaxed,
+		},
+		mpsc::{
+			self,
+			RecvTimeoutError,
+			Sender,
+		},
+	},
+	thread::JoinHandle,
+	time::{
+		Duration,
+		Instant,
+	},
+};
+
+
+
+/// # Watchdog Poll Interval.
+///
+/// How often a [`Watchdog`] checks whether its parent has been cancelled.
+const POLL: Duration = Duration::from_millis(50);
+
+
+
+#[derive(Debug, Clone, Default)]
+/// # Cancellation Token.
+///
+/// This is a shared flag for cutting an [`EncodeIter`](crate::EncodeIter)
+/// short from another thread. Hand a clone to the iterator with
+/// [`EncodeIter::set_cancel_token`](crate::EncodeIter::set_cancel_token),
+/// then call [`CancelToken::cancel`] whenever; the encode in progress is
+/// abandoned, and iteration ends with whatever best had already been found.
+///
+/// How quickly that happens depends on the encoder. `WebP` checks in
+/// regularly while it works. `JPEG XL` checks between output chunks, which
+/// mostly helps streamed encodes (see
+/// [`EncodeIter::set_streaming_above`](crate::EncodeIter::set_streaming_above)),
+/// as otherwise everything is compressed up front. `libavif`, `MozJPEG`,
+/// and the PNG optimizer offer no such hook at all, so only notice between
+/// candidates.
+///
+/// ```
+/// use refract_core::CancelToken;
+///
+/// let token = CancelToken::default();
+/// let other = token.clone();
+/// assert!(! token.is_cancelled());
+///
+/// other.cancel();
+/// assert!(token.is_cancelled());
+///
+/// token.reset();
+/// assert!(! other.is_cancelled());
+/// ```
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+	#[inline]
+	/// # Cancel.
+	pub fn cancel(&self) { self.0.store(true, Relaxed); }
+
+	#[inline]
+	#[must_use]
+	/// # Is Cancelled?
+	pub fn is_cancelled(&self) -> bool { self.0.load(Relaxed) }
+
+	#[inline]
+	/// # Reset.
+	///
+	/// Clear the flag so the token can be reused.
+	pub fn reset(&self) { self.0.store(false, Relaxed); }
+
+	#[cfg(feature = "webp")]
+	#[inline]
+	/// # Raw Flag.
+	///
+	/// This is used to hand the flag to C callbacks, which is fine so long as
+	/// the token outlives the encode.
+	pub(crate) fn as_ptr(&self) -> *const AtomicBool { Arc::as_ptr(&self.0) }
+}
+
+
+
+/// # Watchdog.
+///
+/// This enforces a per-candidate time limit by cancelling a fresh token —
+/// handed to the encoder in place of the search-wide one — once time is up,
+/// or as soon as the parent is cancelled, whichever comes first.
+///
+/// The thread exits when the watchdog is dropped.
+pub(crate) struct Watchdog {
+	/// # Candidate Token.
+	token: CancelToken,
+
+	/// # Start Time.
+	start: Instant,
+
+	/// # Time Limit.
+	limit: Duration,
+
+	/// # Stop Signal.
+	///
+	/// Dropping this disconnects the channel, waking the thread.
+	stop: Option<Sender<()>>,
+
+	/// # Thread.
+	handle: Option<JoinHandle<()>>,
+}
+
+impl Drop for Watchdog {
+	fn drop(&mut self) {
+		drop(self.stop.take());
+		if let Some(handle) = self.handle.take() { let _res = handle.join(); }
+	}
+}
+
+impl Watchdog {
+	/// # Start.
+	pub(crate) fn start(limit: Duration, parent: Option<CancelToken>) -> Self {
+		let token = CancelToken::default();
+		let start = Instant::now();
+		let (stop, rx) = mpsc::channel::<()>();
+
+		let token2 = token.clone();
+		let handle = std::thread::spawn(move || loop {
+			let left = limit.saturating_sub(start.elapsed());
+			if left.is_zero() || parent.as_ref().is_some_and(CancelToken::is_cancelled) {
+				token2.cancel();
+				break;
+			}
+
+			if ! matches!(rx.recv_timeout(POLL.min(left)), Err(RecvTimeoutError::Timeout)) {
+				break;
+			}
+		});
+
+		Self { token, start, limit, stop: Some(stop), handle: Some(handle) }
+	}
+
+	#[inline]
+	/// # Expired?
+	///
+	/// Returns true if the limit has been reached, whether or not the encoder
+	/// was able to stop early because of it.
+	pub(crate) fn expired(&self) -> bool { self.limit <= self.start.elapsed() }
+
+	#[inline]
+	/// # Token.
+	pub(crate) const fn token(&self) -> &CancelToken { &self.token }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn t_watchdog() {
+		// Time runs out.
+		let dog = Watchdog::start(Duration::from_millis(20), None);
+		std::thread::sleep(Duration::from_millis(200));
+		assert!(dog.expired());
+		assert!(dog.token().is_cancelled());
+
+		// The parent is cancelled first.
+		let parent = CancelToken::default();
+		let dog = Watchdog::start(Duration::from_mins(1), Some(parent.clone()));
+		parent.cancel();
+		std::thread::sleep(Duration::from_millis(200));
+		assert!(! dog.expired());
+		assert!(dog.token().is_cancelled());
+
+		// Neither.
+		let dog = Watchdog::start(Duration::from_mins(1), None);
+		assert!(! dog.expired());
+		assert!(! dog.token().is_cancelled());
+	}
+}
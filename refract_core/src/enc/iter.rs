@@ -3,19 +3,26 @@
 */
 
 use crate::{
+	AvifCodec,
+	CancelToken,
 	FLAG_AVIF_RGB,
-	FLAG_AVIF_ROUND_2,
+	FLAG_AVIF_YUV420,
+	FLAG_JXL_PROGRESSIVE,
+	FLAG_LOSSLESS_LAST,
 	FLAG_NO_AVIF_YCBCR,
 	FLAG_NO_LOSSLESS,
 	FLAG_NO_LOSSY,
-	FLAG_DID_LOSSLESS,
 	ImageKind,
 	Input,
+	is_photographic,
+	JxlTuning,
 	Output,
 	PUBLIC_FLAGS,
 	Quality,
 	QualityRange,
 	RefractError,
+	WebpTuning,
+	enc::cancel::Watchdog,
 };
 use std::{
 	num::{
@@ -51,6 +58,10 @@ use std::{
 /// Once iteration has finished, the computation time can be collected via
 /// [`EncodeIter::time`] if you're interested, otherwise the instance can be
 /// consumed, returning the "best" [`Output`] by calling [`EncodeIter::take`].
+///
+/// Alternatively, a byte budget can be set with [`EncodeIter::set_max_size`],
+/// in which case the iterator judges each candidate itself, homing in on the
+/// best quality that fits.
 pub struct EncodeIter<'a> {
 	/// # Source.
 	src: Input<'a>,
@@ -64,11 +75,70 @@ pub struct EncodeIter<'a> {
 	/// # Quality Stepper.
 	steps: QualityRange,
 
+	/// # Quality Bounds.
+	///
+	/// The (raw) lossy qualities open to the search, normally the encoder's
+	/// full range. See [`EncodeIter::set_bounds`].
+	bounds: (NonZeroU8, NonZeroU8),
+
 	/// # Processing Time.
 	time: Duration,
 
+	/// # Processing Time (CPU).
+	cpu_time: Duration,
+
 	/// # Flags.
 	flags: u8,
+
+	/// # Lossless Threshold.
+	lossless_above: Option<NonZeroUsize>,
+
+	/// # Size Budget.
+	max_size: Option<NonZeroUsize>,
+
+	/// # Lossy Candidate Cap.
+	max_steps: Option<NonZeroU8>,
+
+	/// # Lossy Candidates So Far.
+	taken: u8,
+
+	/// # `AVIF` Codec.
+	avif: AvifCodec,
+
+	/// # `JPEG XL` Tuning.
+	jxl: JxlTuning,
+
+	/// # `WebP` Tuning.
+	webp: WebpTuning,
+
+	/// # Cancellation Token.
+	cancel: Option<CancelToken>,
+
+	/// # Per-Candidate Time Limit.
+	time_limit: Option<Duration>,
+
+	/// # Tried Lossless?
+	did_lossless: bool,
+
+	/// # `AVIF` Round Two?
+	///
+	/// The second `AVIF` round retries all quality ranges using limited-range
+	/// `YCbCr` color compression.
+	avif_round_2: bool,
+
+	/// # `JPEG XL` Streaming?
+	///
+	/// See [`EncodeIter::set_streaming_above`].
+	jxl_streaming: bool,
+
+	/// # Last Error.
+	error: Option<RefractError>,
+
+	/// # Undo Snapshot.
+	///
+	/// The range, flags, `AVIF` round, candidate count, best, and candidate
+	/// from before the most recent decision.
+	history: Option<(QualityRange, u8, bool, u8, Output, Output)>,
 }
 
 /// ## Instantiation.
@@ -94,14 +164,26 @@ impl<'a> EncodeIter<'a> {
 		flags &= PUBLIC_FLAGS;
 		if kind == ImageKind::Avif { flags |= FLAG_AVIF_RGB;  }
 		else {
-			// This only applies to AVIF.
-			flags &= ! FLAG_NO_AVIF_YCBCR;
+			// These only apply to AVIF.
+			flags &= ! (FLAG_NO_AVIF_YCBCR | FLAG_AVIF_YUV420);
+		}
+
+		// And this only applies to JPEG XL.
+		if kind != ImageKind::Jxl { flags &= ! FLAG_JXL_PROGRESSIVE; }
+
+		// PNG (and pure Rust WebP) is lossless or nothing, and JPEG is the
+		// opposite.
+		if ! kind.can_encode_lossy() {
+			flags = (flags | FLAG_NO_LOSSY) & ! (FLAG_NO_LOSSLESS | FLAG_LOSSLESS_LAST);
+		}
+		else if kind == ImageKind::Jpeg {
+			flags = (flags | FLAG_NO_LOSSLESS) & ! (FLAG_NO_LOSSY | FLAG_LOSSLESS_LAST);
 		}
 
 		Ok(Self {
 			src: match kind {
-				// JPEG XL takes a compacted buffer.
-				ImageKind::Jxl => src.as_native(),
+				// JPEG and JPEG XL take a compacted buffer.
+				ImageKind::Jpeg | ImageKind::Jxl => src.as_native(),
 				// Everybody else works from full RGBA.
 				_ => src.as_rgba(),
 			},
@@ -109,12 +191,291 @@ impl<'a> EncodeIter<'a> {
 			candidate: Output::new(kind),
 
 			steps: QualityRange::from(kind),
+			bounds: (kind.min_encoder_quality(), kind.max_encoder_quality()),
 			time: Duration::from_secs(0),
+			cpu_time: Duration::from_secs(0),
 			flags,
+			lossless_above: None,
+			max_size: None,
+			max_steps: None,
+			taken: 0,
+			avif: AvifCodec::Aom,
+			jxl: JxlTuning::DEFAULT,
+			webp: WebpTuning::DEFAULT,
+			cancel: None,
+			time_limit: None,
+			did_lossless: false,
+			avif_round_2: false,
+			jxl_streaming: false,
+			error: None,
+			history: None,
 		})
 	}
 }
 
+/// ## One-Shot Encoding.
+impl EncodeIter<'_> {
+	/// # Estimate.
+	///
+	/// Encode the source exactly once, lossily, at a fixed, middle-of-the-road
+	/// quality — roughly `WebP` 75, `AVIF` quantizer 30, or `JPEG XL`
+	/// distance 2.0 — returning the result without any feedback. (`PNG` —
+	/// and `WebP` with the pure Rust encoder — is simply recompressed
+	/// losslessly.)
+	///
+	/// This is much faster than a full guided run, and while the result
+	/// won't necessarily be acceptable, its size gives a reasonable idea of
+	/// how much a source stands to gain from one.
+	///
+	/// ## Errors
+	///
+	/// This will return an error if the output format does not support
+	/// encoding, if encoding fails, or if the result is no smaller than the
+	/// source.
+	pub fn estimate(src: &Input, kind: ImageKind, flags: u8) -> Result<Output, RefractError> {
+		/// # AVIF Quality (Quantizer 30).
+		const Q_AVIF: NonZeroU8 = NonZeroU8::new(33).unwrap();
+
+		/// # JPEG XL Quality (Distance 2.0).
+		const Q_JXL: NonZeroU8 = NonZeroU8::new(130).unwrap();
+
+		/// # WebP (and JPEG) Quality.
+		const Q_WEBP: NonZeroU8 = NonZeroU8::new(75).unwrap();
+
+		let mut guide = EncodeIter::new(src, kind, flags)?;
+		let quality = match kind {
+			// PNG only has the one setting.
+			_ if ! kind.can_encode_lossy() => {
+				guide.lossless(guide.flags)?;
+				guide.keep_candidate();
+				return guide.take();
+			},
+			ImageKind::Avif => Q_AVIF,
+			ImageKind::Jxl => Q_JXL,
+			ImageKind::Jpeg | ImageKind::Png | ImageKind::Webp => Q_WEBP,
+		};
+		guide.lossy(quality, guide.flags)?;
+		guide.keep_candidate();
+		guide.take()
+	}
+
+	/// # Encode Once.
+	///
+	/// Encode the source exactly once at the given (raw) quality — clamped
+	/// to the encoder's range — or losslessly if `None`, returning the result
+	/// without any feedback. This is meant for unattended use, like piping,
+	/// where the quality is decided up front. (`PNG` is always lossless, as
+	/// is `WebP` with the pure Rust encoder.)
+	///
+	/// Unlike [`EncodeIter::estimate`], any tuning set on the iterator
+	/// beforehand applies.
+	///
+	/// ## Errors
+	///
+	/// This will return an error if encoding fails, or if the result is no
+	/// smaller than the source.
+	pub fn once(mut self, quality: Option<NonZeroU8>) -> Result<Output, RefractError> {
+		let kind = self.output_kind();
+		match quality {
+			Some(q) if kind.can_encode_lossy() => {
+				self.lossy(q.min(kind.max_encoder_quality()), self.flags)?;
+			},
+			_ => { self.lossless(self.flags)?; },
+		}
+		self.keep_candidate();
+		self.take()
+	}
+}
+
+/// ## Setters.
+impl EncodeIter<'_> {
+	/// # Set First Quality.
+	///
+	/// Start the lossy search at this (raw) quality instead of the midpoint,
+	/// typically per a [`Predictor`](crate::Predictor). Values outside the
+	/// encoder's range are ignored.
+	///
+	/// This only affects the first lossy probe; the search narrows normally
+	/// from there.
+	pub const fn set_first(&mut self, quality: NonZeroU8) {
+		self.steps.set_first(quality);
+	}
+
+	/// # Set Quality Bounds.
+	///
+	/// Narrow the lossy search to qualities between `bottom` and `top` (raw,
+	/// inclusive), for when the right neighborhood is already known. Both
+	/// ends are clamped to what is still in the running, and a range of one
+	/// pins the quality outright.
+	///
+	/// As with [`EncodeIter::set_first`], this may be called mid-search.
+	pub fn set_bounds(&mut self, bottom: NonZeroU8, top: NonZeroU8) {
+		let (bottom, top) = if bottom <= top { (bottom, top) } else { (top, bottom) };
+		self.steps.set_bottom(bottom);
+		self.steps.set_top(top);
+		self.bounds = (self.steps.bottom(), self.steps.top());
+	}
+
+	/// # Match Color Mode.
+	///
+	/// Carry over the `AVIF` color mode — full-range `RGB` or limited-range
+	/// `YCbCr` — of an earlier result, so that re-encoding at its quality,
+	/// e.g. via [`EncodeIter::once`], comes out the same way.
+	///
+	/// This has no effect for other formats.
+	pub fn match_mode(&mut self, out: &Output) {
+		if self.output_kind() == ImageKind::Avif && out.kind() == ImageKind::Avif {
+			self.flags = (self.flags & ! FLAG_AVIF_RGB) | (out.flags() & FLAG_AVIF_RGB);
+		}
+	}
+
+	/// # Set Lossless Threshold.
+	///
+	/// Run lossless encoding last — as with [`FLAG_LOSSLESS_LAST`] — and
+	/// only if the lossy winner, if any, is larger than `size` bytes.
+	///
+	/// Small lossy results are rarely beaten, so this avoids a long encode
+	/// that would almost certainly be thrown away.
+	pub const fn set_lossless_above(&mut self, size: NonZeroUsize) {
+		self.flags |= FLAG_LOSSLESS_LAST;
+		self.lossless_above = Some(size);
+	}
+
+	/// # Skip Lossless for Photos.
+	///
+	/// Disable lossless encoding — as with [`FLAG_NO_LOSSLESS`] — if the
+	/// source is a JPEG, or otherwise looks photographic (see
+	/// [`is_photographic`]), returning `true` if so.
+	///
+	/// Lossless encodes of photos are almost never competitive, and usually
+	/// take the longest, so this can save a lot of time for little risk. It
+	/// has no effect if lossy encoding is disabled or unsupported.
+	pub fn skip_lossless_if_photo(&mut self) -> bool {
+		if
+			0 == self.flags & (FLAG_NO_LOSSY | FLAG_NO_LOSSLESS) &&
+			is_photographic(&self.src)
+		{
+			self.flags |= FLAG_NO_LOSSLESS;
+			true
+		}
+		else { false }
+	}
+
+	/// # Set Size Budget.
+	///
+	/// Search for the best quality whose output is no larger than `size`
+	/// bytes, rather than the smallest output a human is happy with.
+	///
+	/// In this mode, [`EncodeIter::advance`] needs no feedback: every
+	/// candidate it returns already fits the budget, so each is kept
+	/// automatically and the next probe aims higher. (Calls to
+	/// [`EncodeIter::keep`] and [`EncodeIter::discard`] are ignored.) A
+	/// lossless result that fits beats everything, ending the lossy search.
+	///
+	/// ```no_run
+	/// use refract_core::{EncodeIter, ImageKind, Input};
+	/// use std::num::NonZeroUsize;
+	///
+	/// let raw = std::fs::read("image.png").unwrap();
+	/// let src = Input::try_from(raw.as_slice()).unwrap();
+	/// let mut guide = EncodeIter::new(&src, ImageKind::Webp, 0).unwrap();
+	/// guide.set_max_size(NonZeroUsize::new(200 * 1024).unwrap());
+	/// while guide.advance().is_some() {}
+	/// let best = guide.take();
+	/// ```
+	pub const fn set_max_size(&mut self, size: NonZeroUsize) {
+		self.max_size = Some(size);
+	}
+
+	/// # Set Candidate Cap.
+	///
+	/// Stop the lossy search after `steps` candidates — across both `AVIF`
+	/// rounds, if applicable — and settle for the best found by then, to
+	/// bound the time spent on any one image. Undone decisions give their
+	/// steps back.
+	///
+	/// This applies equally to size budgets.
+	pub const fn set_max_steps(&mut self, steps: NonZeroU8) {
+		self.max_steps = Some(steps);
+	}
+
+	/// # Set `AVIF` Codec.
+	///
+	/// Use a different AV1 codec, e.g. one that trades a few bytes for a much
+	/// faster run. See [`AvifCodec`] for details. This has no effect on other
+	/// formats.
+	pub const fn set_avif_codec(&mut self, codec: AvifCodec) {
+		self.avif = codec;
+	}
+
+	/// # Set `JPEG XL` Tuning.
+	///
+	/// Override the `JPEG XL` encoder's effort and decoding speed, e.g. to
+	/// trade a few bytes for a much faster run. This has no effect on other
+	/// formats.
+	pub const fn set_jxl_tuning(&mut self, tuning: JxlTuning) {
+		self.jxl = tuning;
+	}
+
+	/// # Set `WebP` Tuning.
+	///
+	/// Override the `WebP` encoder's method and sharp YUV settings. This has
+	/// no effect on other formats.
+	pub const fn set_webp_tuning(&mut self, tuning: WebpTuning) {
+		self.webp = tuning;
+	}
+
+	/// # Set Cancellation Token.
+	///
+	/// Let the search be cut short from another thread; see [`CancelToken`]
+	/// for details. Once cancelled, [`EncodeIter::advance`] returns `None`,
+	/// [`EncodeIter::error`] returns [`RefractError::Cancelled`], and the
+	/// best found up to that point, if any, can be taken as usual.
+	pub fn set_cancel_token(&mut self, token: CancelToken) {
+		self.cancel = Some(token);
+	}
+
+	/// # Set Time Limit.
+	///
+	/// Abandon any candidate that takes longer than `limit` to encode, so one
+	/// pathological image can't hold up an unattended batch indefinitely.
+	///
+	/// Only `WebP` (and streamed `JPEG XL`; see [`CancelToken`]) can be
+	/// stopped mid-encode. For everything else the limit is post-hoc: the
+	/// encode runs to completion, however long that takes, and only then is
+	/// the result thrown out. Either way, the search carries on without it —
+	/// lossy from the next lower quality — and [`EncodeIter::error`] returns
+	/// [`RefractError::TimedOut`].
+	///
+	/// The watchdog needs a thread, so this is ignored under the `wasm`
+	/// feature.
+	pub const fn set_time_limit(&mut self, limit: Duration) {
+		self.time_limit = Some(limit);
+	}
+
+	/// # Set Streaming Threshold.
+	///
+	/// Losslessly encode `JPEG XL` images with more than `pixels` pixels in
+	/// streaming mode, working through them a group at a time rather than all
+	/// at once. This bounds the encoder's peak memory usage — otherwise many
+	/// times the size of the image itself — which can make all the difference
+	/// for extremely tall or wide sources, at a small cost to compression.
+	///
+	/// This has no effect on lossy or progressive (see
+	/// [`FLAG_JXL_PROGRESSIVE`]) encodes, which libjxl cannot stream, or on
+	/// other formats. Decoding, likewise, always happens all at once; this
+	/// bounds the encoder's memory, not the source's.
+	pub const fn set_streaming_above(&mut self, pixels: NonZeroUsize) {
+		if
+			matches!(self.output_kind(), ImageKind::Jxl) &&
+			0 == self.flags & FLAG_JXL_PROGRESSIVE &&
+			pixels.get() < self.src.width().saturating_mul(self.src.height())
+		{
+			self.jxl_streaming = true;
+		}
+	}
+}
+
 /// ## Getters.
 impl EncodeIter<'_> {
 	#[inline]
@@ -127,6 +488,65 @@ impl EncodeIter<'_> {
 		else { None }
 	}
 
+	#[inline]
+	#[must_use]
+	/// # Last Error.
+	///
+	/// This returns the most recent encoder error, if any. Candidates that
+	/// were merely too big to be worth considering do not count.
+	///
+	/// If iteration finishes without producing anything, this can be used to
+	/// tell the difference between an image that simply couldn't be made
+	/// smaller and one the encoder choked on.
+	pub const fn error(&self) -> Option<RefractError> { self.error }
+
+	#[inline]
+	#[must_use]
+	/// # Can Undo?
+	///
+	/// Returns `true` if there is a decision for [`EncodeIter::undo`] to
+	/// take back.
+	pub const fn can_undo(&self) -> bool { self.history.is_some() }
+
+	#[inline]
+	#[must_use]
+	/// # Quality Bounds.
+	///
+	/// This returns the lowest and highest (raw) qualities still in the
+	/// running, i.e. what is left of the search after all the keeping and
+	/// discarding so far.
+	pub const fn bounds(&self) -> (NonZeroU8, NonZeroU8) {
+		(self.steps.bottom(), self.steps.top())
+	}
+
+	#[must_use]
+	/// # Progress.
+	///
+	/// Return the number of lossy candidates produced so far, along with
+	/// the most that could yet follow — a worst case, as each decision
+	/// usually rules out more than it has to — taking any second `AVIF`
+	/// round and [`EncodeIter::set_max_steps`] cap into account.
+	pub fn progress(&self) -> (u8, u8) {
+		if 0 != self.flags & FLAG_NO_LOSSY { return (self.taken, 0); }
+
+		let mut left = self.steps.remaining_max();
+
+		// AVIF's limited-range round starts over.
+		if
+			self.output_kind() == ImageKind::Avif &&
+			! self.avif_round_2 &&
+			0 == self.flags & FLAG_NO_AVIF_YCBCR
+		{
+			left = left.saturating_add(QualityRange::new(self.bounds.0, self.bounds.1).remaining_max());
+		}
+
+		if let Some(max) = self.max_steps {
+			left = left.min(max.get().saturating_sub(self.taken));
+		}
+
+		(self.taken, left)
+	}
+
 	#[inline]
 	#[must_use]
 	/// # Input Kind.
@@ -174,7 +594,14 @@ impl EncodeIter<'_> {
 	/// This returns the smaller of the input size and best size. Any time a
 	/// new candidate is created, it must be smaller than these two or we'll
 	/// just chuck it in the garbage.
+	///
+	/// With a size budget, the budget takes the place of the best size;
+	/// higher qualities are bigger, so the best is no bar at all.
 	fn target_size(&self) -> usize {
+		if let Some(max) = self.max_size {
+			return max.get().saturating_add(1).min(self.input_size());
+		}
+
 		self.output_size()
 			.map_or_else(|| self.input_size(), |s| s.get().min(self.input_size()))
 	}
@@ -188,6 +615,20 @@ impl EncodeIter<'_> {
 	///
 	/// It makes for interesting data…
 	pub const fn time(&self) -> Duration { self.time }
+
+	#[inline]
+	#[must_use]
+	/// # Computation Time (CPU).
+	///
+	/// This method returns the total amount of CPU time — across all threads
+	/// of the process — spent encoding the image. Encoders that farm work out
+	/// to multiple threads will rack up more of this than [`EncodeIter::time`].
+	///
+	/// Because it is process-wide, the figure is only meaningful when nothing
+	/// else in the process is busy at the same time, e.g. other searches.
+	///
+	/// This is only tracked on Linux; elsewhere it will always be zero.
+	pub const fn cpu_time(&self) -> Duration { self.cpu_time }
 }
 
 /// ## Encoding.
@@ -203,10 +644,8 @@ impl EncodeIter<'_> {
 	/// file offers no savings over the original.
 	fn lossless(&mut self, flags: u8) -> Result<(), RefractError> {
 		self.set_candidate_quality(None);
-
-		let kind = self.output_kind();
-		kind.encode_lossless(&self.src, &mut self.candidate, flags)?;
-
+		self.maybe_cancel()?;
+		self.encode_candidate(None, flags)?;
 		self.finish_candidate()
 	}
 
@@ -220,11 +659,39 @@ impl EncodeIter<'_> {
 	/// if the resulting file offers no savings over the current best.
 	fn lossy(&mut self, quality: NonZeroU8, flags: u8) -> Result<(), RefractError> {
 		self.set_candidate_quality(Some(quality));
+		self.maybe_cancel()?;
+		self.encode_candidate(Some(quality), flags)?;
+		self.finish_candidate()
+	}
+
+	/// # Encode Candidate.
+	///
+	/// Run the actual encoder — lossy if there's a quality — under the time
+	/// limit, if any.
+	///
+	/// ## Errors
+	///
+	/// Returns an error if the encoder fails, is cancelled, or runs out of
+	/// time.
+	fn encode_candidate(&mut self, quality: Option<NonZeroU8>, flags: u8)
+	-> Result<(), RefractError> {
+		let watchdog =
+			if cfg!(feature = "wasm") { None }
+			else { self.time_limit.map(|limit| Watchdog::start(limit, self.cancel.clone())) };
+		if let Some(w) = watchdog.as_ref() { self.candidate.set_cancel_token(Some(w.token().clone())); }
 
 		let kind = self.output_kind();
-		kind.encode_lossy(&self.src, &mut self.candidate, quality, flags)?;
+		crate::error::clear_upstream();
+		let res = match quality {
+			Some(q) => kind.encode_lossy(&self.src, &mut self.candidate, q, flags),
+			None => kind.encode_lossless(&self.src, &mut self.candidate, flags),
+		};
 
-		self.finish_candidate()
+		// Cancellation trumps everything else.
+		let expired = watchdog.is_some_and(|w| w.expired());
+		self.maybe_cancel()?;
+		if expired { Err(RefractError::TimedOut) }
+		else { res }
 	}
 }
 
@@ -240,20 +707,63 @@ impl EncodeIter<'_> {
 	/// runs. See [`EncodeIter::discard`] and [`EncodeIter::keep`] for more
 	/// information.
 	pub fn advance(&mut self) -> Option<&Output> {
-		// Start a timer.
-		let now = Instant::now();
+		// Start the timers. (The wasm32 clock just panics.)
+		let now = (! cfg!(feature = "wasm")).then(Instant::now);
+		let cpu = process_cpu_time();
 
 		// Handle the actual next business.
 		let res = self.next_inner().or_else(|| self.next_avif());
+		if res.is_none() { self.next_lossless(); }
 
 		// Record the time spent.
-		self.time += now.elapsed();
+		if let Some(now) = now { self.time += now.elapsed(); }
+		if let (Some(before), Some(after)) = (cpu, process_cpu_time()) {
+			self.cpu_time += after.saturating_sub(before);
+		}
 
 		// Return the result!
-		if res.is_some() { self.candidate() }
+		if res.is_some() {
+			// With a budget, anything that made it this far fits, so keep it
+			// and see if a higher quality might too.
+			if self.max_size.is_some() {
+				self.steps.set_bottom(self.candidate.quality().raw());
+				self.keep_candidate();
+			}
+			self.candidate()
+		}
 		else { None }
 	}
 
+	#[cfg(feature = "decode_ng")]
+	/// # Crunch the Next Quality (Automatically)!
+	///
+	/// This works like [`EncodeIter::advance`], except instead of waiting for
+	/// human feedback, each candidate is scored against the source using
+	/// [`similarity`](crate::similarity) and automatically kept if its score is at or
+	/// below `max_dssim`, or discarded otherwise.
+	///
+	/// The judged candidate is returned for reference, or `None` once
+	/// iteration has finished, at which point the best can be collected as
+	/// usual via [`EncodeIter::take`].
+	///
+	/// ```no_run
+	/// use refract_core::{EncodeIter, ImageKind, Input};
+	///
+	/// let raw = std::fs::read("image.png").unwrap();
+	/// let src = Input::try_from(raw.as_slice()).unwrap();
+	/// let mut guide = EncodeIter::new(&src, ImageKind::Webp, 0).unwrap();
+	/// while guide.advance_auto(0.001).is_some() {}
+	/// let best = guide.take();
+	/// ```
+	pub fn advance_auto(&mut self, max_dssim: f64) -> Option<&Output> {
+		self.advance()?;
+		if crate::similarity(&self.src, &self.candidate).is_some_and(|d| d <= max_dssim) {
+			self.keep();
+		}
+		else { self.discard(); }
+		self.candidate()
+	}
+
 	#[inline]
 	/// # Discard Candidate.
 	///
@@ -262,8 +772,13 @@ impl EncodeIter<'_> {
 	///
 	/// This will in turn raise the floor of the range so that the next
 	/// iteration will test a higher quality.
+	///
+	/// This does nothing when a size budget is set.
 	pub fn discard(&mut self) {
-		self.steps.set_bottom(self.candidate.quality().raw());
+		if self.max_size.is_none() {
+			self.push_history();
+			self.steps.set_bottom(self.candidate.quality().raw());
+		}
 	}
 
 	/// # Keep Candidate.
@@ -272,9 +787,52 @@ impl EncodeIter<'_> {
 	///
 	/// This will lower the ceiling of the range so that the next iteration
 	/// will test a lower quality.
+	///
+	/// This does nothing when a size budget is set.
 	pub fn keep(&mut self) {
-		self.steps.set_top(self.candidate.quality().raw());
-		self.keep_candidate();
+		if self.max_size.is_none() {
+			self.push_history();
+			self.steps.set_top(self.candidate.quality().raw());
+			self.keep_candidate();
+		}
+	}
+
+	/// # Undo.
+	///
+	/// Rewind the most recent [`EncodeIter::keep`] or [`EncodeIter::discard`],
+	/// restoring the quality range and best from before it was made, along
+	/// with the candidate it was made about, so it can be judged again. The
+	/// candidate produced since, if any, is thrown away.
+	///
+	/// Only the one decision can be taken back: each snapshot holds two
+	/// encoded images, which would add up quickly for huge sources. Returns
+	/// `false` if there was nothing to undo.
+	pub fn undo(&mut self) -> bool {
+		let Some((steps, flags, avif_round_2, taken, best, candidate)) = self.history.take()
+		else { return false; };
+
+		self.steps = steps;
+		self.flags = flags;
+		self.avif_round_2 = avif_round_2;
+		self.taken = taken;
+		self.best = best;
+		self.candidate = candidate;
+		true
+	}
+
+	/// # Push History.
+	///
+	/// Save the state ahead of a decision, so it can be undone, replacing the
+	/// snapshot of the decision before it.
+	fn push_history(&mut self) {
+		self.history = Some((
+			self.steps.clone(),
+			self.flags,
+			self.avif_round_2,
+			self.taken,
+			self.best.clone(),
+			self.candidate.clone(),
+		));
 	}
 
 	#[inline]
@@ -290,11 +848,24 @@ impl EncodeIter<'_> {
 	///
 	/// This internal method does the actual keeping.
 	fn keep_candidate(&mut self) {
-		if self.candidate.is_valid() {
+		if self.candidate.is_valid() && self.is_upgrade() {
 			self.candidate.copy_to(&mut self.best);
 		}
 	}
 
+	/// # Is Upgrade?
+	///
+	/// Candidates only exist if they're smaller than the best, so are always
+	/// an upgrade, but with a size budget, they need a higher quality
+	/// instead, as might not be the case for the second `AVIF` round.
+	fn is_upgrade(&self) -> bool {
+		match self.max_size {
+			Some(_) if self.best.is_valid() =>
+				self.best.quality().raw() < self.candidate.quality().raw(),
+			_ => true,
+		}
+	}
+
 	/// # Next AVIF Round.
 	///
 	/// `AVIF` is complicated, even by next-gen image standards. Haha. Unlike
@@ -307,14 +878,14 @@ impl EncodeIter<'_> {
 		// The second round is a partial reboot, retrying encoding with
 		// limited YCbCr range. If we haven't done that yet, let's do it
 		// now!
-		if 0 == self.flags & FLAG_AVIF_ROUND_2 {
-			self.flags |= FLAG_AVIF_ROUND_2;
+		if ! self.avif_round_2 {
+			self.avif_round_2 = true;
 
 			// Reset the range and remove the RGB flag so that it can
 			// start again (using the existing best as the size cap) in
 			// limited-range mode.
 			if 0 == self.flags & FLAG_NO_AVIF_YCBCR {
-				self.steps.reboot(kind.min_encoder_quality(), kind.max_encoder_quality());
+				self.steps.reboot(self.bounds.0, self.bounds.1);
 				self.flags &= ! FLAG_AVIF_RGB;
 
 				// Recurse to pull the next result. If there isn't one, we're
@@ -326,6 +897,50 @@ impl EncodeIter<'_> {
 		None
 	}
 
+	/// # Next Lossless.
+	///
+	/// Run the lossless encoding pass, unless it has already been run or is
+	/// disabled. This happens before lossy by default, or after everything
+	/// else with [`FLAG_LOSSLESS_LAST`].
+	///
+	/// Lossless results are never shown for review; if smaller than the
+	/// current best, they simply become it.
+	fn next_lossless(&mut self) {
+		if self.did_lossless || 0 != self.flags & FLAG_NO_LOSSLESS { return; }
+		self.did_lossless = true;
+
+		let mut flags = self.flags;
+		if 0 == flags & FLAG_LOSSLESS_LAST {
+			// Lossy's top quality won't beat lossless, so needn't be tested
+			// (unless it was asked for specifically).
+			let top = self.steps.top();
+			if top == self.output_kind().max_encoder_quality() && self.bounds.0 < top {
+				self.steps.ignore(top);
+			}
+		}
+		else {
+			// Skip it if the lossy winner is already small enough.
+			if self.lossless_above.zip(self.output_size()).is_some_and(|(max, size)| size <= max) {
+				return;
+			}
+
+			// AVIF may have switched to YCbCr for its second round, but
+			// lossless is always RGB.
+			if self.output_kind() == ImageKind::Avif { flags |= FLAG_AVIF_RGB; }
+		}
+
+		match self.lossless(flags) {
+			Ok(()) => {
+				self.keep_candidate();
+
+				// Nothing lossy can beat a lossless result that fits.
+				if self.max_size.is_some() { self.flags |= FLAG_NO_LOSSY; }
+			},
+			Err(RefractError::TooBig) => {},
+			Err(e) => { self.error.replace(e); },
+		}
+	}
+
 	#[inline]
 	/// # (True) Next.
 	///
@@ -334,28 +949,35 @@ impl EncodeIter<'_> {
 	/// time.
 	fn next_inner(&mut self) -> Option<()> {
 		// Before we try lossy, we might lossless to do.
-		if 0 == self.flags & FLAG_DID_LOSSLESS {
-			self.flags |= FLAG_DID_LOSSLESS;
-			if 0 == self.flags & FLAG_NO_LOSSLESS {
-				self.steps.ignore(self.steps.top());
-				if self.lossless(self.flags).is_ok() {
-					self.keep_candidate();
-				}
-			}
-		}
+		if 0 == self.flags & FLAG_LOSSLESS_LAST { self.next_lossless(); }
 
-		// Okay, now lossy.
-		if 0 == self.flags & FLAG_NO_LOSSY {
+		// Okay, now lossy, unless we've hit the cap.
+		if
+			0 == self.flags & FLAG_NO_LOSSY &&
+			self.max_steps.is_none_or(|m| self.taken < m.get())
+		{
 			let quality = self.steps.next()?;
 			match self.lossy(quality, self.flags) {
-				Ok(()) => Some(()),
+				Ok(()) => {
+					self.taken = self.taken.saturating_add(1);
+					Some(())
+				},
 				Err(RefractError::TooBig) => {
 					// This was too big, so drop a step and see if the
 					// next-next quality works out.
 					self.steps.set_top_minus_one(quality);
 					self.next_inner()
 				},
-				Err(_) => None,
+				Err(RefractError::TimedOut) => {
+					// Higher qualities are unlikely to be any quicker.
+					self.error.replace(RefractError::TimedOut);
+					self.steps.set_top_minus_one(quality);
+					self.next_inner()
+				},
+				Err(e) => {
+					self.error.replace(e);
+					None
+				},
 			}
 		}
 		else { None }
@@ -371,5 +993,56 @@ impl EncodeIter<'_> {
 			Quality::new(self.output_kind(), quality),
 			self.flags,
 		);
+		self.candidate.set_avif_codec(self.avif);
+		self.candidate.set_jxl_tuning(self.jxl, self.jxl_streaming);
+		self.candidate.set_webp_tuning(self.webp);
+		self.candidate.set_cancel_token(self.cancel.clone());
+	}
+
+	#[inline]
+	/// # Maybe Cancel.
+	///
+	/// Return an error if the search has been cancelled.
+	fn maybe_cancel(&self) -> Result<(), RefractError> {
+		if self.cancel.as_ref().is_some_and(CancelToken::is_cancelled) {
+			Err(RefractError::Cancelled)
+		}
+		else { Ok(()) }
 	}
 }
+
+
+
+#[cfg(target_os = "linux")]
+#[expect(unsafe_code, reason = "Needed for FFI.")]
+/// # Process CPU Time.
+///
+/// Return the total user and system time consumed by the process — all
+/// threads, living or dead — so far, courtesy of `/proc/self/stat`.
+///
+/// This has to be process-wide to catch the encoders' own worker threads,
+/// which also means anything else running alongside gets counted too.
+fn process_cpu_time() -> Option<Duration> {
+	let raw = std::fs::read_to_string("/proc/self/stat").ok()?;
+
+	// The process name can contain anything, so skip past it before
+	// splitting. The state is the first field after that, making utime and
+	// stime the 12th and 13th.
+	let (_, rest) = raw.rsplit_once(')')?;
+	let mut fields = rest.split_ascii_whitespace().skip(11);
+	let utime: u64 = fields.next()?.parse().ok()?;
+	let stime: u64 = fields.next()?.parse().ok()?;
+
+	// The kernel reports these in clock ticks.
+	// Safety: this is an FFI call…
+	let tick = u64::try_from(unsafe { libc::sysconf(libc::_SC_CLK_TCK) }).ok()
+		.filter(|&t| t != 0)?;
+	let ticks = utime.checked_add(stime)?;
+	Some(Duration::from_nanos(ticks.checked_mul(1_000_000_000)? / tick))
+}
+
+#[cfg(not(target_os = "linux"))]
+/// # Process CPU Time.
+///
+/// This is only supported on Linux.
+const fn process_cpu_time() -> Option<Duration> { None }
@@ -2,7 +2,12 @@
 # `Refract` - Encoding!
 */
 
+pub(super) mod cancel;
 pub(super) mod iter;
 pub(super) mod output;
+pub(super) mod predict;
 pub(super) mod quality;
 pub(super) mod range;
+
+#[cfg(feature = "jxl")]
+pub(super) mod sink;
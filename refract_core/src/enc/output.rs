@@ -3,12 +3,17 @@
 */
 
 use crate::{
-	FLAG_VALID,
+	AvifCodec,
+	CancelToken,
 	ImageKind,
+	Input,
+	JxlTuning,
 	Quality,
 	RefractError,
+	WebpTuning,
 };
 use std::{
+	borrow::Cow,
 	num::NonZeroUsize,
 	ops::Deref,
 };
@@ -36,6 +41,28 @@ pub struct Output {
 
 	/// # Flags.
 	flags: u8,
+
+	/// # `AVIF` Codec.
+	avif: AvifCodec,
+
+	/// # `JPEG XL` Tuning.
+	jxl: JxlTuning,
+
+	/// # `JPEG XL` Streaming?
+	///
+	/// See [`EncodeIter::set_streaming_above`].
+	jxl_streaming: bool,
+
+	/// # `WebP` Tuning.
+	webp: WebpTuning,
+
+	/// # Cancellation Token.
+	cancel: Option<CancelToken>,
+
+	/// # Valid?
+	///
+	/// This is set once the buffer has been validated.
+	valid: bool,
 }
 
 impl AsRef<[u8]> for Output {
@@ -65,6 +92,12 @@ impl Output {
 			data: Vec::new(),
 			quality: Quality::Lossless(kind),
 			flags: 0,
+			avif: AvifCodec::Aom,
+			jxl: JxlTuning::DEFAULT,
+			jxl_streaming: false,
+			webp: WebpTuning::DEFAULT,
+			cancel: None,
+			valid: false,
 		}
 	}
 
@@ -75,6 +108,7 @@ impl Output {
 	fn reset(&mut self) {
 		self.data.truncate(0);
 		self.flags = 0;
+		self.valid = false;
 	}
 
 	/// # Finish (and Validate).
@@ -104,7 +138,7 @@ impl Output {
 			}
 			// We're good! Probably.
 			else if self.quality.kind() == ImageKind::try_from(self.data.as_slice())? {
-				self.flags |= FLAG_VALID;
+				self.valid = true;
 				Ok(())
 			}
 			// Type mismatch.
@@ -117,6 +151,7 @@ impl Output {
 
 /// ## Getters.
 impl Output {
+	#[cfg(feature = "jxl")]
 	#[inline]
 	/// # As Mut Vec.
 	///
@@ -131,14 +166,76 @@ impl Output {
 	/// This returns the [`EncodeIter`] flags that were set when the data was
 	/// written.
 	///
-	/// At the moment, this is only useful for identifying whether an AVIF was
-	/// encoded in full- or limited-range, but may hold other information in
-	/// the future.
+	/// These are the public `FLAG_*` constants, plus one internal bit —
+	/// `0b0000_1000` — set when an AVIF was encoded in full-range `RGB`. Each
+	/// bit means the same thing for every format; other iterator state is
+	/// kept out of the bitfield.
 	///
 	/// Note: a value is returned even in cases where the data itself wound up
 	/// invalid.
 	pub const fn flags(&self) -> u8 { self.flags }
 
+	#[cfg(feature = "avif")]
+	#[inline]
+	/// # `AVIF` Codec.
+	///
+	/// This is only used by the `AVIF` encoder.
+	pub(crate) const fn avif_codec(&self) -> AvifCodec { self.avif }
+
+	#[cfg(any(feature = "jxl", feature = "webp"))]
+	#[inline]
+	/// # Cancellation Token.
+	///
+	/// This is only used by encoders that can be interrupted mid-encode.
+	pub(crate) const fn cancel_token(&self) -> Option<&CancelToken> { self.cancel.as_ref() }
+
+	#[cfg(feature = "jxl")]
+	#[inline]
+	/// # `JPEG XL` Tuning.
+	///
+	/// This is only used by the `JPEG XL` encoder.
+	pub(crate) const fn jxl_tuning(&self) -> JxlTuning { self.jxl }
+
+	#[cfg(feature = "jxl")]
+	#[inline]
+	/// # `JPEG XL` Streaming?
+	///
+	/// This is only used by the `JPEG XL` encoder.
+	pub(crate) const fn jxl_streaming(&self) -> bool { self.jxl_streaming }
+
+	#[cfg(feature = "webp")]
+	#[inline]
+	/// # `WebP` Tuning.
+	///
+	/// This is only used by the `WebP` encoder.
+	pub(crate) const fn webp_tuning(&self) -> WebpTuning { self.webp }
+
+	#[must_use]
+	/// # Encoder Settings.
+	///
+	/// Briefly describe the fixed — non-quality — encoder settings used to
+	/// write the data, e.g. effort or speed.
+	pub fn encoder_settings(&self) -> Cow<'static, str> {
+		use crate::{
+			ImagePng,
+			traits::Encoder,
+		};
+
+		match self.kind() {
+			#[cfg(feature = "avif")]
+			ImageKind::Avif => crate::ImageAvif::settings(self),
+			#[cfg(feature = "jpeg")]
+			ImageKind::Jpeg => crate::ImageJpeg::settings(self),
+			#[cfg(feature = "jxl")]
+			ImageKind::Jxl => crate::ImageJxl::settings(self),
+			ImageKind::Png => ImagePng::settings(self),
+			#[cfg(any(feature = "webp", feature = "webp_rust"))]
+			ImageKind::Webp => crate::ImageWebp::settings(self),
+			#[cfg(not(all(feature = "avif", feature = "jpeg", feature = "jxl", any(feature = "webp", feature = "webp_rust"))))]
+			_ => Cow::Borrowed(""),
+		}
+	}
+
 	#[inline]
 	#[must_use]
 	/// # Is Valid?
@@ -148,7 +245,7 @@ impl Output {
 	///
 	/// In practice, if [`EncodeIter`] returns an [`Output`] reference, it _is_
 	/// valid. Otherwise it will just return an error.
-	pub const fn is_valid(&self) -> bool { FLAG_VALID == self.flags & FLAG_VALID }
+	pub const fn is_valid(&self) -> bool { self.valid }
 
 	#[inline]
 	#[must_use]
@@ -187,6 +284,63 @@ impl Output {
 	}
 }
 
+/// ## Validation.
+impl Output {
+	/// # Decode.
+	///
+	/// Decode the image back into RGBA pixels, returning them — along with
+	/// the dimensions, color type, etc. — as a new [`Input`].
+	///
+	/// The encoders are trusted to produce valid files, but a full round trip
+	/// is the surest way to catch a bad one before it is saved.
+	///
+	/// Note that decoding AVIF, JPEG XL, and WebP images requires the
+	/// `decode_ng` crate feature.
+	///
+	/// ## Errors
+	///
+	/// Returns an error if the data is invalid, cannot be decoded, or
+	/// decodes as some other format.
+	pub fn decode(&self) -> Result<Input<'static>, RefractError> {
+		if ! self.is_valid() { return Err(RefractError::Encode); }
+		let out = Input::try_from(self.data.as_slice())?;
+		if out.kind() == self.kind() { Ok(out) }
+		else { Err(RefractError::Decode) }
+	}
+
+	/// # Verify.
+	///
+	/// Same as [`Output::decode`], but also make sure the decoded image has
+	/// the same dimensions as the source it was encoded from.
+	///
+	/// ## Errors
+	///
+	/// Returns an error if decoding fails or the dimensions don't match.
+	pub fn verify(&self, src: &Input) -> Result<Input<'static>, RefractError> {
+		let out = self.decode()?;
+		if out.width() == src.width() && out.height() == src.height() { Ok(out) }
+		else { Err(RefractError::Dimensions) }
+	}
+
+	#[cfg(feature = "avif_dav1d")]
+	/// # Cross-Check With `dav1d`.
+	///
+	/// AVIF images are re-decoded using `dav1d`, the decoder most browsers
+	/// use, to catch files `aom` is happy with but real users wouldn't be.
+	/// Other formats pass through unchecked.
+	///
+	/// ## Errors
+	///
+	/// Returns an error if the data is invalid or `dav1d` rejects it.
+	pub fn verify_dav1d(&self) -> Result<(), RefractError> {
+		if ! self.is_valid() { Err(RefractError::Encode) }
+		else if ImageKind::Avif == self.kind() {
+			crate::ImageAvif::verify_dav1d(&self.data)
+		}
+		else { Ok(()) }
+	}
+}
+
 /// ## Setters.
 impl Output {
 	/// # Copy To.
@@ -201,6 +355,11 @@ impl Output {
 	pub(crate) fn copy_to(&mut self, dst: &mut Self) {
 		dst.quality = self.quality;
 		dst.flags = self.flags;
+		dst.avif = self.avif;
+		dst.jxl = self.jxl;
+		dst.jxl_streaming = self.jxl_streaming;
+		dst.webp = self.webp;
+		dst.valid = self.valid;
 		dst.data.truncate(0);
 		dst.data.append(&mut self.data);
 	}
@@ -217,6 +376,27 @@ impl Output {
 		self.quality = quality;
 	}
 
+	/// # Set `AVIF` Codec.
+	pub(crate) const fn set_avif_codec(&mut self, codec: AvifCodec) {
+		self.avif = codec;
+	}
+
+	/// # Set Cancellation Token.
+	pub(crate) fn set_cancel_token(&mut self, token: Option<CancelToken>) {
+		self.cancel = token;
+	}
+
+	/// # Set `JPEG XL` Tuning (and Streaming).
+	pub(crate) const fn set_jxl_tuning(&mut self, tuning: JxlTuning, streaming: bool) {
+		self.jxl = tuning;
+		self.jxl_streaming = streaming;
+	}
+
+	/// # Set `WebP` Tuning.
+	pub(crate) const fn set_webp_tuning(&mut self, tuning: WebpTuning) {
+		self.webp = tuning;
+	}
+
 	/// # Set Data From Slice.
 	///
 	/// This method shoves the raw byte slice returned by the `WebP` and `AVIF`
@@ -230,3 +410,55 @@ impl Output {
 		}
 	}
 }
+
+
+
+#[cfg(test)]
+mod tests {
+	#[cfg(any(feature = "avif", feature = "jpeg"))]
+	use super::*;
+	#[cfg(any(feature = "avif", feature = "jpeg"))]
+	use crate::EncodeIter;
+	#[cfg(any(feature = "avif", feature = "jpeg"))]
+	use std::num::NonZeroU8;
+
+	#[cfg(feature = "avif")]
+	#[test]
+	fn t_avif_codec() {
+		let src = Input::try_from(&include_bytes!("../../../skel/assets/circles.jpg")[..])
+			.expect("Unable to read test image.");
+
+		// The codec has to survive the trip from candidate to best.
+		for codec in AvifCodec::ALL {
+			let mut guide = EncodeIter::new(&src, ImageKind::Avif, 0)
+				.expect("Unable to create guide.");
+			guide.set_avif_codec(codec);
+			let out = guide.once(NonZeroU8::new(60)).expect("Encoding failed.");
+			assert_eq!(out.avif_codec(), codec, "Wrong AVIF codec.");
+			assert_eq!(
+				out.encoder_settings().contains("rav1e"),
+				codec == AvifCodec::Rav1e,
+				"Wrong AVIF settings: {}", out.encoder_settings(),
+			);
+		}
+	}
+
+	#[cfg(feature = "jpeg")]
+	#[test]
+	fn t_verify() {
+		let src = Input::try_from(&include_bytes!("../../../skel/assets/circles.jpg")[..])
+			.expect("Unable to read test image.");
+		let out = EncodeIter::new(&src, ImageKind::Jpeg, 0)
+			.and_then(|guide| guide.once(NonZeroU8::new(50)))
+			.expect("Encoding failed.");
+
+		let dec = out.verify(&src).expect("Verification failed.");
+		assert_eq!(dec.kind(), ImageKind::Jpeg);
+		assert_eq!(dec.width(), src.width());
+		assert_eq!(dec.height(), src.height());
+
+		// Against the wrong source.
+		let crop = src.crop(0, 0, 10, 10).expect("Crop failed.");
+		assert_eq!(out.verify(&crop).err(), Some(RefractError::Dimensions));
+	}
+}
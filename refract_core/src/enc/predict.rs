@@ -0,0 +1,110 @@
+/*!
+# `Refract` - Quality Prediction.
+*/
+
+use crate::{
+	ImageKind,
+	Input,
+	Quality,
+};
+use std::num::NonZeroU8;
+
+
+
+/// # History Length.
+///
+/// The number of recent results (per format) [`HistoryPredictor`] bases its
+/// guesses on.
+const HISTORY: usize = 16;
+
+
+
+/// # Quality Predictor.
+///
+/// By default, [`EncodeIter`](crate::EncodeIter) opens its search at the
+/// midpoint of the encoder's quality range. A good predictor can save a
+/// decision or two per image by suggesting a first probe closer to where
+/// things are likely to end up instead; see [`EncodeIter::set_first`](crate::EncodeIter::set_first).
+///
+/// Predictions are expressed as raw — `NonZeroU8` — quality values; see
+/// [`Quality::raw`].
+pub trait Predictor {
+	/// # Predict.
+	///
+	/// Suggest a first (lossy) quality to try for the source and output
+	/// format, or `None` to start from the middle.
+	fn predict(&self, src: &Input, kind: ImageKind) -> Option<NonZeroU8>;
+
+	/// # Learn.
+	///
+	/// Note the final quality settled on for an image.
+	fn learn(&mut self, best: Quality);
+}
+
+
+
+#[derive(Debug, Default)]
+/// # History Predictor.
+///
+/// This predicts each format will land where it usually does, taking the
+/// median of the most recent lossy results for that format. It makes no
+/// prediction until it has seen at least one.
+pub struct HistoryPredictor {
+	/// # Recent Results.
+	history: Vec<(ImageKind, NonZeroU8)>,
+}
+
+impl Predictor for HistoryPredictor {
+	fn predict(&self, _src: &Input, kind: ImageKind) -> Option<NonZeroU8> {
+		let mut set: Vec<NonZeroU8> = self.history.iter()
+			.filter_map(|&(k, q)| (k == kind).then_some(q))
+			.collect();
+		set.sort_unstable();
+		set.get(set.len() / 2).copied()
+	}
+
+	fn learn(&mut self, best: Quality) {
+		if best.is_lossless() { return; }
+
+		let kind = best.kind();
+		if HISTORY <= self.history.iter().filter(|(k, _)| *k == kind).count() {
+			if let Some(idx) = self.history.iter().position(|(k, _)| *k == kind) {
+				self.history.remove(idx);
+			}
+		}
+		self.history.push((kind, best.raw()));
+	}
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn t_history() {
+		let src = Input::try_from(&include_bytes!("../../../skel/assets/poe.png")[..])
+			.expect("Unable to read test image.");
+
+		let mut pred = HistoryPredictor::default();
+		assert_eq!(pred.predict(&src, ImageKind::Webp), None);
+
+		for q in [70, 90, 80] {
+			pred.learn(Quality::new(ImageKind::Webp, NonZeroU8::new(q)));
+		}
+		pred.learn(Quality::new(ImageKind::Webp, None));
+		pred.learn(Quality::new(ImageKind::Avif, NonZeroU8::new(20)));
+
+		assert_eq!(pred.predict(&src, ImageKind::Webp), NonZeroU8::new(80));
+		assert_eq!(pred.predict(&src, ImageKind::Avif), NonZeroU8::new(20));
+		assert_eq!(pred.predict(&src, ImageKind::Jxl), None);
+
+		// Old results roll off.
+		for _ in 0..HISTORY {
+			pred.learn(Quality::new(ImageKind::Webp, NonZeroU8::new(50)));
+		}
+		assert_eq!(pred.predict(&src, ImageKind::Webp), NonZeroU8::new(50));
+		assert_eq!(pred.predict(&src, ImageKind::Avif), NonZeroU8::new(20));
+	}
+}
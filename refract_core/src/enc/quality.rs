@@ -10,6 +10,27 @@ use std::{
 
 
 
+/// # Equivalence Calibration.
+///
+/// Raw qualities that tend to produce comparable results for typical
+/// photographs, ordered from worst to best. The columns are AVIF, JPEG,
+/// JPEG XL, and WebP, respectively.
+///
+/// These are rough anchors, not promises; values in between are linearly
+/// interpolated.
+const EQUIVALENTS: [[u8; 4]; 8] = [
+	[1, 1, 1, 1],
+	[10, 20, 75, 20],
+	[17, 40, 105, 40],
+	[22, 60, 120, 60],
+	[28, 75, 130, 75],
+	[33, 80, 135, 80],
+	[43, 90, 140, 90],
+	[63, 100, 150, 100],
+];
+
+
+
 #[derive(Debug, Clone, Copy)]
 /// # Encoding Quality.
 ///
@@ -38,9 +59,70 @@ impl Quality {
 	#[inline]
 	#[must_use]
 	/// # New.
-	pub(crate) fn new(kind: ImageKind, quality: Option<NonZeroU8>) -> Self {
+	///
+	/// A `None` quality is lossless.
+	pub fn new(kind: ImageKind, quality: Option<NonZeroU8>) -> Self {
 		quality.map_or_else(|| Self::Lossless(kind), |q| Self::Lossy(kind, q))
 	}
+
+	#[must_use]
+	/// # From Native Value.
+	///
+	/// Parse a lossy quality written the way the encoder itself — and
+	/// [`Quality::quality`] — expresses it, e.g. an AVIF quantizer of `28` or
+	/// a JPEG XL distance of `1.5`.
+	///
+	/// Returns `None` if the value is malformed or out of range, or if the
+	/// format has no lossy mode.
+	pub fn from_native(kind: ImageKind, src: &str) -> Option<Self> {
+		let src = src.trim();
+		let max = kind.max_encoder_quality().get();
+		let raw = match kind {
+			ImageKind::Avif => max.checked_sub(src.parse::<u8>().ok()?)?,
+			ImageKind::Jxl => {
+				let d = src.parse::<f32>().ok()?;
+				if ! (0.0..=f32::from(max) / 10.0).contains(&d) { return None; }
+				#[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss, reason = "It is in range.")]
+				let d = (d * 10.0).round() as u8;
+				max.checked_sub(d)?
+			},
+			ImageKind::Jpeg | ImageKind::Webp => src.parse::<u8>().ok().filter(|&q| q <= max)?,
+			ImageKind::Png => return None,
+		};
+		NonZeroU8::new(raw).map(|q| Self::Lossy(kind, q))
+	}
+
+	#[must_use]
+	/// # From Normalized Value.
+	///
+	/// This is the inverse of [`Quality::normalized`], returning the lossy
+	/// quality for `kind` closest to the format-independent value `src`,
+	/// which should be between `0.0` and `1.0`.
+	///
+	/// Note that `1.0` yields the _lossy_ maximum — e.g. JPEG `100` — rather
+	/// than lossless.
+	///
+	/// Returns `None` if `src` is out of range, or if the format has no lossy
+	/// mode.
+	pub fn from_normalized(kind: ImageKind, src: f32) -> Option<Self> {
+		let col = equivalent_column(kind)?;
+		if ! (0.0..=1.0).contains(&src) { return None; }
+
+		// Figure out which pair of anchors we're between, and how far along.
+		#[expect(clippy::cast_precision_loss, reason = "It is tiny.")]
+		let pos = src * (EQUIVALENTS.len() - 1) as f32;
+		#[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss, reason = "It is in range.")]
+		let idx = (pos as usize).min(EQUIVALENTS.len() - 2);
+		#[expect(clippy::cast_precision_loss, reason = "It is tiny.")]
+		let frac = pos - idx as f32;
+
+		let a = EQUIVALENTS[idx][col];
+		let b = EQUIVALENTS[idx + 1][col];
+		#[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss, reason = "It is in range.")]
+		let step = (frac * f32::from(b - a)).round() as u8;
+
+		NonZeroU8::new(a.saturating_add(step).min(b)).map(|q| Self::Lossy(kind, q))
+	}
 }
 
 /// ## Getters.
@@ -49,6 +131,46 @@ impl Quality {
 	/// # Is Lossless?
 	pub const fn is_lossless(self) -> bool { matches!(self, Self::Lossless(_)) }
 
+	#[must_use]
+	/// # Equivalent Quality.
+	///
+	/// Return the quality for `kind` that roughly matches this one for
+	/// typical photographs, e.g. WebP `80` ≈ AVIF `30` ≈ JPEG XL `1.5`,
+	/// according to a bundled calibration table.
+	///
+	/// This is only meant as a starting point for manual qualities or
+	/// bounds; individual images can and will vary.
+	///
+	/// Returns `None` if either format lacks a usable quality scale (PNG
+	/// is lossless-only, JPEG is lossy-only).
+	pub fn equivalent(self, kind: ImageKind) -> Option<Self> {
+		match self {
+			Self::Lossless(_) =>
+				if matches!(kind, ImageKind::Jpeg) { None }
+				else { Some(Self::Lossless(kind)) },
+			Self::Lossy(k, q) => {
+				let from = equivalent_column(k)?;
+				let to = equivalent_column(kind)?;
+				let q = q.get();
+
+				// Find the anchors on either side and interpolate.
+				let out = EQUIVALENTS.windows(2).find_map(|w| {
+					let (a, b) = (w[0], w[1]);
+					if a[from] <= q && q <= b[from] {
+						let span = u16::from(b[from] - a[from]);
+						let diff = u16::from(b[to] - a[to]);
+						let pos = u16::from(q - a[from]);
+						let step = (pos * diff + span / 2) / span;
+						u8::try_from(u16::from(a[to]) + step).ok()
+					}
+					else { None }
+				})?;
+
+				NonZeroU8::new(out).map(|q| Self::Lossy(kind, q))
+			},
+		}
+	}
+
 	#[must_use]
 	/// # Kind.
 	///
@@ -87,6 +209,45 @@ impl Quality {
 		}
 	}
 
+	#[must_use]
+	/// # Format-Independent Quality.
+	///
+	/// Return the quality as a value between `0.0` (worst) and `1.0` (best)
+	/// that can be compared — or stored — independently of the format, such
+	/// that, e.g., WebP `80`, AVIF `30`, and JPEG XL `1.5` all come out about
+	/// the same.
+	///
+	/// This uses the same calibration table as [`Quality::equivalent`], so
+	/// is subject to the same caveats: it is a rough guide, not a
+	/// measurement. See [`Quality::from_normalized`] for the reverse.
+	///
+	/// Lossless is always `1.0`. PNG, having no calibrated lossy scale, is
+	/// simply scaled linearly.
+	pub fn normalized(self) -> f32 {
+		let Self::Lossy(kind, q) = self else { return 1.0; };
+		let q = q.get();
+
+		#[expect(clippy::cast_precision_loss, reason = "It is tiny.")]
+		let segments = (EQUIVALENTS.len() - 1) as f32;
+		let Some(col) = equivalent_column(kind) else {
+			let max = kind.max_encoder_quality().get();
+			return f32::from(q.min(max) - 1) / f32::from(max - 1);
+		};
+
+		// Find the anchors on either side and interpolate.
+		EQUIVALENTS.windows(2).enumerate().find_map(|(idx, w)| {
+			let (a, b) = (w[0][col], w[1][col]);
+			if a <= q && q <= b {
+				let frac = f32::from(q - a) / f32::from(b - a);
+				#[expect(clippy::cast_precision_loss, reason = "It is tiny.")]
+				let idx = idx as f32;
+				Some((idx + frac) / segments)
+			}
+			else { None }
+		})
+			.unwrap_or(1.0)
+	}
+
 	#[must_use]
 	/// # Normalized Quality Value.
 	///
@@ -106,9 +267,11 @@ impl Quality {
 	#[must_use]
 	/// # Raw Quality Value.
 	///
-	/// This returns the raw — `NonZeroU8` — quality value. This method is
-	/// only used internally by this crate.
-	pub(crate) const fn raw(self) -> NonZeroU8 {
+	/// This returns the raw — `NonZeroU8` — quality value, as used
+	/// internally by this crate, and by [`Predictor`](crate::Predictor)s.
+	///
+	/// Lossless qualities are equivalent to the encoder's maximum.
+	pub const fn raw(self) -> NonZeroU8 {
 		match self {
 			Self::Lossless(k) => k.max_encoder_quality(),
 			Self::Lossy(_, q) => q,
@@ -118,6 +281,21 @@ impl Quality {
 
 
 
+/// # Equivalence Column.
+///
+/// Return the [`EQUIVALENTS`] column for a given kind, if any.
+const fn equivalent_column(kind: ImageKind) -> Option<usize> {
+	match kind {
+		ImageKind::Avif => Some(0),
+		ImageKind::Jpeg => Some(1),
+		ImageKind::Jxl => Some(2),
+		ImageKind::Webp => Some(3),
+		ImageKind::Png => None,
+	}
+}
+
+
+
 #[derive(Debug, Clone, Copy)]
 /// # Quality Value.
 ///
@@ -152,3 +330,107 @@ impl fmt::Display for QualityValue {
 		}
 	}
 }
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn t_equivalent() {
+		let webp = Quality::Lossy(ImageKind::Webp, NonZeroU8::new(80).unwrap());
+		assert_eq!(
+			webp.equivalent(ImageKind::Avif).map(|q| q.quality().to_string()),
+			Some("30".to_owned()),
+		);
+		assert_eq!(
+			webp.equivalent(ImageKind::Jxl).map(|q| q.quality().to_string()),
+			Some("1.5".to_owned()),
+		);
+		assert!(webp.equivalent(ImageKind::Png).is_none());
+
+		// Every raw value should map to something in range, and the mapping
+		// should never go backwards.
+		for kind in [ImageKind::Avif, ImageKind::Jpeg, ImageKind::Jxl] {
+			let mut last = 0;
+			for q in 1..=100 {
+				let q = Quality::Lossy(ImageKind::Webp, NonZeroU8::new(q).unwrap())
+					.equivalent(kind)
+					.expect("Missing equivalent.")
+					.raw()
+					.get();
+				assert!(last <= q);
+				assert!(q <= kind.max_encoder_quality().get());
+				last = q;
+			}
+		}
+
+		// Lossless stays lossless, except for JPEG.
+		let lossless = Quality::Lossless(ImageKind::Webp);
+		assert!(lossless.equivalent(ImageKind::Jxl).is_some_and(Quality::is_lossless));
+		assert!(lossless.equivalent(ImageKind::Jpeg).is_none());
+	}
+
+	#[test]
+	fn t_normalized() {
+		// Calibrated equivalents should line up.
+		let webp = Quality::Lossy(ImageKind::Webp, NonZeroU8::new(80).unwrap());
+		let avif = Quality::Lossy(ImageKind::Avif, NonZeroU8::new(33).unwrap());
+		let jxl = Quality::Lossy(ImageKind::Jxl, NonZeroU8::new(135).unwrap());
+		assert!((webp.normalized() - avif.normalized()).abs() < f32::EPSILON);
+		assert!((webp.normalized() - jxl.normalized()).abs() < f32::EPSILON);
+		assert!((Quality::Lossless(ImageKind::Png).normalized() - 1.0).abs() < f32::EPSILON);
+
+		// Every lossy value should survive a round trip, in order.
+		for kind in [ImageKind::Avif, ImageKind::Jpeg, ImageKind::Jxl, ImageKind::Webp] {
+			let mut last = -1.0;
+			for q in 1..=kind.max_encoder_quality().get() {
+				let n = Quality::Lossy(kind, NonZeroU8::new(q).unwrap()).normalized();
+				assert!(last < n && n <= 1.0, "Bad normalization for {kind} {q}.");
+				assert_eq!(
+					Quality::from_normalized(kind, n).map(|q| q.raw().get()),
+					Some(q),
+					"Round trip failed for {kind} {q}.",
+				);
+				last = n;
+			}
+		}
+
+		assert!(Quality::from_normalized(ImageKind::Webp, 1.1).is_none());
+		assert!(Quality::from_normalized(ImageKind::Webp, f32::NAN).is_none());
+		assert!(Quality::from_normalized(ImageKind::Png, 0.5).is_none());
+	}
+
+	#[test]
+	fn t_from_native() {
+		for (kind, native, raw) in [
+			(ImageKind::Avif, "28", 35),
+			(ImageKind::Avif, "0", 63),
+			(ImageKind::Jxl, "1.5", 135),
+			(ImageKind::Jxl, "0", 150),
+			(ImageKind::Jpeg, "75", 75),
+			(ImageKind::Webp, " 80 ", 80),
+		] {
+			let q = Quality::from_native(kind, native).expect("Missing quality.");
+			assert_eq!(q.raw().get(), raw, "Wrong raw value for {kind} {native}.");
+			assert_eq!(
+				q.quality().to_string().parse::<f32>().ok(),
+				native.trim().parse::<f32>().ok(),
+				"Round trip failed for {kind} {native}.",
+			);
+		}
+
+		for (kind, native) in [
+			(ImageKind::Avif, "63"),
+			(ImageKind::Jxl, "15.1"),
+			(ImageKind::Jxl, "-1"),
+			(ImageKind::Webp, "0"),
+			(ImageKind::Webp, "101"),
+			(ImageKind::Webp, "high"),
+			(ImageKind::Png, "80"),
+		] {
+			assert!(Quality::from_native(kind, native).is_none(), "{kind} {native} should be invalid.");
+		}
+	}
+}
@@ -11,7 +11,7 @@ use std::{
 
 
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 /// # Quality Range.
 pub struct QualityRange {
 	/// # Low End.
@@ -20,6 +20,9 @@ pub struct QualityRange {
 	/// # High End.
 	top: NonZeroU8,
 
+	/// # First Probe.
+	first: Option<NonZeroU8>,
+
 	/// # Already Tried.
 	tried: HashSet<NonZeroU8, NoHash>,
 }
@@ -31,6 +34,7 @@ impl From<ImageKind> for QualityRange {
 		Self {
 			bottom: kind.min_encoder_quality(),
 			top: kind.max_encoder_quality(),
+			first: None,
 			tried: HashSet::default(),
 		}
 	}
@@ -42,12 +46,20 @@ impl Iterator for QualityRange {
 	/// # Next Quality.
 	///
 	/// Return the next untested quality value from the moving range. In the
-	/// early stages, the value will fall roughly in the middle of the ends,
-	/// but as we run out of options, it may perform more sequentially.
+	/// early stages, the value will fall roughly in the middle of the ends —
+	/// unless a different first probe was set with [`QualityRange::set_first`]
+	/// — but as we run out of options, it may perform more sequentially.
 	///
 	/// Once every possibility (within the closing range) has been tried, `None`
 	/// will be returned.
 	fn next(&mut self) -> Option<Self::Item> {
+		// Start with the first probe, if any.
+		if let Some(next) = self.first.take() {
+			if self.bottom <= next && next <= self.top && self.tried.insert(next) {
+				return Some(next);
+			}
+		}
+
 		let min = self.bottom.get();
 		let max = self.top.get();
 		let mut diff = max - min;
@@ -95,6 +107,7 @@ impl QualityRange {
 			Self {
 				bottom,
 				top,
+				first: None,
 				tried: HashSet::default(),
 			}
 		}
@@ -103,6 +116,7 @@ impl QualityRange {
 			Self {
 				bottom: top,
 				top: bottom,
+				first: None,
 				tried: HashSet::default(),
 			}
 		}
@@ -121,6 +135,7 @@ impl QualityRange {
 
 		self.bottom = bottom;
 		self.top = top;
+		self.first = None;
 		self.tried.clear();
 	}
 }
@@ -136,6 +151,22 @@ impl QualityRange {
 	#[must_use]
 	/// # Get the top.
 	pub const fn top(&self) -> NonZeroU8 { self.top }
+
+	#[must_use]
+	/// # Most Steps Remaining.
+	///
+	/// Return the most values [`QualityRange::next`] could yet return, i.e.
+	/// the worst case for a search that keeps halving what's left, or the
+	/// number of untried values, whichever is smaller.
+	pub fn remaining_max(&self) -> u8 {
+		let untried = (self.bottom.get()..=self.top.get())
+			.filter_map(NonZeroU8::new)
+			.filter(|q| ! self.tried.contains(q))
+			.count();
+		let diff = self.top.get() - self.bottom.get();
+		let halves = u8::try_from(u8::BITS - diff.leading_zeros() + 1).unwrap_or(u8::MAX);
+		u8::try_from(untried).map_or(halves, |u| u.min(halves))
+	}
 }
 
 /// ## Setters.
@@ -149,6 +180,15 @@ impl QualityRange {
 		self.tried.insert(quality);
 	}
 
+	#[inline]
+	/// # Set First Probe.
+	///
+	/// Have the next call to `next` return this value — if it falls within
+	/// the range and hasn't been tried yet — instead of the midpoint.
+	pub const fn set_first(&mut self, quality: NonZeroU8) {
+		self.first = Some(quality);
+	}
+
 	#[inline]
 	/// # Raise Bottom.
 	///
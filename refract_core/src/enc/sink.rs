@@ -0,0 +1,144 @@
+/*!
+# `Refract` - Output Sinks.
+*/
+
+use crate::RefractError;
+use std::{
+	fs::File,
+	io::Write,
+};
+
+
+
+/// # Chunk Size.
+///
+/// Streaming encoders are handed a scratch buffer of this size to write into
+/// each pass.
+const CHUNK_SIZE: usize = 65_536;
+
+
+
+/// # Output Sink.
+///
+/// This is a destination for streamed encoder output.
+///
+/// Encoders write into a fixed, fully-initialized scratch buffer (see
+/// [`drain`]), and whatever they produce is then handed off here, so no
+/// uninitialized memory or manual length fiddling is ever involved.
+///
+/// Outputs are normally collected in memory, but they can also be streamed
+/// straight to a `File`, keeping very large outputs off the heap.
+pub(crate) trait Sink {
+	/// # Push Chunk.
+	///
+	/// ## Errors
+	///
+	/// This will return an error if the chunk cannot be stored.
+	fn push(&mut self, chunk: &[u8]) -> Result<(), RefractError>;
+}
+
+impl Sink for Vec<u8> {
+	fn push(&mut self, chunk: &[u8]) -> Result<(), RefractError> {
+		self.try_reserve(chunk.len()).map_err(|_| RefractError::Overflow)?;
+		self.extend_from_slice(chunk);
+		Ok(())
+	}
+}
+
+impl Sink for File {
+	fn push(&mut self, chunk: &[u8]) -> Result<(), RefractError> {
+		self.write_all(chunk).map_err(|_| RefractError::Encode)
+	}
+}
+
+
+
+/// # Drain.
+///
+/// Repeatedly call `process` with a scratch buffer, passing along whatever
+/// it writes to the sink, until it reports it is finished.
+///
+/// The callback should return the number of bytes written to the front of
+/// the buffer and whether or not it is done.
+///
+/// ## Errors
+///
+/// This bubbles up any errors from the callback or sink, and will also
+/// return an error if the callback claims to have written more than the
+/// buffer can hold, or asks for more room without having used any of it
+/// (which would otherwise loop forever).
+pub(crate) fn drain<F, S>(mut process: F, sink: &mut S) -> Result<(), RefractError>
+where
+	F: FnMut(&mut [u8]) -> Result<(usize, bool), RefractError>,
+	S: Sink {
+	let mut chunk = vec![0_u8; CHUNK_SIZE];
+	loop {
+		let (len, done) = process(&mut chunk)?;
+		let written = chunk.get(..len).ok_or(RefractError::Overflow)?;
+		if ! written.is_empty() { sink.push(written)?; }
+
+		if done { return Ok(()); }
+		if written.is_empty() { return Err(RefractError::Encode); }
+	}
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn t_drain() {
+		// Three full chunks and a partial.
+		let total = CHUNK_SIZE * 3 + 17;
+		let mut remaining = total;
+		let mut out: Vec<u8> = Vec::new();
+		drain(|buf| {
+			let len = remaining.min(buf.len());
+			buf[..len].fill(7);
+			remaining -= len;
+			Ok((len, remaining == 0))
+		}, &mut out).expect("Drain failed.");
+		assert_eq!(out.len(), total);
+		assert!(out.iter().all(|&b| b == 7), "Data was mangled.");
+
+		// Done on an empty pass is fine.
+		let mut out: Vec<u8> = Vec::new();
+		assert!(drain(|_| Ok((0, true)), &mut out).is_ok());
+		assert!(out.is_empty());
+
+		// Wanting more room without using any is not.
+		assert_eq!(drain(|_| Ok((0, false)), &mut out), Err(RefractError::Encode));
+
+		// Nor is claiming to have written more than there was room for.
+		assert_eq!(
+			drain(|buf| Ok((buf.len() + 1, false)), &mut out),
+			Err(RefractError::Overflow),
+		);
+
+		// Errors pass through.
+		assert_eq!(
+			drain(|_| Err(RefractError::TooBig), &mut out),
+			Err(RefractError::TooBig),
+		);
+	}
+
+	#[test]
+	fn t_file_sink() {
+		let path = std::env::temp_dir().join(format!("refract-sink-{}.bin", std::process::id()));
+		let mut sink = File::create(&path).expect("Unable to create file.");
+
+		let mut passes = 0_u8;
+		drain(|buf| {
+			passes += 1;
+			buf[..3].copy_from_slice(b"abc");
+			Ok((3, passes == 2))
+		}, &mut sink).expect("Drain failed.");
+		drop(sink);
+
+		let raw = std::fs::read(&path).expect("Unable to read file.");
+		let _res = std::fs::remove_file(&path);
+		assert_eq!(raw, b"abcabc");
+	}
+}
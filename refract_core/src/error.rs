@@ -4,8 +4,13 @@
 
 use crate::ImageKind;
 use std::{
+	cell::RefCell,
 	error::Error,
 	fmt,
+	path::{
+		Path,
+		PathBuf,
+	},
 };
 
 
@@ -31,19 +36,71 @@ const HELP: &str = concat!(r"
 
 USAGE:
     refract [FLAGS] [OPTIONS] <PATH(S)>...
+    refract preset <NAME> [FLAGS] [OPTIONS] <PATH(S)>...
+    refract replay <FILE>
+    refract estimate [FLAGS] [OPTIONS] <PATH(S)>...
+    refract decode <FILE> [-o <FILE>]
+    refract --job <FILE> [--job-root <DIR>] [FLAGS] [OPTIONS]
 
 FORMAT FLAGS:
         --no-avif     Skip AVIF encoding.
         --no-jxl      Skip JPEG-XL encoding.
         --no-webp     Skip WebP encoding.
+        --png         Also save a losslessly optimized PNG.
+        --jpeg        Also save a MozJPEG-encoded JPEG (unless the source has
+                      transparency).
 
 MODE FLAGS:
         --no-lossless Skip lossless encoding passes.
         --no-lossy    Skip lossy encoding passes.
         --no-ycbcr    Skip AVIF YCbCr encoding passes.
+        --avif-yuv420 Encode AVIF YCbCr passes with 4:2:0 chroma
+                      subsampling, usually much smaller for photos.
+        --jxl-progressive
+                      Encode JPEG XL images progressively, center-first, so
+                      browsers can show something sooner.
+        --webp-sharp-yuv
+                      Use a slower, more accurate color conversion for lossy
+                      WebP, keeping edges around saturated colors crisp.
+        --lossless-last
+                      Try lossless encoding after lossy rather than before,
+                      keeping it only if it wins. (Faster for photos.)
+        --photo-lossless
+                      Try lossless encoding even for JPEGs and other
+                      photographic sources. By default it is skipped for
+                      them, as it almost never wins.
+        --smart       Pick formats and modes per image: lossless WebP/JPEG-XL
+                      for PNG graphics with transparency and few colors,
+                      lossy AVIF/JPEG-XL for JPEG photos.
+        --greyscale   Convert sources to greyscale before encoding.
+        --keep-metadata
+                      Copy ICC color profiles and EXIF data (orientation,
+                      copyright, etc.) from JPEG and PNG sources into the
+                      AVIF, JPEG XL, and WebP outputs.
 
 MISC FLAGS:
+        --background  Run the encoders at the lowest CPU priority so the
+                      rest of the system stays responsive.
+        --ci          Print GitHub Actions annotations and a Markdown job
+                      summary of the results at the end of the run.
+        --dedupe-lossless
+                      Only keep the smallest lossless conversion of each
+                      source, discarding larger lossless copies in other
+                      formats.
+        --durable     Flush saved images (and their parent directories) to
+                      disk before moving on.
     -h, --help        Print help information and exit.
+        --new-instance
+                      Open a separate window even if Refract is already
+                      running, rather than handing the <PATH(S)> off to it.
+        --no-recurse  Only queue images in the top level of directories passed
+                      as <PATH(S)>. This is equivalent to --max-depth 0.
+        --picture     Save an HTML <picture> snippet alongside each source
+                      referencing its new conversions.
+        --sidecar     Save a JSON record of how each output was made — encoder
+                      version, settings, flags, and preset — alongside it.
+        --stdin       Read a single JPEG or PNG source from STDIN and write
+                      its --format conversion to STDOUT. (See PIPE below.)
     -V, --version     Print version information and exit.
 
 OPTIONS:
@@ -51,6 +108,173 @@ OPTIONS:
                       text file — or STDIN if "-" — one path per line, instead
                       of or in addition to those specified inline via
                       <PATH(S)>.
+        --avif-codec <NAME>
+                      Encode AVIF images with this AV1 codec: "aom" or, if
+                      built with the avif_rav1e feature, "rav1e".
+                      [default: aom]
+        --existing <MODE>
+                      What to do when a format's suggested output already
+                      exists: "ask", "skip", "overwrite", or replace only if
+                      "smaller". [default: ask]
+        --fallback <MODE>
+                      When a format yields no savings (or is skipped), stand
+                      the source in at the suggested output path instead, via
+                      "copy", "hardlink", or "symlink".
+        --format <EXT>
+                      The --stdin output format: avif, jpg, jxl, png, or
+                      webp.
+        --job <FILE>  Queue the images — and apply the settings — saved to a
+                      job file via File > Export Queue….
+        --job-root <DIR>
+                      Rebase the --job paths onto this directory instead of
+                      the root they were exported from.
+        --jobs <NUM>
+                      Encode this many images at once when no feedback is
+                      needed — i.e. with --max-size or --no-lossy — or 0
+                      for one per CPU. [default: 1]
+        --jxl-decoding-speed <NUM>
+                      Favor faster JPEG XL decoding, from 0 (smallest) to 4
+                      (fastest). [default: 0]
+        --jxl-effort <NUM>
+                      Spend this much effort on JPEG XL encoding, from 1
+                      (fastest) to 9 (smallest). Effort 7 is several times
+                      faster with nearly identical results. [default: 9]
+        --lossless-above <SIZE>
+                      Only try lossless if the lossy winner is bigger than
+                      this many bytes, e.g. 500K, or 0 for no threshold.
+                      Implies --lossless-last. [default: 0]
+        --max-depth <NUM>
+                      Only descend this many levels into the subdirectories
+                      of directories passed as <PATH(S)>. Zero means the top
+                      level only. [default: unlimited]
+        --max-size <SIZE>
+                      Skip the feedback and keep the best quality that fits
+                      in this many bytes, e.g. 200K or 1M, or 0 for no
+                      limit. [default: 0]
+        --max-steps <NUM>
+                      Stop each format's quality search after this many
+                      candidates, settling for the best so far, or 0 for no
+                      limit. [default: 0]
+        --name-template <TPL>
+                      Suggest output file names from this template rather
+                      than "{src_name}.{ext}", e.g. "{src_stem}.{ext}" to
+                      replace the source extension instead of appending to
+                      it. See the README for the supported tokens.
+    -o, --output <FILE>
+                      Save "refract decode" output here instead of alongside
+                      the source.
+        --out-dir <DIR>
+                      Suggest saving outputs under this directory — mirroring
+                      the source tree, with subdirectories created as needed
+                      — instead of alongside the sources.
+        --preset <NAME>
+                      Apply this preset, the same as "refract preset
+                      <NAME>". See PRESETS below.
+        --preview-max <PX>
+                      Show sources and candidates with a longer side than
+                      this scaled down to fit, e.g. 3840, or 0 to always
+                      show them at full size. Encoding is unaffected, and
+                      View > View 1:1 shows the full-size images on demand.
+                      [default: 0]
+        --progress <FMT>
+                      Stream machine-readable progress events — one JSON
+                      object per line — to STDOUT. The only supported format
+                      is "json".
+        --quality <NUM>
+                      The --stdin encoding quality; or, as FORMAT=VALUE or
+                      FORMAT=LOW-HIGH, the quality to start that format's
+                      search from, or the range to limit it to. Either way,
+                      it is in the format's own scale, e.g. 28 for AVIF.
+                      [default: lossless, or 80 for JPEG]
+        --record <FILE>
+                      Append each keep/discard decision — along with the
+                      final results — to this file, for later replay.
+        --region <GEOMETRY>
+                      Run the quality search on this crop of each source —
+                      WIDTHxHEIGHT+X+Y, or WIDTHxHEIGHT to center it — and
+                      apply the chosen quality to the whole image. Shift-
+                      dragging across a source does the same.
+        --report <FILE>
+                      Save the activity log to this file as plain text,
+                      periodically, at the end of each run, and in the event
+                      of a crash. If the file ends in .json, the per-source
+                      results are saved as JSON instead.
+        --report-every <NUM>
+                      Flush the --report after this many images.
+                      [default: 10]
+        --resume <FILE>
+                      Journal each finished source — and its saved outputs —
+                      to this file, skipping any sources it already lists, so
+                      an interrupted batch can pick up where it left off.
+        --stream-above <MPX>
+                      Losslessly encode JPEG XL images larger than this many
+                      megapixels a group at a time to bound memory usage, or
+                      0 to never do so. [default: 64]
+        --time-limit <SECS>
+                      Abandon any candidate that takes longer than this to
+                      encode, moving on to the next, or 0 for no limit. Only
+                      WebP (and streamed JPEG XL) can be cut short; other
+                      encodes are discarded once they finish. [default: 0]
+        --webp-method <NUM>
+                      Spend this much effort on WebP encoding, from 0
+                      (fastest) to 6 (smallest). [default: 6]
+
+REPLAY:
+    "refract replay <FILE>" re-runs a recorded feedback script without the
+    GUI, answering each candidate the same way, and verifies the final
+    qualities and sizes match the recording.
+
+PIPE:
+    "refract --stdin --format <EXT> [--quality <NUM>]" encodes a source read
+    from STDIN exactly once — no GUI, no feedback — and writes the result
+    to STDOUT, e.g. "refract --stdin --format webp --quality 80 < in.jpg >
+    out.webp". The quality is written the same way as --quality
+    FORMAT=VALUE: 1–100 for WebP and JPEG, a quantizer for AVIF, and a
+    distance for JPEG XL, the latter two lower being better. Without one,
+    the image is encoded losslessly, or at 80 for JPEG. Nothing is written
+    if the result is no smaller than the source.
+
+ESTIMATE:
+    "refract estimate <PATH(S)>..." encodes each source once per enabled
+    format at a fixed, medium quality and prints the projected savings
+    without the GUI, to help triage which images are worth a full run.
+
+DECODE:
+    "refract decode <FILE>" converts an AVIF, JPEG XL, or WebP image back
+    to PNG without the GUI, for tools that don't support those formats yet.
+    The PNG is saved alongside the source, e.g. "image.avif.png", unless
+    --output is given.
+
+PRESETS:
+    Three presets are built in — "web-hero", "thumbnails", and "archival"
+    — and more can be defined (or those overridden) in
+    ~/.config/refract/refract.toml. Apply them with "refract preset <NAME>",
+    --preset <NAME>, or from the Settings menu:
+
+        [presets.web-hero]
+        formats = ["avif", "webp"]
+        lossless = false
+        picture = true
+        max-steps = 10
+        quality = { avif = "20-35" }
+
+    Supported keys are formats, lossless, lossy, ycbcr, avif-yuv420,
+    jxl-progressive, webp-sharp-yuv, lossless-last, photo-lossless, smart,
+    greyscale, keep-metadata, background, dedupe, durable, picture, and
+    sidecar, as well as avif-codec, jxl-decoding-speed, jxl-effort,
+    lossless-above, max-size, max-steps, time-limit, webp-method, and
+    quality, which work like the options of the same name. Any flags or options passed
+    alongside a preset are added on top.
+
+NAMING:
+    Suggested output file names can be customized per format in the same
+    file, using the tokens {src_name}, {src_stem}, {src_ext}, {ext}, and
+    {EXT}:
+
+        [naming]
+        avif = "{src_stem}.{ext}"
+        webp = "{src_stem}.{ext}"
+        html = "{src_stem}.picture.html"
 
 TRAILING ARGS:
     <PATH(S)>...      Image and/or directory paths to re-encode. Directories
@@ -62,15 +286,31 @@ TRAILING ARGS:
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 /// # Errors.
 pub enum RefractError {
+	/// # Encoding cancelled.
+	Cancelled,
+
 	/// # Unsupported color.
 	Color,
 
+	/// # Unsupported color: 16-bit JPEG.
+	ColorJpeg16,
+
 	/// # Decoding failed.
 	Decode,
 
+	/// # Candidate/source dimension mismatch.
+	Dimensions,
+
+	/// # Empty file.
+	Empty,
+
 	/// # Encoding failed.
 	Encode,
 
+	#[cfg(feature = "avif_dav1d")]
+	/// # Cross-decoding failed.
+	CrossDecode,
+
 	/// # Invalid image.
 	Image,
 
@@ -89,21 +329,127 @@ pub enum RefractError {
 	/// # Image dimensions are too big.
 	Overflow,
 
+	/// # Encoding took too long.
+	TimedOut,
+
 	/// # Image is too big.
 	TooBig,
 
+	/// # Truncated file.
+	Truncated(ImageKind),
+
+	#[cfg(feature = "bin")]
+	/// # Invalid AVIF codec.
+	AvifCodec,
+
+	#[cfg(feature = "bin")]
+	/// # Invalid config.
+	Config,
+
+	#[cfg(feature = "bin")]
+	/// # Invalid existing output policy.
+	Existing,
+
+	#[cfg(feature = "bin")]
+	/// # Invalid fallback mode.
+	Fallback,
+
 	#[cfg(feature = "bin")]
 	/// # GTK failed.
 	GtkInit,
 
+	#[cfg(feature = "bin")]
+	/// # Invalid job file.
+	Job,
+
+	#[cfg(feature = "bin")]
+	/// # Invalid job count.
+	Jobs,
+
+	#[cfg(feature = "bin")]
+	/// # Invalid JPEG XL tuning.
+	JxlTuning,
+
+	#[cfg(feature = "bin")]
+	/// # Invalid lossless threshold.
+	LosslessAbove,
+
+	#[cfg(feature = "bin")]
+	/// # Invalid max depth.
+	MaxDepth,
+
+	#[cfg(feature = "bin")]
+	/// # Invalid size budget.
+	MaxSize,
+
+	#[cfg(feature = "bin")]
+	/// # Invalid candidate cap.
+	MaxSteps,
+
 	#[cfg(feature = "bin")]
 	/// # No source image set.
 	MissingSource,
 
+	#[cfg(feature = "bin")]
+	/// # Invalid name template.
+	NameTemplate,
+
 	#[cfg(feature = "bin")]
 	/// # No encoders enabled.
 	NoEncoders,
 
+	#[cfg(feature = "bin")]
+	/// # Unknown preset.
+	NoPreset,
+
+	#[cfg(feature = "bin")]
+	/// # Nothing to review.
+	NoReview,
+
+	#[cfg(feature = "bin")]
+	/// # Invalid progress format.
+	Progress,
+
+	#[cfg(feature = "bin")]
+	/// # Invalid output directory.
+	OutDir,
+
+	#[cfg(feature = "bin")]
+	/// # Invalid pipe format.
+	PipeFormat,
+
+	#[cfg(feature = "bin")]
+	/// # Invalid pipe quality.
+	PipeQuality,
+
+	#[cfg(feature = "bin")]
+	/// # Invalid preview size.
+	PreviewMax,
+
+	#[cfg(feature = "bin")]
+	/// # Invalid quality override.
+	QualityOverride,
+
+	#[cfg(feature = "bin")]
+	/// # Invalid region of interest.
+	Region,
+
+	#[cfg(feature = "bin")]
+	/// # Invalid report interval.
+	ReportEvery,
+
+	#[cfg(feature = "bin")]
+	/// # Invalid streaming threshold.
+	StreamAbove,
+
+	#[cfg(feature = "bin")]
+	/// # Invalid time limit.
+	TimeLimit,
+
+	#[cfg(feature = "bin")]
+	/// # Invalid WebP method.
+	WebpMethod,
+
 	#[cfg(feature = "bin")]
 	/// # Result was ont saved.
 	NoSave,
@@ -112,6 +458,18 @@ pub enum RefractError {
 	/// # I/O read error.
 	Read,
 
+	#[cfg(feature = "bin")]
+	/// # Replay mismatch.
+	Replay,
+
+	#[cfg(feature = "bin")]
+	/// # Output would replace the source.
+	SaveSource,
+
+	#[cfg(feature = "bin")]
+	/// # Invalid feedback script.
+	Script,
+
 	#[cfg(feature = "bin")]
 	/// # I/O write error.
 	Write,
@@ -141,14 +499,23 @@ impl fmt::Display for RefractError {
 
 impl RefractError {
 	#[must_use]
+	#[expect(clippy::too_many_lines, reason = "It is just a big match.")]
 	/// # As Str.
 	///
 	/// Return the error as an English string slice.
 	pub const fn as_str(self) -> &'static str {
 		match self {
+			Self::Cancelled => "The encoding was cancelled.",
 			Self::Color => "Unsupported color encoding format.",
+			Self::ColorJpeg16 => "16-bit (lossless) JPEGs are not supported; re-save the source as a PNG — 16-bit is fine — or an 8-bit JPEG first.",
 			Self::Decode => "The image could not be decoded.",
+			Self::Dimensions => "The candidate's dimensions do not match the source's; it has been discarded.",
+			Self::Empty => "The file is empty (zero bytes); if it was downloaded, try downloading it again.",
 			Self::Encode => "The image could not be encoded.",
+
+			#[cfg(feature = "avif_dav1d")]
+			Self::CrossDecode => "The AVIF could not be decoded by dav1d, so browsers may not be able to display it.",
+
 			Self::Image => "Invalid image.",
 			Self::ImageDecode(k) => match k {
 				ImageKind::Avif => "Refract cannot decode AVIF images.",
@@ -157,35 +524,130 @@ impl RefractError {
 				_ => "",
 			},
 			Self::ImageEncode(k) => match k {
-				ImageKind::Jpeg => "Refract cannot encode JPEG files.",
-				ImageKind::Png => "Refract cannot encode PNG files.",
-				_ => "",
+				ImageKind::Avif => "Refract cannot encode AVIF images.",
+				ImageKind::Jpeg => "Refract cannot encode JPEG images.",
+				ImageKind::Jxl => "Refract cannot encode JPEG XL images.",
+				ImageKind::Webp => "Refract cannot encode WebP images.",
+				ImageKind::Png => "",
 			},
 			Self::NoBest(k) => match k {
 				ImageKind::Avif => "No acceptable AVIF candidate was found.",
+				ImageKind::Jpeg => "No acceptable JPEG candidate was found.",
 				ImageKind::Jxl => "No acceptable JPEG XL candidate was found.",
+				ImageKind::Png => "No acceptable PNG candidate was found.",
 				ImageKind::Webp => "No acceptable WebP candidate was found.",
-				_ => "",
 			},
 			Self::NothingDoing => "There is nothing else to do.",
 			Self::Overflow => "The image dimensions are out of range.",
+			Self::TimedOut => "The encoder ran out of time.",
 			Self::TooBig => "The encoded image was too big.",
+			Self::Truncated(k) => match k {
+				ImageKind::Avif => "Truncated file (expected more AVIF data per its box headers); if it was downloaded, try downloading it again.",
+				ImageKind::Jpeg => "Truncated file (expected a JPEG end-of-image marker); if it was downloaded, try downloading it again.",
+				ImageKind::Jxl => "Truncated file (expected more JPEG XL data per its box headers); if it was downloaded, try downloading it again.",
+				ImageKind::Png => "Truncated file (expected a PNG IEND chunk); if it was downloaded, try downloading it again.",
+				ImageKind::Webp => "Truncated file (expected more WebP data per its RIFF header); if it was downloaded, try downloading it again.",
+			},
+
+			#[cfg(feature = "bin")]
+			Self::AvifCodec => "The --avif-codec must be aom or, if built with the avif_rav1e feature, rav1e.",
+
+			#[cfg(feature = "bin")]
+			Self::Config => "Unable to parse the config file.",
+
+			#[cfg(feature = "bin")]
+			Self::Existing => "The --existing policy must be ask, skip, overwrite, or smaller.",
+
+			#[cfg(feature = "bin")]
+			Self::Fallback => "The --fallback mode must be copy, hardlink, or symlink.",
 
 			#[cfg(feature = "bin")]
 			Self::GtkInit => "Failed to initialize GTK.",
 
+			#[cfg(feature = "bin")]
+			Self::Job => "Unable to parse the job file.",
+
+			#[cfg(feature = "bin")]
+			Self::Jobs => "The --jobs must be a whole number between 0 and 255.",
+
+			#[cfg(feature = "bin")]
+			Self::JxlTuning => "The --jxl-effort must be between 1 and 9, and the --jxl-decoding-speed between 0 and 4.",
+
+			#[cfg(feature = "bin")]
+			Self::LosslessAbove => "The --lossless-above value must be a non-negative whole number of bytes, optionally suffixed with K or M.",
+
+			#[cfg(feature = "bin")]
+			Self::MaxDepth => "The --max-depth must be a non-negative whole number.",
+
+			#[cfg(feature = "bin")]
+			Self::MaxSize => "The max size must be a non-negative whole number of bytes, optionally suffixed with K or M.",
+
+			#[cfg(feature = "bin")]
+			Self::MaxSteps => "The --max-steps must be a whole number between 0 and 100.",
+
 			#[cfg(feature = "bin")]
 			Self::MissingSource => "A source image must be set before a candidate image.",
 
+			#[cfg(feature = "bin")]
+			Self::NameTemplate => "The --name-template must be a file name containing {ext}.",
+
 			#[cfg(feature = "bin")]
 			Self::NoEncoders => "At least one encoder must be enabled.",
 
+			#[cfg(feature = "bin")]
+			Self::NoPreset => "The requested preset is neither built in nor defined in the config file.",
+
+			#[cfg(feature = "bin")]
+			Self::NoReview => "No saved conversions of that image were found.",
+
+			#[cfg(feature = "bin")]
+			Self::Progress => "The only supported --progress format is \"json\".",
+
+			#[cfg(feature = "bin")]
+			Self::OutDir => "The --out-dir could not be created.",
+
+			#[cfg(feature = "bin")]
+			Self::PipeFormat => "The --stdin mode requires a --format of avif, jpg, jxl, png, or webp.",
+
+			#[cfg(feature = "bin")]
+			Self::PipeQuality => "The --quality must be a lossy quality in the --format's own scale, e.g. 80 for webp or 28 for avif.",
+
+			#[cfg(feature = "bin")]
+			Self::PreviewMax => "The --preview-max must be a non-negative whole number.",
+
+			#[cfg(feature = "bin")]
+			Self::QualityOverride => "Each --quality override must be FORMAT=VALUE or FORMAT=LOW-HIGH, in the format's own scale, e.g. avif=28.",
+
+			#[cfg(feature = "bin")]
+			Self::Region => "The --region must be WIDTHxHEIGHT+X+Y, or WIDTHxHEIGHT to center it, e.g. 512x512+100+40.",
+
+			#[cfg(feature = "bin")]
+			Self::ReportEvery => "The --report-every value must be a positive whole number.",
+
+			#[cfg(feature = "bin")]
+			Self::StreamAbove => "The --stream-above value must be a non-negative whole number of megapixels.",
+
+			#[cfg(feature = "bin")]
+			Self::TimeLimit => "The --time-limit value must be a non-negative whole number of seconds.",
+
+			#[cfg(feature = "bin")]
+			Self::WebpMethod => "The --webp-method must be between 0 and 6.",
+
 			#[cfg(feature = "bin")]
 			Self::NoSave => "The result was not saved.",
 
 			#[cfg(feature = "bin")]
 			Self::Read => "Unable to read the source file.",
 
+			#[cfg(feature = "bin")]
+			Self::Replay => "The replay did not reproduce the recorded results.",
+
+			#[cfg(feature = "bin")]
+			Self::SaveSource => "The output path is the source image itself; it has not been overwritten.",
+
+			#[cfg(feature = "bin")]
+			Self::Script => "Unable to parse the feedback script.",
+
 			#[cfg(feature = "bin")]
 			Self::Write => "Unable to save the file.",
 
@@ -197,3 +659,164 @@ impl RefractError {
 		}
 	}
 }
+
+
+
+thread_local! {
+	/// # Upstream Error Message.
+	///
+	/// [`RefractError`] is a simple `Copy` enum, so the specifics reported by
+	/// third-party libraries — libavif's result strings, etc. — are stashed
+	/// here instead, right before the corresponding error is returned, for
+	/// [`ErrorContext::new`] to pick up.
+	static UPSTREAM: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// # Set Upstream Error Message.
+pub(crate) fn set_upstream<S: Into<String>>(msg: S) {
+	UPSTREAM.with_borrow_mut(|v| { v.replace(msg.into()); });
+}
+
+/// # Clear Upstream Error Message.
+///
+/// This is called before each encoding or decoding attempt so a stale message
+/// can't get attached to some later, unrelated error.
+pub(crate) fn clear_upstream() {
+	UPSTREAM.with_borrow_mut(|v| { v.take(); });
+}
+
+
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+/// # Error With Context.
+///
+/// This wraps a [`RefractError`] with whatever else is known about the
+/// failure — the source path, the output format, the underlying cause, and
+/// the message from the library that actually failed — so that batch logs
+/// can say _which_ image broke, and how.
+///
+/// ## Examples
+///
+/// ```
+/// use refract_core::{ErrorContext, ImageKind, RefractError};
+///
+/// let err = ErrorContext::new(RefractError::NoBest(ImageKind::Avif))
+///     .with_path("/tmp/cats.jpg")
+///     .with_kind(ImageKind::Avif)
+///     .with_cause(RefractError::Encode);
+///
+/// assert_eq!(err.error(), RefractError::NoBest(ImageKind::Avif));
+/// assert_eq!(
+///     err.to_string(),
+///     "/tmp/cats.jpg: AVIF: No acceptable AVIF candidate was found. The image could not be encoded.",
+/// );
+/// ```
+pub struct ErrorContext {
+	/// # Error.
+	err: RefractError,
+
+	/// # Source Path.
+	path: Option<PathBuf>,
+
+	/// # Output Format.
+	kind: Option<ImageKind>,
+
+	/// # Underlying Error.
+	cause: Option<RefractError>,
+
+	/// # Upstream Message.
+	upstream: Option<String>,
+}
+
+impl Error for ErrorContext {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		self.cause.as_ref().map(|e| -> &(dyn Error + 'static) { e })
+	}
+}
+
+impl fmt::Display for ErrorContext {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if let Some(path) = &self.path { write!(f, "{}: ", path.display())?; }
+		if let Some(kind) = self.kind { write!(f, "{kind}: ")?; }
+		f.write_str(self.err.as_str())?;
+		if let Some(cause) = self.cause { write!(f, " {cause}")?; }
+		if let Some(upstream) = &self.upstream { write!(f, " ({upstream})")?; }
+		Ok(())
+	}
+}
+
+impl From<RefractError> for ErrorContext {
+	#[inline]
+	fn from(err: RefractError) -> Self { Self::new(err) }
+}
+
+impl ErrorContext {
+	#[must_use]
+	/// # New.
+	///
+	/// Wrap the error, along with the upstream library's message about it, if
+	/// any. (Messages are tracked per-thread, so this should be called from
+	/// the thread that did the encoding or decoding.)
+	pub fn new(err: RefractError) -> Self {
+		let upstream =
+			if matches!(err, RefractError::Decode | RefractError::Encode | RefractError::NoBest(_)) {
+				UPSTREAM.with_borrow_mut(Option::take)
+			}
+			else { None };
+
+		Self { err, path: None, kind: None, cause: None, upstream }
+	}
+
+	#[must_use]
+	/// # With Path.
+	pub fn with_path<P: AsRef<Path>>(self, path: P) -> Self {
+		Self { path: Some(path.as_ref().to_path_buf()), ..self }
+	}
+
+	#[must_use]
+	/// # With Output Format.
+	pub fn with_kind(self, kind: ImageKind) -> Self {
+		Self { kind: Some(kind), ..self }
+	}
+
+	#[must_use]
+	/// # With Cause.
+	///
+	/// Attach the underlying error, e.g. why an encoder came up empty. This
+	/// is ignored if it is the same as the main error.
+	pub fn with_cause(self, cause: RefractError) -> Self {
+		if cause == self.err { self }
+		else { Self { cause: Some(cause), ..self } }
+	}
+}
+
+/// ## Getters.
+impl ErrorContext {
+	#[inline]
+	#[must_use]
+	/// # Error.
+	pub const fn error(&self) -> RefractError { self.err }
+
+	#[inline]
+	#[must_use]
+	/// # Source Path.
+	pub fn path(&self) -> Option<&Path> { self.path.as_deref() }
+
+	#[inline]
+	#[must_use]
+	/// # Output Format.
+	pub const fn kind(&self) -> Option<ImageKind> { self.kind }
+
+	#[inline]
+	#[must_use]
+	/// # Underlying Error.
+	pub const fn cause(&self) -> Option<RefractError> { self.cause }
+
+	#[inline]
+	#[must_use]
+	/// # Upstream Message.
+	///
+	/// Return the message reported by the third-party library that failed,
+	/// if any.
+	pub fn upstream(&self) -> Option<&str> { self.upstream.as_deref() }
+}
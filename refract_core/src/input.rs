@@ -5,23 +5,69 @@
 use crate::{
 	ColorKind,
 	ImageKind,
+	kind::{
+		icc::IccTransform,
+		meta::Metadata,
+	},
 	RefractError,
 };
 use std::{
-	borrow::{
-		Borrow,
-		Cow,
-	},
 	fmt,
 	num::{
 		NonZeroU32,
 		NonZeroUsize,
 	},
 	ops::Deref,
+	sync::Arc,
 };
 
 
 
+#[derive(Clone)]
+/// # Pixel Storage.
+///
+/// This works like a `Cow`, with a third option: a reference-counted buffer
+/// that can be handed off to other threads without copying. (See
+/// [`Input::share_pixels`].)
+enum Pixels<'a> {
+	/// # Borrowed.
+	Borrowed(&'a [u8]),
+
+	/// # Owned.
+	Owned(Vec<u8>),
+
+	/// # Shared.
+	Shared(Arc<[u8]>),
+}
+
+impl Deref for Pixels<'_> {
+	type Target = [u8];
+
+	#[inline]
+	fn deref(&self) -> &Self::Target {
+		match self {
+			Self::Borrowed(buf) => buf,
+			Self::Owned(buf) => buf,
+			Self::Shared(buf) => buf,
+		}
+	}
+}
+
+impl Pixels<'_> {
+	/// # Into Owned.
+	///
+	/// Return an owned buffer, copying the pixels if need be.
+	fn into_owned(self) -> Vec<u8> {
+		match self {
+			Self::Owned(buf) => buf,
+			Self::Borrowed(buf) => buf.to_vec(),
+			Self::Shared(buf) => buf.to_vec(),
+		}
+	}
+}
+
+
+
 #[derive(Clone)]
 /// # Input Image.
 ///
@@ -57,7 +103,7 @@ use std::{
 /// ```
 pub struct Input<'a> {
 	/// # Image Pixels.
-	pixels: Cow<'a, [u8]>,
+	pixels: Pixels<'a>,
 
 	/// # Image Width.
 	width: NonZeroU32,
@@ -76,6 +122,21 @@ pub struct Input<'a> {
 
 	/// # Image Kind.
 	kind: ImageKind,
+
+	/// # Source Bit Depth.
+	bits: u8,
+
+	/// # Non-sRGB Color Profile.
+	icc: bool,
+
+	/// # Source Metadata.
+	meta: Metadata,
+
+	/// # Full-Precision Pixels.
+	///
+	/// For sources with more than 8 bits per channel, this holds a 16-bit
+	/// RGBA copy of the pixels for the encoders that can use them.
+	wide: Option<Arc<[u16]>>,
 }
 
 impl AsRef<[u8]> for Input<'_> {
@@ -92,6 +153,10 @@ impl fmt::Debug for Input<'_> {
 		.field("color", &self.color)
 		.field("depth", &self.depth)
 		.field("kind", &self.kind)
+		.field("bits", &self.bits)
+		.field("icc", &self.icc)
+		.field("meta", &self.meta)
+		.field("wide", &self.wide.is_some())
 		.finish()
 	}
 }
@@ -100,15 +165,29 @@ impl Deref for Input<'_> {
 	type Target = [u8];
 
 	#[inline]
-	fn deref(&self) -> &Self::Target { self.pixels.as_ref() }
+	fn deref(&self) -> &Self::Target { &self.pixels }
 }
 
 impl TryFrom<&[u8]> for Input<'_> {
 	type Error = RefractError;
 
 	fn try_from(src: &[u8]) -> Result<Self, Self::Error> {
+		crate::error::clear_upstream();
 		let kind = ImageKind::try_from(src)?;
-		let (buf, width, height, color) = kind.decode(src)?;
+		let ((mut buf, width, height, color), mut wide) = kind.decode_wide(src).map_err(|e|
+			// A cut-off file is worth calling out specifically.
+			if e == RefractError::Decode && kind.is_truncated(src) { RefractError::Truncated(kind) }
+			else { e }
+		)?;
+
+		// The buffer should hold exactly width × height RGBA pixels; the encoders
+		// will be passing it to C, so there's no room for wiggle.
+		if
+			Some(buf.len()) != width.checked_mul(height).and_then(|x| x.checked_mul(4)) ||
+			wide.as_ref().is_some_and(|w| w.len() != buf.len())
+		{
+			return Err(RefractError::Decode);
+		}
 
 		// Make sure the dimensions are in range.
 		let width = u32::try_from(width).ok()
@@ -122,20 +201,47 @@ impl TryFrom<&[u8]> for Input<'_> {
 		// This shouldn't fail since the image decoded, but just in case…
 		let size = NonZeroUsize::new(src.len()).ok_or(RefractError::Image)?;
 
+		// Note any color details that got lost in translation.
+		let (bits, mut icc) = kind.inspect(src);
+		let mut meta = kind.metadata(src);
+
+		// Convert wide-gamut sources to sRGB so they look the same here as
+		// they do in a browser. The profile no longer applies afterwards.
+		if let Some(xform) = meta.icc().and_then(IccTransform::new) {
+			xform.apply(&mut buf);
+			if let Some(w) = wide.as_mut() { xform.apply_wide(w); }
+			meta = meta.without_icc();
+			icc = false;
+		}
+
 		Ok(Self {
-			pixels: Cow::Owned(buf),
+			pixels: Pixels::Owned(buf),
 			width,
 			height,
 			size,
 			color,
 			depth: ColorKind::Rgba,
 			kind,
+			bits,
+			icc,
+			meta,
+			wide: wide.map(Arc::from),
 		})
 	}
 }
 
 /// ## Getters.
 impl Input<'_> {
+	#[inline]
+	#[must_use]
+	/// # Source Bit Depth.
+	///
+	/// This returns the per-channel bit depth of the original source.
+	///
+	/// Refract works with 8-bit channels, so anything higher is downsampled
+	/// during decoding, but see [`Input::is_high_bit_depth`].
+	pub const fn bit_depth(&self) -> u8 { self.bits }
+
 	#[inline]
 	#[must_use]
 	/// # Color Kind.
@@ -163,6 +269,66 @@ impl Input<'_> {
 	/// This returns true if any pixel has an alpha value other than `255`.
 	pub const fn has_alpha(&self) -> bool { self.color.has_alpha() }
 
+	#[inline]
+	#[must_use]
+	/// # Has Color Profile?
+	///
+	/// This returns true if the original source embedded a (non-sRGB) ICC
+	/// color profile that could not be converted to sRGB. Refract treats all
+	/// pixels as sRGB, so such images may look a little different once
+	/// re-encoded.
+	///
+	/// Matrix/TRC RGB profiles — Display P3, Adobe RGB, etc. — are converted
+	/// during decoding and do not count.
+	pub const fn has_color_profile(&self) -> bool { self.icc }
+
+	#[inline]
+	#[must_use]
+	/// # Is High Bit Depth?
+	///
+	/// This returns true if the source has meaningful precision beyond 8 bits
+	/// per channel, a full copy of which is retained for the encoders that
+	/// support it. (See [`ImageKind::supports_high_bit_depth`].)
+	pub const fn is_high_bit_depth(&self) -> bool { self.wide.is_some() }
+
+	#[cfg(any(feature = "avif", feature = "jxl", feature = "webp", feature = "webp_rust"))]
+	#[inline]
+	#[must_use]
+	/// # Metadata.
+	///
+	/// Return the ICC profile and EXIF data carried over from the source, if
+	/// any.
+	pub(crate) const fn metadata(&self) -> &Metadata { &self.meta }
+
+	#[cfg(feature = "avif")]
+	#[inline]
+	#[must_use]
+	/// # Full-Precision RGBA Pixels.
+	///
+	/// Return the 16-bit RGBA pixels, if the source has them.
+	pub(crate) fn wide_rgba(&self) -> Option<&[u16]> { self.wide.as_deref() }
+
+	#[cfg(feature = "jxl")]
+	#[must_use]
+	/// # Full-Precision Native Pixels.
+	///
+	/// Return a copy of the 16-bit pixels, if any, reduced to only those
+	/// channels actually used by the source (like [`Input::as_native`]).
+	pub(crate) fn wide_native(&self) -> Option<Vec<u16>> {
+		let wide = self.wide.as_deref()?;
+		let keep: &[usize] = match self.color {
+			ColorKind::Grey => &[0],
+			ColorKind::GreyAlpha => &[0, 3],
+			ColorKind::Rgb => &[0, 1, 2],
+			ColorKind::Rgba => &[0, 1, 2, 3],
+		};
+		Some(
+			wide.chunks_exact(4)
+				.flat_map(|px| keep.iter().map(|&i| px[i]))
+				.collect()
+		)
+	}
+
 	#[inline]
 	#[must_use]
 	/// # Height.
@@ -202,6 +368,24 @@ impl Input<'_> {
 		self.width.get() as usize * self.depth.channels() as usize
 	}
 
+	#[must_use]
+	/// # Share Pixels.
+	///
+	/// Move the pixels into a reference-counted buffer — unless they're
+	/// already in one — and return a handle to it, so that e.g. a preview can
+	/// display the very same pixels the encoders read from, rather than a
+	/// copy of its own.
+	///
+	/// Moving an owned buffer takes one last copy, but the original is freed
+	/// straight after, so the two only coexist for a moment.
+	pub fn share_pixels(&mut self) -> Arc<[u8]> {
+		if let Pixels::Shared(buf) = &self.pixels { return Arc::clone(buf); }
+
+		let buf: Arc<[u8]> = Arc::from(&*self.pixels);
+		self.pixels = Pixels::Shared(Arc::clone(&buf));
+		buf
+	}
+
 	#[inline]
 	#[must_use]
 	/// # Size.
@@ -318,15 +502,15 @@ impl<'a> Input<'a> {
 		if self.color == self.depth { return self.borrow(); }
 		assert!(self.depth == ColorKind::Rgba, "BUG: expected RGBA color.");
 
-		let (buf, depth): (Cow<[u8]>, ColorKind) = match self.color {
+		let (buf, depth): (Pixels, ColorKind) = match self.color {
 			ColorKind::Grey => (
-				Cow::Owned(
+				Pixels::Owned(
 					self.pixels.chunks_exact(4).map(|px| px[0]).collect()
 				),
 				ColorKind::Grey,
 			),
 			ColorKind::GreyAlpha => (
-				Cow::Owned(
+				Pixels::Owned(
 					self.pixels.chunks_exact(4)
 					.fold(Vec::with_capacity(self.width() * self.height() * 2), |mut acc, px| {
 						acc.push(px[0]); // Keep one color.
@@ -337,7 +521,7 @@ impl<'a> Input<'a> {
 				ColorKind::GreyAlpha,
 			),
 			ColorKind::Rgb => (
-				Cow::Owned(
+				Pixels::Owned(
 					self.pixels.chunks_exact(4)
 					.fold(Vec::with_capacity(self.width() * self.height() * 3), |mut acc, px| {
 						acc.extend_from_slice(&px[..3]); // Keep RGB.
@@ -347,7 +531,7 @@ impl<'a> Input<'a> {
 				ColorKind::Rgb,
 			),
 			// This shouldn't be reachable, but is painless enough to include.
-			ColorKind::Rgba => (Cow::Borrowed(self.pixels.borrow()), ColorKind::Rgba),
+			ColorKind::Rgba => (Pixels::Borrowed(&self.pixels), ColorKind::Rgba),
 		};
 
 		assert!(
@@ -363,6 +547,10 @@ impl<'a> Input<'a> {
 			color: self.color,
 			depth,
 			kind: self.kind,
+			bits: self.bits,
+			icc: self.icc,
+			meta: self.meta.clone(),
+			wide: self.wide.clone(),
 		}
 	}
 
@@ -383,9 +571,9 @@ impl<'a> Input<'a> {
 		// The expected size.
 		let size = self.width() * self.height() * 4;
 
-		let buf: Cow<[u8]> = match self.depth {
-			ColorKind::Rgba => Cow::Borrowed(self.pixels.borrow()),
-			ColorKind::Rgb => Cow::Owned(
+		let buf: Pixels = match self.depth {
+			ColorKind::Rgba => Pixels::Borrowed(&self.pixels),
+			ColorKind::Rgb => Pixels::Owned(
 				self.pixels.chunks_exact(3)
 				.fold(Vec::with_capacity(size), |mut acc, px| {
 					acc.extend_from_slice(px); // Push RGB.
@@ -393,14 +581,14 @@ impl<'a> Input<'a> {
 					acc
 				})
 			),
-			ColorKind::GreyAlpha => Cow::Owned(
+			ColorKind::GreyAlpha => Pixels::Owned(
 				self.pixels.chunks_exact(2)
 				.fold(Vec::with_capacity(size), |mut acc, px| {
 					acc.extend_from_slice(&[px[0], px[0], px[0], px[1]]);
 					acc
 				})
 			),
-			ColorKind::Grey => Cow::Owned(
+			ColorKind::Grey => Pixels::Owned(
 				self.pixels.iter()
 				.copied()
 				.fold(Vec::with_capacity(size), |mut acc, px| {
@@ -421,7 +609,116 @@ impl<'a> Input<'a> {
 			color: self.color,
 			depth: ColorKind::Rgba,
 			kind: self.kind,
+			bits: self.bits,
+			icc: self.icc,
+			meta: self.meta.clone(),
+			wide: self.wide.clone(),
+		}
+	}
+
+	#[must_use]
+	/// ## Into Greyscale.
+	///
+	/// Flatten the colors to their (sRGB) luminance, returning an owned
+	/// greyscale instance with a 4-byte RGBA buffer. Alpha, if any, is
+	/// preserved.
+	///
+	/// This is a lossy, opt-in preprocessing step, but can be worth it for
+	/// sources like scanned documents, as it lets the encoders take their
+	/// greyscale fast paths.
+	///
+	/// If the source is already greyscale, it is returned as-is. Otherwise
+	/// any full-precision (16-bit) copy of the pixels is dropped.
+	pub fn into_greyscale(self) -> Self {
+		if self.color.is_greyscale() { return self; }
+
+		let channels = self.depth.channels() as usize;
+		let buf: Vec<u8> = self.pixels.chunks_exact(channels)
+			.fold(Vec::with_capacity(self.width() * self.height() * 4), |mut acc, px| {
+				// Rec. 709 weights, scaled to 256.
+				let y = (
+					u32::from(px[0]) * 54 +
+					u32::from(px[1]) * 183 +
+					u32::from(px[2]) * 19
+				) >> 8;
+				let y = u8::try_from(y).unwrap_or(u8::MAX);
+				acc.extend_from_slice(&[y, y, y, px.get(3).copied().unwrap_or(255)]);
+				acc
+			});
+
+		Self {
+			pixels: Pixels::Owned(buf),
+			width: self.width,
+			height: self.height,
+			size: self.size,
+			color:
+				if self.color.has_alpha() { ColorKind::GreyAlpha }
+				else { ColorKind::Grey },
+			depth: ColorKind::Rgba,
+			kind: self.kind,
+			bits: self.bits,
+			icc: self.icc,
+			meta: self.meta,
+			wide: None,
+		}
+	}
+
+	#[must_use]
+	/// ## Crop.
+	///
+	/// Return an owned copy of the `width`×`height` rectangle with its top
+	/// left corner at `x`, `y`, or `None` if it is empty, runs past the edges
+	/// of the image, or is simply the whole thing.
+	///
+	/// The original file size is scaled to the crop's share of the pixels,
+	/// so savings are judged in proportion.
+	pub fn crop(&self, x: usize, y: usize, width: usize, height: usize)
+	-> Option<Input<'static>> {
+		let right = x.checked_add(width)?;
+		let bottom = y.checked_add(height)?;
+		if
+			self.width() < right ||
+			self.height() < bottom ||
+			(width == self.width() && height == self.height())
+		{
+			return None;
 		}
+
+		let channels = self.depth.channels() as usize;
+		let pixels: Vec<u8> = self.pixels.chunks_exact(self.width() * channels)
+			.skip(y)
+			.take(height)
+			.fold(Vec::with_capacity(width * height * channels), |mut acc, row| {
+				acc.extend_from_slice(&row[x * channels..right * channels]);
+				acc
+			});
+		let wide = self.wide.as_ref().map(|wide| wide.chunks_exact(self.width() * 4)
+			.skip(y)
+			.take(height)
+			.fold(Vec::with_capacity(width * height * 4), |mut acc, row| {
+				acc.extend_from_slice(&row[x * 4..right * 4]);
+				acc
+			})
+			.into()
+		);
+
+		let share = u128::try_from(width * height).ok()?;
+		let total = u128::try_from(self.width() * self.height()).ok()?;
+		let size = u128::try_from(self.size()).ok()? * share / total;
+
+		Some(Input {
+			pixels: Pixels::Owned(pixels),
+			width: NonZeroU32::new(u32::try_from(width).ok()?)?,
+			height: NonZeroU32::new(u32::try_from(height).ok()?)?,
+			size: NonZeroUsize::new(usize::try_from(size).ok()?.max(1))?,
+			color: self.color,
+			depth: self.depth,
+			kind: self.kind,
+			bits: self.bits,
+			icc: self.icc,
+			meta: self.meta.clone(),
+			wide,
+		})
 	}
 
 	#[inline]
@@ -432,13 +729,69 @@ impl<'a> Input<'a> {
 	/// same format as the original), avoiding unnecessary reallocation.
 	pub fn borrow(&'a self) -> Self {
 		Self {
-			pixels: Cow::Borrowed(self.pixels.borrow()),
+			pixels: Pixels::Borrowed(&self.pixels),
 			width: self.width,
 			height: self.height,
 			size: self.size,
 			color: self.color,
 			depth: self.depth,
 			kind: self.kind,
+			bits: self.bits,
+			icc: self.icc,
+			meta: self.meta.clone(),
+			wide: self.wide.clone(),
 		}
 	}
 }
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn t_crop() {
+		let src = Input::try_from(&include_bytes!("../../skel/assets/poe.png")[..])
+			.expect("Unable to read test image.");
+		let channels = src.depth().channels() as usize;
+
+		let crop = src.crop(3, 2, 10, 5).expect("Crop failed.");
+		assert_eq!(crop.width(), 10);
+		assert_eq!(crop.height(), 5);
+		assert_eq!(crop.depth(), src.depth());
+		assert_eq!(crop.len(), 10 * 5 * channels);
+		assert!(crop.size() < src.size());
+
+		// The first pixel should be the source's (3, 2).
+		let start = (2 * src.width() + 3) * channels;
+		assert_eq!(&crop[..channels], &src[start..start + channels]);
+
+		// The last row should be the source's row 6.
+		let start = (6 * src.width() + 3) * channels;
+		assert_eq!(&crop[4 * 10 * channels..], &src[start..start + 10 * channels]);
+
+		// Nothing, too much, or everything.
+		assert!(src.crop(0, 0, 0, 5).is_none());
+		assert!(src.crop(src.width() - 5, 0, 10, 5).is_none());
+		assert!(src.crop(0, 0, src.width(), src.height()).is_none());
+	}
+
+	#[test]
+	fn t_share_pixels() {
+		let mut src = Input::try_from(&include_bytes!("../../skel/assets/poe.png")[..])
+			.expect("Unable to read test image.");
+		let before = src.to_vec();
+
+		// Sharing shouldn't change anything, and should only happen once.
+		let a = src.share_pixels();
+		let b = src.share_pixels();
+		assert!(Arc::ptr_eq(&a, &b), "Pixels were shared twice.");
+		assert_eq!(&*a, before.as_slice());
+		assert_eq!(&*src, before.as_slice());
+
+		// The borrows should point at the very same buffer.
+		assert_eq!(src.as_rgba().as_ptr(), a.as_ptr());
+		assert_eq!(src.take_pixels(), before);
+	}
+}
@@ -2,45 +2,77 @@
 # `Refract`: `AVIF` Handling
 */
 
+use crate::NZ_063;
+use std::{
+	fmt,
+	num::NonZeroU8,
+};
+
+#[cfg(feature = "avif")]
 use crate::{
 	FLAG_AVIF_RGB,
+	FLAG_AVIF_YUV420,
+	FLAG_KEEP_METADATA,
 	Input,
-	NZ_063,
 	Output,
 	RefractError,
+	error::set_upstream,
 	traits::Encoder,
 };
+
+#[cfg(feature = "avif")]
 use libavif_sys::{
 	AVIF_CHROMA_DOWNSAMPLING_BEST_QUALITY,
 	AVIF_CHROMA_SAMPLE_POSITION_COLOCATED,
 	AVIF_CHROMA_UPSAMPLING_BILINEAR,
 	AVIF_CODEC_CHOICE_AOM,
+	AVIF_CODEC_CHOICE_RAV1E,
+	AVIF_CODEC_FLAG_CAN_ENCODE,
 	AVIF_COLOR_PRIMARIES_BT709,
 	AVIF_MATRIX_COEFFICIENTS_BT709,
 	AVIF_MATRIX_COEFFICIENTS_IDENTITY,
 	AVIF_PIXEL_FORMAT_YUV400,
+	AVIF_PIXEL_FORMAT_YUV420,
 	AVIF_PIXEL_FORMAT_YUV444,
+	AVIF_PLANES_A,
+	AVIF_PLANES_YUV,
 	AVIF_RANGE_FULL,
 	AVIF_RANGE_LIMITED,
 	AVIF_RESULT_OK,
 	AVIF_RGB_FORMAT_RGBA,
 	AVIF_TRANSFER_CHARACTERISTICS_SRGB,
+	avifCodecChoice,
+	avifCodecName,
 	avifEncoder,
 	avifEncoderCreate,
 	avifEncoderDestroy,
 	avifEncoderWrite,
+	avifCodecVersions,
 	avifImage,
+	avifImageAllocatePlanes,
 	avifImageCreate,
 	avifImageDestroy,
 	avifImageRGBToYUV,
+	avifImageSetMetadataExif,
+	avifImageSetProfileICC,
 	avifResult,
+	avifResultToString,
 	avifRGBImage,
 	avifRWData,
 	avifRWDataFree,
+	avifVersion,
+};
+
+#[cfg(feature = "avif")]
+use std::{
+	borrow::Cow,
+	ffi::{
+		c_char,
+		CStr,
+	},
 };
-use std::num::NonZeroU8;
 
-#[cfg(feature = "decode_ng")]
+#[cfg(all(feature = "avif", feature = "decode_ng"))]
 use crate::{
 	ColorKind,
 	traits::{
@@ -49,13 +81,20 @@ use crate::{
 	},
 };
 
-#[cfg(feature = "decode_ng")]
+#[cfg(all(feature = "avif", any(feature = "avif_dav1d", feature = "decode_ng")))]
 use libavif_sys::{
 	avifDecoder,
 	avifDecoderCreate,
 	avifDecoderDestroy,
 	avifDecoderReadMemory,
 	avifImageCreateEmpty,
+};
+
+#[cfg(feature = "avif_dav1d")]
+use libavif_sys::AVIF_CODEC_CHOICE_DAV1D;
+
+#[cfg(all(feature = "avif", feature = "decode_ng"))]
+use libavif_sys::{
 	avifImageYUVToRGB,
 	avifRGBImageAllocatePixels,
 	avifRGBImageFreePixels,
@@ -64,10 +103,128 @@ use libavif_sys::{
 
 
 
+#[derive(Debug, Clone, Copy, Default, Eq, Hash, PartialEq)]
+/// # AV1 Codec.
+///
+/// `libavif` leaves the actual AV1 compression to one of several codecs.
+/// `aom`, the reference implementation, is always built (alongside `libavif`
+/// itself, per the `avif` crate feature) and used by default; `rav1e` is only
+/// available with the `avif_rav1e` crate feature. Pass one to
+/// [`EncodeIter::set_avif_codec`](crate::EncodeIter::set_avif_codec) to
+/// change it.
+///
+/// Each codec gets its own speed, and the same quality can come out quite
+/// differently from one to the next, so it is best to stick with one codec
+/// for the whole of a given batch.
+///
+/// (`SVT-AV1` is not offered, as the bundled `libavif` cannot be built with
+/// it.)
+pub enum AvifCodec {
+	#[default]
+	/// # `aom`.
+	Aom,
+
+	/// # `rav1e`.
+	Rav1e,
+}
+
+impl fmt::Display for AvifCodec {
+	#[inline]
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
+impl AvifCodec {
+	/// # All Codecs.
+	pub const ALL: [Self; 2] = [Self::Aom, Self::Rav1e];
+
+	#[must_use]
+	/// # As Str.
+	pub const fn as_str(self) -> &'static str {
+		match self {
+			Self::Aom => "aom",
+			Self::Rav1e => "rav1e",
+		}
+	}
+
+	#[must_use]
+	/// # From Name.
+	///
+	/// Match a codec by its [`AvifCodec::as_str`] name.
+	pub fn from_name(name: &str) -> Option<Self> {
+		let name = name.trim();
+		Self::ALL.into_iter().find(|c| c.as_str() == name)
+	}
+
+	#[cfg(feature = "avif")]
+	#[expect(unsafe_code, reason = "Needed for FFI.")]
+	#[must_use]
+	/// # Is Available?
+	///
+	/// Returns true if the codec was built into this copy of `libavif`.
+	pub fn is_available(self) -> bool {
+		// Safety: this is an FFI call returning a static string, or null.
+		! unsafe { avifCodecName(self.choice(), AVIF_CODEC_FLAG_CAN_ENCODE) }.is_null()
+	}
+
+	#[cfg(not(feature = "avif"))]
+	#[must_use]
+	/// # Is Available?
+	///
+	/// Without the `avif` feature, there is no `libavif` and no codecs.
+	pub const fn is_available(self) -> bool { false }
+
+	#[cfg(feature = "avif")]
+	/// # Codec Choice.
+	const fn choice(self) -> avifCodecChoice {
+		match self {
+			Self::Aom => AVIF_CODEC_CHOICE_AOM,
+			Self::Rav1e => AVIF_CODEC_CHOICE_RAV1E,
+		}
+	}
+
+	#[cfg(feature = "avif")]
+	/// # Speed.
+	///
+	/// `aom` has a speed 0, but it is brutally slow and has very little
+	/// benefit, so 1 is the slowest worth using. `rav1e`'s own default is
+	/// plenty slow enough.
+	const fn speed(self) -> u8 {
+		match self {
+			Self::Aom => 1,
+			Self::Rav1e => 6,
+		}
+	}
+}
+
+
+
 /// # AVIF Image.
 pub(crate) struct ImageAvif;
 
-#[cfg(feature = "decode_ng")]
+impl ImageAvif {
+	/// # Maximum Quality.
+	pub(crate) const MAX_QUALITY: NonZeroU8 = NZ_063;
+
+	/// # Is Animated?
+	///
+	/// Image sequences are identified by an `avis` brand in the `ftyp` box,
+	/// either as the major brand or one of the compatible ones.
+	pub(crate) fn is_animated(raw: &[u8]) -> bool {
+		let Some(size) = raw.first_chunk::<4>()
+			.and_then(|s| usize::try_from(u32::from_be_bytes(*s)).ok())
+		else { return false; };
+
+		// The second chunk is the minor version rather than a brand.
+		raw.get(8..size).is_some_and(|brands| brands.chunks_exact(4)
+			.enumerate()
+			.any(|(k, b)| k != 1 && b == b"avis")
+		)
+	}
+}
+
+#[cfg(all(feature = "avif", feature = "decode_ng"))]
 impl Decoder for ImageAvif {
 	#[expect(unsafe_code, reason = "Needed for FFI.")]
 	fn decode(raw: &[u8]) -> Result<DecoderResult, RefractError> {
@@ -75,7 +232,7 @@ impl Decoder for ImageAvif {
 		let rgb = unsafe {
 			// Decode the raw image to an avifImage.
 			let image = LibAvifImage::empty()?;
-			let decoder = LibAvifDecoder::new()?;
+			let decoder = LibAvifDecoder::new(AVIF_CODEC_CHOICE_AOM)?;
 			if AVIF_RESULT_OK != avifDecoderReadMemory(
 				decoder.0,
 				image.0,
@@ -130,63 +287,104 @@ impl Decoder for ImageAvif {
 	}
 }
 
-impl Encoder for ImageAvif {
-	/// # Maximum Quality.
-	const MAX_QUALITY: NonZeroU8 = NZ_063;
-
+#[cfg(feature = "avif_dav1d")]
+impl ImageAvif {
 	#[expect(unsafe_code, reason = "Needed for FFI.")]
-	/// # Encode Lossy.
-	fn encode_lossy(img: &Input, candidate: &mut Output, quality: NonZeroU8, flags: u8)
-	-> Result<(), RefractError> {
-		let image = LibAvifImage::new(img, flags)?;
-		let encoder = LibAvifEncoder::try_from(quality)?;
+	/// # Verify With `dav1d`.
+	///
+	/// `libavif` (via `aom`) is sometimes more forgiving than the decoders
+	/// used by browsers. This re-decodes the data using `dav1d` — what most
+	/// of them actually use — to make sure real users will be able to view
+	/// the image.
+	///
+	/// ## Errors
+	///
+	/// Returns an error if `dav1d` rejects the image.
+	pub(crate) fn verify_dav1d(raw: &[u8]) -> Result<(), RefractError> {
+		let image = LibAvifImage::empty().map_err(|_| RefractError::CrossDecode)?;
+		let decoder = LibAvifDecoder::new(AVIF_CODEC_CHOICE_DAV1D)
+			.map_err(|_| RefractError::CrossDecode)?;
 
-		// Encode!
-		let mut data = LibAvifRwData(avifRWData::default());
 		// Safety: this is an FFI call…
-		maybe_die(unsafe { avifEncoderWrite(encoder.0, image.0, &mut data.0) })?;
-
-		// But make sure it gave us something.
-		if data.0.data.is_null() { return Err(RefractError::Encode); }
-
-		// Grab the output.
-		// Safety: the pointer is non-null; we have to trust libavif gave us
-		// the correct size.
-		candidate.set_slice(unsafe {
-			std::slice::from_raw_parts(data.0.data, data.0.size)
-		});
+		let res = unsafe {
+			avifDecoderReadMemory(decoder.0, image.0, raw.as_ptr(), raw.len())
+		};
 
-		drop(data);
-		drop(encoder);
-		drop(image);
+		if AVIF_RESULT_OK == res { Ok(()) }
+		else { Err(RefractError::CrossDecode) }
+	}
+}
 
-		Ok(())
+#[cfg(feature = "avif")]
+impl Encoder for ImageAvif {
+	#[inline]
+	/// # Encode Lossy.
+	fn encode_lossy(img: &Input, candidate: &mut Output, quality: NonZeroU8, flags: u8)
+	-> Result<(), RefractError> {
+		encode(img, candidate, Some(quality), flags)
 	}
 
 	#[inline]
 	/// # Encode Lossless.
+	///
+	/// This is true lossless: full-range, unsubsampled, identity-matrix
+	/// "YCbCr" (i.e. RGB), at quantizer zero. (Greyscale sources use a single
+	/// full-range luma plane instead.)
+	///
+	/// High bit depth sources are still capped at 10 bits, so are only
+	/// lossless to that degree.
 	fn encode_lossless(input: &Input, output: &mut Output, flags: u8)
 	-> Result<(), RefractError> {
-		if input.is_greyscale() { Err(RefractError::NothingDoing) }
-		else {
-			Self::encode_lossy(input, output, Self::MAX_QUALITY, flags)
-		}
+		encode(input, output, None, (flags | FLAG_AVIF_RGB) & ! FLAG_AVIF_YUV420)
+	}
+
+	#[expect(unsafe_code, reason = "Needed for FFI.")]
+	/// # Encoder Version.
+	fn version() -> String {
+		// libavif wants at least 256 bytes for this.
+		let mut codecs: [c_char; 256] = [0; 256];
+
+		// Safety: these are FFI calls returning nul-terminated strings.
+		let (lib, codecs) = unsafe {
+			avifCodecVersions(codecs.as_mut_ptr());
+			(CStr::from_ptr(avifVersion()), CStr::from_ptr(codecs.as_ptr()))
+		};
+
+		format!("libavif {} ({})", lib.to_string_lossy(), codecs.to_string_lossy())
+	}
+
+	/// # Encoder Settings.
+	///
+	/// The codec is only mentioned if it isn't the default.
+	fn settings(output: &Output) -> Cow<'static, str> {
+		let flags = output.flags();
+		let mode =
+			if output.quality().is_lossless() { "lossless RGB" }
+			else if 0 != flags & FLAG_AVIF_RGB { "full-range RGB" }
+			else if 0 != flags & FLAG_AVIF_YUV420 { "limited-range YCbCr 4:2:0" }
+			else { "limited-range YCbCr" };
+
+		let codec = output.avif_codec();
+		if codec == AvifCodec::Aom { Cow::Owned(format!("speed {}, {mode}", codec.speed())) }
+		else { Cow::Owned(format!("{codec} speed {}, {mode}", codec.speed())) }
 	}
 }
 
 
 
-#[cfg(feature = "decode_ng")]
+#[cfg(all(feature = "avif", any(feature = "avif_dav1d", feature = "decode_ng")))]
 /// # AVIF Decoder.
 ///
 /// This wraps the AVIF decoder. It exists solely for garbage cleanup.
 struct LibAvifDecoder(*mut avifDecoder);
 
-#[cfg(feature = "decode_ng")]
+#[cfg(all(feature = "avif", any(feature = "avif_dav1d", feature = "decode_ng")))]
 impl LibAvifDecoder {
 	#[expect(unsafe_code, reason = "Needed for FFI.")]
 	/// # New.
-	fn new() -> Result<Self, RefractError> {
+	///
+	/// Create a new decoder using the specified AV1 codec.
+	fn new(codec: avifCodecChoice) -> Result<Self, RefractError> {
 		// Safety: this is an FFI call…
 		let decoder = unsafe { avifDecoderCreate() };
 		if decoder.is_null() {
@@ -202,7 +400,7 @@ impl LibAvifDecoder {
 		// Safety: We're only holding a pointer; we need to dereference it to
 		// update the values.
 		unsafe {
-			(*decoder).codecChoice = AVIF_CODEC_CHOICE_AOM;
+			(*decoder).codecChoice = codec;
 			(*decoder).maxThreads = threads;
 		}
 
@@ -210,7 +408,7 @@ impl LibAvifDecoder {
 	}
 }
 
-#[cfg(feature = "decode_ng")]
+#[cfg(all(feature = "avif", any(feature = "avif_dav1d", feature = "decode_ng")))]
 impl Drop for LibAvifDecoder {
 	#[expect(unsafe_code, reason = "Needed for FFI.")]
 	#[inline]
@@ -222,20 +420,22 @@ impl Drop for LibAvifDecoder {
 
 
 
+#[cfg(feature = "avif")]
 /// # AVIF Encoder.
 ///
 /// This wraps the AVIF encoder. It primarily exists to give us a way to free
 /// resources on drop, but also handles setup.
 struct LibAvifEncoder(*mut avifEncoder);
 
-impl TryFrom<NonZeroU8> for LibAvifEncoder {
-	type Error = RefractError;
-
+#[cfg(feature = "avif")]
+impl LibAvifEncoder {
 	#[expect(unsafe_code, reason = "Needed for FFI.")]
 	/// # New Instance.
-	fn try_from(quality: NonZeroU8) -> Result<Self, RefractError> {
+	///
+	/// A `None` quality means lossless.
+	fn new(quality: Option<NonZeroU8>, codec: AvifCodec) -> Result<Self, RefractError> {
 		// Convert quality to quantizers. AVIF is so convoluted...
-		let (q, aq) = quality_to_quantizers(quality);
+		let (q, aq) = quality.map_or((0, 0), quality_to_quantizers);
 
 		// Total threads.
 		let threads = std::thread::available_parallelism().ok()
@@ -243,6 +443,12 @@ impl TryFrom<NonZeroU8> for LibAvifEncoder {
 			.unwrap_or(1)
 			.max(1);
 
+		// Make sure the codec is actually there.
+		if ! codec.is_available() {
+			set_upstream(format!("libavif: the {codec} codec is not available"));
+			return Err(RefractError::Encode);
+		}
+
 		// Start up the encoder!
 		// Safety: this is an FFI call…
 		let encoder = unsafe { avifEncoderCreate() };
@@ -251,7 +457,7 @@ impl TryFrom<NonZeroU8> for LibAvifEncoder {
 		// Safety: we're only holding a pointer; we need to dereference it to
 		// update the member values.
 		unsafe {
-			(*encoder).codecChoice = AVIF_CODEC_CHOICE_AOM;
+			(*encoder).codecChoice = codec.choice();
 			(*encoder).maxThreads = threads;
 
 			(*encoder).minQuantizer = i32::from(q);
@@ -260,15 +466,14 @@ impl TryFrom<NonZeroU8> for LibAvifEncoder {
 			(*encoder).minQuantizerAlpha = i32::from(aq);
 			(*encoder).maxQuantizerAlpha = i32::from(aq);
 
-			// There is a speed 0, but it is brutally slow and has very little
-			// benefit.
-			(*encoder).speed = 1;
+			(*encoder).speed = i32::from(codec.speed());
 		};
 
 		Ok(Self(encoder))
 	}
 }
 
+#[cfg(feature = "avif")]
 impl Drop for LibAvifEncoder {
 	#[expect(unsafe_code, reason = "Needed for FFI.")]
 	#[inline]
@@ -280,12 +485,14 @@ impl Drop for LibAvifEncoder {
 
 
 
+#[cfg(feature = "avif")]
 /// # Avif Image.
 ///
 /// The struct includes initialization helpers, but exists primarily for
 /// garbage cleanup.
 struct LibAvifImage(*mut avifImage);
 
+#[cfg(feature = "avif")]
 impl LibAvifImage {
 	#[expect(clippy::cast_possible_truncation, reason = "False positive.")]
 	#[expect(unsafe_code, reason = "Needed for FFI.")]
@@ -297,45 +504,37 @@ impl LibAvifImage {
 
 		// AVIF dimensions can't exceed this amount. We might as well bail as
 		// early as possible.
-		if src.width() * src.height() > 16_384 * 16_384 {
+		if src.width().checked_mul(src.height()).is_none_or(|x| x > 16_384 * 16_384) {
 			return Err(RefractError::Overflow);
 		}
 
 		let limited = 0 == flags & FLAG_AVIF_RGB;
 		let greyscale: bool = src.is_greyscale();
 
-		// Make an "avifRGBImage" from our buffer.
-		let raw: &[u8] = src;
-		let rgb = avifRGBImage {
-			width,
-			height,
-			depth: 8,
-			format: AVIF_RGB_FORMAT_RGBA,
-			chromaUpsampling: AVIF_CHROMA_UPSAMPLING_BILINEAR,
-			chromaDownsampling: AVIF_CHROMA_DOWNSAMPLING_BEST_QUALITY,
-			avoidLibYUV: 0,
-			ignoreAlpha: i32::from(! src.has_alpha()),
-			alphaPremultiplied: 0,
-			isFloat: 0,
-			maxThreads: 1,
-			pixels: raw.as_ptr().cast_mut(),
-			rowBytes: width * 4,
-		};
+		// Subsampling only works with YCbCr; RGB needs every sample.
+		let format =
+			if greyscale { AVIF_PIXEL_FORMAT_YUV400 }
+			else if limited && 0 != flags & FLAG_AVIF_YUV420 { AVIF_PIXEL_FORMAT_YUV420 }
+			else { AVIF_PIXEL_FORMAT_YUV444 };
+
+		// Full-precision pixels, if any, get encoded as 10-bit.
+		let wide = src.wide_rgba();
 
-		// And convert it to YUV.
 		// Safety: these are FFI calls…
 		let yuv = unsafe {
 			let tmp = avifImageCreate(
 				width,
 				height,
-				8, // Depth.
-				if greyscale { AVIF_PIXEL_FORMAT_YUV400 }
-				else { AVIF_PIXEL_FORMAT_YUV444 }
+				if wide.is_some() { 10 } else { 8 }, // Depth.
+				format,
 			);
 
 			// This shouldn't happen, but could, maybe.
 			if tmp.is_null() { return Err(RefractError::Encode); }
 
+			// Wrap it right away so it gets freed if anything goes wrong.
+			let out = Self(tmp);
+
 			(*tmp).yuvRange =
 				if limited { AVIF_RANGE_LIMITED }
 				else { AVIF_RANGE_FULL };
@@ -347,15 +546,134 @@ impl LibAvifImage {
 				if greyscale || limited { AVIF_MATRIX_COEFFICIENTS_BT709 as _ }
 				else { AVIF_MATRIX_COEFFICIENTS_IDENTITY as _ };
 
-			maybe_die(avifImageRGBToYUV(tmp, &rgb))?;
+			// Greyscale only has the one (luma) plane, which we can fill
+			// directly; everything else needs converting.
+			if greyscale { out.fill_grey(src, limited)?; }
+			else { out.fill_rgb(src)?; }
+
+			// Carry over the source metadata, if requested.
+			if 0 != flags & FLAG_KEEP_METADATA {
+				let meta = src.metadata();
+				if let Some(icc) = meta.icc_for(greyscale) {
+					maybe_die(avifImageSetProfileICC(tmp, icc.as_ptr(), icc.len()))?;
+				}
+				if let Some(exif) = meta.exif() {
+					maybe_die(avifImageSetMetadataExif(tmp, exif.as_ptr(), exif.len()))?;
+				}
+			}
 
-			tmp
+			out
 		};
 
-		Ok(Self(yuv))
+		Ok(yuv)
 	}
 
-	#[cfg(feature = "decode_ng")]
+	#[expect(unsafe_code, reason = "Needed for FFI.")]
+	/// # Fill (RGB).
+	///
+	/// Convert the source's RGBA pixels to YUV, using the full-precision
+	/// copy if there is one.
+	fn fill_rgb(&self, src: &Input) -> Result<(), RefractError> {
+		let width = src.width_u32();
+		let (pixels, depth, row_bytes) = src.wide_rgba().map_or_else(
+			|| (<[u8]>::as_ptr(src), 8, width * 4),
+			|w| (w.as_ptr().cast::<u8>(), 16, width * 8),
+		);
+		let rgb = avifRGBImage {
+			width,
+			height: src.height_u32(),
+			depth,
+			format: AVIF_RGB_FORMAT_RGBA,
+			chromaUpsampling: AVIF_CHROMA_UPSAMPLING_BILINEAR,
+			chromaDownsampling: AVIF_CHROMA_DOWNSAMPLING_BEST_QUALITY,
+			avoidLibYUV: 0,
+			ignoreAlpha: i32::from(! src.has_alpha()),
+			alphaPremultiplied: 0,
+			isFloat: 0,
+			maxThreads: 1,
+			pixels: pixels.cast_mut(),
+			rowBytes: row_bytes,
+		};
+
+		// Safety: this is an FFI call…
+		maybe_die(unsafe { avifImageRGBToYUV(self.0, &rgb) })
+	}
+
+	#[expect(clippy::cast_ptr_alignment, reason = "libavif allocates 16-bit planes as such.")]
+	#[expect(unsafe_code, reason = "Needed for FFI.")]
+	/// # Fill (Greyscale).
+	///
+	/// Copy the source's grey (and alpha) channel straight into the luma
+	/// (and alpha) plane. For grey, luma _is_ the value — no matter the
+	/// matrix — so there's nothing to convert beyond squeezing it into
+	/// limited range, if applicable. Full range is thus truly lossless.
+	fn fill_grey(&self, src: &Input, limited: bool) -> Result<(), RefractError> {
+		let alpha = src.has_alpha();
+		let planes =
+			if alpha { AVIF_PLANES_YUV | AVIF_PLANES_A }
+			else { AVIF_PLANES_YUV };
+
+		// Safety: this is an FFI call…
+		maybe_die(unsafe { avifImageAllocatePlanes(self.0, planes) })?;
+
+		// Safety: libavif has allocated (and will free) the planes, sized
+		// to these dimensions.
+		let (luma, luma_stride, alpha, alpha_stride) = unsafe {
+			let img = &*self.0;
+			(
+				img.yuvPlanes[0],
+				img.yuvRowBytes[0] as usize,
+				if alpha { img.alphaPlane } else { std::ptr::null_mut() },
+				img.alphaRowBytes as usize,
+			)
+		};
+
+		let width = src.width();
+		if let Some(wide) = src.wide_rgba() {
+			// Ten-bit samples, stored as native-endian u16.
+			let (lo, span) = if limited { (64, 876) } else { (0, 1023) };
+			let scale = |v: u16, lo: u32, span: u32|
+				u16::try_from(lo + (u32::from(v) * span + 32_767) / 65_535).unwrap_or(1023);
+			for (y, row) in wide.chunks_exact(width * 4).enumerate() {
+				// Safety: each row holds width samples.
+				let dst = unsafe {
+					std::slice::from_raw_parts_mut(luma.add(y * luma_stride).cast::<u16>(), width)
+				};
+				for (d, px) in dst.iter_mut().zip(row.chunks_exact(4)) {
+					*d = scale(px[0], lo, span);
+				}
+				if ! alpha.is_null() {
+					// Safety: ditto.
+					let dst = unsafe {
+						std::slice::from_raw_parts_mut(alpha.add(y * alpha_stride).cast::<u16>(), width)
+					};
+					for (d, px) in dst.iter_mut().zip(row.chunks_exact(4)) {
+						*d = scale(px[3], 0, 1023);
+					}
+				}
+			}
+		}
+		else {
+			for (y, row) in src.chunks_exact(width * 4).enumerate() {
+				// Safety: each row holds width samples.
+				let dst = unsafe { std::slice::from_raw_parts_mut(luma.add(y * luma_stride), width) };
+				for (d, px) in dst.iter_mut().zip(row.chunks_exact(4)) {
+					*d =
+						if limited { u8::try_from(16 + (u16::from(px[0]) * 219 + 127) / 255).unwrap_or(235) }
+						else { px[0] };
+				}
+				if ! alpha.is_null() {
+					// Safety: ditto.
+					let dst = unsafe { std::slice::from_raw_parts_mut(alpha.add(y * alpha_stride), width) };
+					for (d, px) in dst.iter_mut().zip(row.chunks_exact(4)) { *d = px[3]; }
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	#[cfg(any(feature = "avif_dav1d", feature = "decode_ng"))]
 	#[expect(unsafe_code, reason = "Needed for FFI.")]
 	/// # Empty.
 	fn empty() -> Result<Self, RefractError> {
@@ -366,6 +684,7 @@ impl LibAvifImage {
 	}
 }
 
+#[cfg(feature = "avif")]
 impl Drop for LibAvifImage {
 	#[expect(unsafe_code, reason = "Needed for FFI.")]
 	#[inline]
@@ -377,7 +696,7 @@ impl Drop for LibAvifImage {
 
 
 
-#[cfg(feature = "decode_ng")]
+#[cfg(all(feature = "avif", feature = "decode_ng"))]
 #[derive(Default)]
 /// # Avif RGB Image.
 ///
@@ -385,7 +704,7 @@ impl Drop for LibAvifImage {
 /// decoding.
 struct LibAvifRGBImage(avifRGBImage);
 
-#[cfg(feature = "decode_ng")]
+#[cfg(all(feature = "avif", feature = "decode_ng"))]
 impl Drop for LibAvifRGBImage {
 	#[expect(unsafe_code, reason = "Needed for FFI.")]
 	fn drop(&mut self) {
@@ -396,11 +715,13 @@ impl Drop for LibAvifRGBImage {
 
 
 
+#[cfg(feature = "avif")]
 /// # Data Struct.
 ///
 /// This wrapper only exists to provide garbage cleanup.
 struct LibAvifRwData(avifRWData);
 
+#[cfg(feature = "avif")]
 impl Drop for LibAvifRwData {
 	#[expect(unsafe_code, reason = "Needed for FFI.")]
 	#[inline]
@@ -412,16 +733,65 @@ impl Drop for LibAvifRwData {
 
 
 
-#[inline]
+#[cfg(feature = "avif")]
+#[expect(unsafe_code, reason = "Needed for FFI.")]
+/// # Encode `AVIF`.
+///
+/// This encodes a raw image source as an `AVIF`, lossily if a quality is
+/// given, losslessly if not.
+///
+/// ## Errors
+///
+/// This will return an error if there are any problems along the way or if
+/// the resulting image is empty (for some reason).
+fn encode(
+	img: &Input,
+	candidate: &mut Output,
+	quality: Option<NonZeroU8>,
+	flags: u8,
+) -> Result<(), RefractError> {
+	let image = LibAvifImage::new(img, flags)?;
+	let encoder = LibAvifEncoder::new(quality, candidate.avif_codec())?;
+
+	// Encode!
+	let mut data = LibAvifRwData(avifRWData::default());
+	// Safety: this is an FFI call…
+	maybe_die(unsafe { avifEncoderWrite(encoder.0, image.0, &mut data.0) })?;
+
+	// But make sure it gave us something.
+	if data.0.data.is_null() { return Err(RefractError::Encode); }
+
+	// Grab the output.
+	// Safety: the pointer is non-null; we have to trust libavif gave us
+	// the correct size.
+	candidate.set_slice(unsafe {
+		std::slice::from_raw_parts(data.0.data, data.0.size)
+	});
+
+	drop(data);
+	drop(encoder);
+	drop(image);
+
+	Ok(())
+}
+
+#[cfg(feature = "avif")]
+#[expect(unsafe_code, reason = "Needed for FFI.")]
 /// # Verify Encoder Status.
 ///
 /// This converts unsuccessful AVIF system function results into proper Rust
-/// errors.
-const fn maybe_die(res: avifResult) -> Result<(), RefractError> {
+/// errors, noting libavif's own explanation for the logs.
+fn maybe_die(res: avifResult) -> Result<(), RefractError> {
 	if AVIF_RESULT_OK == res { Ok(()) }
-	else { Err(RefractError::Encode) }
+	else {
+		// Safety: libavif returns a static string for every result.
+		let msg = unsafe { CStr::from_ptr(avifResultToString(res)) };
+		set_upstream(format!("libavif: {}", msg.to_string_lossy()));
+		Err(RefractError::Encode)
+	}
 }
 
+#[cfg(feature = "avif")]
 /// # Quality to Quantizer(s).
 ///
 /// This converts the quality stepping from [`EncodeIter`] into appropriate
@@ -452,6 +822,7 @@ fn quality_to_quantizers(quality: NonZeroU8) -> (u8, u8) {
 	(q, aq)
 }
 
+#[cfg(feature = "avif")]
 #[expect(clippy::cast_sign_loss, reason = "In and out are both unsigned.")]
 #[expect(clippy::cast_possible_truncation, reason = "In and out are both `u8`.")]
 #[inline]
@@ -124,3 +124,21 @@ impl ColorKind {
 		else { Self::Grey }
 	}
 }
+
+
+
+/// # Is sRGB ICC Profile?
+///
+/// Refract works exclusively in the sRGB color space, so any embedded profile
+/// describing something else is worth a mention.
+///
+/// Parsing ICC profiles properly is a whole thing, so this just checks for the
+/// usual sRGB markers — "sRGB" in either the (v2) ASCII or (v4) UTF-16
+/// description — and assumes greyscale profiles are close enough.
+pub(crate) fn is_srgb_icc(icc: &[u8]) -> bool {
+	// Greyscale profiles only describe gamma, which is fine.
+	if icc.get(16..20) == Some(b"GRAY") { return true; }
+
+	icc.windows(4).any(|w| w == b"sRGB") ||
+	icc.windows(8).any(|w| w == [0, b's', 0, b'R', 0, b'G', 0, b'B'])
+}
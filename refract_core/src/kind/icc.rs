@@ -0,0 +1,396 @@
+/*!
+# `Refract` - ICC Color Conversion
+*/
+
+use super::color::is_srgb_icc;
+
+
+
+/// # sRGB Colorants (D50).
+///
+/// The red, green, and blue primaries of sRGB — chromatically adapted to
+/// the ICC's D50 connection space — as XYZ columns, straight from the sRGB
+/// profile itself.
+const SRGB_D50: [[f32; 3]; 3] = [
+	[0.436_074_7, 0.385_064_9, 0.143_080_4],
+	[0.222_504_5, 0.716_878_6, 0.060_616_9],
+	[0.013_932_2, 0.097_104_5, 0.714_173_3],
+];
+
+
+
+/// # Tone Curve.
+///
+/// An ICC `curv` or `para` tag, mapping encoded channel values to linear
+/// light.
+enum Curve {
+	/// # Parametric.
+	///
+	/// This covers simple gammas as well as every `para` function type; see
+	/// [`Curve::eval`].
+	Para([f32; 7]),
+
+	/// # Lookup Table.
+	Table(Vec<f32>),
+}
+
+impl Curve {
+	/// # Parse.
+	///
+	/// Parse the curve tag starting at `pos`.
+	fn parse(icc: &[u8], pos: usize) -> Option<Self> {
+		match icc.get(pos..pos + 4)? {
+			b"curv" => {
+				let count = be_u32(icc, pos + 8)?;
+				match count {
+					// Identity.
+					0 => Some(Self::Para([1.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0])),
+					// A plain gamma, as u8Fixed8.
+					1 => {
+						let g = f32::from(be_u16(icc, pos + 12)?) / 256.0;
+						Some(Self::Para([g, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0]))
+					},
+					_ => {
+						let count = usize::try_from(count).ok()?;
+						let table = (0..count)
+							.map(|i| be_u16(icc, pos + 12 + i * 2).map(|v| f32::from(v) / 65_535.0))
+							.collect::<Option<Vec<f32>>>()?;
+						Some(Self::Table(table))
+					},
+				}
+			},
+			b"para" => {
+				// The number of parameters depends on the function type.
+				let len = match be_u16(icc, pos + 8)? {
+					0 => 1,
+					1 => 3,
+					2 => 4,
+					3 => 5,
+					4 => 7,
+					_ => return None,
+				};
+				let mut p = [1.0, 1.0, 0.0, 0.0, f32::NEG_INFINITY, 0.0, 0.0];
+				for (i, v) in p.iter_mut().enumerate().take(len) {
+					*v = s15_fixed16(icc, pos + 12 + i * 4)?;
+				}
+
+				// Fill in the gaps so a single formula works for every type.
+				match len {
+					3 => { p[4] = -p[2] / p[1]; },
+					4 => {
+						p[4] = -p[2] / p[1];
+						p[5] = p[3];
+						p[6] = p[3];
+						p[3] = 0.0;
+					},
+					_ => {},
+				}
+				Some(Self::Para(p))
+			},
+			_ => None,
+		}
+	}
+
+	#[expect(clippy::many_single_char_names, reason = "These are the spec's names.")]
+	/// # Evaluate.
+	///
+	/// Return the linear value for an encoded one, both `0.0..=1.0`. The
+	/// parameters follow ICC's function type 4 — `(aX + b)^g + e` at or
+	/// above `d`, `cX + f` below — which every other type reduces to.
+	fn eval(&self, x: f32) -> f32 {
+		match self {
+			Self::Para([g, a, b, c, d, e, f]) => {
+				if *d <= x { a.mul_add(x, *b).max(0.0).powf(*g) + e }
+				else { c.mul_add(x, *f) }
+			},
+			Self::Table(t) => {
+				let Some(last) = t.len().checked_sub(1) else { return x; };
+				#[expect(clippy::cast_precision_loss, reason = "Tables are small.")]
+				let pos = x.clamp(0.0, 1.0) * last as f32;
+				#[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss, reason = "It is clamped.")]
+				let idx = (pos as usize).min(last);
+				let next = t[(idx + 1).min(last)];
+				#[expect(clippy::cast_precision_loss, reason = "Tables are small.")]
+				let frac = pos - idx as f32;
+				(next - t[idx]).mul_add(frac, t[idx])
+			},
+		}
+	}
+}
+
+
+
+/// # ICC Transform.
+///
+/// Refract works exclusively in sRGB, and that is what browsers assume of
+/// untagged images too, so sources with some other embedded RGB profile —
+/// Display P3, Adobe RGB, etc. — are converted on the way in. Otherwise both
+/// the A/B preview and the outputs would look washed out (or oversaturated)
+/// compared to how the source renders.
+///
+/// Only "matrix/TRC" profiles are supported — a tone curve per channel plus
+/// a set of primaries — but that covers the vast majority of camera and
+/// phone output. Out-of-gamut colors are clipped.
+pub(crate) struct IccTransform {
+	/// # Tone Curves (RGB).
+	curves: [Curve; 3],
+
+	/// # Linear Source to Linear sRGB.
+	matrix: [[f32; 3]; 3],
+}
+
+impl IccTransform {
+	/// # New.
+	///
+	/// Parse the profile, returning `None` if it is already sRGB, or isn't
+	/// a matrix/TRC RGB profile.
+	pub(crate) fn new(icc: &[u8]) -> Option<Self> {
+		if
+			icc.get(16..20) != Some(b"RGB ") ||
+			icc.get(20..24) != Some(b"XYZ ") ||
+			is_srgb_icc(icc)
+		{
+			return None;
+		}
+
+		// Find the tags we need.
+		let count = usize::try_from(be_u32(icc, 128)?).ok()?;
+		let tag = |sig: &[u8; 4]| (0..count).find_map(|i| {
+			let pos = 132 + i * 12;
+			if icc.get(pos..pos + 4)? == sig { usize::try_from(be_u32(icc, pos + 4)?).ok() }
+			else { None }
+		});
+		let xyz = |sig: &[u8; 4]| -> Option<[f32; 3]> {
+			let pos = tag(sig)?;
+			if icc.get(pos..pos + 4)? != b"XYZ " { return None; }
+			Some([
+				s15_fixed16(icc, pos + 8)?,
+				s15_fixed16(icc, pos + 12)?,
+				s15_fixed16(icc, pos + 16)?,
+			])
+		};
+
+		let curves = [
+			Curve::parse(icc, tag(b"rTRC")?)?,
+			Curve::parse(icc, tag(b"gTRC")?)?,
+			Curve::parse(icc, tag(b"bTRC")?)?,
+		];
+		let (r, g, b) = (xyz(b"rXYZ")?, xyz(b"gXYZ")?, xyz(b"bXYZ")?);
+		let src = [
+			[r[0], g[0], b[0]],
+			[r[1], g[1], b[1]],
+			[r[2], g[2], b[2]],
+		];
+		let matrix = mul(invert(SRGB_D50)?, src);
+
+		Some(Self { curves, matrix })
+	}
+
+	/// # Apply (8-bit).
+	///
+	/// Convert an RGBA buffer to sRGB in place.
+	pub(crate) fn apply(&self, rgba: &mut [u8]) {
+		let lin: [Vec<f32>; 3] = self.curves.each_ref().map(|c|
+			(0..=u8::MAX).map(|v| c.eval(f32::from(v) / 255.0)).collect()
+		);
+		let enc = encode_table(255.0);
+		for px in rgba.chunks_exact_mut(4) {
+			let rgb = [
+				lin[0][usize::from(px[0])],
+				lin[1][usize::from(px[1])],
+				lin[2][usize::from(px[2])],
+			];
+			for (i, v) in self.convert(rgb).into_iter().enumerate() {
+				px[i] = u8::try_from(enc[v]).unwrap_or(u8::MAX);
+			}
+		}
+	}
+
+	/// # Apply (16-bit).
+	///
+	/// Convert a 16-bit RGBA buffer to sRGB in place.
+	pub(crate) fn apply_wide(&self, rgba: &mut [u16]) {
+		let lin: [Vec<f32>; 3] = self.curves.each_ref().map(|c|
+			(0..=u16::MAX).map(|v| c.eval(f32::from(v) / 65_535.0)).collect()
+		);
+		let enc = encode_table(65_535.0);
+		for px in rgba.chunks_exact_mut(4) {
+			let rgb = [
+				lin[0][usize::from(px[0])],
+				lin[1][usize::from(px[1])],
+				lin[2][usize::from(px[2])],
+			];
+			for (i, v) in self.convert(rgb).into_iter().enumerate() {
+				px[i] = enc[v];
+			}
+		}
+	}
+
+	/// # Convert.
+	///
+	/// Convert linear source RGB to linear sRGB, returning each channel as
+	/// an index into the [`encode_table`].
+	fn convert(&self, rgb: [f32; 3]) -> [usize; 3] {
+		self.matrix.map(|row| {
+			let v = row[2].mul_add(rgb[2], row[1].mul_add(rgb[1], row[0] * rgb[0]));
+			#[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss, reason = "It is clamped.")]
+			{ (v.clamp(0.0, 1.0) * 65_535.0).round() as usize }
+		})
+	}
+}
+
+
+
+/// # Big-Endian `u16`.
+fn be_u16(icc: &[u8], pos: usize) -> Option<u16> {
+	Some(u16::from_be_bytes(icc.get(pos..pos + 2)?.try_into().ok()?))
+}
+
+/// # Big-Endian `u32`.
+fn be_u32(icc: &[u8], pos: usize) -> Option<u32> {
+	Some(u32::from_be_bytes(icc.get(pos..pos + 4)?.try_into().ok()?))
+}
+
+/// # `s15Fixed16Number`.
+fn s15_fixed16(icc: &[u8], pos: usize) -> Option<f32> {
+	let v = i32::from_be_bytes(icc.get(pos..pos + 4)?.try_into().ok()?);
+	#[expect(clippy::cast_precision_loss, reason = "Sixteen fractional bits is plenty.")]
+	Some(v as f32 / 65_536.0)
+}
+
+/// # sRGB Encoding Table.
+///
+/// Map linear light, in 65,536 steps, to sRGB-encoded values scaled to
+/// `max`.
+fn encode_table(max: f32) -> Vec<u16> {
+	(0..=u16::MAX)
+		.map(|v| {
+			let v = f32::from(v) / 65_535.0;
+			let v =
+				if v <= 0.003_130_8 { v * 12.92 }
+				else { 1.055_f32.mul_add(v.powf(1.0 / 2.4), -0.055) };
+			#[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss, reason = "It is clamped.")]
+			{ (v.clamp(0.0, 1.0) * max).round() as u16 }
+		})
+		.collect()
+}
+
+/// # Invert (3x3).
+fn invert(m: [[f32; 3]; 3]) -> Option<[[f32; 3]; 3]> {
+	let cof = |r1: usize, r2: usize, c1: usize, c2: usize|
+		m[r1][c1].mul_add(m[r2][c2], -(m[r1][c2] * m[r2][c1]));
+	let adj = [
+		[cof(1, 2, 1, 2), -cof(0, 2, 1, 2), cof(0, 1, 1, 2)],
+		[-cof(1, 2, 0, 2), cof(0, 2, 0, 2), -cof(0, 1, 0, 2)],
+		[cof(1, 2, 0, 1), -cof(0, 2, 0, 1), cof(0, 1, 0, 1)],
+	];
+	let det = m[0][2].mul_add(adj[2][0], m[0][1].mul_add(adj[1][0], m[0][0] * adj[0][0]));
+	if det.abs() < f32::EPSILON { return None; }
+	Some(adj.map(|row| row.map(|v| v / det)))
+}
+
+/// # Multiply (3x3).
+fn mul(a: [[f32; 3]; 3], b: [[f32; 3]; 3]) -> [[f32; 3]; 3] {
+	std::array::from_fn(|r| std::array::from_fn(|c|
+		a[r][2].mul_add(b[2][c], a[r][1].mul_add(b[1][c], a[r][0] * b[0][c]))
+	))
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// # Display P3 Colorants (D50).
+	const P3: [[f32; 3]; 3] = [
+		[0.515_1, 0.292_0, 0.157_1],
+		[0.241_2, 0.692_2, 0.066_6],
+		[-0.001_1, 0.041_9, 0.784_1],
+	];
+
+	/// # Build Profile.
+	///
+	/// Put together a bare-bones matrix/TRC profile with the given colorants
+	/// and the sRGB tone curve.
+	fn profile(colorants: [[f32; 3]; 3]) -> Vec<u8> {
+		let fixed = |v: f32| {
+			#[expect(clippy::cast_possible_truncation, reason = "It's a test.")]
+			let v = (v * 65_536.0).round() as i32;
+			v.to_be_bytes()
+		};
+
+		let mut out = vec![0_u8; 128];
+		out[16..20].copy_from_slice(b"RGB ");
+		out[20..24].copy_from_slice(b"XYZ ");
+		out.extend_from_slice(&6_u32.to_be_bytes());
+
+		// The tag data follows the table.
+		let mut data = Vec::new();
+		let start = 132 + 6 * 12;
+		for (i, sig) in [b"rXYZ", b"gXYZ", b"bXYZ"].into_iter().enumerate() {
+			out.extend_from_slice(sig);
+			out.extend_from_slice(&u32::try_from(start + data.len()).unwrap().to_be_bytes());
+			out.extend_from_slice(&20_u32.to_be_bytes());
+			data.extend_from_slice(b"XYZ \0\0\0\0");
+			for row in colorants { data.extend_from_slice(&fixed(row[i])); }
+		}
+		let curve = start + data.len();
+		for sig in [b"rTRC", b"gTRC", b"bTRC"] {
+			out.extend_from_slice(sig);
+			out.extend_from_slice(&u32::try_from(curve).unwrap().to_be_bytes());
+			out.extend_from_slice(&32_u32.to_be_bytes());
+		}
+		data.extend_from_slice(b"para\0\0\0\0\0\x03\0\0");
+		for v in [2.4, 1.0 / 1.055, 0.055 / 1.055, 1.0 / 12.92, 0.040_45] {
+			data.extend_from_slice(&fixed(v));
+		}
+
+		out.extend_from_slice(&data);
+		out
+	}
+
+	#[test]
+	fn t_identity() {
+		// A profile that is sRGB in all but name shouldn't change anything
+		// (beyond rounding).
+		let icc = profile(SRGB_D50);
+		let xform = IccTransform::new(&icc).expect("Profile failed to parse.");
+		let src: Vec<u8> = (0..=u8::MAX).flat_map(|v| [v, v / 2, u8::MAX - v, 77]).collect();
+		let mut buf = src.clone();
+		xform.apply(&mut buf);
+		for (a, b) in src.iter().zip(&buf) { assert!(a.abs_diff(*b) <= 1, "{a} != {b}"); }
+
+		// Same for 16-bit.
+		let src: Vec<u16> = (0..=u16::MAX).step_by(257).flat_map(|v| [v, v / 3, u16::MAX - v, 0]).collect();
+		let mut buf = src.clone();
+		xform.apply_wide(&mut buf);
+		for (a, b) in src.iter().zip(&buf) { assert!(a.abs_diff(*b) <= 64, "{a} != {b}"); }
+	}
+
+	#[test]
+	fn t_p3() {
+		let icc = profile(P3);
+		let xform = IccTransform::new(&icc).expect("Profile failed to parse.");
+
+		// Neutrals stay neutral, and the primaries clip to sRGB's.
+		let mut buf = [
+			255, 255, 255, 255,
+			128, 128, 128, 255,
+			255, 0, 0, 255,
+			0, 255, 0, 9,
+		];
+		xform.apply(&mut buf);
+		assert!(buf[..3].iter().all(|&v| 254 <= v), "White shifted: {buf:?}");
+		assert!(buf[4..7].iter().all(|&v| v.abs_diff(128) <= 2), "Grey shifted: {buf:?}");
+		assert_eq!(buf[8], 255, "P3 red should max out sRGB red.");
+		assert!(buf[9] < 5 && buf[10] < 5, "P3 red should clip: {buf:?}");
+		assert_eq!(buf[13], 255, "P3 green should max out sRGB green.");
+		assert_eq!(buf[15], 9, "Alpha changed!");
+
+		// Actual sRGB profiles are left alone.
+		let mut icc = icc;
+		icc.extend_from_slice(b"sRGB");
+		assert!(IccTransform::new(&icc).is_none());
+	}
+}
@@ -7,8 +7,8 @@ use crate::{
 	ImageJpeg,
 	ImageJxl,
 	ImagePng,
-	ImageWebp,
 	Input,
+	kind::meta::Metadata,
 	NZ_100,
 	Output,
 	RefractError,
@@ -19,6 +19,9 @@ use std::{
 	num::NonZeroU8,
 };
 
+#[cfg(any(feature = "webp", feature = "webp_rust"))]
+use crate::ImageWebp;
+
 
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
@@ -58,85 +61,117 @@ impl TryFrom<&[u8]> for ImageKind {
 	/// # From Raw Bytes.
 	///
 	/// This examines the first 12 bytes of the raw image file to see what
-	/// magic its headers contain.
+	/// magic its headers contain. No decoding is performed, so this is cheap
+	/// enough to use for things like validating uploads.
+	///
+	/// For additional details — animation, etc. — see [`SniffResult`](crate::SniffResult).
+	///
+	/// Matching is done with slice patterns rather than indexing, so there is
+	/// no way for short or otherwise hostile inputs to trigger a panic.
 	fn try_from(src: &[u8]) -> Result<Self, Self::Error> {
-		// We need at least twelve bytes to hold header info!
-		if src.len() > 12 {
+		// Nothing is nothing.
+		if src.is_empty() { return Err(RefractError::Empty); }
+
+		// We need at least twelve bytes to hold header info! (Strictly more,
+		// actually, since an image needs some data to go with its header.)
+		let Some(head) = src.first_chunk::<12>().filter(|_| 12 < src.len())
+		else { return Err(RefractError::Image); };
+
+		match head {
 			// PNG has just one way to be!
-			if src[..8] == [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'] {
-				return Ok(Self::Png);
-			}
+			[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n', ..] => Ok(Self::Png),
 
 			// WebP is fairly straightforward.
-			if src[..4] == *b"RIFF" && src[8..12] == *b"WEBP" {
-				return Ok(Self::Webp);
-			}
+			[b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P'] => Ok(Self::Webp),
 
-			// AVIF has a few ways to be. We're ignoring sequences since we
-			// aren't building them.
-			if
-				src[4..8] == *b"ftyp" &&
-				matches!(&src[8..12], b"avif" | b"MA1B" | b"MA1A")
-			{
-				return Ok(Self::Avif);
-			}
+			// AVIF has a few ways to be, including as a sequence.
+			[_, _, _, _, b'f', b't', b'y', b'p', b'a', b'v', b'i', b'f' | b's'] |
+			[_, _, _, _, b'f', b't', b'y', b'p', b'M', b'A', b'1', b'A' | b'B'] => Ok(Self::Avif),
 
 			// JPEG XL can either be a codestream or containerized.
-			if
-				src[..2] == [0xFF, 0x0A] ||
-				src[..12] == [0x00, 0x00, 0x00, 0x0C, b'J', b'X', b'L', 0x20, 0x0D, 0x0A, 0x87, 0x0A]
-			{
-				return Ok(Self::Jxl);
-			}
+			[0xFF, 0x0A, ..] |
+			[0x00, 0x00, 0x00, 0x0C, b'J', b'X', b'L', 0x20, 0x0D, 0x0A, 0x87, 0x0A] => Ok(Self::Jxl),
 
 			// JPEG can look a few different ways, particularly in the middle.
-			if
-				src[..3] == [0xFF, 0xD8, 0xFF] &&
-				src[src.len() - 2..] == [0xFF, 0xD9] &&
-				(
-					src[3] == 0xDB ||
-					src[3] == 0xEE ||
-					(src[3..12] == [0xE0, 0x00, 0x10, b'J', b'F', b'I', b'F', 0x00, 0x01]) ||
-					(src[3] == 0xE1 && src[6..12] == [b'E', b'x', b'i', b'f', 0x00, 0x00])
-				)
-			{
-				return Ok(Self::Jpeg);
-			}
+			[0xFF, 0xD8, 0xFF, 0xDB | 0xEE, ..] |
+			[0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10, b'J', b'F', b'I', b'F', 0x00, 0x01] |
+			[0xFF, 0xD8, 0xFF, 0xE1, _, _, b'E', b'x', b'i', b'f', 0x00, 0x00]
+				if src.ends_with(&[0xFF, 0xD9]) => Ok(Self::Jpeg),
+
+			// A JPEG that doesn't end right was probably cut short.
+			[0xFF, 0xD8, 0xFF, 0xDB | 0xEE, ..] |
+			[0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10, b'J', b'F', b'I', b'F', 0x00, 0x01] |
+			[0xFF, 0xD8, 0xFF, 0xE1, _, _, b'E', b'x', b'i', b'f', 0x00, 0x00] =>
+				Err(RefractError::Truncated(Self::Jpeg)),
+
+			_ => Err(RefractError::Image),
 		}
-
-		Err(RefractError::Image)
 	}
 }
 
 /// ## Information.
 impl ImageKind {
-	#[cfg(not(feature = "decode_ng"))]
 	#[inline]
 	#[must_use]
 	/// # Can Decode?
 	///
 	/// Returns `true` if decoding is supported for this image type.
 	///
-	/// When the feature flag `decode_ng` is used, this always returns `true`.
-	pub const fn can_decode(self) -> bool { matches!(self, Self::Jpeg | Self::Png) }
+	/// JPEG and PNG can always be decoded; the next-gen formats require the
+	/// feature flag `decode_ng`, as well as their own format features.
+	pub const fn can_decode(self) -> bool {
+		match self {
+			Self::Jpeg | Self::Png => true,
+			Self::Avif => cfg!(all(feature = "decode_ng", feature = "avif")),
+			Self::Jxl => cfg!(all(feature = "decode_ng", feature = "jxl")),
+			Self::Webp => cfg!(all(feature = "decode_ng", any(feature = "webp", feature = "webp_rust"))),
+		}
+	}
 
-	#[cfg(feature = "decode_ng")]
 	#[inline]
 	#[must_use]
-	/// # Can Decode?
+	/// # Can Encode?
 	///
-	/// Returns `true` if decoding is supported for this image type.
+	/// Returns `true` if encoding is supported for this image type.
 	///
-	/// When the feature flag `decode_ng` is used, this always returns `true`.
-	pub const fn can_decode(self) -> bool { true }
+	/// PNG can always be encoded; everything else requires the corresponding
+	/// feature flag — `avif`, `jpeg`, `jxl`, or `webp` — all of which are
+	/// enabled by default. (`WebP` can alternatively be encoded with
+	/// `webp_rust`, albeit only losslessly.)
+	pub const fn can_encode(self) -> bool {
+		match self {
+			Self::Png => true,
+			Self::Avif => cfg!(feature = "avif"),
+			Self::Jpeg => cfg!(feature = "jpeg"),
+			Self::Jxl => cfg!(feature = "jxl"),
+			Self::Webp => cfg!(any(feature = "webp", feature = "webp_rust")),
+		}
+	}
 
 	#[inline]
 	#[must_use]
-	/// # Can Encode?
+	/// # Can Encode Lossy?
 	///
-	/// Returns `true` if encoding is supported for this image type.
-	pub const fn can_encode(self) -> bool {
-		matches!(self, Self::Avif | Self::Jxl | Self::Webp)
+	/// Returns `true` if lossy encoding is supported for this image type,
+	/// i.e. everything but `PNG`, and `WebP` when using the pure Rust
+	/// encoder.
+	pub const fn can_encode_lossy(self) -> bool {
+		match self {
+			Self::Png => false,
+			Self::Webp => cfg!(feature = "webp"),
+			_ => self.can_encode(),
+		}
+	}
+
+	#[inline]
+	#[must_use]
+	/// # Supports High Bit Depths?
+	///
+	/// Returns `true` if the encoder receives the full precision of sources
+	/// with more than 8 bits per channel, i.e. `AVIF` (as 10-bit) and `JPEG
+	/// XL`. Everything else gets 8-bit pixels.
+	pub const fn supports_high_bit_depth(self) -> bool {
+		matches!(self, Self::Avif | Self::Jxl)
 	}
 }
 
@@ -191,8 +226,6 @@ impl ImageKind {
 	/// This returns the maximum encoding quality value for the given format,
 	/// or a default of `100`.
 	pub(crate) const fn max_encoder_quality(self) -> NonZeroU8 {
-		use crate::traits::Encoder;
-
 		match self {
 			Self::Avif => ImageAvif::MAX_QUALITY,
 			Self::Jxl => ImageJxl::MAX_QUALITY,
@@ -209,8 +242,8 @@ impl ImageKind {
 	/// and color type).
 	///
 	/// Decoding support for the next-gen formats can be enabled with the
-	/// feature flag `decode_ng`. Otherwise only JPEG and PNG image sources
-	/// can be decoded.
+	/// feature flag `decode_ng` (and their own format features). Otherwise
+	/// only JPEG and PNG image sources can be decoded.
 	///
 	/// ## Errors
 	///
@@ -223,10 +256,89 @@ impl ImageKind {
 			Self::Jpeg => ImageJpeg::decode(raw),
 			Self::Png => ImagePng::decode(raw),
 
-			#[cfg(feature = "decode_ng")] Self::Avif => ImageAvif::decode(raw),
-			#[cfg(feature = "decode_ng")] Self::Jxl => ImageJxl::decode(raw),
-			#[cfg(feature = "decode_ng")] Self::Webp => ImageWebp::decode(raw),
-			#[cfg(not(feature = "decode_ng"))] _ => Err(RefractError::ImageDecode(self)),
+			#[cfg(all(feature = "decode_ng", feature = "avif"))]
+			Self::Avif => ImageAvif::decode(raw),
+			#[cfg(all(feature = "decode_ng", feature = "jxl"))]
+			Self::Jxl => ImageJxl::decode(raw),
+			#[cfg(all(feature = "decode_ng", any(feature = "webp", feature = "webp_rust")))]
+			Self::Webp => ImageWebp::decode(raw),
+			#[cfg(not(all(feature = "decode_ng", feature = "avif", feature = "jxl", any(feature = "webp", feature = "webp_rust"))))]
+			_ => Err(RefractError::ImageDecode(self)),
+		}
+	}
+
+	/// # Decode (Wide).
+	///
+	/// This is the same as [`ImageKind::decode`], except 16-bit sources also
+	/// return a full-precision RGBA copy of the pixels (if they actually need
+	/// more than eight bits).
+	///
+	/// At the moment, this only applies to PNG.
+	pub(crate) fn decode_wide(self, raw: &[u8])
+	-> Result<(DecoderResult, Option<Vec<u16>>), RefractError> {
+		if self == Self::Png && 16 == ImagePng::inspect(raw).0 {
+			ImagePng::decode_wide(raw)
+		}
+		else { self.decode(raw).map(|res| (res, None)) }
+	}
+
+	#[must_use]
+	/// # Is Truncated?
+	///
+	/// Returns `true` if the raw image is missing its ending — per its own
+	/// headers — as happens with interrupted downloads. This is only useful
+	/// for explaining why decoding failed; it says nothing about the data
+	/// that _is_ there.
+	///
+	/// JPEG XL codestreams have nothing to check, so always return `false`.
+	pub(crate) fn is_truncated(self, raw: &[u8]) -> bool {
+		match self {
+			// The end-of-image marker.
+			Self::Jpeg => ! raw.ends_with(&[0xFF, 0xD9]),
+
+			// The IEND chunk, CRC and all.
+			Self::Png => ! raw.ends_with(b"IEND\xAE\x42\x60\x82"),
+
+			// The RIFF header holds the size of everything after it.
+			Self::Webp => match raw {
+				[b'R', b'I', b'F', b'F', a, b, c, d, ..] =>
+					raw.len() - 8 < u32::from_le_bytes([*a, *b, *c, *d]) as usize,
+				_ => false,
+			},
+
+			// ISO-BMFF boxes declare their sizes up front.
+			Self::Avif | Self::Jxl => is_truncated_bmff(raw),
+		}
+	}
+
+	#[must_use]
+	/// # Inspect.
+	///
+	/// Return the source bit depth and whether or not it has a non-sRGB
+	/// color profile embedded, without decoding any pixels.
+	///
+	/// Only JPEG and PNG sources are inspected; everything else is assumed to
+	/// be 8-bit sRGB.
+	pub(crate) fn inspect(self, raw: &[u8]) -> (u8, bool) {
+		match self {
+			Self::Jpeg => ImageJpeg::inspect(raw),
+			Self::Png => ImagePng::inspect(raw),
+			_ => (8, false),
+		}
+	}
+
+	#[must_use]
+	/// # Metadata.
+	///
+	/// Return the ICC profile and EXIF data embedded in the source, if any.
+	///
+	/// As with [`ImageKind::inspect`], only JPEG and PNG sources are
+	/// examined.
+	pub(crate) fn metadata(self, raw: &[u8]) -> Metadata {
+		match self {
+			Self::Jpeg => ImageJpeg::metadata(raw),
+			Self::Png => ImagePng::metadata(raw),
+			_ => Metadata::default(),
 		}
 	}
 }
@@ -251,13 +363,40 @@ impl ImageKind {
 		use crate::traits::Encoder;
 
 		match self {
+			#[cfg(feature = "avif")]
 			Self::Avif => ImageAvif::encode_lossy(input, output, quality, flags),
+			#[cfg(feature = "jpeg")]
+			Self::Jpeg => ImageJpeg::encode_lossy(input, output, quality, flags),
+			#[cfg(feature = "jxl")]
 			Self::Jxl => ImageJxl::encode_lossy(input, output, quality, flags),
+			Self::Png => ImagePng::encode_lossy(input, output, quality, flags),
+			#[cfg(any(feature = "webp", feature = "webp_rust"))]
 			Self::Webp => ImageWebp::encode_lossy(input, output, quality, flags),
+			#[cfg(not(all(feature = "avif", feature = "jpeg", feature = "jxl", any(feature = "webp", feature = "webp_rust"))))]
 			_ => Err(RefractError::ImageEncode(self)),
 		}
 	}
 
+	#[must_use]
+	/// # Encoder Version.
+	///
+	/// Return the name and version of the library used to encode this kind,
+	/// if encoding is supported.
+	pub fn encoder_version(self) -> Option<String> {
+		use crate::traits::Encoder;
+
+		match self {
+			#[cfg(feature = "avif")] Self::Avif => Some(ImageAvif::version()),
+			#[cfg(feature = "jpeg")] Self::Jpeg => Some(ImageJpeg::version()),
+			#[cfg(feature = "jxl")] Self::Jxl => Some(ImageJxl::version()),
+			Self::Png => Some(ImagePng::version()),
+			#[cfg(any(feature = "webp", feature = "webp_rust"))]
+			Self::Webp => Some(ImageWebp::version()),
+			#[cfg(not(all(feature = "avif", feature = "jpeg", feature = "jxl", any(feature = "webp", feature = "webp_rust"))))]
+			_ => None,
+		}
+	}
+
 	/// # Encode Lossless.
 	///
 	/// Encode pixels into a raw image using lossless compression.
@@ -275,10 +414,184 @@ impl ImageKind {
 		use crate::traits::Encoder;
 
 		match self {
+			#[cfg(feature = "avif")]
 			Self::Avif => ImageAvif::encode_lossless(input, output, flags),
+			#[cfg(feature = "jpeg")]
+			Self::Jpeg => ImageJpeg::encode_lossless(input, output, flags),
+			#[cfg(feature = "jxl")]
 			Self::Jxl => ImageJxl::encode_lossless(input, output, flags),
+			Self::Png => ImagePng::encode_lossless(input, output, flags),
+			#[cfg(any(feature = "webp", feature = "webp_rust"))]
 			Self::Webp => ImageWebp::encode_lossless(input, output, flags),
+			#[cfg(not(all(feature = "avif", feature = "jpeg", feature = "jxl", any(feature = "webp", feature = "webp_rust"))))]
 			_ => Err(RefractError::ImageEncode(self)),
 		}
 	}
 }
+
+
+
+/// # Is ISO-BMFF Truncated?
+///
+/// Walk the top-level boxes of an AVIF or containerized JPEG XL, returning
+/// `true` if the last one runs past the end of the data.
+fn is_truncated_bmff(raw: &[u8]) -> bool {
+	// JPEG XL codestreams aren't boxed.
+	if raw.starts_with(&[0xFF, 0x0A]) { return false; }
+
+	let mut rest = raw;
+	while ! rest.is_empty() {
+		// Not enough left for a box header.
+		let Some(head) = rest.first_chunk::<8>() else { return true; };
+		let size = match u32::from_be_bytes([head[0], head[1], head[2], head[3]]) {
+			// The box runs to the end.
+			0 => return false,
+			// A 64-bit size follows the type.
+			1 => match rest.get(8..16).and_then(|x| <[u8; 8]>::try_from(x).ok()) {
+				Some(x) => usize::try_from(u64::from_be_bytes(x)).unwrap_or(usize::MAX),
+				None => return true,
+			},
+			x => x as usize,
+		};
+
+		// Too small to be a box; this is something else.
+		if size < 8 { return false; }
+		match rest.get(size..) {
+			Some(next) => { rest = next; },
+			None => return true,
+		}
+	}
+
+	false
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// # Sample Headers.
+	///
+	/// The smallest valid-looking "file" for each kind, padded out past the
+	/// twelve-byte minimum.
+	const SAMPLES: [(ImageKind, &[u8]); 8] = [
+		(ImageKind::Avif, b"\0\0\0\x1cftypavif\0\0\0\0"),
+		(ImageKind::Avif, b"\0\0\0\x1cftypMA1B\0\0\0\0"),
+		(ImageKind::Jpeg, b"\xFF\xD8\xFF\xDB\0\0\0\0\0\0\0\0\xFF\xD9"),
+		(ImageKind::Jpeg, b"\xFF\xD8\xFF\xE0\0\x10JFIF\0\x01\0\0\xFF\xD9"),
+		(ImageKind::Jxl, b"\xFF\x0A\0\0\0\0\0\0\0\0\0\0\0"),
+		(ImageKind::Jxl, b"\0\0\0\x0CJXL \x0D\x0A\x87\x0A\0"),
+		(ImageKind::Png, b"\x89PNG\r\n\x1A\n\0\0\0\0\0"),
+		(ImageKind::Webp, b"RIFF\0\0\0\0WEBPVP8 "),
+	];
+
+	/// # Xorshift.
+	///
+	/// A tiny, deterministic PRNG so the "arbitrary" inputs are reproducible
+	/// without pulling in any extra dependencies.
+	fn xorshift(state: &mut u64) -> u8 {
+		*state ^= *state << 13;
+		*state ^= *state >> 7;
+		*state ^= *state << 17;
+		state.to_be_bytes()[0]
+	}
+
+	#[test]
+	fn t_kind_magic() {
+		for (kind, raw) in SAMPLES {
+			assert_eq!(ImageKind::try_from(raw), Ok(kind), "Failed to detect {kind}.");
+		}
+
+		// JPEGs also need to end right.
+		let raw = b"\xFF\xD8\xFF\xDB\0\0\0\0\0\0\0\0\0\0";
+		assert!(ImageKind::try_from(&raw[..]).is_err(), "Unterminated JPEG detected.");
+	}
+
+	#[test]
+	fn t_kind_truncated() {
+		for (kind, raw) in SAMPLES {
+			for len in 0..raw.len() {
+				let res = ImageKind::try_from(&raw[..len]);
+				if len <= 12 {
+					assert!(res.is_err(), "Truncated {kind} ({len}) detected.");
+				}
+
+				// Header inspection shouldn't care either way.
+				let _res = kind.inspect(&raw[..len]);
+				let _res = kind.metadata(&raw[..len]);
+			}
+		}
+	}
+
+	#[test]
+	fn t_kind_incomplete() {
+		assert_eq!(ImageKind::try_from(&b""[..]), Err(RefractError::Empty));
+		assert_eq!(
+			ImageKind::try_from(&b"\xFF\xD8\xFF\xDB\0\0\0\0\0\0\0\0\0\0"[..]),
+			Err(RefractError::Truncated(ImageKind::Jpeg)),
+		);
+
+		// PNGs need their IEND.
+		let png = b"\x89PNG\r\n\x1A\n\0\0\0\0IEND\xAE\x42\x60\x82";
+		assert!(! ImageKind::Png.is_truncated(png));
+		assert!(ImageKind::Png.is_truncated(&png[..png.len() - 1]));
+
+		// WebPs need as much as the RIFF header says.
+		let webp = b"RIFF\x06\0\0\0WEBPVP";
+		assert!(! ImageKind::Webp.is_truncated(webp));
+		assert!(ImageKind::Webp.is_truncated(&webp[..webp.len() - 1]));
+
+		// AVIFs need complete boxes.
+		let avif = b"\0\0\0\x0cftypavif\0\0\0\x09mdat\0";
+		assert!(! ImageKind::Avif.is_truncated(avif));
+		assert!(ImageKind::Avif.is_truncated(&avif[..avif.len() - 1]));
+		assert!(ImageKind::Avif.is_truncated(&avif[..avif.len() - 5]));
+
+		// Codestreams can't be checked.
+		assert!(! ImageKind::Jxl.is_truncated(b"\xFF\x0A\0"));
+	}
+
+	#[test]
+	fn t_kind_support() {
+		// Encoding support depends on the enabled features, but should be
+		// reported consistently either way.
+		for (kind, _) in SAMPLES {
+			assert_eq!(
+				kind.can_encode(),
+				kind.encoder_version().is_some(),
+				"Inconsistent {kind} encoder support.",
+			);
+
+			// JPEG decoding doesn't need mozjpeg, but the other formats use
+			// the same library both ways.
+			assert!(
+				kind.can_encode() || ! kind.can_decode() || kind == ImageKind::Jpeg,
+				"{kind} can be decoded but not encoded.",
+			);
+		}
+	}
+
+	#[test]
+	fn t_kind_arbitrary() {
+		let mut state = 0x2545_F491_4F6C_DD1D_u64;
+		let mut buf = Vec::with_capacity(256);
+		for len in 0..256 {
+			buf.truncate(0);
+			buf.extend(std::iter::repeat_with(|| xorshift(&mut state)).take(len));
+
+			// Random noise.
+			let _res = ImageKind::try_from(buf.as_slice());
+
+			// Random noise behind a legitimate header.
+			for (kind, raw) in SAMPLES {
+				let n = raw.len().min(len);
+				buf[..n].copy_from_slice(&raw[..n]);
+				let _res = ImageKind::try_from(buf.as_slice());
+				let _res = kind.inspect(&buf);
+				let _res = kind.metadata(&buf);
+				let _res = kind.is_truncated(&buf);
+			}
+		}
+	}
+}
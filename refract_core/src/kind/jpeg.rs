@@ -5,6 +5,14 @@
 use crate::{
 	ColorKind,
 	RefractError,
+	error::set_upstream,
+	kind::{
+		color::is_srgb_icc,
+		meta::{
+			exif_orientation,
+			Metadata,
+		},
+	},
 	traits::{
 		Decoder,
 		DecoderResult,
@@ -16,18 +24,116 @@ use rgb::{
 	FromSlice,
 };
 
+#[cfg(feature = "jpeg")]
+use crate::{
+	Input,
+	Output,
+	traits::Encoder,
+};
+
+#[cfg(feature = "jpeg")]
+use mozjpeg::{
+	ColorSpace,
+	Compress,
+};
+
+#[cfg(feature = "jpeg")]
+use std::{
+	borrow::Cow,
+	num::NonZeroU8,
+};
+
 
 
 /// # JPEG Image.
 pub(crate) struct ImageJpeg;
 
+impl ImageJpeg {
+	/// # Inspect.
+	///
+	/// Parse the headers — without decoding any pixels — to find the source
+	/// bit depth and whether or not a non-sRGB color profile is embedded.
+	///
+	/// Unreadable headers are treated as boring 8-bit sRGB; the decoder will
+	/// complain about them soon enough.
+	pub(crate) fn inspect(mut raw: &[u8]) -> (u8, bool) {
+		let mut jecoder = jpeg_decoder::Decoder::new(&mut raw);
+		if jecoder.read_info().is_err() { return (8, false); }
+
+		let bits =
+			if jecoder.info().is_some_and(|i| i.pixel_format == PixelFormat::L16) { 16 }
+			else { 8 };
+		let icc = jecoder.icc_profile().is_some_and(|p| ! is_srgb_icc(&p));
+
+		(bits, icc)
+	}
+
+	/// # Metadata.
+	///
+	/// Pull the ICC profile and EXIF data, if any, from the headers.
+	///
+	/// The pixels are rotated upright during decoding, so the EXIF
+	/// orientation, if any, is reset to match.
+	pub(crate) fn metadata(mut raw: &[u8]) -> Metadata {
+		let mut jecoder = jpeg_decoder::Decoder::new(&mut raw);
+		if jecoder.read_info().is_err() { return Metadata::default(); }
+
+		let mut exif = jecoder.exif_data().map(<[u8]>::to_vec);
+		if let Some(exif) = exif.as_mut() {
+			if let Some((pos, _)) = exif_orientation(exif) {
+				let one = if exif.starts_with(b"II") { [1, 0] } else { [0, 1] };
+				exif[pos..pos + 2].copy_from_slice(&one);
+			}
+		}
+
+		Metadata::new(jecoder.icc_profile().as_deref(), exif.as_deref())
+	}
+
+	/// # Is Progressive?
+	///
+	/// Walk the marker segments up to the first start-of-frame to see whether
+	/// or not the image is progressively encoded.
+	pub(crate) fn is_progressive(raw: &[u8]) -> bool {
+		let mut rest = raw.get(2..).unwrap_or_default();
+		loop {
+			let [0xFF, marker, tail @ ..] = rest else { return false; };
+			match *marker {
+				// Progressive frames.
+				0xC2 | 0xC6 | 0xCA | 0xCE => return true,
+
+				// Baseline/sequential/lossless frames, or the data itself.
+				0xC0 | 0xC1 | 0xC3 | 0xC5 | 0xC7 | 0xC9 | 0xCB | 0xCD | 0xCF |
+				0xD9 | 0xDA => return false,
+
+				// Fill bytes and standalone markers have no length.
+				0xFF => { rest = rest.get(1..).unwrap_or_default(); },
+				0x01 | 0xD0..=0xD7 => { rest = tail; },
+
+				// Everything else has a length we can skip past. (The length
+				// includes itself.)
+				_ => {
+					let [a, b, ..] = tail else { return false; };
+					let len = usize::from(u16::from_be_bytes([*a, *b]));
+					rest = tail.get(len..).unwrap_or_default();
+				},
+			}
+		}
+	}
+}
+
 impl Decoder for ImageJpeg {
 	/// # Decode.
 	fn decode(mut raw: &[u8]) -> Result<DecoderResult, RefractError> {
-		// Decode the image.
+		// Check the headers first to avoid pointlessly decoding formats we
+		// can't handle.
 		let mut jecoder = jpeg_decoder::Decoder::new(&mut raw);
-		let pixels = jecoder.decode()
-			.map_err(|_| RefractError::Decode)?;
+		jecoder.read_info().map_err(|e| decode_error(&e))?;
+		if jecoder.info().is_some_and(|i| i.pixel_format == PixelFormat::L16) {
+			return Err(RefractError::ColorJpeg16);
+		}
+
+		// Decode the image.
+		let pixels = jecoder.decode().map_err(|e| decode_error(&e))?;
 		let info = jecoder.info().ok_or(RefractError::Decode)?;
 
 		let width: usize = info.width.into();
@@ -58,8 +164,20 @@ impl Decoder for ImageJpeg {
 						acc.1 || px.r != px.g || px.r != px.b,
 					)
 				}),
-			// Lossless and CMYK aren't supported.
-			PixelFormat::CMYK32 | PixelFormat::L16 => return Err(RefractError::Color),
+			// Approximate CMYK as RGBA.
+			PixelFormat::CMYK32 => pixels.chunks_exact(4)
+				.map(cmyk_to_rgba)
+				.fold(
+					(Vec::with_capacity(size), false), |mut acc, px| {
+					acc.0.extend_from_slice(&px);
+					(
+						acc.0,
+						acc.1 || px[0] != px[1] || px[0] != px[2],
+					)
+				}),
+			// Lossless isn't supported. (This should have been caught
+			// already.)
+			PixelFormat::L16 => return Err(RefractError::Color),
 		};
 
 		// JPEGs don't have alpha.
@@ -68,9 +186,200 @@ impl Decoder for ImageJpeg {
 			else { ColorKind::Grey };
 
 		// Make sure the buffer was actually filled to the right size.
-		if raw.len() == size {
-			Ok((raw, width, height, color))
+		if raw.len() != size { return Err(RefractError::Overflow); }
+
+		// Turn it the right way up.
+		match jecoder.exif_data().and_then(exif_orientation) {
+			Some((_, o)) if 1 < o => {
+				let (raw, width, height) = orient(&raw, width, height, o);
+				Ok((raw, width, height, color))
+			},
+			_ => Ok((raw, width, height, color)),
+		}
+	}
+}
+
+#[cfg(feature = "jpeg")]
+impl Encoder for ImageJpeg {
+	/// # Encode Lossy.
+	///
+	/// This uses `mozjpeg`'s default — maximum compression — profile, i.e.
+	/// progressive scans, optimized Huffman tables, and trellis quantization.
+	///
+	/// The input should be in its native color format, so greyscale sources
+	/// stay greyscale. JPEGs have no alpha channel, so any transparency in
+	/// RGBA sources is simply dropped; greyscale-with-alpha sources are
+	/// refused outright.
+	fn encode_lossy(input: &Input, output: &mut Output, quality: NonZeroU8, _flags: u8)
+	-> Result<(), RefractError> {
+		let space = match input.depth() {
+			ColorKind::Grey => ColorSpace::JCS_GRAYSCALE,
+			ColorKind::Rgb => ColorSpace::JCS_RGB,
+			ColorKind::Rgba => ColorSpace::JCS_EXT_RGBA,
+			ColorKind::GreyAlpha => return Err(RefractError::Encode),
+		};
+		let (width, height) = (input.width(), input.height());
+		let raw: &[u8] = input;
+
+		// mozjpeg reports errors by panicking.
+		let data = std::panic::catch_unwind(|| {
+			let mut jpeg = Compress::new(space);
+			jpeg.set_size(width, height);
+			jpeg.set_quality(f32::from(quality.get()));
+
+			let mut jpeg = jpeg.start_compress(Vec::new())?;
+			jpeg.write_scanlines(raw)?;
+			jpeg.finish()
+		})
+			.ok()
+			.and_then(Result::ok)
+			.ok_or(RefractError::Encode)?;
+
+		output.set_slice(&data);
+		Ok(())
+	}
+
+	#[inline]
+	/// # Encode Lossless.
+	///
+	/// JPEG is lossy-only; [`EncodeIter`](crate::EncodeIter) never asks.
+	fn encode_lossless(_input: &Input, _output: &mut Output, _flags: u8)
+	-> Result<(), RefractError> {
+		Err(RefractError::Encode)
+	}
+
+	#[expect(unsafe_code, reason = "Needed for FFI.")]
+	/// # Encoder Version.
+	///
+	/// The library doesn't have a version getter, per se, but does carry its
+	/// (`mozjpeg-sys` package) version in its message table.
+	fn version() -> String {
+		use mozjpeg_sys::{
+			jpeg_error_mgr,
+			jpeg_std_error,
+			JMSG_LASTMSGCODE,
+			JMSG_VERSION,
+		};
+
+		// Safety: the struct is plain old data, fit for jpeg_std_error to
+		// populate.
+		let mut err: jpeg_error_mgr = unsafe { std::mem::zeroed() };
+		// Safety: this is an FFI call…
+		let err = unsafe { jpeg_std_error(&mut err) };
+
+		let v = usize::try_from(JMSG_VERSION).ok().filter(|_|
+			! err.jpeg_message_table.is_null() &&
+			(JMSG_VERSION..JMSG_LASTMSGCODE).contains(&err.last_jpeg_message)
+		)
+			// Safety: the index is within the table, which holds static strings.
+			.map(|idx| unsafe { *err.jpeg_message_table.add(idx) })
+			.filter(|ptr| ! ptr.is_null())
+			// Safety: the entries are NUL-terminated C strings.
+			.map(|ptr| unsafe { std::ffi::CStr::from_ptr(ptr) }.to_string_lossy().into_owned());
+
+		v.map_or_else(|| String::from("mozjpeg"), |v| format!("mozjpeg (mozjpeg-sys {v})"))
+	}
+
+	/// # Encoder Settings.
+	fn settings(_output: &Output) -> Cow<'static, str> {
+		Cow::Borrowed("progressive, optimized scans, trellis quantization")
+	}
+}
+
+
+
+/// # Orient.
+///
+/// Rotate and/or flip an RGBA buffer according to its EXIF orientation
+/// (`2..=8`), returning the new buffer and dimensions. Orientations `5..=8`
+/// swap the width and height.
+fn orient(src: &[u8], width: usize, height: usize, o: u16) -> (Vec<u8>, usize, usize) {
+	let (w, h) = if 5 <= o { (height, width) } else { (width, height) };
+	let mut out = Vec::with_capacity(src.len());
+	for y in 0..h {
+		for x in 0..w {
+			// Find the source pixel for this spot.
+			let (sx, sy) = match o {
+				2 => (width - 1 - x, y),
+				3 => (width - 1 - x, height - 1 - y),
+				4 => (x, height - 1 - y),
+				5 => (y, x),
+				6 => (y, height - 1 - x),
+				7 => (width - 1 - y, height - 1 - x),
+				8 => (width - 1 - y, x),
+				_ => (x, y),
+			};
+			let pos = (sy * width + sx) * 4;
+			out.extend_from_slice(&src[pos..pos + 4]);
+		}
+	}
+
+	(out, w, h)
+}
+
+#[expect(clippy::cast_possible_truncation, reason = "The product is scaled back down to 0..=255.")]
+/// # CMYK to RGBA.
+///
+/// Convert a CMYK pixel to RGBA using the naive, profile-less formula, i.e.
+/// `R = (1 - C) × (1 - K)`.
+///
+/// Any embedded (CMYK) color profile is ignored — there's no color
+/// management here — so colors may drift a bit from what a proper press
+/// conversion would give, but are generally close enough for the web.
+fn cmyk_to_rgba(px: &[u8]) -> [u8; 4] {
+	let k = 255 - u16::from(px[3]);
+	let chan = |c: u8| (((255 - u16::from(c)) * k + 127) / 255) as u8;
+	[chan(px[0]), chan(px[1]), chan(px[2]), 255]
+}
+
+/// # Decode Error.
+///
+/// Note the decoder's explanation for the logs, and return the generic
+/// error.
+fn decode_error(e: &jpeg_decoder::Error) -> RefractError {
+	set_upstream(format!("jpeg-decoder: {e}"));
+	RefractError::Decode
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[cfg(feature = "jpeg")]
+	#[test]
+	fn t_version() {
+		// The linked library should know its own version.
+		let v = ImageJpeg::version();
+		let v = v.strip_prefix("mozjpeg (mozjpeg-sys ")
+			.and_then(|v| v.strip_suffix(')'))
+			.expect("Missing mozjpeg version.");
+		assert!(
+			v.split('.').all(|n| n.parse::<u8>().is_ok()),
+			"Bad mozjpeg version: {v}",
+		);
+	}
+
+	#[test]
+	fn t_orient() {
+		// A 3×2 image with single-byte "pixels" (padded out to RGBA):
+		// a b c
+		// d e f
+		let src: Vec<u8> = b"abcdef".iter().flat_map(|&b| [b, 0, 0, 255]).collect();
+		for (o, expected, w) in [
+			(2, "cbafed", 3),
+			(3, "fedcba", 3),
+			(4, "defabc", 3),
+			(5, "adbecf", 2),
+			(6, "daebfc", 2),
+			(7, "fcebda", 2),
+			(8, "cfbead", 2),
+		] {
+			let (out, width, height) = orient(&src, 3, 2, o);
+			assert_eq!((width, height), (w, 6 / w), "Wrong dimensions for {o}.");
+			let out: String = out.chunks_exact(4).map(|px| char::from(px[0])).collect();
+			assert_eq!(out, expected, "Wrong pixels for {o}.");
 		}
-		else { Err(RefractError::Overflow) }
 	}
 }
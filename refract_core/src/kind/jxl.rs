@@ -2,19 +2,31 @@
 # `Refract`: `JPEG XL` Handling
 */
 
+use crate::NZ_150;
+use std::num::NonZeroU8;
+
+#[cfg(feature = "jxl")]
 use crate::{
+	CancelToken,
+	enc::sink::{
+		drain,
+		Sink,
+	},
+	FLAG_JXL_PROGRESSIVE,
+	FLAG_KEEP_METADATA,
 	Input,
-	NZ_150,
 	Output,
 	RefractError,
 	traits::Encoder,
 };
+
+#[cfg(feature = "jxl")]
 use jpegxl_sys::{
-	metadata::codestream_header::JxlBasicInfo,
 	color::color_encoding::JxlColorEncoding,
 	encoder::encode::{
 		JxlColorEncodingSetToSRGB,
 		JxlEncoder,
+		JxlEncoderAddBox,
 		JxlEncoderAddImageFrame,
 		JxlEncoderCloseInput,
 		JxlEncoderCreate,
@@ -30,12 +42,16 @@ use jpegxl_sys::{
 		JxlEncoderSetExtraChannelDistance,
 		JxlEncoderSetFrameDistance,
 		JxlEncoderSetFrameLossless,
+		JxlEncoderSetICCProfile,
 		JxlEncoderSetParallelRunner,
 		JxlEncoderStatus,
+		JxlEncoderUseBoxes,
 		JxlEncoderUseContainer,
+		JxlEncoderVersion,
 	},
 	common::types::{
 		JxlBool,
+		JxlBoxType,
 		JxlEndianness,
 		JxlDataType,
 		JxlPixelFormat,
@@ -46,16 +62,19 @@ use jpegxl_sys::{
 		JxlThreadParallelRunnerDestroy,
 	},
 };
+
+#[cfg(feature = "jxl")]
 use std::{
-	ffi::c_void,
-	mem::MaybeUninit,
-	num::{
-		NonZeroU8,
-		NonZeroUsize,
+	borrow::Cow,
+	ffi::{
+		c_char,
+		c_void,
 	},
+	mem::MaybeUninit,
+	num::NonZeroUsize,
 };
 
-#[cfg(feature = "decode_ng")]
+#[cfg(all(feature = "jxl", feature = "decode_ng"))]
 use crate::{
 	ColorKind,
 	traits::{
@@ -64,7 +83,7 @@ use crate::{
 	},
 };
 
-#[cfg(feature = "decode_ng")]
+#[cfg(all(feature = "jxl", feature = "decode_ng"))]
 use jpegxl_sys::decode::{
 	JxlColorProfileTarget,
 	JxlDecoder,
@@ -83,12 +102,74 @@ use jpegxl_sys::decode::{
 	JxlDecoderSubscribeEvents,
 };
 
+#[cfg(all(feature = "jxl", feature = "decode_ng"))]
+use jpegxl_sys::metadata::codestream_header::JxlBasicInfo;
+
+
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+/// # `JPEG XL` Tuning.
+///
+/// This holds the `JPEG XL` encoder's effort and decoding speed settings,
+/// which trade encoding time and decoding time, respectively, against
+/// compression. Pass one to
+/// [`EncodeIter::set_jxl_tuning`](crate::EncodeIter::set_jxl_tuning) to
+/// change them.
+///
+/// The default — effort 9, decoding speed 0 — squeezes out every last byte,
+/// but effort 7 is several times faster with nearly identical results, which
+/// can make more sense for large batches.
+pub struct JxlTuning {
+	/// # Effort (`1..=9`).
+	effort: u8,
+
+	/// # Decoding Speed (`0..=4`).
+	decoding_speed: u8,
+}
+
+impl Default for JxlTuning {
+	#[inline]
+	fn default() -> Self { Self::DEFAULT }
+}
+
+impl JxlTuning {
+	/// # Default.
+	pub const DEFAULT: Self = Self { effort: 9, decoding_speed: 0 };
+
+	#[must_use]
+	/// # New.
+	///
+	/// Values outside the supported ranges — `1..=9` for effort, `0..=4` for
+	/// decoding speed — are clamped.
+	pub fn new(effort: u8, decoding_speed: u8) -> Self {
+		Self {
+			effort: effort.clamp(1, 9),
+			decoding_speed: decoding_speed.min(4),
+		}
+	}
+
+	#[inline]
+	#[must_use]
+	/// # Effort.
+	pub const fn effort(self) -> u8 { self.effort }
+
+	#[inline]
+	#[must_use]
+	/// # Decoding Speed.
+	pub const fn decoding_speed(self) -> u8 { self.decoding_speed }
+}
+
 
 
 /// # JPEG XL Image.
 pub(crate) struct ImageJxl;
 
-#[cfg(feature = "decode_ng")]
+impl ImageJxl {
+	/// # Maximum Quality.
+	pub(crate) const MAX_QUALITY: NonZeroU8 = NZ_150;
+}
+
+#[cfg(all(feature = "jxl", feature = "decode_ng"))]
 impl Decoder for ImageJxl {
 	#[expect(unsafe_code, reason = "Needed for FFI.")]
 	fn decode(raw: &[u8]) -> Result<DecoderResult, RefractError> {
@@ -151,34 +232,55 @@ impl Decoder for ImageJxl {
 	}
 }
 
+#[cfg(feature = "jxl")]
 impl Encoder for ImageJxl {
-	/// # Maximum Quality.
-	const MAX_QUALITY: NonZeroU8 = NZ_150;
-
 	#[inline]
 	/// # Encode Lossy.
-	fn encode_lossy(input: &Input, output: &mut Output, quality: NonZeroU8, _flags: u8)
+	fn encode_lossy(input: &Input, output: &mut Output, quality: NonZeroU8, flags: u8)
 	-> Result<(), RefractError> {
-		encode(input, output, Some(quality))
+		encode(input, output, Some(quality), flags)
 	}
 
 	#[inline]
 	/// # Encode Lossless.
-	fn encode_lossless(input: &Input, output: &mut Output, _flags: u8)
+	fn encode_lossless(input: &Input, output: &mut Output, flags: u8)
 	-> Result<(), RefractError> {
-		encode(input, output, None)
+		encode(input, output, None, flags)
+	}
+
+	#[expect(unsafe_code, reason = "Needed for FFI.")]
+	/// # Encoder Version.
+	fn version() -> String {
+		// Safety: this is an FFI call…
+		let v = unsafe { JxlEncoderVersion() };
+		format!("libjxl {}.{}.{}", v / 1_000_000, v / 1000 % 1000, v % 1000)
+	}
+
+	/// # Encoder Settings.
+	fn settings(output: &Output) -> Cow<'static, str> {
+		let flags = output.flags();
+		let tuning = output.jxl_tuning();
+		let mode =
+			if 0 != flags & FLAG_JXL_PROGRESSIVE { ", progressive, center-first" }
+			else if output.quality().is_lossless() && output.jxl_streaming() { ", streaming" }
+			else { "" };
+		Cow::Owned(format!(
+			"effort {}, decoding speed {}{mode}",
+			tuning.effort(),
+			tuning.decoding_speed(),
+		))
 	}
 }
 
 
 
-#[cfg(feature = "decode_ng")]
+#[cfg(all(feature = "jxl", feature = "decode_ng"))]
 /// # Hold the Decoder.
 ///
 /// This wrapper exists solely to help with drop cleanup.
 struct LibJxlDecoder(*mut JxlDecoder);
 
-#[cfg(feature = "decode_ng")]
+#[cfg(all(feature = "jxl", feature = "decode_ng"))]
 impl LibJxlDecoder {
 	#[expect(unsafe_code, reason = "Needed for FFI.")]
 	/// # New Decoder.
@@ -295,7 +397,7 @@ impl LibJxlDecoder {
 	}
 }
 
-#[cfg(feature = "decode_ng")]
+#[cfg(all(feature = "jxl", feature = "decode_ng"))]
 impl Drop for LibJxlDecoder {
 	#[expect(unsafe_code, reason = "Needed for FFI.")]
 	#[inline]
@@ -307,11 +409,13 @@ impl Drop for LibJxlDecoder {
 
 
 
+#[cfg(feature = "jxl")]
 /// # Hold the Encoder.
 ///
 /// This wrapper exists solely to help with drop cleanup.
 struct LibJxlEncoder(*mut JxlEncoder);
 
+#[cfg(feature = "jxl")]
 impl LibJxlEncoder {
 	#[expect(unsafe_code, reason = "Needed for FFI.")]
 	/// # New instance!
@@ -324,7 +428,17 @@ impl LibJxlEncoder {
 
 	#[expect(unsafe_code, reason = "Needed for FFI.")]
 	/// # Set Basic Info.
-	fn set_basic_info(&self, width: u32, height: u32, alpha: bool, grey: bool) -> Result<(), RefractError> {
+	///
+	/// The color space is sRGB unless an ICC profile is passed through.
+	fn set_basic_info(
+		&self,
+		width: u32,
+		height: u32,
+		bits: u32,
+		alpha: bool,
+		grey: bool,
+		icc: Option<&[u8]>,
+	) -> Result<(), RefractError> {
 		// Set up JPEG XL's "basic info" struct.
 		// Safety: this is an FFI call…
 		let mut basic_info = unsafe {
@@ -338,7 +452,7 @@ impl LibJxlEncoder {
 		basic_info.uses_original_profile = JxlBool::True;
 		basic_info.have_container = JxlBool::False;
 
-		basic_info.bits_per_sample = 8;
+		basic_info.bits_per_sample = bits;
 		basic_info.exponent_bits_per_sample = 0;
 		basic_info.alpha_premultiplied = JxlBool::False;
 		basic_info.alpha_exponent_bits = 0;
@@ -346,7 +460,7 @@ impl LibJxlEncoder {
 		// Adjust for alpha.
 		if alpha {
 			basic_info.num_extra_channels = 1;
-			basic_info.alpha_bits = 8;
+			basic_info.alpha_bits = bits;
 		}
 		else {
 			basic_info.num_extra_channels = 0;
@@ -357,6 +471,15 @@ impl LibJxlEncoder {
 		// default is three.)
 		if grey { basic_info.num_color_channels = 1; }
 
+		// Safety: this is an FFI call…
+		maybe_die(unsafe { JxlEncoderSetBasicInfo(self.0, &basic_info) })?;
+
+		// Use the original profile, if any.
+		if let Some(icc) = icc {
+			// Safety: this is an FFI call…
+			return maybe_die(unsafe { JxlEncoderSetICCProfile(self.0, icc.as_ptr(), icc.len()) });
+		}
+
 		// Safety: this is an FFI call…
 		let color_encoding: JxlColorEncoding = unsafe {
 			let mut color_encoding = MaybeUninit::uninit();
@@ -367,59 +490,43 @@ impl LibJxlEncoder {
 			color_encoding.assume_init()
 		};
 
-		// Safety: this is an FFI call…
-		maybe_die(unsafe { JxlEncoderSetBasicInfo(self.0, &basic_info) })?;
 		// Safety: this is an FFI call…
 		maybe_die(unsafe { JxlEncoderSetColorEncoding(self.0, &color_encoding) })
 	}
 
 	#[expect(unsafe_code, reason = "Needed for FFI.")]
 	/// # Write.
-	fn write(&self, candidate: &mut Output) -> Result<(), RefractError> {
-		// Grab the buffer.
-		let buf = candidate.as_mut_vec();
-
-		// Process the output.
-		loop {
-			let mut len: usize = buf.len();
-			let mut avail_out = buf.capacity() - len;
-
-			// Make sure we can write at least 64KiB to the buffer.
-			if avail_out < 65_536 {
-				buf.try_reserve(65_536).map_err(|_| RefractError::Overflow)?;
-				avail_out = buf.capacity() - len;
+	///
+	/// Stream the encoded output to the sink, one chunk at a time, bailing
+	/// early if cancelled.
+	fn write<S: Sink>(&self, sink: &mut S, cancel: Option<&CancelToken>)
+	-> Result<(), RefractError> {
+		drain(|chunk| {
+			if cancel.is_some_and(CancelToken::is_cancelled) {
+				return Err(RefractError::Cancelled);
 			}
 
-			// Let JPEG XL do its thing.
-			// Safety: this is an FFI call…
-			let mut next_out = unsafe { buf.as_mut_ptr().add(len).cast() };
-			// Safety: this is an FFI call…
+			let mut next_out = chunk.as_mut_ptr();
+			let mut avail_out = chunk.len();
+
+			// Safety: the pointer and length describe a single initialized
+			// buffer that outlives the call.
 			let res = unsafe {
 				JxlEncoderProcessOutput(self.0, &mut next_out, &mut avail_out)
 			};
 
-			// Abort on error.
-			if res != JxlEncoderStatus::Success && res != JxlEncoderStatus::NeedMoreOutput {
-				return Err(RefractError::Encode);
+			// The amount written is however much room got used up.
+			let len = chunk.len().checked_sub(avail_out).ok_or(RefractError::Overflow)?;
+			match res {
+				JxlEncoderStatus::Success => Ok((len, true)),
+				JxlEncoderStatus::NeedMoreOutput => Ok((len, false)),
+				JxlEncoderStatus::Error => Err(RefractError::Encode),
 			}
-
-			// The new next offset is how far from the beginning?
-			// Safety: this is an FFI call…
-			len = usize::try_from(unsafe { next_out.offset_from(buf.as_ptr()) })
-				.map_err(|_| RefractError::Overflow)?;
-
-			// Adjust the buffer length to match.
-			// Safety: this is an FFI call…
-			unsafe { buf.set_len(len); }
-
-			// We're done!
-			if JxlEncoderStatus::Success == res { break; }
-		}
-
-		Ok(())
+		}, sink)
 	}
 }
 
+#[cfg(feature = "jxl")]
 impl Drop for LibJxlEncoder {
 	#[expect(unsafe_code, reason = "Needed for FFI.")]
 	#[inline]
@@ -431,11 +538,13 @@ impl Drop for LibJxlEncoder {
 
 
 
+#[cfg(feature = "jxl")]
 /// # Hold the Thread Runner.
 ///
 /// This wrapper exists solely to help with drop cleanup.
 struct LibJxlThreadParallelRunner(*mut c_void);
 
+#[cfg(feature = "jxl")]
 impl LibJxlThreadParallelRunner {
 	#[expect(unsafe_code, reason = "Needed for FFI.")]
 	/// # New instance!
@@ -452,6 +561,7 @@ impl LibJxlThreadParallelRunner {
 	}
 }
 
+#[cfg(feature = "jxl")]
 impl Drop for LibJxlThreadParallelRunner {
 	#[expect(unsafe_code, reason = "Needed for FFI.")]
 	#[inline]
@@ -463,6 +573,7 @@ impl Drop for LibJxlThreadParallelRunner {
 
 
 
+#[cfg(feature = "jxl")]
 #[expect(unsafe_code, reason = "Needed for FFI.")]
 /// # Encode.
 ///
@@ -471,7 +582,8 @@ impl Drop for LibJxlThreadParallelRunner {
 fn encode(
 	img: &Input,
 	candidate: &mut Output,
-	quality: Option<NonZeroU8>
+	quality: Option<NonZeroU8>,
+	flags: u8,
 ) -> Result<(), RefractError> {
 	// Initialize the encoder.
 	let enc = LibJxlEncoder::new()?;
@@ -493,9 +605,27 @@ fn encode(
 		JxlEncoderFrameSettingsCreate(enc.0, std::ptr::null())
 	};
 
-	// No containers.
-	// Safety: this is an FFI call…
-	maybe_die(unsafe { JxlEncoderUseContainer(enc.0, false) })?;
+	// No containers, unless there's EXIF to box up.
+	let keep = 0 != flags & FLAG_KEEP_METADATA;
+	let exif = if keep { img.metadata().exif() } else { None };
+	if let Some(exif) = exif {
+		// The box leads with the offset to the TIFF header, i.e. zero.
+		let mut data = Vec::with_capacity(exif.len() + 4);
+		data.extend_from_slice(&[0, 0, 0, 0]);
+		data.extend_from_slice(exif);
+
+		let kind = JxlBoxType(b"Exif".map(|b| c_char::from_ne_bytes([b])));
+		// Safety: this is an FFI call…
+		maybe_die(unsafe { JxlEncoderUseBoxes(enc.0) })?;
+		// Safety: this is an FFI call…
+		maybe_die(unsafe {
+			JxlEncoderAddBox(enc.0, &kind, data.as_ptr(), data.len(), JxlBool::False)
+		})?;
+	}
+	else {
+		// Safety: this is an FFI call…
+		maybe_die(unsafe { JxlEncoderUseContainer(enc.0, false) })?;
+	}
 
 	// Set distance and losslessness.
 	let q = match quality.map(NonZeroU8::get) {
@@ -507,22 +637,54 @@ fn encode(
 	// Safety: this is an FFI call…
 	maybe_die(unsafe { JxlEncoderSetFrameDistance(options, q) })?;
 
-	// Effort. 9 == Tortoise.
+	// Effort (9 == Tortoise) and decoding speed (0 == Highest quality).
+	let tuning = candidate.jxl_tuning();
 	// Safety: this is an FFI call…
-	maybe_die(unsafe { JxlEncoderFrameSettingsSetOption(options, JxlEncoderFrameSettingId::Effort, 9) })?;
-
-	// Decoding speed. 0 == Highest quality.
+	maybe_die(unsafe {
+		JxlEncoderFrameSettingsSetOption(options, JxlEncoderFrameSettingId::Effort, i64::from(tuning.effort()))
+	})?;
 	// Safety: this is an FFI call…
-	maybe_die(unsafe { JxlEncoderFrameSettingsSetOption(options, JxlEncoderFrameSettingId::DecodingSpeed, 0) })?;
+	maybe_die(unsafe {
+		JxlEncoderFrameSettingsSetOption(options, JxlEncoderFrameSettingId::DecodingSpeed, i64::from(tuning.decoding_speed()))
+	})?;
+
+	// Progressive passes, center-first.
+	if 0 != flags & FLAG_JXL_PROGRESSIVE {
+		for (id, v) in [
+			(JxlEncoderFrameSettingId::Responsive, 1),
+			(JxlEncoderFrameSettingId::QprogressiveAc, 1),
+			(JxlEncoderFrameSettingId::ProgressiveDc, 1),
+			(JxlEncoderFrameSettingId::GroupOrder, 1),
+		] {
+			// Safety: this is an FFI call…
+			maybe_die(unsafe { JxlEncoderFrameSettingsSetOption(options, id, v) })?;
+		}
+	}
+
+	// Work through huge images a group at a time to keep memory in check.
+	// (libjxl can only do this for lossless, given our use of the original
+	// color profile.)
+	if 0.0 == q && candidate.jxl_streaming() {
+		// Safety: this is an FFI call…
+		maybe_die(unsafe { JxlEncoderFrameSettingsSetOption(options, JxlEncoderFrameSettingId::Buffering, 2) })?;
+	}
 
 	// Set up JPEG XL's "basic info" struct.
 	let color = img.color();
-	enc.set_basic_info(img.width_u32(), img.height_u32(), color.has_alpha(), color.is_greyscale())?;
+	let wide = img.wide_native();
+	enc.set_basic_info(
+		img.width_u32(),
+		img.height_u32(),
+		if wide.is_some() { 16 } else { 8 },
+		color.has_alpha(),
+		color.is_greyscale(),
+		if keep { img.metadata().icc_for(color.is_greyscale()) } else { None },
+	)?;
 
 	// Set up a "frame".
 	let pixel_format = JxlPixelFormat {
 		num_channels: color.channels(),
-		data_type: JxlDataType::Uint8,
+		data_type: if wide.is_some() { JxlDataType::Uint16 } else { JxlDataType::Uint8 },
 		endianness: JxlEndianness::Native,
 		align: 0,
 	};
@@ -533,23 +695,25 @@ fn encode(
 		maybe_die(unsafe { JxlEncoderSetExtraChannelDistance(options, 0, 0.0) })?;
 	}
 
+	// Use the full-precision pixels if there are any.
 	let data: &[u8] = img;
+	let (ptr, len) = wide.as_deref().map_or_else(
+		|| (data.as_ptr().cast::<c_void>(), size_of_val(data)),
+		|w| (w.as_ptr().cast::<c_void>(), size_of_val(w)),
+	);
 	// Safety: this is an FFI call…
 	maybe_die(unsafe {
-		JxlEncoderAddImageFrame(
-			options,
-			&pixel_format,
-			data.as_ptr().cast(),
-			size_of_val(data),
-		)
+		JxlEncoderAddImageFrame(options, &pixel_format, ptr, len)
 	})?;
 
 	// Finalize the encoder.
 	// Safety: this is an FFI call…
 	unsafe { JxlEncoderCloseInput(enc.0); }
-	enc.write(candidate)
+	let cancel = candidate.cancel_token().cloned();
+	enc.write(candidate.as_mut_vec(), cancel.as_ref())
 }
 
+#[cfg(feature = "jxl")]
 /// # Verify Encoder Status.
 ///
 /// Most `JPEG XL` API methods return a status; this converts unsuccessful
@@ -561,7 +725,7 @@ const fn maybe_die(res: JxlEncoderStatus) -> Result<(), RefractError> {
 	}
 }
 
-#[cfg(feature = "decode_ng")]
+#[cfg(all(feature = "jxl", feature = "decode_ng"))]
 /// # Verify Decoder Status.
 const fn maybe_die_dec(res: JxlDecoderStatus) -> Result<(), RefractError> {
 	match res {
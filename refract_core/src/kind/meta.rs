@@ -0,0 +1,135 @@
+/*!
+# `Refract` - Metadata
+*/
+
+use std::sync::Arc;
+
+
+
+#[derive(Debug, Clone, Default)]
+/// # Source Metadata.
+///
+/// This holds the bits of a source's metadata worth carrying over to its
+/// outputs when [`FLAG_KEEP_METADATA`](crate::FLAG_KEEP_METADATA) is set: the
+/// embedded ICC color profile, and the EXIF data (which is where orientation
+/// and copyright tags live).
+///
+/// The buffers are reference-counted so borrowing an [`Input`](crate::Input)
+/// doesn't mean copying them.
+pub(crate) struct Metadata {
+	/// # ICC Profile.
+	icc: Option<Arc<[u8]>>,
+
+	/// # EXIF.
+	///
+	/// This is the raw TIFF structure, sans any container-specific prefix
+	/// like JPEG's `Exif\0\0`.
+	exif: Option<Arc<[u8]>>,
+}
+
+impl Metadata {
+	/// # New.
+	///
+	/// Empty buffers are treated as missing.
+	pub(crate) fn new(icc: Option<&[u8]>, exif: Option<&[u8]>) -> Self {
+		Self {
+			icc: icc.filter(|v| ! v.is_empty()).map(Arc::from),
+			exif: exif.filter(|v| is_tiff(v)).map(Arc::from),
+		}
+	}
+
+	#[cfg(any(feature = "avif", feature = "jxl", feature = "webp", feature = "webp_rust"))]
+	/// # EXIF.
+	pub(crate) fn exif(&self) -> Option<&[u8]> { self.exif.as_deref() }
+
+	/// # ICC Profile.
+	pub(crate) fn icc(&self) -> Option<&[u8]> { self.icc.as_deref() }
+
+	/// # Without ICC Profile.
+	///
+	/// Drop the ICC profile, e.g. once the pixels have been converted to
+	/// sRGB.
+	pub(crate) fn without_icc(self) -> Self {
+		Self { icc: None, exif: self.exif }
+	}
+
+	#[cfg(any(feature = "avif", feature = "jxl"))]
+	/// # ICC Profile (Matching).
+	///
+	/// Return the ICC profile, but only if its color space agrees with the
+	/// channels being encoded; some encoders reject an RGB profile attached
+	/// to greyscale pixels, and vice versa.
+	pub(crate) fn icc_for(&self, greyscale: bool) -> Option<&[u8]> {
+		let icc = self.icc()?;
+		let space = icc.get(16..20)?;
+		if space == if greyscale { b"GRAY" } else { b"RGB " } { Some(icc) }
+		else { None }
+	}
+}
+
+
+
+/// # EXIF Orientation.
+///
+/// Return the value of the orientation tag (`1..=8`) from the first IFD of
+/// the TIFF structure, along with its offset, or `None` if missing or
+/// nonsensical.
+pub(crate) fn exif_orientation(tiff: &[u8]) -> Option<(usize, u16)> {
+	// Read a two- or four-byte integer in the file's byte order.
+	let le = tiff.starts_with(b"II");
+	let u16_at = |pos: usize| -> Option<u16> {
+		let bytes = [*tiff.get(pos)?, *tiff.get(pos + 1)?];
+		Some(if le { u16::from_le_bytes(bytes) } else { u16::from_be_bytes(bytes) })
+	};
+	let u32_at = |pos: usize| -> Option<usize> {
+		let bytes = [*tiff.get(pos)?, *tiff.get(pos + 1)?, *tiff.get(pos + 2)?, *tiff.get(pos + 3)?];
+		usize::try_from(if le { u32::from_le_bytes(bytes) } else { u32::from_be_bytes(bytes) }).ok()
+	};
+
+	if ! is_tiff(tiff) { return None; }
+	let ifd = u32_at(4)?;
+	let count = usize::from(u16_at(ifd)?);
+
+	// Each entry is twelve bytes: tag, type, count, then the value itself.
+	for i in 0..count {
+		let entry = ifd + 2 + i * 12;
+		if 0x0112 == u16_at(entry)? {
+			let pos = entry + 8;
+			return u16_at(pos).filter(|v| (1..=8).contains(v)).map(|v| (pos, v));
+		}
+	}
+
+	None
+}
+
+/// # Is TIFF?
+///
+/// EXIF data is a TIFF structure, which starts with a byte-order mark
+/// followed by the magic number 42.
+fn is_tiff(raw: &[u8]) -> bool {
+	matches!(raw, [b'I', b'I', 42, 0, ..] | [b'M', b'M', 0, 42, ..])
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn t_exif_orientation() {
+		// Big-endian, one entry (orientation = 6).
+		let mm = b"MM\0\x2A\0\0\0\x08\0\x01\x01\x12\0\x03\0\0\0\x01\0\x06\0\0\0\0\0\0";
+		assert_eq!(exif_orientation(mm), Some((18, 6)));
+
+		// Little-endian, orientation preceded by another tag.
+		let ii = b"II\x2A\0\x08\0\0\0\x02\0\x0F\x01\x02\0\x01\0\0\0\0\0\0\0\x12\x01\x03\0\x01\0\0\0\x08\0\0\0";
+		assert_eq!(exif_orientation(ii), Some((30, 8)));
+
+		// Out of range, and truncated.
+		let mut bad = mm.to_vec();
+		bad[19] = 9;
+		assert_eq!(exif_orientation(&bad), None);
+		assert_eq!(exif_orientation(&mm[..18]), None);
+	}
+}
@@ -4,8 +4,14 @@
 
 pub(super) mod avif;
 pub(super) mod color;
+pub(super) mod icc;
 pub(super) mod image;
 pub(super) mod jpeg;
 pub(super) mod jxl;
+pub(super) mod meta;
 pub(super) mod png;
+pub(super) mod sniff;
 pub(super) mod webp;
+
+#[cfg(all(feature = "webp_rust", not(feature = "webp")))]
+pub(super) mod webp_rust;
@@ -6,22 +6,225 @@ mod alpha;
 
 use crate::{
 	ColorKind,
+	Input,
+	Output,
 	RefractError,
+	error::set_upstream,
+	kind::{
+		color::is_srgb_icc,
+		meta::Metadata,
+	},
 	traits::{
 		Decoder,
 		DecoderResult,
+		Encoder,
 	},
 };
 use lodepng::{
 	Bitmap,
+	ColorType,
+	FilterStrategy,
+	Image,
 	RGBA,
 };
+use std::{
+	borrow::Cow,
+	num::NonZeroU8,
+};
+
+
+
+/// # Decode to PNG.
+///
+/// Decode an image — any format [`Input`] supports — and re-encode it as a
+/// lossless RGBA PNG, e.g. for editing in tools that do not understand
+/// `AVIF` or `JPEG XL` yet.
+///
+/// Note: without the `decode_ng` crate feature, only `JPEG` and `PNG`
+/// sources can be decoded.
+///
+/// ## Errors
+///
+/// Returns an error if the source cannot be decoded or the PNG cannot be
+/// encoded.
+pub fn decode_to_png(raw: &[u8]) -> Result<Vec<u8>, RefractError> {
+	let src = Input::try_from(raw)?;
+	let rgba = src.as_rgba();
+	lodepng::encode32(rgba.as_ref(), rgba.width(), rgba.height())
+		.map_err(|_| RefractError::Encode)
+}
 
 
 
 /// # PNG Image.
 pub(crate) struct ImagePng;
 
+impl ImagePng {
+	/// # Inspect.
+	///
+	/// Walk the chunks leading up to the image data — without decoding any
+	/// pixels — to find the source bit depth and whether or not a non-sRGB
+	/// color profile is embedded.
+	///
+	/// Note: `iCCP` profiles are compressed, so their names are used as a
+	/// stand-in for the profile itself.
+	pub(crate) fn inspect(raw: &[u8]) -> (u8, bool) {
+		let mut bits = 8;
+		let mut icc = false;
+		for (kind, data) in PngChunks::new(raw) {
+			match &kind {
+				b"IHDR" => if let Some(&b) = data.get(8) { bits = b; },
+				b"iCCP" => {
+					let name = data.split(|&b| b == 0).next().unwrap_or_default();
+					icc = ! is_srgb_icc(name);
+				},
+				// An explicit sRGB chunk trumps everything else.
+				b"sRGB" => { icc = false; break; },
+				// Color information has to come before the data.
+				b"IDAT" | b"IEND" => break,
+				_ => {},
+			}
+		}
+
+		(bits, icc)
+	}
+
+	/// # Metadata.
+	///
+	/// Pull the ICC profile and EXIF data, if any, from their respective
+	/// chunks. The former is zlib-compressed; profiles that fail to inflate —
+	/// or inflate to something absurd — are ignored.
+	pub(crate) fn metadata(raw: &[u8]) -> Metadata {
+		/// # Maximum ICC Profile Size.
+		const MAX_ICC: usize = 4 * 1024 * 1024;
+
+		let mut icc = None;
+		let mut exif = None;
+		for (kind, data) in PngChunks::new(raw) {
+			match &kind {
+				// Name, nul, compression method (always zero), then data.
+				b"iCCP" => if let Some(pos) = data.iter().position(|&b| b == 0) {
+					icc = data.get(pos + 2..).and_then(|z|
+						miniz_oxide::inflate::decompress_to_vec_zlib_with_limit(z, MAX_ICC).ok()
+					);
+				},
+				// Some writers include the JPEG-style prefix by mistake.
+				b"eXIf" => { exif = Some(data.strip_prefix(b"Exif\0\0").unwrap_or(data)); },
+				b"IEND" => break,
+				_ => {},
+			}
+		}
+
+		Metadata::new(icc.as_deref(), exif)
+	}
+
+	/// # Decode (16-Bit).
+	///
+	/// Decode a 16-bit source, returning the usual 8-bit result alongside a
+	/// full-precision RGBA copy of the pixels for the encoders that can make
+	/// use of it.
+	///
+	/// The copy is omitted if the extra precision is fake, i.e. every sample
+	/// is just an 8-bit value scaled up.
+	pub(crate) fn decode_wide(raw: &[u8]) -> Result<(DecoderResult, Option<Vec<u16>>), RefractError> {
+		let Ok(Image::RGBA16(Bitmap { buffer, width, height })) = lodepng::decode_memory(raw, ColorType::RGBA, 16)
+		else { return Err(RefractError::Decode); };
+
+		// The pixel buffer should match the dimensions..
+		let size = width.checked_mul(height).and_then(|x| x.checked_mul(4))
+			.ok_or(RefractError::Overflow)?;
+
+		// Note: lodepng hands over the samples in PNG (big-endian) byte
+		// order.
+		let mut wide: Vec<u16> = buffer.into_iter()
+			.flat_map(|px| [px.r, px.g, px.b, px.a])
+			.map(u16::from_be)
+			.collect();
+		if wide.len() != size { return Err(RefractError::Decode); }
+
+		// The 8-bit version keeps the most significant byte of each sample.
+		let mut raw: Vec<u8> = wide.iter().map(|v| v.to_be_bytes()[0]).collect();
+		let color = ColorKind::from_rgba(&raw);
+
+		// If we have alpha, clean it up, then bring any pixels that changed
+		// over to the wide copy too.
+		if color.has_alpha() {
+			alpha::clean_alpha(&mut raw, width, height);
+			for (w, n) in wide.chunks_exact_mut(4).zip(raw.chunks_exact(4)) {
+				if w.iter().zip(n).any(|(a, b)| a.to_be_bytes()[0] != *b) {
+					for (a, b) in w.iter_mut().zip(n) { *a = u16::from(*b) * 257; }
+				}
+			}
+		}
+
+		let wide = (! wide.iter().all(|v| 0 == v % 257)).then_some(wide);
+		Ok(((raw, width, height, color), wide))
+	}
+
+	/// # Sniff.
+	///
+	/// Walk the chunks leading up to the image data to see whether the image
+	/// is animated (APNG) and/or interlaced (Adam7).
+	pub(crate) fn sniff(raw: &[u8]) -> (bool, bool) {
+		let mut animated = false;
+		let mut interlaced = false;
+		for (kind, data) in PngChunks::new(raw) {
+			match &kind {
+				b"IHDR" => { interlaced = data.get(12) == Some(&1); },
+				b"acTL" => { animated = true; },
+				// Animation control has to come before the data.
+				b"IDAT" | b"IEND" => break,
+				_ => {},
+			}
+		}
+
+		(animated, interlaced)
+	}
+}
+
+impl Encoder for ImagePng {
+	#[inline]
+	/// # Encode Lossy.
+	///
+	/// PNG is lossless-only; [`EncodeIter`](crate::EncodeIter) never asks.
+	fn encode_lossy(_input: &Input, _output: &mut Output, _quality: NonZeroU8, _flags: u8)
+	-> Result<(), RefractError> {
+		Err(RefractError::Encode)
+	}
+
+	/// # Encode Lossless.
+	///
+	/// Losslessly recompress the image, `oxipng`-style: the color type and
+	/// bit depth are reduced as far as the pixels allow — e.g. to an indexed
+	/// palette or greyscale — and each of the main row filter strategies is
+	/// tried in turn at maximum deflate compression, keeping the smallest.
+	fn encode_lossless(input: &Input, output: &mut Output, _flags: u8)
+	-> Result<(), RefractError> {
+		let mut best: Option<Vec<u8>> = None;
+		for strategy in [FilterStrategy::ZERO, FilterStrategy::MINSUM, FilterStrategy::ENTROPY] {
+			let mut enc = lodepng::Encoder::new();
+			enc.set_auto_convert(true);
+			enc.set_filter_strategy(strategy, true);
+			enc.settings_mut().zlibsettings.set_level(9);
+
+			let Ok(out) = enc.encode(input.as_ref(), input.width(), input.height())
+			else { continue; };
+			if best.as_ref().is_none_or(|b| out.len() < b.len()) { best = Some(out); }
+		}
+
+		output.set_slice(&best.ok_or(RefractError::Encode)?);
+		Ok(())
+	}
+
+	/// # Encoder Version.
+	fn version() -> String { String::from("lodepng 3.10.7") }
+
+	/// # Encoder Settings.
+	fn settings(_output: &Output) -> Cow<'static, str> {
+		Cow::Borrowed("color reduction, deflate level 9, best of 3 filters")
+	}
+}
+
 impl Decoder for ImagePng {
 	/// # Decode.
 	fn decode(raw: &[u8]) -> Result<DecoderResult, RefractError> {
@@ -29,7 +232,10 @@ impl Decoder for ImagePng {
 		let (mut raw, width, height): (Vec<u8>, usize, usize) = {
 			// Parse the file.
 			let Bitmap::<RGBA> { buffer, width, height } = lodepng::decode32(raw)
-				.map_err(|_| RefractError::Decode)?;
+				.map_err(|e| {
+					set_upstream(format!("lodepng: {e}"));
+					RefractError::Decode
+				})?;
 
 			// The pixel buffer should match the dimensions..
 			let size = width.checked_mul(height).and_then(|x| x.checked_mul(4))
@@ -58,3 +264,87 @@ impl Decoder for ImagePng {
 		Ok((raw, width, height, color))
 	}
 }
+
+
+
+/// # PNG Chunks.
+///
+/// This is a simple iterator over the raw chunks of a PNG file, yielding the
+/// four-byte type and data slice for each. Iteration stops at the first sign
+/// of trouble.
+struct PngChunks<'a>(&'a [u8]);
+
+impl<'a> PngChunks<'a> {
+	/// # New.
+	///
+	/// Skip past the file signature, if present. If not, there's nothing to
+	/// iterate.
+	fn new(raw: &'a [u8]) -> Self {
+		match raw.split_at_checked(8) {
+			Some((sig, rest)) if sig == b"\x89PNG\r\n\x1a\n" => Self(rest),
+			_ => Self(&[]),
+		}
+	}
+}
+
+impl<'a> Iterator for PngChunks<'a> {
+	type Item = ([u8; 4], &'a [u8]);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		// Length and type come first.
+		let [a, b, c, d, k1, k2, k3, k4, rest @ ..] = self.0 else {
+			self.0 = &[];
+			return None;
+		};
+		let len = usize::try_from(u32::from_be_bytes([*a, *b, *c, *d])).ok()?;
+
+		// Then the data and CRC.
+		let Some((data, rest)) = rest.split_at_checked(len)
+			.and_then(|(data, rest)| Some((data, rest.get(4..)?)))
+		else {
+			self.0 = &[];
+			return None;
+		};
+
+		self.0 = rest;
+		Some(([*k1, *k2, *k3, *k4], data))
+	}
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn t_decode_wide() {
+		// A 2×1 opaque image, first with real 16-bit samples, then with
+		// 8-bit samples scaled up.
+		for (samples, real) in [
+			([0x1234_u16, 0xABCD, 0x00FF, 0xFFFF, 0x8000, 0x0001, 0xFFFE, 0xFFFF], true),
+			([0x1212_u16, 0xABAB, 0x0000, 0xFFFF, 0x8080, 0x0101, 0xFEFE, 0xFFFF], false),
+		] {
+			// (Auto-conversion would drop the fake precision.)
+			let raw: Vec<u8> = samples.iter().flat_map(|v| v.to_be_bytes()).collect();
+			let mut enc = lodepng::Encoder::new();
+			enc.set_auto_convert(false);
+			enc.info_raw_mut().colortype = ColorType::RGBA;
+			enc.info_raw_mut().set_bitdepth(16);
+			enc.info_png_mut().color.colortype = ColorType::RGBA;
+			enc.info_png_mut().color.set_bitdepth(16);
+			let png = enc.encode(&raw, 2, 1).unwrap();
+			assert_eq!(ImagePng::inspect(&png).0, 16, "Expected a 16-bit PNG.");
+
+			let ((buf, width, height, _), wide) = ImagePng::decode_wide(&png).unwrap();
+			assert_eq!((width, height), (2, 1));
+			assert_eq!(
+				buf,
+				samples.iter().map(|v| v.to_be_bytes()[0]).collect::<Vec<u8>>(),
+				"The 8-bit copy should keep the high bytes.",
+			);
+			if real { assert_eq!(wide.as_deref(), Some(&samples[..])); }
+			else { assert!(wide.is_none(), "Scaled 8-bit samples should be ignored."); }
+		}
+	}
+}
@@ -0,0 +1,159 @@
+/*!
+# `Refract` - Sniffing
+*/
+
+use crate::{
+	ImageAvif,
+	ImageJpeg,
+	ImageKind,
+	ImagePng,
+	ImageWebp,
+	RefractError,
+};
+
+
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+/// # Sniff Result.
+///
+/// This holds the [`ImageKind`] of a raw image file along with a few other
+/// details gleaned from its headers — whether it is animated, progressive,
+/// and/or interlaced — without decoding any pixels.
+///
+/// Sniffing is cheap and panic-free, making it suitable for things like
+/// validating uploads, but it only looks at the headers, so a positive match
+/// does not guarantee the image will actually decode.
+///
+/// Note: JPEG XL headers are not examined beyond the magic; they will always
+/// report as static and non-progressive.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use refract_core::{ImageKind, SniffResult};
+///
+/// let raw = std::fs::read("/path/to/my.png").unwrap();
+/// let sniff = SniffResult::try_from(raw.as_slice()).unwrap();
+/// assert_eq!(sniff.kind(), ImageKind::Png);
+/// if sniff.is_animated() {
+///     println!("This is an APNG!");
+/// }
+/// ```
+pub struct SniffResult {
+	/// # Image Kind.
+	kind: ImageKind,
+
+	/// # Animated?
+	animated: bool,
+
+	/// # Progressive?
+	progressive: bool,
+
+	/// # Interlaced?
+	interlaced: bool,
+}
+
+impl TryFrom<&[u8]> for SniffResult {
+	type Error = RefractError;
+
+	/// # From Raw Bytes.
+	///
+	/// ## Errors
+	///
+	/// This will return an error if the image kind cannot be determined.
+	fn try_from(src: &[u8]) -> Result<Self, Self::Error> {
+		let kind = ImageKind::try_from(src)?;
+		let mut out = Self {
+			kind,
+			animated: false,
+			progressive: false,
+			interlaced: false,
+		};
+
+		match kind {
+			ImageKind::Avif => { out.animated = ImageAvif::is_animated(src); },
+			ImageKind::Jpeg => { out.progressive = ImageJpeg::is_progressive(src); },
+			ImageKind::Jxl => {},
+			ImageKind::Png => {
+				(out.animated, out.interlaced) = ImagePng::sniff(src);
+			},
+			ImageKind::Webp => { out.animated = ImageWebp::is_animated(src); },
+		}
+
+		Ok(out)
+	}
+}
+
+/// ## Getters.
+impl SniffResult {
+	#[inline]
+	#[must_use]
+	/// # Image Kind.
+	pub const fn kind(&self) -> ImageKind { self.kind }
+
+	#[inline]
+	#[must_use]
+	/// # Is Animated?
+	///
+	/// Returns `true` for animated PNG (APNG), `WebP`, and AVIF sequences.
+	pub const fn is_animated(&self) -> bool { self.animated }
+
+	#[inline]
+	#[must_use]
+	/// # Is Progressive?
+	///
+	/// Returns `true` for progressive JPEGs.
+	pub const fn is_progressive(&self) -> bool { self.progressive }
+
+	#[inline]
+	#[must_use]
+	/// # Is Interlaced?
+	///
+	/// Returns `true` for (Adam7) interlaced PNGs.
+	pub const fn is_interlaced(&self) -> bool { self.interlaced }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn t_sniff() {
+		// Progressive JPEG: SOI, APP0, SOF2.
+		let raw = b"\xFF\xD8\xFF\xE0\0\x10JFIF\0\x01\0\0\0\0\0\0\0\0\xFF\xC2\0\x02\xFF\xD9";
+		let res = SniffResult::try_from(&raw[..]).expect("JPEG sniff failed.");
+		assert_eq!(res.kind(), ImageKind::Jpeg);
+		assert!(res.is_progressive(), "JPEG should be progressive.");
+
+		// Baseline JPEG.
+		let raw = b"\xFF\xD8\xFF\xE0\0\x10JFIF\0\x01\0\0\0\0\0\0\0\0\xFF\xC0\0\x02\xFF\xD9";
+		let res = SniffResult::try_from(&raw[..]).expect("JPEG sniff failed.");
+		assert!(! res.is_progressive(), "JPEG should not be progressive.");
+
+		// Interlaced, animated PNG: IHDR, acTL.
+		let raw = b"\x89PNG\r\n\x1A\n\
+			\0\0\0\x0DIHDR\0\0\0\x01\0\0\0\x01\x08\x06\0\0\x01\0\0\0\0\
+			\0\0\0\x08acTL\0\0\0\x02\0\0\0\0\0\0\0\0";
+		let res = SniffResult::try_from(&raw[..]).expect("PNG sniff failed.");
+		assert_eq!(res.kind(), ImageKind::Png);
+		assert!(res.is_interlaced(), "PNG should be interlaced.");
+		assert!(res.is_animated(), "PNG should be animated.");
+
+		// Animated WebP.
+		let raw = b"RIFF\0\0\0\0WEBPVP8X\x0A\0\0\0\x02\0\0\0";
+		let res = SniffResult::try_from(&raw[..]).expect("WebP sniff failed.");
+		assert!(res.is_animated(), "WebP should be animated.");
+
+		// AVIF sequence.
+		let raw = b"\0\0\0\x18ftypavis\0\0\0\0avifmsf1";
+		let res = SniffResult::try_from(&raw[..]).expect("AVIF sniff failed.");
+		assert!(res.is_animated(), "AVIF should be animated.");
+
+		// Still AVIF.
+		let raw = b"\0\0\0\x18ftypavif\0\0\0\0avifmif1";
+		let res = SniffResult::try_from(&raw[..]).expect("AVIF sniff failed.");
+		assert!(! res.is_animated(), "AVIF should not be animated.");
+	}
+}
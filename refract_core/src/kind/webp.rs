@@ -5,18 +5,33 @@ This uses [`libwebp-sys2`](https://crates.io/crates/libwebp-sys2) bindings to Go
 `libwebp`. Operations should be equivalent to the corresponding `cwebp` output.
 */
 
+#[cfg(feature = "webp")]
 use crate::{
+	FLAG_KEEP_METADATA,
 	Input,
 	Output,
 	RefractError,
+	error::set_upstream,
 	traits::Encoder,
 };
+
+#[cfg(feature = "webp")]
 use libwebp_sys::{
+	VP8_ENC_ERROR_BAD_DIMENSION,
+	VP8_ENC_ERROR_BITSTREAM_OUT_OF_MEMORY,
+	VP8_ENC_ERROR_FILE_TOO_BIG,
+	VP8_ENC_ERROR_INVALID_CONFIGURATION,
+	VP8_ENC_ERROR_OUT_OF_MEMORY,
+	VP8_ENC_ERROR_PARTITION0_OVERFLOW,
+	VP8_ENC_ERROR_PARTITION_OVERFLOW,
+	VP8_ENC_ERROR_USER_ABORT,
 	WEBP_MAX_DIMENSION,
 	WebPConfig,
 	WebPConfigInit,
 	WebPConfigLosslessPreset,
 	WebPEncode,
+	WebPEncodingError,
+	WebPGetEncoderVersion,
 	WebPMemoryWrite,
 	WebPMemoryWriter,
 	WebPMemoryWriterClear,
@@ -27,12 +42,19 @@ use libwebp_sys::{
 	WebPPictureInit,
 	WebPValidateConfig,
 };
+
+#[cfg(feature = "webp")]
 use std::{
+	borrow::Cow,
 	ffi::c_int,
 	num::NonZeroU8,
+	sync::atomic::{
+		AtomicBool,
+		Ordering::Relaxed,
+	},
 };
 
-#[cfg(feature = "decode_ng")]
+#[cfg(all(feature = "webp", feature = "decode_ng"))]
 use crate::{
 	ColorKind,
 	traits::{
@@ -43,10 +65,86 @@ use crate::{
 
 
 
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+/// # `WebP` Tuning.
+///
+/// This holds the `WebP` encoder's method and sharp YUV settings. Pass one to
+/// [`EncodeIter::set_webp_tuning`](crate::EncodeIter::set_webp_tuning) to
+/// change them.
+///
+/// The method (`0..=6`) trades encoding time for compression; the default,
+/// `6`, is the slowest and smallest. For lossless encodes it is scaled to the
+/// equivalent `-z` preset level (`0..=9`).
+///
+/// Sharp YUV uses a slower, more accurate RGB-to-YUV conversion, which keeps
+/// edges around saturated colors from bleeding. It only affects lossy
+/// encodes, and is disabled by default.
+pub struct WebpTuning {
+	/// # Method (`0..=6`).
+	method: u8,
+
+	/// # Sharp YUV.
+	sharp_yuv: bool,
+}
+
+impl Default for WebpTuning {
+	#[inline]
+	fn default() -> Self { Self::DEFAULT }
+}
+
+impl WebpTuning {
+	/// # Default.
+	pub const DEFAULT: Self = Self { method: 6, sharp_yuv: false };
+
+	#[must_use]
+	/// # New.
+	///
+	/// Methods above `6` are clamped.
+	pub fn new(method: u8, sharp_yuv: bool) -> Self {
+		Self { method: method.min(6), sharp_yuv }
+	}
+
+	#[inline]
+	#[must_use]
+	/// # Method.
+	pub const fn method(self) -> u8 { self.method }
+
+	#[inline]
+	#[must_use]
+	/// # Sharp YUV.
+	pub const fn sharp_yuv(self) -> bool { self.sharp_yuv }
+
+	#[cfg(feature = "webp")]
+	#[inline]
+	#[must_use]
+	/// # Lossless Preset Level.
+	///
+	/// The method scaled to `0..=9`.
+	const fn lossless_level(self) -> u8 { self.method * 3 / 2 }
+}
+
+
+
 /// # `WebP` Image.
 pub(crate) struct ImageWebp;
 
-#[cfg(feature = "decode_ng")]
+impl ImageWebp {
+	/// # Is Animated?
+	///
+	/// Animated `WebP` images use the extended (`VP8X`) format, which has a
+	/// header flag for the purpose.
+	pub(crate) const fn is_animated(raw: &[u8]) -> bool {
+		matches!(
+			raw,
+			[
+				b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P',
+				b'V', b'P', b'8', b'X', _, _, _, _, flags, ..
+			] if 0 != *flags & 0b0000_0010
+		)
+	}
+}
+
+#[cfg(all(feature = "webp", feature = "decode_ng"))]
 impl Decoder for ImageWebp {
 	#[expect(unsafe_code, reason = "Needed for FFI.")]
 	/// # Decode.
@@ -73,25 +171,51 @@ impl Decoder for ImageWebp {
 	}
 }
 
+#[cfg(feature = "webp")]
 impl Encoder for ImageWebp {
 	#[inline]
 	/// # Encode Lossy.
-	fn encode_lossy(input: &Input, output: &mut Output, quality: NonZeroU8, _flags: u8)
+	fn encode_lossy(input: &Input, output: &mut Output, quality: NonZeroU8, flags: u8)
 	-> Result<(), RefractError> {
-		encode(input, output, Some(quality))
+		encode(input, output, Some(quality), flags)
 	}
 
 	#[inline]
 	/// # Encode Lossless.
-	fn encode_lossless(input: &Input, output: &mut Output, _flags: u8)
+	fn encode_lossless(input: &Input, output: &mut Output, flags: u8)
 	-> Result<(), RefractError> {
-		encode(input, output, None)
+		encode(input, output, None, flags)
+	}
+
+	#[expect(unsafe_code, reason = "Needed for FFI.")]
+	/// # Encoder Version.
+	fn version() -> String {
+		// Safety: this is an FFI call…
+		let v = unsafe { WebPGetEncoderVersion() };
+		format!("libwebp {}.{}.{}", (v >> 16) & 0xff, (v >> 8) & 0xff, v & 0xff)
+	}
+
+	/// # Encoder Settings.
+	fn settings(output: &Output) -> Cow<'static, str> {
+		let tuning = output.webp_tuning();
+		if output.quality().is_lossless() {
+			if tuning == WebpTuning::DEFAULT { Cow::Borrowed("lossless preset 9") }
+			else { Cow::Owned(format!("lossless preset {}", tuning.lossless_level())) }
+		}
+		else if tuning == WebpTuning::DEFAULT { Cow::Borrowed("method 6, 10 passes") }
+		else {
+			Cow::Owned(format!(
+				"method {}, 10 passes{}",
+				tuning.method(),
+				if tuning.sharp_yuv() { ", sharp yuv" } else { "" },
+			))
+		}
 	}
 }
 
 
 
-#[cfg(feature = "decode_ng")]
+#[cfg(all(feature = "webp", feature = "decode_ng"))]
 /// # Decode Wrapper.
 ///
 /// This exists solely to help with garbage cleanup.
@@ -106,7 +230,7 @@ struct LibWebPDecode {
 	ptr: *mut u8,
 }
 
-#[cfg(feature = "decode_ng")]
+#[cfg(all(feature = "webp", feature = "decode_ng"))]
 impl TryFrom<&[u8]> for LibWebPDecode {
 	type Error = RefractError;
 
@@ -132,7 +256,7 @@ impl TryFrom<&[u8]> for LibWebPDecode {
 	}
 }
 
-#[cfg(feature = "decode_ng")]
+#[cfg(all(feature = "webp", feature = "decode_ng"))]
 impl Drop for LibWebPDecode {
 	#[expect(unsafe_code, reason = "Needed for FFI.")]
 	#[inline]
@@ -144,12 +268,14 @@ impl Drop for LibWebPDecode {
 
 
 
+#[cfg(feature = "webp")]
 /// # Picture Wrapper.
 ///
 /// This `C` struct is Rust-wrapped to help with garbage cleanup, but while
 /// we're here, may as well provide initialization code too.
 struct LibWebpPicture(WebPPicture);
 
+#[cfg(feature = "webp")]
 impl TryFrom<&Input<'_>> for LibWebpPicture {
 	type Error = RefractError;
 
@@ -185,7 +311,9 @@ impl TryFrom<&Input<'_>> for LibWebpPicture {
 
 			// A few additional sanity checks.
 			let len = i32::try_from(raw.len()).map_err(|_| RefractError::Overflow)?;
-			let expected_size = width * height * 4;
+			let expected_size = width.checked_mul(height)
+				.and_then(|x| x.checked_mul(4))
+				.ok_or(RefractError::Overflow)?;
 			if expected_size == 0 || expected_size != len {
 				return Err(RefractError::Encode);
 			}
@@ -200,6 +328,7 @@ impl TryFrom<&Input<'_>> for LibWebpPicture {
 	}
 }
 
+#[cfg(feature = "webp")]
 impl Drop for LibWebpPicture {
 	#[expect(unsafe_code, reason = "Needed for FFI.")]
 	#[inline]
@@ -211,12 +340,14 @@ impl Drop for LibWebpPicture {
 
 
 
+#[cfg(feature = "webp")]
 /// # Writer Wrapper.
 ///
 /// This `C` struct is Rust-wrapped to help with garbage cleanup, but while
 /// we're here, may as well provide initialization code too.
 struct LibWebpWriter(*mut WebPMemoryWriter);
 
+#[cfg(feature = "webp")]
 impl From<&mut WebPPicture> for LibWebpWriter {
 	#[expect(unsafe_code, reason = "Needed for FFI.")]
 	fn from(picture: &mut WebPPicture) -> Self {
@@ -245,6 +376,7 @@ impl From<&mut WebPPicture> for LibWebpWriter {
 	}
 }
 
+#[cfg(feature = "webp")]
 impl Drop for LibWebpWriter {
 	#[expect(unsafe_code, reason = "Needed for FFI.")]
 	#[inline]
@@ -256,12 +388,16 @@ impl Drop for LibWebpWriter {
 
 
 
+#[cfg(feature = "webp")]
 #[expect(unsafe_code, reason = "Needed for FFI.")]
 /// # Encode `WebP`.
 ///
 /// This encodes a raw image source as a `WebP` using the provided
 /// configuration profile, returning a regular byte vector of the result.
 ///
+/// If [`FLAG_KEEP_METADATA`] is set, the source's ICC profile and EXIF data,
+/// if any, are spliced into the container afterward.
+///
 /// ## Errors
 ///
 /// This will return an error if there are any problems along the way or if
@@ -270,24 +406,46 @@ fn encode(
 	img: &Input,
 	candidate: &mut Output,
 	quality: Option<NonZeroU8>,
+	flags: u8,
 ) -> Result<(), RefractError> {
 	// Setup.
-	let config = make_config(quality)?;
+	let config = make_config(quality, candidate.webp_tuning())?;
 	let mut picture = LibWebpPicture::try_from(img)?;
 	let writer = LibWebpWriter::from(&mut picture.0);
 
+	// Check in now and then in case we've been cancelled.
+	if let Some(token) = candidate.cancel_token() {
+		picture.0.progress_hook = Some(progress_hook);
+		picture.0.user_data = token.as_ptr().cast_mut().cast();
+	}
+
 	// Encode!
 	// Safety: this is an FFI call…
-	maybe_die(unsafe { WebPEncode(&config, &mut picture.0) })?;
+	if let Err(e) = maybe_die(unsafe { WebPEncode(&config, &mut picture.0) }) {
+		if picture.0.error_code == VP8_ENC_ERROR_USER_ABORT { return Err(RefractError::Cancelled); }
+		set_upstream(encoding_error(picture.0.error_code));
+		return Err(e);
+	}
 
 	// Copy output.
 	// Safety: we need to box the data to access it.
 	let data = unsafe { Box::from_raw(writer.0) };
 	// Safety: candidate makes a copy of the data so it's short lifetime is no
 	// problem.
-	candidate.set_slice(unsafe {
-		std::slice::from_raw_parts_mut(data.mem, data.size)
-	});
+	let raw = unsafe { std::slice::from_raw_parts(data.mem, data.size) };
+	let meta = img.metadata();
+	if 0 != flags & FLAG_KEEP_METADATA && (meta.icc().is_some() || meta.exif().is_some()) {
+		let raw = with_metadata(
+			raw,
+			img.width_u32(),
+			img.height_u32(),
+			img.has_alpha(),
+			meta.icc(),
+			meta.exif(),
+		).ok_or(RefractError::Encode)?;
+		candidate.set_slice(&raw);
+	}
+	else { candidate.set_slice(raw); }
 
 	// Clean-up.
 	drop(picture);
@@ -297,6 +455,7 @@ fn encode(
 	Ok(())
 }
 
+#[cfg(feature = "webp")]
 #[expect(unsafe_code, reason = "Needed for FFI.")]
 /// # Make Config.
 ///
@@ -305,15 +464,16 @@ fn encode(
 /// For lossy (with quality), this is roughly equivalent to:
 ///
 /// ```bash
-/// cwebp -m 6 -pass 10 -q {QUALITY}
+/// cwebp -m {METHOD} -pass 10 [-sharp_yuv] -q {QUALITY}
 /// ```
 ///
 /// For lossless (no quality), this is instead like:
 ///
 /// ```bash
-/// cwebp -lossless -z 9 -q 100
+/// cwebp -lossless -z {LEVEL} -q 100
 /// ```
-fn make_config(quality: Option<NonZeroU8>) -> Result<WebPConfig, RefractError> {
+fn make_config(quality: Option<NonZeroU8>, tuning: WebpTuning)
+-> Result<WebPConfig, RefractError> {
 	// Safety: the subsequent call expects zeroed memory.
 	let mut config: WebPConfig = unsafe { std::mem::zeroed() };
 	// Safety: this is an FFI call…
@@ -324,13 +484,14 @@ fn make_config(quality: Option<NonZeroU8>) -> Result<WebPConfig, RefractError> {
 	// Lossy bits.
 	if let Some(quality) = quality {
 		config.quality = f32::from(quality.get());
-		config.method = 6;
+		config.method = c_int::from(tuning.method());
 		config.pass = 10;
+		config.use_sharp_yuv = c_int::from(tuning.sharp_yuv());
 	}
 	// Lossless bits.
 	else {
 		// Safety: this is an FFI call…
-		maybe_die(unsafe { WebPConfigLosslessPreset(&mut config, 9) })?;
+		maybe_die(unsafe { WebPConfigLosslessPreset(&mut config, c_int::from(tuning.lossless_level())) })?;
 		config.lossless = 1;
 		config.quality = 100.0;
 	}
@@ -338,6 +499,104 @@ fn make_config(quality: Option<NonZeroU8>) -> Result<WebPConfig, RefractError> {
 	Ok(config)
 }
 
+#[cfg(feature = "webp")]
+/// # Add Metadata.
+///
+/// `libwebp` only writes the bare minimum container, so this rebuilds it in
+/// the extended format, with the `ICCP` chunk ahead of the image data and the
+/// `EXIF` chunk after, as the spec requires.
+///
+/// Returns `None` if the source isn't a well-formed `WebP` or the result
+/// would be too big.
+fn with_metadata(
+	raw: &[u8],
+	width: u32,
+	height: u32,
+	alpha: bool,
+	icc: Option<&[u8]>,
+	exif: Option<&[u8]>,
+) -> Option<Vec<u8>> {
+	/// # Push Chunk.
+	fn push_chunk(out: &mut Vec<u8>, kind: &[u8], data: &[u8]) -> Option<()> {
+		out.extend_from_slice(kind);
+		out.extend_from_slice(&u32::try_from(data.len()).ok()?.to_le_bytes());
+		out.extend_from_slice(data);
+		if 1 == data.len() % 2 { out.push(0); }
+		Some(())
+	}
+
+	let [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', rest @ ..] = raw
+	else { return None; };
+	let mut rest: &[u8] = rest;
+
+	// Split the existing chunks, holding back the (old) VP8X header flags.
+	let mut vp8x = 0_u8;
+	let mut chunks = Vec::new();
+	while ! rest.is_empty() {
+		let [k1, k2, k3, k4, a, b, c, d, tail @ ..] = rest else { return None; };
+		let len = usize::try_from(u32::from_le_bytes([*a, *b, *c, *d])).ok()?;
+		let data = tail.get(..len)?;
+		rest = tail.get(len + len % 2..).unwrap_or_default();
+
+		let kind = [*k1, *k2, *k3, *k4];
+		if kind == *b"VP8X" { vp8x = data.first().copied().unwrap_or(0); }
+		else { chunks.push((kind, data)); }
+	}
+
+	// The extended header: flags, three reserved bytes, then the canvas
+	// dimensions (minus one) as 24-bit integers.
+	if alpha { vp8x |= 0b0001_0000; }
+	if icc.is_some() { vp8x |= 0b0010_0000; }
+	if exif.is_some() { vp8x |= 0b0000_1000; }
+	let mut header = [0_u8; 10];
+	header[0] = vp8x;
+	header[4..7].copy_from_slice(&width.checked_sub(1)?.to_le_bytes()[..3]);
+	header[7..].copy_from_slice(&height.checked_sub(1)?.to_le_bytes()[..3]);
+
+	let mut out = Vec::with_capacity(raw.len() + 64);
+	out.extend_from_slice(b"RIFF\0\0\0\0WEBP");
+	push_chunk(&mut out, b"VP8X", &header)?;
+	if let Some(icc) = icc { push_chunk(&mut out, b"ICCP", icc)?; }
+	for (kind, data) in chunks { push_chunk(&mut out, &kind, data)?; }
+	if let Some(exif) = exif { push_chunk(&mut out, b"EXIF", exif)?; }
+
+	// Patch the RIFF size.
+	let size = u32::try_from(out.len() - 8).ok()?;
+	out[4..8].copy_from_slice(&size.to_le_bytes());
+	Some(out)
+}
+
+#[cfg(feature = "webp")]
+#[expect(unsafe_code, reason = "Needed for FFI.")]
+/// # Progress Hook.
+///
+/// libwebp calls this periodically while encoding; returning zero aborts the
+/// encode. The picture's user data, if set, points to a
+/// [`CancelToken`](crate::CancelToken)'s flag.
+extern "C" fn progress_hook(_percent: c_int, picture: *const WebPPicture) -> c_int {
+	// Safety: the flag, if any, outlives the encode.
+	let flag = unsafe {
+		picture.as_ref().and_then(|p| p.user_data.cast::<AtomicBool>().cast_const().as_ref())
+	};
+	c_int::from(! flag.is_some_and(|f| f.load(Relaxed)))
+}
+
+#[cfg(feature = "webp")]
+/// # Encoding Error.
+///
+/// Return a human-readable explanation for a libwebp error code.
+const fn encoding_error(code: WebPEncodingError) -> &'static str {
+	match code {
+		VP8_ENC_ERROR_OUT_OF_MEMORY | VP8_ENC_ERROR_BITSTREAM_OUT_OF_MEMORY => "libwebp: out of memory",
+		VP8_ENC_ERROR_INVALID_CONFIGURATION => "libwebp: invalid configuration",
+		VP8_ENC_ERROR_BAD_DIMENSION => "libwebp: bad dimensions",
+		VP8_ENC_ERROR_PARTITION0_OVERFLOW | VP8_ENC_ERROR_PARTITION_OVERFLOW => "libwebp: partition overflow",
+		VP8_ENC_ERROR_FILE_TOO_BIG => "libwebp: file too big",
+		_ => "libwebp: encoding failed",
+	}
+}
+
+#[cfg(feature = "webp")]
 #[inline]
 /// # Verify Encoder Status.
 ///
@@ -347,3 +606,28 @@ const fn maybe_die(res: c_int) -> Result<(), RefractError> {
 	if 0 == res { Err(RefractError::Encode) }
 	else { Ok(()) }
 }
+
+
+
+#[cfg(all(test, feature = "webp"))]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn t_with_metadata() {
+		// A "lossless" image with an odd-length bitstream.
+		let raw = b"RIFF\x11\0\0\0WEBPVP8L\x05\0\0\0abcde\0";
+		let out = with_metadata(raw, 300, 2, true, Some(&b"icc"[..]), Some(&b"MM\0\x2A"[..])).unwrap();
+		assert_eq!(
+			out,
+			b"RIFF\x3C\0\0\0WEBP\
+			VP8X\x0A\0\0\0\x38\0\0\0\x2B\x01\0\x01\0\0\
+			ICCP\x03\0\0\0icc\0\
+			VP8L\x05\0\0\0abcde\0\
+			EXIF\x04\0\0\0MM\0\x2A",
+		);
+
+		// Not a WebP.
+		assert!(with_metadata(b"RIFF\0\0\0\0WAVE", 1, 1, false, None, None).is_none());
+	}
+}
@@ -0,0 +1,156 @@
+/*!
+# `Refract`: `WebP` Handling (Pure Rust)
+
+This uses [`image-webp`](https://crates.io/crates/image-webp) in place of
+Google's `libwebp`, for builds where linking C libraries is more trouble than
+it is worth. It only supports lossless encoding, and compresses a little less
+tightly than `libwebp`, but has no native dependencies whatsoever.
+
+It is only used when the `webp_rust` feature is enabled _without_ `webp`.
+*/
+
+use crate::{
+	ColorKind,
+	error::set_upstream,
+	FLAG_KEEP_METADATA,
+	ImageWebp,
+	Input,
+	Output,
+	RefractError,
+	traits::Encoder,
+};
+use image_webp::{
+	ColorType,
+	EncodingError,
+	WebPEncoder,
+};
+use std::{
+	borrow::Cow,
+	num::NonZeroU8,
+};
+
+#[cfg(feature = "decode_ng")]
+use crate::traits::{
+	Decoder,
+	DecoderResult,
+};
+
+#[cfg(feature = "decode_ng")]
+use image_webp::WebPDecoder;
+
+
+
+#[cfg(feature = "decode_ng")]
+impl Decoder for ImageWebp {
+	/// # Decode.
+	fn decode(raw: &[u8]) -> Result<DecoderResult, RefractError> {
+		let mut d = WebPDecoder::new(std::io::Cursor::new(raw))
+			.map_err(|_| RefractError::Decode)?;
+
+		let (width, height) = d.dimensions();
+		let width = usize::try_from(width).map_err(|_| RefractError::Overflow)?;
+		let height = usize::try_from(height).map_err(|_| RefractError::Overflow)?;
+		let size = d.output_buffer_size().ok_or(RefractError::Overflow)?;
+
+		let mut buf = vec![0_u8; size];
+		d.read_image(&mut buf).map_err(|_| RefractError::Decode)?;
+
+		// Opaque images come back as RGB; everything else wants RGBA.
+		if ! d.has_alpha() {
+			buf = buf.chunks_exact(3)
+				.flat_map(|px| [px[0], px[1], px[2], 255])
+				.collect();
+		}
+
+		if width.checked_mul(height).and_then(|x| x.checked_mul(4)) == Some(buf.len()) {
+			let color = ColorKind::from_rgba(&buf);
+			Ok((buf, width, height, color))
+		}
+		else { Err(RefractError::Decode) }
+	}
+}
+
+impl Encoder for ImageWebp {
+	#[inline]
+	/// # Encode Lossy.
+	///
+	/// `image-webp` can't do lossy, so this always fails.
+	fn encode_lossy(_input: &Input, _output: &mut Output, _quality: NonZeroU8, _flags: u8)
+	-> Result<(), RefractError> {
+		Err(RefractError::Encode)
+	}
+
+	/// # Encode Lossless.
+	fn encode_lossless(input: &Input, output: &mut Output, flags: u8)
+	-> Result<(), RefractError> {
+		let mut out = Vec::new();
+		let mut enc = WebPEncoder::new(&mut out);
+
+		if 0 != flags & FLAG_KEEP_METADATA {
+			let meta = input.metadata();
+			if let Some(icc) = meta.icc() { enc.set_icc_profile(icc.to_vec()); }
+			if let Some(exif) = meta.exif() { enc.set_exif_metadata(exif.to_vec()); }
+		}
+
+		let color = match input.depth() {
+			ColorKind::Grey => ColorType::L8,
+			ColorKind::GreyAlpha => ColorType::La8,
+			ColorKind::Rgb => ColorType::Rgb8,
+			ColorKind::Rgba => ColorType::Rgba8,
+		};
+
+		match enc.encode(input, input.width_u32(), input.height_u32(), color) {
+			Ok(()) => {
+				output.set_slice(&out);
+				Ok(())
+			},
+			Err(EncodingError::InvalidDimensions) => Err(RefractError::Overflow),
+			Err(e) => {
+				set_upstream(format!("image-webp: {e}"));
+				Err(RefractError::Encode)
+			},
+		}
+	}
+
+	#[inline]
+	/// # Encoder Version.
+	fn version() -> String { String::from("image-webp 0.2.4") }
+
+	#[inline]
+	/// # Encoder Settings.
+	fn settings(_output: &Output) -> Cow<'static, str> {
+		Cow::Borrowed("lossless, pure Rust")
+	}
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		EncodeIter,
+		ImageKind,
+	};
+
+	#[test]
+	fn t_lossless_only() {
+		let src = Input::try_from(&include_bytes!("../../../skel/assets/r.png")[..])
+			.expect("Unable to read test image.");
+		assert!(! ImageKind::Webp.can_encode_lossy());
+
+		// Asking for a quality should still get us a (lossless) image.
+		let out = EncodeIter::new(&src, ImageKind::Webp, 0)
+			.and_then(|guide| guide.once(NonZeroU8::new(75)))
+			.expect("Encoding failed.");
+		assert_eq!(out.kind(), ImageKind::Webp);
+		assert!(out.quality().is_lossless(), "Expected a lossless image.");
+
+		#[cfg(feature = "decode_ng")]
+		{
+			let dec = out.verify(&src).expect("Verification failed.");
+			assert_eq!(dec.width(), src.width());
+			assert_eq!(dec.height(), src.height());
+		}
+	}
+}
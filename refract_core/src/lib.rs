@@ -2,6 +2,34 @@
 # `Refract` - Library
 
 This is the library powering [Refract](https://github.com/Blobfolio/refract), a guided CLI image encoding tool.
+
+## Features
+
+PNG is always supported, as is JPEG decoding, but each of the other encoders
+has its own crate feature — `avif`, `jpeg`, `jxl`, and `webp` — all enabled
+by default. If you only need one or two of them, disable the default features
+and pick what you want; the rest will then be reported as unsupported by
+[`ImageKind::can_encode`] and friends rather than pulling in their (rather
+heavy) libraries.
+
+For builds where linking C libraries is impractical, the `webp_rust` feature
+can stand in for `webp`, encoding lossless `WebP` images in pure Rust. It is
+ignored if `webp` is also enabled.
+
+The `wasm` feature, meanwhile, makes the library usable on
+`wasm32-unknown-unknown`, which has neither threads nor a clock. Time limits
+are ignored and encoding times aren't tracked, but everything else —
+candidate generation included — works the same. Pair it with `webp_rust` and
+no default features:
+
+```text
+cargo build -p refract_core --target wasm32-unknown-unknown \
+    --no-default-features --features wasm,webp_rust,decode_ng
+```
+
+That is the only combination that builds without a C toolchain; the other
+encoder features, `jpeg` included, compile C/C++ libraries from source, and
+so need a wasm32-capable clang and sysroot.
 */
 
 #![deny(
@@ -54,31 +82,61 @@ This is the library powering [Refract](https://github.com/Blobfolio/refract), a
 
 #![expect(clippy::redundant_pub_crate, reason = "Unresolvable.")]
 
+// libwebp takes precedence if both are enabled.
+#[cfg(all(feature = "webp", feature = "webp_rust"))]
+use image_webp as _;
+
+#[cfg(feature = "jxl")]
 #[expect(unused_extern_crates, reason = "This is needed for JXL.")]
 extern crate link_cplusplus;
 
+mod analysis;
 mod enc;
 mod error;
 mod input;
 mod kind;
+#[cfg(feature = "decode_ng")]
+mod metrics;
 pub(crate) mod traits;
 
 
 
+pub use analysis::{
+	is_photographic,
+	Profile,
+};
 pub use enc::{
+	cancel::CancelToken,
 	iter::EncodeIter,
 	output::Output,
+	predict::{
+		HistoryPredictor,
+		Predictor,
+	},
 	quality::{
 		Quality,
 		QualityValue,
 	},
 	range::QualityRange,
 };
-pub use error::RefractError;
+#[cfg(feature = "decode_ng")]
+pub use metrics::{
+	difference,
+	similarity,
+};
+pub use error::{
+	ErrorContext,
+	RefractError,
+};
 pub use input::Input;
 pub use kind::{
+	avif::AvifCodec,
 	color::ColorKind,
 	image::ImageKind,
+	jxl::JxlTuning,
+	png::decode_to_png,
+	sniff::SniffResult,
+	webp::WebpTuning,
 };
 pub(crate) use kind::{
 	avif::ImageAvif,
@@ -110,34 +168,58 @@ pub const FLAG_NO_LOSSLESS: u8         = 0b0000_0010;
 /// When enabled, only full-range `RGB` encoding will be attempted.
 pub const FLAG_NO_AVIF_YCBCR: u8       = 0b0000_0100;
 
-/// # (Internal) Encoder Flag: Public Flags Mask.
+/// # Encoder Flag: Lossless Last.
 ///
-/// These are flags that can be set externally.
-pub(crate) const PUBLIC_FLAGS: u8      = 0b0000_0111;
+/// By default, lossless encoding is attempted first, giving lossy candidates
+/// a size to beat. For photos, though, lossless rarely wins, and is usually
+/// the slowest single encode of the lot.
+///
+/// When enabled, lossless encoding is instead attempted after the lossy
+/// search has finished, and only kept if it beats the lossy winner. See also
+/// [`EncodeIter::set_lossless_above`].
+pub const FLAG_LOSSLESS_LAST: u8       = 0b1000_0000;
 
-/// # (Internal) Encoder Flag: `AVIF` RGB.
+/// # Encoder Flag: Progressive `JPEG XL`.
 ///
-/// When set, `AVIF` encoding will use `RGB` colors. When not set, it will use
-/// `YCbCr`.
-pub(crate) const FLAG_AVIF_RGB: u8     = 0b0000_1000;
+/// When enabled, `JPEG XL` images are encoded progressively — with extra
+/// lower-resolution passes, and the middle of the image first — so browsers
+/// can show something useful sooner. This costs a few bytes.
+///
+/// This has no effect on other formats.
+pub const FLAG_JXL_PROGRESSIVE: u8     = 0b0100_0000;
+
+/// # Encoder Flag: Keep Metadata.
+///
+/// By default, outputs carry no metadata beyond what their formats require.
+///
+/// When enabled, any ICC color profile and EXIF data — orientation,
+/// copyright, etc. — found in a `JPEG` or `PNG` source is copied into the
+/// `AVIF`, `JPEG XL`, and `WebP` outputs.
+pub const FLAG_KEEP_METADATA: u8       = 0b0001_0000;
 
-/// # (Internal) Encoder Flag: `AVIF` Round Two.
+/// # Encoder Flag: `AVIF` 4:2:0 Chroma Subsampling.
+///
+/// By default, `AVIF` images are encoded with full-resolution (4:4:4) color.
+///
+/// When enabled, the limited-range `YCbCr` round instead stores color at half
+/// the resolution (4:2:0) in each direction, as most production pipelines
+/// do. This often makes for much smaller photos, though fine colored detail,
+/// like red text, can suffer.
 ///
-/// The second `AVIF` encoding stage retries all quality ranges using `YCbCr`
-/// color compression.
-pub(crate) const FLAG_AVIF_ROUND_2: u8 = 0b0001_0000;
+/// This has no effect on full-range `RGB` or greyscale encodes, on other
+/// formats, or when combined with [`FLAG_NO_AVIF_YCBCR`].
+pub const FLAG_AVIF_YUV420: u8         = 0b0010_0000;
 
-/// # (Internal) Encoder Flag: Valid Output.
+/// # (Internal) Encoder Flag: Public Flags Mask.
 ///
-/// This is used by [`Output`] to determine whether or not the buffer has been
-/// validated.
-pub(crate) const FLAG_VALID:        u8 = 0b0010_0000;
+/// These are flags that can be set externally.
+pub(crate) const PUBLIC_FLAGS: u8      = 0b1111_0111;
 
-/// # (Internal) Encoder Flag: Tried Lossless.
+/// # (Internal) Encoder Flag: `AVIF` RGB.
 ///
-/// This is used by [`EncodeIter`] to determine whether or not lossless
-/// encoding needs to be completed during iteration.
-pub(crate) const FLAG_DID_LOSSLESS: u8 = 0b0100_0000;
+/// When set, `AVIF` encoding will use `RGB` colors. When not set, it will use
+/// `YCbCr`.
+pub(crate) const FLAG_AVIF_RGB: u8     = 0b0000_1000;
 
 /// # 63 is Non-Zero.
 pub(crate) const NZ_063: NonZeroU8 = NonZeroU8::new(63).unwrap();
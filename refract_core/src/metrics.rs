@@ -0,0 +1,224 @@
+/*!
+# `Refract` - Metrics
+*/
+
+use crate::{
+	Input,
+	Output,
+};
+
+
+
+/// # Window Size.
+///
+/// SSIM is computed over non-overlapping square blocks of this many pixels
+/// per side, then averaged.
+const WINDOW: usize = 8;
+
+/// # Stability Constant (Luminance).
+///
+/// This is `(0.01 * 255)²`, per the original SSIM paper.
+const C1: f64 = 6.5025;
+
+/// # Stability Constant (Contrast).
+///
+/// This is `(0.03 * 255)²`, per the original SSIM paper.
+const C2: f64 = 58.5225;
+
+/// # Difference Amplification.
+///
+/// Per-pixel differences are multiplied by this much before being mapped to
+/// the heatmap, so even off-by-a-few changes are visible. Anything off by
+/// `32` or more is maxed out.
+const AMPLIFY: u16 = 8;
+
+
+
+#[must_use]
+/// # Similarity (DSSIM).
+///
+/// Decode the output and compare it against the source, returning a
+/// perceptual distance score: `0.0` for identical images, growing as the
+/// differences become more noticeable. As a rough guide, anything under
+/// `0.001` is typically indistinguishable, while `0.01` and up is usually
+/// visible on close inspection.
+///
+/// This is the `1 / SSIM - 1` form used by the popular `dssim` tool, though
+/// the SSIM calculation here is simpler — an average of the red, green,
+/// blue, and alpha channels across 8x8 blocks — so the scores won't match
+/// exactly.
+///
+/// `None` is returned if the output cannot be decoded or its dimensions
+/// differ from the source.
+pub fn similarity(src: &Input, out: &Output) -> Option<f64> {
+	let out = Input::try_from(out.as_ref()).ok()?;
+	if out.width() != src.width() || out.height() != src.height() { return None; }
+
+	let ssim = ssim_rgba(
+		src.as_rgba().as_ref(),
+		out.as_rgba().as_ref(),
+		src.width(),
+		src.height(),
+	)?;
+
+	if 0.0 < ssim { Some(1.0 / ssim - 1.0) }
+	else { Some(f64::INFINITY) }
+}
+
+
+
+#[must_use]
+/// # Difference Heatmap.
+///
+/// Compare two equally-sized RGBA pixel buffers — e.g. a source and its
+/// decoded candidate — returning a new RGBA buffer visualizing where they
+/// differ.
+///
+/// Each pixel's largest channel difference is amplified and mapped to a
+/// black-red-yellow-white ramp: black where the images match, white where
+/// they differ the most.
+///
+/// `None` is returned if the buffer sizes don't match each other or the
+/// dimensions.
+pub fn difference(a: &[u8], b: &[u8], width: usize, height: usize) -> Option<Vec<u8>> {
+	if
+		a.len() != b.len() ||
+		Some(a.len()) != width.checked_mul(height).and_then(|x| x.checked_mul(4))
+	{
+		return None;
+	}
+
+	Some(
+		a.chunks_exact(4)
+			.zip(b.chunks_exact(4))
+			.flat_map(|(a, b)| {
+				let diff = a.iter()
+					.zip(b)
+					.map(|(a, b)| a.abs_diff(*b))
+					.max()
+					.unwrap_or(0);
+				heat(u16::from(diff) * AMPLIFY)
+			})
+			.collect()
+	)
+}
+
+/// # Heat Color.
+///
+/// Map an (amplified) difference to an opaque RGBA color.
+fn heat(v: u16) -> [u8; 4] {
+	let v = v.min(255) * 3;
+	let ch = |start: u16| v.saturating_sub(start).min(255) as u8;
+	[ch(0), ch(255), ch(510), 255]
+}
+
+#[expect(clippy::cast_precision_loss, reason = "Precision is not critical here.")]
+/// # SSIM (RGBA).
+///
+/// Compute the mean SSIM of two equally-sized RGBA pixel buffers.
+fn ssim_rgba(a: &[u8], b: &[u8], width: usize, height: usize) -> Option<f64> {
+	if
+		width == 0 || height == 0 ||
+		a.len() != b.len() ||
+		Some(a.len()) != width.checked_mul(height).and_then(|x| x.checked_mul(4))
+	{
+		return None;
+	}
+
+	let mut total = 0.0;
+	let mut count = 0_usize;
+	for y in (0..height).step_by(WINDOW) {
+		let y_end = (y + WINDOW).min(height);
+		for x in (0..width).step_by(WINDOW) {
+			let x_end = (x + WINDOW).min(width);
+			for channel in 0..4 {
+				let idx = (y..y_end).flat_map(|yy|
+					(x..x_end).map(move |xx| (yy * width + xx) * 4 + channel)
+				);
+				total += ssim_block(idx.map(|i| (a[i], b[i])));
+				count += 1;
+			}
+		}
+	}
+
+	Some(total / count as f64)
+}
+
+/// # SSIM (Block).
+///
+/// Compute the SSIM of a single block of paired channel values.
+fn ssim_block<I>(src: I) -> f64
+where I: Iterator<Item=(u8, u8)> {
+	let mut n = 0_u32;
+	let (mut sa, mut sb, mut saa, mut sbb, mut sab) = (0.0, 0.0, 0.0, 0.0, 0.0);
+	for (a, b) in src {
+		let (a, b) = (f64::from(a), f64::from(b));
+		n += 1;
+		sa += a;
+		sb += b;
+		saa = a.mul_add(a, saa);
+		sbb = b.mul_add(b, sbb);
+		sab = a.mul_add(b, sab);
+	}
+	if n == 0 { return 1.0; }
+
+	let n = f64::from(n);
+	let (ma, mb) = (sa / n, sb / n);
+	let va = ma.mul_add(-ma, saa / n);
+	let vb = mb.mul_add(-mb, sbb / n);
+	let cov = ma.mul_add(-mb, sab / n);
+
+	((2.0 * ma).mul_add(mb, C1) * 2.0_f64.mul_add(cov, C2)) /
+	((ma.mul_add(ma, mb * mb) + C1) * (va + vb + C2))
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn t_ssim_rgba() {
+		// A 16x16 gradient.
+		let a: Vec<u8> = (0..=255_u8).flat_map(|v| [v, v, v, 255]).collect();
+
+		// Identical images are, well, identical.
+		let ssim = ssim_rgba(&a, &a, 16, 16).expect("SSIM failed.");
+		assert!((ssim - 1.0).abs() < 1e-9, "Identical images should have an SSIM of one.");
+
+		// A little noise should cost less than a lot.
+		let noisy = |amt: u8| -> Vec<u8> {
+			a.chunks_exact(4)
+				.enumerate()
+				.flat_map(|(i, px)| {
+					let v = if i % 2 == 0 { px[0].saturating_add(amt) } else { px[0].saturating_sub(amt) };
+					[v, v, v, 255]
+				})
+				.collect()
+		};
+		let low = ssim_rgba(&a, &noisy(2), 16, 16).expect("SSIM failed.");
+		let high = ssim_rgba(&a, &noisy(40), 16, 16).expect("SSIM failed.");
+		assert!(low < 1.0, "Noise should reduce the SSIM.");
+		assert!(high < low, "More noise should reduce the SSIM further.");
+
+		// Bad dimensions.
+		assert!(ssim_rgba(&a, &a, 15, 16).is_none(), "Dimensions should not match.");
+		assert!(ssim_rgba(&a, &a[..32], 16, 16).is_none(), "Lengths should not match.");
+		assert!(ssim_rgba(&[], &[], 0, 0).is_none(), "Empty images should fail.");
+	}
+
+	#[test]
+	fn t_difference() {
+		let a = [10, 20, 30, 255, 0, 0, 0, 255, 200, 200, 200, 255];
+		let b = [10, 20, 30, 255, 0, 11, 0, 255, 0, 200, 200, 0];
+		assert_eq!(
+			difference(&a, &b, 3, 1),
+			Some(vec![0, 0, 0, 255, 255, 9, 0, 255, 255, 255, 255, 255]),
+		);
+
+		// Bad dimensions.
+		assert!(difference(&a, &b, 2, 1).is_none(), "Dimensions should not match.");
+		assert!(difference(&a, &b[..8], 3, 1).is_none(), "Lengths should not match.");
+	}
+}
@@ -5,11 +5,13 @@
 use crate::{
 	ColorKind,
 	Input,
-	NZ_100,
 	Output,
 	RefractError,
 };
-use std::num::NonZeroU8;
+use std::{
+	borrow::Cow,
+	num::NonZeroU8,
+};
 
 
 
@@ -42,9 +44,6 @@ pub(super) trait Encoder {
 	/// # Minimum Quality.
 	const MIN_QUALITY: NonZeroU8 = NonZeroU8::MIN;
 
-	/// # Maximum Quality.
-	const MAX_QUALITY: NonZeroU8 = NZ_100;
-
 	/// # Encode Lossy.
 	///
 	/// Encode a slice of pixels into a complete image using lossy compression
@@ -66,4 +65,15 @@ pub(super) trait Encoder {
 	/// Return any errors encountered during decoding.
 	fn encode_lossless(input: &Input, output: &mut Output, flags: u8)
 	-> Result<(), RefractError>;
+
+	/// # Encoder Version.
+	///
+	/// Return the name and version of the underlying encoder library.
+	fn version() -> String;
+
+	/// # Encoder Settings.
+	///
+	/// Briefly describe the fixed — non-quality — settings used to encode
+	/// the output.
+	fn settings(output: &Output) -> Cow<'static, str>;
 }
@@ -0,0 +1,106 @@
+/*!
+# `Refract` - Distortion
+*/
+
+use refract_core::{
+	Input,
+	Output,
+};
+
+
+
+/// # Change Threshold.
+///
+/// Pixels with any channel differing by more than this amount count as
+/// "changed".
+pub const DISTORTION_THRESHOLD: u8 = 4;
+
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// # Distortion Stats.
+///
+/// This holds some simple summary statistics comparing the (RGBA) pixels of
+/// a source and its accepted conversion, providing a lightweight, objective
+/// record of what "looked fine" actually meant.
+pub struct Distortion {
+	/// # Max Per-Channel Delta.
+	pub max: u8,
+
+	/// # Mean Per-Channel Delta.
+	pub mean: f64,
+
+	/// # Percentage of Pixels Changed.
+	///
+	/// This is expressed as a fraction between `0.0` and `1.0`.
+	pub changed: f64,
+}
+
+impl Distortion {
+	#[must_use]
+	/// # From Outputs.
+	///
+	/// Decode the output and compare it against the source. `None` is
+	/// returned if the output cannot be decoded or its dimensions differ.
+	pub fn new(src: &Input, out: &Output) -> Option<Self> {
+		let out = Input::try_from(out.as_ref()).ok()?;
+		if out.width() != src.width() || out.height() != src.height() { return None; }
+
+		Self::from_rgba(src.as_rgba().as_ref(), out.as_rgba().as_ref())
+	}
+
+	/// # From RGBA Buffers.
+	///
+	/// Compare two equally-sized RGBA pixel buffers.
+	fn from_rgba(a: &[u8], b: &[u8]) -> Option<Self> {
+		if a.len() != b.len() || a.is_empty() || ! a.len().is_multiple_of(4) { return None; }
+
+		let mut max: u8 = 0;
+		let mut total: u64 = 0;
+		let mut changed: u64 = 0;
+		for (pa, pb) in a.chunks_exact(4).zip(b.chunks_exact(4)) {
+			let mut px_max: u8 = 0;
+			for (ca, cb) in pa.iter().zip(pb) {
+				let d = ca.abs_diff(*cb);
+				total += u64::from(d);
+				px_max = px_max.max(d);
+			}
+			max = max.max(px_max);
+			if DISTORTION_THRESHOLD < px_max { changed += 1; }
+		}
+
+		#[expect(clippy::cast_precision_loss, reason = "Precision is not critical here.")]
+		Some(Self {
+			max,
+			mean: total as f64 / a.len() as f64,
+			changed: changed as f64 / (a.len() / 4) as f64,
+		})
+	}
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn t_distortion() {
+		let a = [0_u8, 0, 0, 255, 10, 10, 10, 255, 100, 100, 100, 255, 50, 50, 50, 255];
+		let b = [0_u8, 0, 0, 255, 12, 10, 10, 255, 100, 120, 100, 255, 50, 50, 40, 255];
+
+		let d = Distortion::from_rgba(&a, &b).expect("Distortion failed.");
+		assert_eq!(d.max, 20);
+		assert!((d.mean - 2.0).abs() < f64::EPSILON);
+		assert!((d.changed - 0.5).abs() < f64::EPSILON);
+
+		// Identical buffers.
+		let d = Distortion::from_rgba(&a, &a).expect("Distortion failed.");
+		assert_eq!(d.max, 0);
+		assert!(d.changed.abs() < f64::EPSILON);
+
+		// Mismatched buffers.
+		assert!(Distortion::from_rgba(&a, &b[..8]).is_none());
+		assert!(Distortion::from_rgba(&[], &[]).is_none());
+	}
+}
@@ -0,0 +1,99 @@
+/*!
+# `Refract` - Existing Outputs
+*/
+
+use refract_core::RefractError;
+
+
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+/// # Existing Output Policy.
+///
+/// What to do when a conversion's suggested output path is already taken,
+/// e.g. when re-running a directory that was crunched before.
+pub enum Existing {
+	#[default]
+	/// # Ask.
+	///
+	/// Leave it to the save dialog, which confirms before overwriting.
+	Ask,
+
+	/// # Skip the Format.
+	///
+	/// Don't bother encoding the format at all.
+	Skip,
+
+	/// # Overwrite.
+	///
+	/// Replace the existing file without asking.
+	Overwrite,
+
+	/// # Replace If Smaller.
+	///
+	/// Replace the existing file without asking, but only if the new one is
+	/// smaller.
+	Smaller,
+}
+
+impl TryFrom<&str> for Existing {
+	type Error = RefractError;
+
+	fn try_from(src: &str) -> Result<Self, Self::Error> {
+		match src.trim().to_ascii_lowercase().as_str() {
+			"ask" => Ok(Self::Ask),
+			"skip" => Ok(Self::Skip),
+			"overwrite" => Ok(Self::Overwrite),
+			"smaller" => Ok(Self::Smaller),
+			_ => Err(RefractError::Existing),
+		}
+	}
+}
+
+impl Existing {
+	/// # All Policies.
+	pub const ALL: [Self; 4] = [Self::Ask, Self::Skip, Self::Overwrite, Self::Smaller];
+
+	#[must_use]
+	/// # As Str.
+	pub const fn as_str(self) -> &'static str {
+		match self {
+			Self::Ask => "ask",
+			Self::Skip => "skip",
+			Self::Overwrite => "overwrite",
+			Self::Smaller => "smaller",
+		}
+	}
+
+	#[must_use]
+	/// # Replace Without Asking?
+	///
+	/// Returns `true` if an existing file of `old_size` bytes may be quietly
+	/// replaced with a new one of `new_size` bytes.
+	pub const fn replaces(self, old_size: u64, new_size: u64) -> bool {
+		match self {
+			Self::Ask | Self::Skip => false,
+			Self::Overwrite => true,
+			Self::Smaller => new_size < old_size,
+		}
+	}
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn t_existing() {
+		for mode in Existing::ALL {
+			assert_eq!(Existing::try_from(mode.as_str()), Ok(mode));
+		}
+		assert_eq!(Existing::try_from("clobber"), Err(RefractError::Existing));
+
+		assert!(! Existing::Ask.replaces(100, 50));
+		assert!(Existing::Overwrite.replaces(50, 100));
+		assert!(Existing::Smaller.replaces(100, 50));
+		assert!(! Existing::Smaller.replaces(100, 100));
+	}
+}
@@ -0,0 +1,111 @@
+/*!
+# `Refract` - Extensions
+*/
+
+use dowser::Extension;
+use refract_core::ImageKind;
+use std::path::{
+	Path,
+	PathBuf,
+};
+
+
+
+/// # Extension: AVIF.
+pub const E_AVIF: Extension = Extension::new4(*b"avif");
+
+/// # Extension: JPEG.
+pub const E_JPEG: Extension = Extension::new4(*b"jpeg");
+
+/// # Extension: JPG.
+pub const E_JPG: Extension = Extension::new3(*b"jpg");
+
+/// # Extension: JXL.
+pub const E_JXL: Extension = Extension::new3(*b"jxl");
+
+/// # Extension: PNG.
+pub const E_PNG: Extension = Extension::new3(*b"png");
+
+/// # Extension: WEBP.
+pub const E_WEBP: Extension = Extension::new4(*b"webp");
+
+
+
+#[must_use]
+/// # Is JPEG/PNG File?
+///
+/// This checks the path's extension (case-insensitively); it does not look
+/// at the file itself.
+pub fn is_jpeg_png(path: &Path) -> bool {
+	Extension::try_from3(path).map_or_else(
+		|| Extension::try_from4(path) == Some(E_JPEG),
+		|e| e == E_JPG || e == E_PNG
+	)
+}
+
+#[must_use]
+/// # Is AVIF/JPEG XL/WebP File?
+///
+/// This checks the path's extension (case-insensitively); it does not look
+/// at the file itself.
+pub fn is_next_gen(path: &Path) -> bool {
+	Extension::try_from3(path).map_or_else(
+		|| Extension::try_from4(path).is_some_and(|e| e == E_AVIF || e == E_WEBP),
+		|e| e == E_JXL
+	)
+}
+
+#[must_use]
+/// # With Extension.
+///
+/// Make sure an output path ends with the proper extension for its kind,
+/// tacking it onto the end if not.
+pub fn with_extension(mut path: PathBuf, kind: ImageKind) -> PathBuf {
+	let ext = match kind {
+		ImageKind::Avif => E_AVIF,
+		ImageKind::Jpeg => E_JPG,
+		ImageKind::Jxl => E_JXL,
+		ImageKind::Png => E_PNG,
+		ImageKind::Webp => E_WEBP,
+	};
+
+	// JPEGs have two common extensions; either will do.
+	if ext != path && (kind != ImageKind::Jpeg || E_JPEG != path) {
+		path.as_mut_os_string().push(".");
+		path.as_mut_os_string().push(kind.extension());
+	}
+
+	path
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn t_with_extension() {
+		assert_eq!(
+			with_extension(PathBuf::from("/tmp/image.WEBP"), ImageKind::Webp),
+			PathBuf::from("/tmp/image.WEBP"),
+		);
+		assert_eq!(
+			with_extension(PathBuf::from("/tmp/image.jpg"), ImageKind::Avif),
+			PathBuf::from("/tmp/image.jpg.avif"),
+		);
+		assert_eq!(
+			with_extension(PathBuf::from("/tmp/image.jpeg"), ImageKind::Jpeg),
+			PathBuf::from("/tmp/image.jpeg"),
+		);
+		assert_eq!(
+			with_extension(PathBuf::from("/tmp/image.png"), ImageKind::Jpeg),
+			PathBuf::from("/tmp/image.png.jpg"),
+		);
+		assert!(is_jpeg_png(Path::new("/tmp/image.JPEG")));
+		assert!(! is_jpeg_png(Path::new("/tmp/image.jxl")));
+		assert!(is_next_gen(Path::new("/tmp/image.jxl")));
+		assert!(is_next_gen(Path::new("/tmp/image.AVIF")));
+		assert!(! is_next_gen(Path::new("/tmp/image.png")));
+	}
+}
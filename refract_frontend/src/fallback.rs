@@ -0,0 +1,122 @@
+/*!
+# `Refract` - Fallbacks
+*/
+
+use refract_core::RefractError;
+use std::path::Path;
+
+
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+/// # Fallback Mode.
+///
+/// When a source can't be (or isn't) converted to a given format, some
+/// pipelines still expect a file with the target extension to exist. A
+/// fallback stands one in, pointing back at — or duplicating — the source.
+pub enum Fallback {
+	/// # Copy the Source.
+	Copy,
+
+	/// # Hard Link the Source.
+	HardLink,
+
+	/// # Symlink the Source.
+	SymLink,
+}
+
+impl TryFrom<&str> for Fallback {
+	type Error = RefractError;
+
+	fn try_from(src: &str) -> Result<Self, Self::Error> {
+		match src.trim().to_ascii_lowercase().as_str() {
+			"copy" => Ok(Self::Copy),
+			"hardlink" => Ok(Self::HardLink),
+			"symlink" => Ok(Self::SymLink),
+			_ => Err(RefractError::Fallback),
+		}
+	}
+}
+
+impl Fallback {
+	#[must_use]
+	/// # As Str.
+	pub const fn as_str(self) -> &'static str {
+		match self {
+			Self::Copy => "copy",
+			Self::HardLink => "hardlink",
+			Self::SymLink => "symlink",
+		}
+	}
+
+	/// # Apply.
+	///
+	/// Stand `src` in at `dst`. Existing files are never overwritten.
+	///
+	/// Symlinks are relative when both paths share a directory, and absolute
+	/// otherwise. They are only supported on Unix and Windows — where they
+	/// also require Developer Mode or administrator rights — otherwise the
+	/// source is copied instead.
+	///
+	/// ## Errors
+	///
+	/// Returns an error if `dst` already exists or the operation fails.
+	pub fn apply(self, src: &Path, dst: &Path) -> Result<(), RefractError> {
+		if dst.symlink_metadata().is_ok() { return Err(RefractError::Write); }
+
+		match self {
+			Self::Copy => write_atomic::copy_file(src, dst),
+			Self::HardLink => std::fs::hard_link(src, dst),
+			#[cfg(unix)]
+			Self::SymLink => std::os::unix::fs::symlink(symlink_target(src, dst), dst),
+			// Windows only lets administrators (or developers) make symlinks,
+			// so copy if need be.
+			#[cfg(windows)]
+			Self::SymLink => std::os::windows::fs::symlink_file(symlink_target(src, dst), dst)
+				.or_else(|_| write_atomic::copy_file(src, dst)),
+			#[cfg(not(any(unix, windows)))]
+			Self::SymLink => write_atomic::copy_file(src, dst),
+		}
+			.map_err(|_| RefractError::Write)
+	}
+}
+
+
+
+#[cfg(any(unix, windows))]
+/// # Symlink Target.
+///
+/// Use a relative target when both paths share a directory, so the pair can
+/// be moved together.
+fn symlink_target<'a>(src: &'a Path, dst: &Path) -> &'a Path {
+	if src.parent() == dst.parent() { src.file_name().map_or(src, Path::new) }
+	else { src }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn t_apply() {
+		let dir = std::env::temp_dir().join(format!("refract-fallback-{}", std::process::id()));
+		std::fs::create_dir_all(&dir).expect("Unable to create directory.");
+		let src = dir.join("image.jpg");
+		std::fs::write(&src, b"jpeg").expect("Unable to write source.");
+
+		for mode in [Fallback::Copy, Fallback::HardLink, Fallback::SymLink] {
+			assert_eq!(Fallback::try_from(mode.as_str()), Ok(mode));
+
+			let dst = dir.join(format!("image.jpg.{}.webp", mode.as_str()));
+			assert!(mode.apply(&src, &dst).is_ok(), "{} failed.", mode.as_str());
+			assert_eq!(std::fs::read(&dst).ok().as_deref(), Some(&b"jpeg"[..]));
+
+			// No clobbering.
+			assert_eq!(mode.apply(&src, &dst), Err(RefractError::Write));
+		}
+
+		assert_eq!(Fallback::try_from("move"), Err(RefractError::Fallback));
+		let _res = std::fs::remove_dir_all(&dir);
+	}
+}
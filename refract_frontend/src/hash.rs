@@ -0,0 +1,122 @@
+/*!
+# `Refract` - Content Hashes
+*/
+
+use std::fmt::Write;
+
+
+
+/// # Round Constants.
+const K: [u32; 64] = [
+	0x428a_2f98, 0x7137_4491, 0xb5c0_fbcf, 0xe9b5_dba5, 0x3956_c25b, 0x59f1_11f1, 0x923f_82a4, 0xab1c_5ed5,
+	0xd807_aa98, 0x1283_5b01, 0x2431_85be, 0x550c_7dc3, 0x72be_5d74, 0x80de_b1fe, 0x9bdc_06a7, 0xc19b_f174,
+	0xe49b_69c1, 0xefbe_4786, 0x0fc1_9dc6, 0x240c_a1cc, 0x2de9_2c6f, 0x4a74_84aa, 0x5cb0_a9dc, 0x76f9_88da,
+	0x983e_5152, 0xa831_c66d, 0xb003_27c8, 0xbf59_7fc7, 0xc6e0_0bf3, 0xd5a7_9147, 0x06ca_6351, 0x1429_2967,
+	0x27b7_0a85, 0x2e1b_2138, 0x4d2c_6dfc, 0x5338_0d13, 0x650a_7354, 0x766a_0abb, 0x81c2_c92e, 0x9272_2c85,
+	0xa2bf_e8a1, 0xa81a_664b, 0xc24b_8b70, 0xc76c_51a3, 0xd192_e819, 0xd699_0624, 0xf40e_3585, 0x106a_a070,
+	0x19a4_c116, 0x1e37_6c08, 0x2748_774c, 0x34b0_bcb5, 0x391c_0cb3, 0x4ed8_aa4a, 0x5b9c_ca4f, 0x682e_6ff3,
+	0x748f_82ee, 0x78a5_636f, 0x84c8_7814, 0x8cc7_0208, 0x90be_fffa, 0xa450_6ceb, 0xbef9_a3f7, 0xc671_78f2,
+];
+
+/// # Initial State.
+const H: [u32; 8] = [
+	0x6a09_e667, 0xbb67_ae85, 0x3c6e_f372, 0xa54f_f53a,
+	0x510e_527f, 0x9b05_688c, 0x1f83_d9ab, 0x5be0_cd19,
+];
+
+
+
+#[must_use]
+/// # SHA-256.
+///
+/// Return the SHA-256 digest of the data as a lowercase hex string, e.g. for
+/// verifying files weren't corrupted in transit, or spotting identical
+/// outputs made from different sources.
+///
+/// Refract only ever hashes a handful of images at a time, so this simple,
+/// dependency-free implementation is plenty fast enough.
+pub fn sha256(data: &[u8]) -> String {
+	let mut state = H;
+
+	// Hash all the full blocks.
+	let mut chunks = data.chunks_exact(64);
+	for chunk in chunks.by_ref() { compress(&mut state, chunk); }
+
+	// Pad and hash the remainder, which may spill into a second block.
+	let rem = chunks.remainder();
+	let mut tail = [0_u8; 128];
+	tail[..rem.len()].copy_from_slice(rem);
+	tail[rem.len()] = 0x80;
+	let tail_len = if rem.len() < 56 { 64 } else { 128 };
+	let bits = (data.len() as u64).wrapping_mul(8);
+	tail[tail_len - 8..tail_len].copy_from_slice(&bits.to_be_bytes());
+	for chunk in tail[..tail_len].chunks_exact(64) { compress(&mut state, chunk); }
+
+	let mut out = String::with_capacity(64);
+	for word in state { let _res = write!(out, "{word:08x}"); }
+	out
+}
+
+#[expect(clippy::many_single_char_names, reason = "These are the names from the spec.")]
+/// # Compress Block.
+fn compress(state: &mut [u32; 8], block: &[u8]) {
+	let mut w = [0_u32; 64];
+	for (dst, src) in w.iter_mut().zip(block.chunks_exact(4)) {
+		*dst = u32::from_be_bytes([src[0], src[1], src[2], src[3]]);
+	}
+	for i in 16..64 {
+		let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+		let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+		w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+	}
+
+	let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+	for (k, w) in K.into_iter().zip(w) {
+		let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+		let ch = (e & f) ^ (! e & g);
+		let t1 = h.wrapping_add(s1).wrapping_add(ch).wrapping_add(k).wrapping_add(w);
+		let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+		let maj = (a & b) ^ (a & c) ^ (b & c);
+		let t2 = s0.wrapping_add(maj);
+
+		h = g;
+		g = f;
+		f = e;
+		e = d.wrapping_add(t1);
+		d = c;
+		c = b;
+		b = a;
+		a = t1.wrapping_add(t2);
+	}
+
+	for (s, v) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+		*s = s.wrapping_add(v);
+	}
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn t_sha256() {
+		for (src, expected) in [
+			(&b""[..], "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"),
+			(b"abc", "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"),
+			(
+				b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+				"248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+			),
+		] {
+			assert_eq!(sha256(src), expected, "Hash mismatch for {src:?}.");
+		}
+
+		// Something spanning a few blocks.
+		assert_eq!(
+			sha256(&[b'a'; 1000]),
+			"41edece42d63e8d9bf515a9ba6932e1c20cbc9f5a5d134645adb5db1b9737ea3",
+		);
+	}
+}
@@ -0,0 +1,109 @@
+/*!
+# `Refract` - Frontend
+
+This crate holds the frontend-agnostic state and helpers shared by
+[Refract](https://github.com/Blobfolio/refract)'s user interfaces: queueing
+and crawling sources, naming and saving outputs, and summarizing the results.
+
+Keeping these bits out of the UI code ensures every frontend queues, names,
+and saves things exactly the same way.
+*/
+
+#![deny(
+	clippy::allow_attributes_without_reason,
+	clippy::correctness,
+	unreachable_pub,
+	unsafe_code,
+)]
+
+#![warn(
+	clippy::complexity,
+	clippy::nursery,
+	clippy::pedantic,
+	clippy::perf,
+	clippy::style,
+
+	clippy::allow_attributes,
+	clippy::clone_on_ref_ptr,
+	clippy::create_dir,
+	clippy::filetype_is_file,
+	clippy::format_push_string,
+	clippy::get_unwrap,
+	clippy::impl_trait_in_params,
+	clippy::lossy_float_literal,
+	clippy::missing_assert_message,
+	clippy::missing_docs_in_private_items,
+	clippy::needless_raw_strings,
+	clippy::panic_in_result_fn,
+	clippy::pub_without_shorthand,
+	clippy::rest_pat_in_fully_bound_structs,
+	clippy::semicolon_inside_block,
+	clippy::str_to_string,
+	clippy::todo,
+	clippy::undocumented_unsafe_blocks,
+	clippy::unneeded_field_pattern,
+	clippy::unseparated_literal_suffix,
+	clippy::unwrap_in_result,
+
+	macro_use_extern_crate,
+	missing_copy_implementations,
+	missing_docs,
+	non_ascii_idents,
+	trivial_casts,
+	trivial_numeric_casts,
+	unused_crate_dependencies,
+	unused_extern_crates,
+	unused_import_braces,
+)]
+
+mod distortion;
+mod existing;
+mod ext;
+mod fallback;
+mod hash;
+mod naming;
+mod picture;
+mod quality;
+mod queue;
+mod region;
+mod save;
+mod sparkline;
+
+
+
+pub use distortion::{
+	Distortion,
+	DISTORTION_THRESHOLD,
+};
+pub use existing::Existing;
+pub use ext::{
+	E_AVIF,
+	E_JPEG,
+	E_JPG,
+	E_JXL,
+	E_PNG,
+	E_WEBP,
+	is_jpeg_png,
+	is_next_gen,
+	with_extension,
+};
+pub use fallback::Fallback;
+pub use hash::sha256;
+pub use naming::Naming;
+pub use picture::{
+	picture,
+	PictureSource,
+};
+pub use quality::QualityOverride;
+pub use queue::{
+	crawl,
+	interleave,
+	read_list,
+};
+pub use region::Region;
+pub use save::{
+	same_file,
+	save,
+	sync_service,
+};
+pub use sparkline::sparkline;
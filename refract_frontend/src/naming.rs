@@ -0,0 +1,289 @@
+/*!
+# `Refract` - Output Naming
+*/
+
+use refract_core::{
+	ImageKind,
+	RefractError,
+};
+use std::{
+	ffi::OsStr,
+	path::{
+		Path,
+		PathBuf,
+	},
+};
+
+
+
+/// # Default Template.
+///
+/// This appends the new extension to the full source file name, e.g.
+/// `image.jpg.webp`.
+const DEFAULT_TEMPLATE: &str = "{src_name}.{ext}";
+
+/// # Default HTML Template.
+const DEFAULT_HTML_TEMPLATE: &str = "{src_name}.html";
+
+
+
+#[derive(Debug, Clone)]
+/// # Output Naming.
+///
+/// This holds the file name templates used when suggesting output paths, one
+/// per output format (plus one for `<picture>` snippets).
+///
+/// The following tokens are supported:
+///
+/// | Token | Value |
+/// | ----- | ----- |
+/// | `{src_name}` | The source file name, e.g. `image.jpg`. |
+/// | `{src_stem}` | The source file name sans extension, e.g. `image`. |
+/// | `{src_ext}` | The source extension, e.g. `jpg`. |
+/// | `{ext}` | The output extension, e.g. `webp`. |
+/// | `{EXT}` | The output extension, uppercase, e.g. `WEBP`. |
+///
+/// Outputs are suggested alongside their sources unless an output directory
+/// has been set, in which case the source tree is mirrored beneath it.
+/// (`<picture>` snippets always stay with their sources, so the `<img>`
+/// fallback can be referenced by name.)
+pub struct Naming {
+	/// # AVIF Template.
+	avif: String,
+
+	/// # JPEG Template.
+	jpeg: String,
+
+	/// # JPEG XL Template.
+	jxl: String,
+
+	/// # PNG Template.
+	png: String,
+
+	/// # WebP Template.
+	webp: String,
+
+	/// # HTML Template.
+	html: String,
+
+	/// # Output Directory (Source Root, Output Root).
+	out_dir: Option<(PathBuf, PathBuf)>,
+}
+
+impl Default for Naming {
+	fn default() -> Self {
+		Self {
+			avif: DEFAULT_TEMPLATE.to_owned(),
+			jpeg: DEFAULT_TEMPLATE.to_owned(),
+			jxl: DEFAULT_TEMPLATE.to_owned(),
+			png: DEFAULT_TEMPLATE.to_owned(),
+			webp: DEFAULT_TEMPLATE.to_owned(),
+			html: DEFAULT_HTML_TEMPLATE.to_owned(),
+			out_dir: None,
+		}
+	}
+}
+
+impl Naming {
+	/// # Set Template.
+	///
+	/// Override the template for the given key — `avif`, `jpeg`, `jxl`,
+	/// `png`, `webp`, or `html` — as loaded from the config file.
+	///
+	/// ## Errors
+	///
+	/// Returns an error if the key is unknown or the template is empty or
+	/// contains path separators.
+	pub fn set(&mut self, key: &str, tpl: &str) -> Result<(), RefractError> {
+		if tpl.trim().is_empty() || tpl.contains(['/', '\\']) {
+			return Err(RefractError::Config);
+		}
+
+		let dst = match key {
+			"avif" => &mut self.avif,
+			"jpeg" => &mut self.jpeg,
+			"jxl" => &mut self.jxl,
+			"png" => &mut self.png,
+			"webp" => &mut self.webp,
+			"html" => &mut self.html,
+			_ => return Err(RefractError::Config),
+		};
+		tpl.clone_into(dst);
+		Ok(())
+	}
+
+	/// # Set Image Templates.
+	///
+	/// Override the templates for all image formats at once, as passed via
+	/// `--name-template`. Unlike the per-format templates, this must contain
+	/// an `{ext}` or `{EXT}` token, or every format would be suggested the
+	/// same name.
+	///
+	/// Templates like `{src_stem}.{ext}` are allowed even though they render
+	/// a source's own name for its own format — `image.png` for a PNG — as
+	/// they are perfectly sensible for the others. Saves refuse to write over
+	/// the source regardless; see [`same_file`](crate::same_file).
+	///
+	/// ## Errors
+	///
+	/// Returns an error if the template is empty, contains path separators,
+	/// or lacks an extension token.
+	pub fn set_images(&mut self, tpl: &str) -> Result<(), RefractError> {
+		if
+			tpl.trim().is_empty() ||
+			tpl.contains(['/', '\\']) ||
+			! (tpl.contains("{ext}") || tpl.contains("{EXT}"))
+		{
+			return Err(RefractError::NameTemplate);
+		}
+
+		for dst in [&mut self.avif, &mut self.jpeg, &mut self.jxl, &mut self.png, &mut self.webp] {
+			tpl.clone_into(dst);
+		}
+		Ok(())
+	}
+
+	/// # Set Output Directory.
+	///
+	/// Mirror the source tree — rooted at the deepest directory containing
+	/// all of the `sources` — under `dir` rather than saving outputs
+	/// alongside their sources. The directory is created if missing.
+	///
+	/// Sources outside the root, like those added later from the GUI, are
+	/// suggested in `dir` itself.
+	///
+	/// ## Errors
+	///
+	/// Returns an error if the directory cannot be created.
+	pub fn set_out_dir(&mut self, dir: &Path, sources: &[PathBuf])
+	-> Result<(), RefractError> {
+		std::fs::create_dir_all(dir).map_err(|_| RefractError::OutDir)?;
+		let dir = std::fs::canonicalize(dir).map_err(|_| RefractError::OutDir)?;
+
+		// Directories are roots in their own right; files are rooted in
+		// their parents.
+		let roots: Vec<PathBuf> = sources.iter()
+			.filter_map(|p| std::fs::canonicalize(p).ok())
+			.filter_map(|p| if p.is_dir() { Some(p) } else { p.parent().map(Path::to_path_buf) })
+			.collect();
+		let root = roots.first()
+			.and_then(|first| first.ancestors().find(|a| roots.iter().all(|p| p.starts_with(a))))
+			.map_or_else(PathBuf::new, Path::to_path_buf);
+
+		self.out_dir = Some((root, dir));
+		Ok(())
+	}
+
+	#[must_use]
+	/// # Output Directory.
+	///
+	/// Return the directory the source's outputs belong in: its own, or
+	/// its mirror under the output directory, if set.
+	pub fn out_dir(&self, src: &Path) -> PathBuf {
+		let dir = src.parent().unwrap_or_else(|| Path::new(""));
+		match &self.out_dir {
+			Some((root, out)) => dir.strip_prefix(root)
+				.ok()
+				.filter(|_| ! root.as_os_str().is_empty())
+				.map_or_else(|| out.clone(), |rel| out.join(rel)),
+			None => dir.to_path_buf(),
+		}
+	}
+
+	#[must_use]
+	/// # Create Output Directory.
+	///
+	/// Same as [`Naming::out_dir`], but create the directory — best effort —
+	/// if needed, before saving something to it.
+	pub fn create_out_dir(&self, src: &Path) -> PathBuf {
+		let dir = self.out_dir(src);
+		if self.out_dir.is_some() { let _res = std::fs::create_dir_all(&dir); }
+		dir
+	}
+
+	#[must_use]
+	/// # Suggested Output Path.
+	///
+	/// Return the suggested output file name for the source and kind, joined
+	/// to its output directory.
+	pub fn output_path(&self, src: &Path, kind: ImageKind) -> PathBuf {
+		self.out_dir(src).join(self.file_name(src, kind))
+	}
+
+	#[must_use]
+	/// # Suggested File Name.
+	///
+	/// Return the suggested output file name for the source and kind.
+	pub fn file_name(&self, src: &Path, kind: ImageKind) -> String {
+		let tpl = match kind {
+			ImageKind::Avif => &self.avif,
+			ImageKind::Jpeg => &self.jpeg,
+			ImageKind::Jxl => &self.jxl,
+			ImageKind::Png => &self.png,
+			ImageKind::Webp => &self.webp,
+		};
+		render(tpl, src, kind.extension())
+	}
+
+	#[must_use]
+	/// # HTML Snippet Path.
+	///
+	/// Return the full path to use for a source's `<picture>` snippet.
+	pub fn html_path(&self, src: &Path) -> PathBuf {
+		src.with_file_name(render(&self.html, src, "html"))
+	}
+}
+
+
+
+#[expect(clippy::literal_string_with_formatting_args, reason = "These are our own tokens.")]
+/// # Render Template.
+fn render(tpl: &str, src: &Path, ext: &str) -> String {
+	let name = src.file_name().map_or_else(|| "image".into(), OsStr::to_string_lossy);
+	let stem = src.file_stem().map_or_else(|| "image".into(), OsStr::to_string_lossy);
+	let src_ext = src.extension().map(OsStr::to_string_lossy).unwrap_or_default();
+
+	tpl.replace("{src_name}", &name)
+		.replace("{src_stem}", &stem)
+		.replace("{src_ext}", &src_ext)
+		.replace("{ext}", ext)
+		.replace("{EXT}", &ext.to_ascii_uppercase())
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::same_file;
+
+	#[test]
+	fn t_source_collision() {
+		let root = std::env::temp_dir().join(format!("refract-naming-{}", std::process::id()));
+		std::fs::create_dir_all(&root).expect("Unable to create directory.");
+		let src = root.join("image.png");
+		std::fs::write(&src, b"PNG").expect("Unable to write source.");
+
+		let mut naming = Naming::default();
+		naming.set_images("{src_stem}.{ext}").expect("Template rejected.");
+
+		// The template is fine for other formats…
+		let webp = naming.output_path(&src, ImageKind::Webp);
+		assert_eq!(webp, root.join("image.webp"));
+		assert!(! same_file(&webp, &src), "WebP output is not the source.");
+
+		// …but renders the source's own name for its own format, which is
+		// what the save guard is there to catch.
+		let png = naming.output_path(&src, ImageKind::Png);
+		assert_eq!(png, src);
+		assert!(same_file(&png, &src), "PNG output is the source.");
+
+		// The default template never collides.
+		let png = Naming::default().output_path(&src, ImageKind::Png);
+		assert_eq!(png, root.join("image.png.png"));
+		assert!(! same_file(&png, &src), "Default PNG output is not the source.");
+
+		let _res = std::fs::remove_dir_all(&root);
+	}
+}
@@ -0,0 +1,126 @@
+/*!
+# `Refract` - Picture Snippets
+*/
+
+use refract_core::ImageKind;
+use std::{
+	ffi::OsStr,
+	path::{
+		Path,
+		PathBuf,
+	},
+};
+
+
+
+#[derive(Debug, Clone)]
+/// # Saved Image.
+///
+/// This holds the details of a single saved output, for use in the `<picture>`
+/// snippet and lossless deduplication.
+pub struct PictureSource {
+	/// # File Path.
+	pub path: PathBuf,
+
+	/// # Image Kind.
+	pub kind: ImageKind,
+
+	/// # File Size.
+	pub size: usize,
+
+	/// # Lossless?
+	pub lossless: bool,
+}
+
+impl PictureSource {
+	#[must_use]
+	/// # New.
+	pub const fn new(path: PathBuf, kind: ImageKind, size: usize, lossless: bool)
+	-> Self {
+		Self { path, kind, size, lossless }
+	}
+}
+
+
+
+#[must_use]
+/// # Build `<picture>` Snippet.
+///
+/// This generates an HTML `<picture>` element for the source and its saved
+/// conversions. The conversions are listed smallest-first — browsers use the
+/// first type they support — with the original source serving as the `<img>`
+/// fallback, or the smallest JPEG or PNG copy of it, if any were saved.
+///
+/// Paths sharing the source's directory are written as bare file names;
+/// anything else is written in full.
+pub fn picture(
+	src: &Path,
+	width: usize,
+	height: usize,
+	saved: &mut [PictureSource],
+) -> String {
+	use std::fmt::Write;
+
+	saved.sort_by_key(|s| s.size);
+	let dir = src.parent();
+
+	let fallback = saved.iter()
+		.find(|s| is_fallback(s.kind))
+		.map_or(src, |s| s.path.as_path());
+
+	let mut out = String::from("<picture>\n");
+	for s in saved.iter().filter(|s| ! is_fallback(s.kind)) {
+		out.push_str("\t<source type=\"");
+		out.push_str(s.kind.mime());
+		out.push_str("\" srcset=\"");
+		push_escaped(&mut out, &rel_path(&s.path, dir));
+		out.push_str("\">\n");
+	}
+
+	out.push_str("\t<img src=\"");
+	push_escaped(&mut out, &rel_path(fallback, dir));
+	let _res = write!(
+		out,
+		"\" width=\"{width}\" height=\"{height}\" alt=\"\" loading=\"lazy\" decoding=\"async\">\n</picture>\n",
+	);
+
+	out
+}
+
+
+
+/// # Is Fallback Kind?
+///
+/// JPEG and PNG copies work everywhere, so belong in the `<img>` rather than
+/// a `<source>`.
+const fn is_fallback(kind: ImageKind) -> bool {
+	matches!(kind, ImageKind::Jpeg | ImageKind::Png)
+}
+
+/// # Push Escaped.
+///
+/// Push an HTML-attribute-safe copy of `src` onto `out`.
+fn push_escaped(out: &mut String, src: &str) {
+	for c in src.chars() {
+		match c {
+			'&' => out.push_str("&amp;"),
+			'"' => out.push_str("&quot;"),
+			'<' => out.push_str("&lt;"),
+			'>' => out.push_str("&gt;"),
+			_ => out.push(c),
+		}
+	}
+}
+
+/// # Relative Path.
+///
+/// Return the file name if `path` lives in `dir`, otherwise the whole thing.
+fn rel_path(path: &Path, dir: Option<&Path>) -> String {
+	if path.parent() == dir {
+		if let Some(name) = path.file_name().map(OsStr::to_string_lossy) {
+			return name.into_owned();
+		}
+	}
+
+	path.to_string_lossy().into_owned()
+}
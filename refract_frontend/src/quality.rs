@@ -0,0 +1,128 @@
+/*!
+# `Refract` - Quality Overrides
+*/
+
+use refract_core::{
+	ImageKind,
+	Quality,
+	RefractError,
+};
+use std::num::NonZeroU8;
+
+
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+/// # Quality Override.
+///
+/// A user-supplied starting point for a format's quality search, for when the
+/// right neighborhood is already known. It either seeds the first probe or
+/// narrows the range searched; a range of one pins the quality outright.
+///
+/// Values are written the way the format itself expresses them — an AVIF
+/// quantizer, a JPEG XL distance, etc. — e.g. `avif=28` or `jxl=0.5-2.0`.
+pub struct QualityOverride {
+	/// # Format.
+	kind: ImageKind,
+
+	/// # Low End (Raw).
+	bottom: NonZeroU8,
+
+	/// # High End (Raw).
+	top: NonZeroU8,
+
+	/// # Range?
+	///
+	/// A lone value seeds the search rather than bounding it.
+	range: bool,
+}
+
+impl TryFrom<&str> for QualityOverride {
+	type Error = RefractError;
+
+	fn try_from(src: &str) -> Result<Self, Self::Error> {
+		let (kind, value) = src.split_once('=').ok_or(RefractError::QualityOverride)?;
+		let kind = match kind.trim().to_ascii_lowercase().as_str() {
+			"avif" => ImageKind::Avif,
+			"jpg" | "jpeg" => ImageKind::Jpeg,
+			"jxl" => ImageKind::Jxl,
+			"webp" => ImageKind::Webp,
+			_ => return Err(RefractError::QualityOverride),
+		};
+		let parse = |v: &str| Quality::from_native(kind, v)
+			.map(Quality::raw)
+			.ok_or(RefractError::QualityOverride);
+
+		let (bottom, top, range) =
+			if let Some((a, b)) = value.split_once('-') {
+				let (a, b) = (parse(a)?, parse(b)?);
+				(a.min(b), a.max(b), true)
+			}
+			else {
+				let q = parse(value)?;
+				(q, q, false)
+			};
+
+		Ok(Self { kind, bottom, top, range })
+	}
+}
+
+impl QualityOverride {
+	#[must_use]
+	/// # Format.
+	pub const fn kind(self) -> ImageKind { self.kind }
+
+	#[must_use]
+	/// # First Probe.
+	///
+	/// Return the (raw) quality to try first, if seeding.
+	pub const fn first(self) -> Option<NonZeroU8> {
+		if self.range { None }
+		else { Some(self.bottom) }
+	}
+
+	#[must_use]
+	/// # Bounds.
+	///
+	/// Return the (raw) low and high ends of the search, if bounding.
+	pub const fn bounds(self) -> Option<(NonZeroU8, NonZeroU8)> {
+		if self.range { Some((self.bottom, self.top)) }
+		else { None }
+	}
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn t_quality_override() {
+		let nz = |n: u8| NonZeroU8::new(n).unwrap();
+
+		let q = QualityOverride::try_from("avif=28").expect("Invalid override.");
+		assert_eq!(q.kind(), ImageKind::Avif);
+		assert_eq!(q.first(), Some(nz(35)));
+		assert_eq!(q.bounds(), None);
+
+		// Quantizers run backwards, but the bounds shouldn't.
+		let q = QualityOverride::try_from("AVIF=20-35").expect("Invalid override.");
+		assert_eq!(q.first(), None);
+		assert_eq!(q.bounds(), Some((nz(28), nz(43))));
+
+		let q = QualityOverride::try_from("jxl=0.5-2.0").expect("Invalid override.");
+		assert_eq!(q.bounds(), Some((nz(130), nz(145))));
+
+		let q = QualityOverride::try_from("jpeg=80-80").expect("Invalid override.");
+		assert_eq!(q.kind(), ImageKind::Jpeg);
+		assert_eq!(q.bounds(), Some((nz(80), nz(80))));
+
+		for bad in ["80", "png=80", "webp=", "webp=0", "webp=50-101", "gif=50"] {
+			assert_eq!(
+				QualityOverride::try_from(bad),
+				Err(RefractError::QualityOverride),
+				"{bad} should be invalid.",
+			);
+		}
+	}
+}
@@ -0,0 +1,166 @@
+/*!
+# `Refract` - Queue
+*/
+
+use dowser::Dowser;
+use std::{
+	collections::{
+		HashSet,
+		VecDeque,
+	},
+	path::{
+		Path,
+		PathBuf,
+	},
+};
+
+
+
+#[must_use]
+/// # Crawl Paths.
+///
+/// Expand a list of file and/or directory paths — from the command line, a
+/// file chooser, etc. — into a list of JPEG and PNG images.
+///
+/// AVIF, JPEG XL, and WebP images are included too, but only when passed
+/// explicitly; directories are usually full of earlier conversions, which
+/// shouldn't be re-crunched by accident.
+///
+/// By default, directories are crawled recursively, courtesy of `Dowser`.
+/// If `max_depth` is set, only that many levels of subdirectories will be
+/// descended into; zero means the top level only.
+pub fn crawl(roots: &[PathBuf], max_depth: Option<usize>) -> Vec<PathBuf> {
+	let mut next_gen: Vec<PathBuf> = roots.iter()
+		.filter(|p| crate::is_next_gen(p))
+		.filter_map(|p| std::fs::canonicalize(p).ok())
+		.filter(|p| p.is_file())
+		.collect();
+	next_gen.sort();
+	next_gen.dedup();
+
+	let Some(max_depth) = max_depth else {
+		let mut files = Dowser::default()
+			.with_paths(roots)
+			.into_vec_filtered(crate::is_jpeg_png);
+		files.append(&mut next_gen);
+		return files;
+	};
+
+	let mut seen: HashSet<PathBuf> = next_gen.iter().cloned().collect();
+	let mut files: Vec<PathBuf> = next_gen;
+	let mut dirs: Vec<PathBuf> = Vec::new();
+	let mut push = |path: &Path, dirs: &mut Vec<PathBuf>| {
+		let Ok(path) = std::fs::canonicalize(path) else { return; };
+		if seen.insert(path.clone()) {
+			if path.is_dir() { dirs.push(path); }
+			else if crate::is_jpeg_png(&path) { files.push(path); }
+		}
+	};
+
+	for path in roots { push(path, &mut dirs); }
+
+	// Work through one level at a time.
+	for _ in 0..=max_depth {
+		if dirs.is_empty() { break; }
+		for dir in std::mem::take(&mut dirs) {
+			let Ok(rd) = std::fs::read_dir(dir) else { continue; };
+			for e in rd.flatten() { push(&e.path(), &mut dirs); }
+		}
+	}
+
+	files
+}
+
+#[must_use]
+/// # Read Path List.
+///
+/// Read paths from a text file — or STDIN if `-` — one per line.
+///
+/// Read errors are ignored.
+pub fn read_list(src: &str) -> Vec<PathBuf> {
+	let raw =
+		if src == "-" { std::io::read_to_string(std::io::stdin()) }
+		else { std::fs::read_to_string(src) };
+
+	raw.map(|raw| raw.lines()
+		.map(str::trim)
+		.filter(|l| ! l.is_empty())
+		.map(PathBuf::from)
+		.collect()
+	).unwrap_or_default()
+}
+
+#[must_use]
+/// # Interleave Paths by Directory.
+///
+/// Rather than crunching through each directory in turn — letting one giant
+/// folder starve all the others — this regroups the queue by parent directory
+/// and takes one path from each in round-robin fashion.
+///
+/// Directories retain the order in which they were first seen, and the paths
+/// within each are sorted.
+pub fn interleave(paths: Vec<PathBuf>) -> Vec<PathBuf> {
+	let len = paths.len();
+
+	// Group by parent.
+	let mut groups: Vec<(PathBuf, Vec<PathBuf>)> = Vec::new();
+	for path in paths {
+		let dir = path.parent().map_or_else(PathBuf::new, Path::to_path_buf);
+		match groups.iter_mut().find(|(d, _)| *d == dir) {
+			Some((_, g)) => { g.push(path); },
+			None => { groups.push((dir, vec![path])); },
+		}
+	}
+
+	// Nothing to interleave?
+	if groups.len() < 2 {
+		return groups.pop()
+			.map(|(_, mut g)| { g.sort(); g })
+			.unwrap_or_default();
+	}
+
+	let mut groups: VecDeque<VecDeque<PathBuf>> = groups.into_iter()
+		.map(|(_, mut g)| { g.sort(); VecDeque::from(g) })
+		.collect();
+
+	// Round and round we go!
+	let mut out = Vec::with_capacity(len);
+	while let Some(mut g) = groups.pop_front() {
+		if let Some(p) = g.pop_front() { out.push(p); }
+		if ! g.is_empty() { groups.push_back(g); }
+	}
+
+	out
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn t_interleave() {
+		let paths: Vec<PathBuf> = [
+			"/a/3.jpg", "/a/1.jpg", "/a/2.jpg", "/a/4.jpg",
+			"/b/1.png",
+			"/c/2.png", "/c/1.png",
+		].into_iter().map(PathBuf::from).collect();
+
+		let expected: Vec<PathBuf> = [
+			"/a/1.jpg", "/b/1.png", "/c/1.png",
+			"/a/2.jpg", "/c/2.png",
+			"/a/3.jpg",
+			"/a/4.jpg",
+		].into_iter().map(PathBuf::from).collect();
+
+		assert_eq!(interleave(paths), expected);
+
+		// A single directory should just be sorted.
+		let paths: Vec<PathBuf> = ["/a/2.jpg", "/a/1.jpg"].into_iter().map(PathBuf::from).collect();
+		assert_eq!(
+			interleave(paths),
+			["/a/1.jpg", "/a/2.jpg"].into_iter().map(PathBuf::from).collect::<Vec<_>>(),
+		);
+	}
+}
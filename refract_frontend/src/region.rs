@@ -0,0 +1,127 @@
+/*!
+# `Refract` - Region of Interest
+*/
+
+use refract_core::RefractError;
+use std::{
+	fmt,
+	num::NonZeroU32,
+};
+
+
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+/// # Region of Interest.
+///
+/// A crop rectangle — faces, text, fine texture — to run the quality search
+/// on in place of the whole source. It is written like `WxH+X+Y`, or just
+/// `WxH` to center it.
+///
+/// Sources come in all sizes, so the region is shrunk or shifted as needed
+/// to fit each one; see [`Region::fit`].
+pub struct Region {
+	/// # Width.
+	width: NonZeroU32,
+
+	/// # Height.
+	height: NonZeroU32,
+
+	/// # Top Left Corner.
+	///
+	/// If `None`, the region is centered.
+	offset: Option<(u32, u32)>,
+}
+
+impl fmt::Display for Region {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}x{}", self.width, self.height)?;
+		if let Some((x, y)) = self.offset { write!(f, "+{x}+{y}")?; }
+		Ok(())
+	}
+}
+
+impl TryFrom<&str> for Region {
+	type Error = RefractError;
+
+	fn try_from(src: &str) -> Result<Self, Self::Error> {
+		let parse = |v: &str| v.trim().parse::<u32>().map_err(|_| RefractError::Region);
+
+		let src = src.trim();
+		let (size, offset) = match src.split_once('+') {
+			Some((size, offset)) => {
+				let (x, y) = offset.split_once('+').ok_or(RefractError::Region)?;
+				(size, Some((parse(x)?, parse(y)?)))
+			},
+			None => (src, None),
+		};
+		let (width, height) = size.split_once(['x', 'X']).ok_or(RefractError::Region)?;
+		let width = NonZeroU32::new(parse(width)?).ok_or(RefractError::Region)?;
+		let height = NonZeroU32::new(parse(height)?).ok_or(RefractError::Region)?;
+
+		Ok(Self { width, height, offset })
+	}
+}
+
+impl Region {
+	#[must_use]
+	/// # New.
+	///
+	/// Create a region with its top left corner at `x`, `y`.
+	pub const fn new(x: u32, y: u32, width: NonZeroU32, height: NonZeroU32) -> Self {
+		Self { width, height, offset: Some((x, y)) }
+	}
+
+	#[must_use]
+	/// # Fit.
+	///
+	/// Return the `(x, y, width, height)` of the region within an image of
+	/// the given dimensions, shrinking and/or shifting it as needed to fit,
+	/// or `None` if it would cover the whole image anyway.
+	pub fn fit(self, width: usize, height: usize) -> Option<(usize, usize, usize, usize)> {
+		let w = usize::try_from(self.width.get()).ok()?.min(width);
+		let h = usize::try_from(self.height.get()).ok()?.min(height);
+		if w == width && h == height { return None; }
+
+		let (x, y) = match self.offset {
+			Some((x, y)) => (
+				usize::try_from(x).ok()?.min(width - w),
+				usize::try_from(y).ok()?.min(height - h),
+			),
+			None => ((width - w) / 2, (height - h) / 2),
+		};
+		Some((x, y, w, h))
+	}
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn t_region() {
+		let r = Region::try_from("100x50+10+20").expect("Invalid region.");
+		assert_eq!(r.to_string(), "100x50+10+20");
+		assert_eq!(r.fit(1000, 1000), Some((10, 20, 100, 50)));
+
+		// Shifted to fit.
+		assert_eq!(r.fit(105, 1000), Some((5, 20, 100, 50)));
+
+		// Shrunk to fit.
+		assert_eq!(r.fit(80, 1000), Some((0, 20, 80, 50)));
+
+		// Or not needed at all.
+		assert_eq!(r.fit(100, 50), None);
+		assert_eq!(r.fit(60, 40), None);
+
+		// Centered.
+		let r = Region::try_from(" 100X50 ").expect("Invalid region.");
+		assert_eq!(r.to_string(), "100x50");
+		assert_eq!(r.fit(300, 150), Some((100, 50, 100, 50)));
+
+		for bad in ["", "100", "0x50", "100x50+10", "100x50+a+b", "-100x50"] {
+			assert_eq!(Region::try_from(bad), Err(RefractError::Region), "{bad} should be invalid.");
+		}
+	}
+}
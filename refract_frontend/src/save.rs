@@ -0,0 +1,194 @@
+/*!
+# `Refract` - Saving
+*/
+
+use refract_core::RefractError;
+use std::{
+	fs::File,
+	io::{
+		ErrorKind,
+		Write,
+	},
+	path::{
+		Path,
+		PathBuf,
+	},
+};
+
+
+
+/// # Cloud Sync Markers.
+///
+/// Files or directories whose presence in a folder (or any of its ancestors)
+/// indicate it is being watched by a cloud/file-sync client.
+const SYNC_MARKERS: [(&str, &str); 6] = [
+	(".dropbox", "Dropbox"),
+	(".dropbox.cache", "Dropbox"),
+	(".nextcloudsync.log", "Nextcloud"),
+	(".owncloudsync.log", "ownCloud"),
+	(".stfolder", "Syncthing"),
+	(".sync_journal.db", "Nextcloud"),
+];
+
+
+
+/// # Save File.
+///
+/// This writes `data` to `path` atomically, courtesy of `write_atomic`, which
+/// stages the data in a temporary file within the same directory and renames
+/// it into place once complete.
+///
+/// When `durable` is set, the file — and on Unix, its parent directory — will
+/// also be explicitly synced to disk afterwards, ensuring the rename itself
+/// survives a sudden crash or power loss. This is slower, but can be useful
+/// when saving directly to production-served locations.
+///
+/// Saves into cloud-synced folders (see [`sync_service`]) are always treated
+/// as durable so sync clients never pick up a half-flushed file.
+///
+/// Network and other exotic filesystems don't always play nice with that
+/// approach — copying ownership, or the rename itself, might be refused — so
+/// if the atomic write fails, a more conservative copy-then-rename — with
+/// verification — is attempted instead.
+///
+/// ## Errors
+///
+/// Returns an error if the data cannot be written or synced.
+pub fn save(path: &Path, data: &[u8], durable: bool) -> Result<(), RefractError> {
+	if write_atomic::write_file(path, data).is_err() {
+		save_verified(path, data).map_err(|_| RefractError::Write)?;
+	}
+	if durable || sync_service(path).is_some() {
+		sync(path).map_err(|_| RefractError::Write)?;
+	}
+	Ok(())
+}
+
+#[must_use]
+/// # Same File?
+///
+/// Returns `true` if `a` and `b` both exist and resolve — links and all — to
+/// the same path, e.g. when a name template renders an output name identical
+/// to its source's.
+pub fn same_file(a: &Path, b: &Path) -> bool {
+	match (std::fs::canonicalize(a), std::fs::canonicalize(b)) {
+		(Ok(a), Ok(b)) => a == b,
+		_ => false,
+	}
+}
+
+#[must_use]
+/// # Cloud Sync Service.
+///
+/// If the file's directory — or any of its ancestors — appears to be managed
+/// by a cloud/file-sync client (Dropbox, Nextcloud, ownCloud, Syncthing),
+/// return the name of the service.
+///
+/// Detection is based on the marker files these clients leave behind, so is
+/// best-effort only.
+pub fn sync_service(path: &Path) -> Option<&'static str> {
+	path.parent()?.ancestors()
+		.filter(|dir| ! dir.as_os_str().is_empty())
+		.find_map(|dir|
+			SYNC_MARKERS.iter().find_map(|&(marker, name)|
+				dir.join(marker).exists().then_some(name)
+			)
+		)
+}
+
+/// # Save (Copy-Then-Rename).
+///
+/// Stage the data in a hidden file alongside the destination, flush it, and
+/// read it back to make sure it arrived intact before renaming it into place.
+///
+/// If the rename crosses devices — e.g. the destination is a link onto a
+/// different mount — the staged copy is copied over instead, and the result
+/// verified again.
+///
+/// The staging file is removed if anything goes wrong.
+fn save_verified(path: &Path, data: &[u8]) -> Result<(), std::io::Error> {
+	let tmp = staging_path(path).ok_or(ErrorKind::InvalidInput)?;
+	let res = File::create(&tmp)
+		.and_then(|mut f| f.write_all(data).and_then(|()| f.sync_all()))
+		.and_then(|()| verify(&tmp, data))
+		.and_then(|()| match std::fs::rename(&tmp, path) {
+			Err(e) if ErrorKind::CrossesDevices == e.kind() =>
+				std::fs::copy(&tmp, path).and_then(|_| verify(path, data)),
+			res => res,
+		});
+
+	if tmp.exists() { let _res = std::fs::remove_file(&tmp); }
+	res
+}
+
+/// # Staging Path.
+///
+/// Return a hidden, process-specific sibling of `path` to write to first.
+fn staging_path(path: &Path) -> Option<PathBuf> {
+	let name = path.file_name()?.to_string_lossy();
+	Some(path.with_file_name(format!(".{name}.{}.refract", std::process::id())))
+}
+
+/// # Sync File (and Directory).
+///
+/// Flush the file and its parent directory to disk.
+fn sync(path: &Path) -> Result<(), std::io::Error> {
+	File::open(path)?.sync_all()?;
+
+	// Directories can only be opened (and synced) this way on Unix.
+	#[cfg(unix)]
+	if let Some(dir) = path.parent().filter(|p| ! p.as_os_str().is_empty()) {
+		File::open(dir)?.sync_all()?;
+	}
+
+	Ok(())
+}
+
+
+/// # Verify Contents.
+///
+/// Make sure the file holds exactly `data`.
+fn verify(path: &Path, data: &[u8]) -> Result<(), std::io::Error> {
+	if std::fs::read(path)? == data { Ok(()) }
+	else { Err(ErrorKind::InvalidData.into()) }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn t_sync_service() {
+		let root = std::env::temp_dir().join(format!("refract-sync-{}", std::process::id()));
+		let nested = root.join("a/b");
+		std::fs::create_dir_all(&nested).expect("Unable to create directories.");
+
+		let file = nested.join("image.webp");
+		assert_eq!(sync_service(&file), None);
+
+		// A marker anywhere up the tree counts.
+		std::fs::create_dir_all(root.join(".stfolder")).expect("Unable to create marker.");
+		assert_eq!(sync_service(&file), Some("Syncthing"));
+
+		let _res = std::fs::remove_dir_all(&root);
+	}
+
+	#[test]
+	fn t_save_verified() {
+		let root = std::env::temp_dir().join(format!("refract-verified-{}", std::process::id()));
+		std::fs::create_dir_all(&root).expect("Unable to create directory.");
+
+		let file = root.join("image.webp");
+		save_verified(&file, b"Hello World").expect("Save failed.");
+		assert_eq!(std::fs::read(&file).ok().as_deref(), Some(&b"Hello World"[..]));
+
+		// Existing files are replaced, and nothing is left behind.
+		save_verified(&file, b"Goodbye").expect("Save failed.");
+		assert_eq!(std::fs::read(&file).ok().as_deref(), Some(&b"Goodbye"[..]));
+		assert_eq!(std::fs::read_dir(&root).map(Iterator::count).ok(), Some(1));
+
+		let _res = std::fs::remove_dir_all(&root);
+	}
+}
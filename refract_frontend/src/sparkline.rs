@@ -0,0 +1,52 @@
+/*!
+# `Refract` - Sparklines
+*/
+
+
+
+/// # Bars.
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+
+
+#[must_use]
+/// # Sparkline.
+///
+/// Render a series of values — e.g. candidate file sizes ordered by quality —
+/// as a tiny unicode bar chart, one character per value, scaled between the
+/// smallest and largest.
+///
+/// If all the values are equal, the bars will sit at the midpoint. An empty
+/// series produces an empty string.
+pub fn sparkline(values: &[usize]) -> String {
+	let Some(min) = values.iter().copied().min() else { return String::new(); };
+	let max = values.iter().copied().max().unwrap_or(min);
+	let range = max - min;
+
+	values.iter()
+		.map(|&v|
+			if range == 0 { BARS[BARS.len() / 2] }
+			else {
+				#[expect(clippy::cast_possible_truncation, reason = "False positive.")]
+				#[expect(clippy::cast_precision_loss, reason = "Precision is not critical here.")]
+				#[expect(clippy::cast_sign_loss, reason = "False positive.")]
+				let idx = ((v - min) as f64 / range as f64 * (BARS.len() - 1) as f64).round() as usize;
+				BARS[idx.min(BARS.len() - 1)]
+			}
+		)
+		.collect()
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn t_sparkline() {
+		assert_eq!(sparkline(&[]), "");
+		assert_eq!(sparkline(&[5, 5]), "▅▅");
+		assert_eq!(sparkline(&[100, 200, 300, 800]), "▁▂▃█");
+	}
+}